@@ -1,22 +1,99 @@
+#[cfg(feature = "alloc-audit")]
+use crate::core::AllocationViolation;
 use crate::{
+    ext,
     system::{
-        IntoSystemConfigs,
-        executor::RunMode,
-        schedule::{Phase, Schedule, Systems},
+        IntoSystemConfigs, SystemConfigs,
+        diagnostics::{DiagnosticsStore, SystemDiagnostics},
+        error::{ErrorHandler, log_error},
+        executor::{CancelHandle, PhaseRunResult, RunMode},
+        readonly::{ReadOnlySystems, ReadOnlyViolation},
+        runtime::{RuntimeRestoreReport, RuntimeState},
+        schedule::{Phase, Schedule, Schedules, Systems},
+        state::{NextState, State, StateTransition, States, apply_state_transition},
+        time::{FixedTime, FixedUpdate, Time},
     },
-    world::{Component, Resource, World},
+    world::{
+        ChangeHistory, ChangeHistoryConfig, Component, Event, EventReader, Events, NonSendMut,
+        Relationship, Resource, World,
+    },
+};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
 };
 
+/// Runs once when the default runner starts.
+pub struct Start;
+impl Phase for Start {}
+
+/// Runs every frame of the default runner, after [`FixedUpdate`].
+pub struct Update;
+impl Phase for Update {}
+
+/// Runs at the end of every frame of the default runner.
+pub struct Last;
+impl Phase for Last {}
+
+/// Stops the main loop at the end of the frame it was sent in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppExit {
+    Success,
+    Error(u8),
+}
+
+impl Event for AppExit {}
+
+/// Drives a built [`App`], see [`AppBuilder::set_runner`].
+pub type AppRunner = fn(App);
+
+/// Runs [`Start`] once, then [`App::tick`], [`Update`] and [`Last`] every frame until an
+/// [`AppExit`] is sent.
+pub fn default_runner(mut app: App) {
+    app.run(Start);
+    let mut last = Instant::now();
+    while app.exit_requested().is_none() {
+        let now = Instant::now();
+        app.tick(now - last);
+        last = now;
+
+        app.run(Update);
+        app.run(Last);
+        app.update_sub_apps();
+    }
+}
+
 pub struct AppBuilder {
     world: World,
     schedule: Schedule,
+    states: Vec<StateTransition>,
+    plugins: HashSet<&'static str>,
+    runner: AppRunner,
+    error_handler: ErrorHandler,
+    sub_apps: Vec<(&'static str, SubApp)>,
+    schedules: Vec<(&'static str, Schedule)>,
+    #[cfg(feature = "alloc-audit")]
+    allocation_audit: Option<u32>,
 }
 
 impl AppBuilder {
     pub fn new() -> Self {
+        let mut world = World::new();
+        world.add_resource(Time::default());
+        world.add_resource(FixedTime::default());
+        world.register_event::<AppExit>();
+
         Self {
-            world: World::new(),
+            world,
             schedule: Schedule::new(RunMode::Sequential),
+            states: Vec::new(),
+            plugins: HashSet::new(),
+            runner: default_runner,
+            error_handler: log_error,
+            sub_apps: Vec::new(),
+            schedules: Vec::new(),
+            #[cfg(feature = "alloc-audit")]
+            allocation_audit: None,
         }
     }
 
@@ -41,6 +118,24 @@ impl AppBuilder {
         self
     }
 
+    /// Makes inserting `C` also insert `R::default()` if the entity doesn't have `R`.
+    pub fn register_required<C: Component, R: Component + Default>(&mut self) -> &mut Self {
+        self.world.register_required::<C, R>();
+        self
+    }
+
+    /// Indexes `R` on its targets, see [`World::register_relationship`].
+    pub fn register_relationship<R: Relationship>(&mut self) -> &mut Self {
+        self.world.register_relationship::<R>();
+        self
+    }
+
+    /// Registers the component so its values are included in [`World::state_hash`].
+    pub fn register_hashable<C: Component + std::hash::Hash>(&mut self) -> &mut Self {
+        self.world.register_hashable::<C>();
+        self
+    }
+
     pub fn add_resource<R: Resource + Send>(&mut self, resource: R) -> &mut Self {
         self.world.add_resource(resource);
         self
@@ -51,6 +146,65 @@ impl AppBuilder {
         self
     }
 
+    /// Records recent component changes into a [`ChangeHistory`] resource at the end of every frame.
+    pub fn enable_change_history(&mut self, config: ChangeHistoryConfig) -> &mut Self {
+        self.world.add_resource(ChangeHistory::new(config));
+        self
+    }
+
+    /// Fails any phase that allocates on the running thread once the world is past `after_frame`.
+    /// Debug builds panic, release builds record an [`AllocationViolation`].
+    #[cfg(feature = "alloc-audit")]
+    pub fn forbid_steady_state_allocations(&mut self, after_frame: u32) -> &mut Self {
+        self.allocation_audit = Some(after_frame);
+        self
+    }
+
+    /// Adds the [`State`] and [`NextState`] resources of `S`. The first [`App::run`] enters
+    /// `initial`, transitions requested through [`NextState`] are applied at the end of each run.
+    /// Replaces the default [`FixedTime`], which steps 64 times per second.
+    pub fn set_fixed_timestep(&mut self, fixed: FixedTime) -> &mut Self {
+        self.world.add_resource(fixed);
+        self
+    }
+
+    pub fn add_state<S: States>(&mut self, initial: S) -> &mut Self {
+        if self.world.resources().get_id::<State<S>>().is_none() {
+            self.states.push(apply_state_transition::<S>);
+        }
+
+        self.world.add_resource(State::new(initial));
+        self.world.add_resource(NextState::<S>::default());
+        self
+    }
+
+    /// Builds the plugin into the app. Panics if a unique plugin with the same name was already
+    /// added.
+    pub fn add_plugin(&mut self, plugin: impl Plugin) -> &mut Self {
+        self.add_boxed_plugin(Box::new(plugin))
+    }
+
+    /// Adds every plugin of the group in order.
+    pub fn add_plugins(&mut self, group: impl PluginGroup) -> &mut Self {
+        for plugin in group.build().plugins {
+            self.add_boxed_plugin(plugin);
+        }
+        self
+    }
+
+    pub fn has_plugin<P: Plugin>(&self) -> bool {
+        self.plugins.contains(std::any::type_name::<P>())
+    }
+
+    fn add_boxed_plugin(&mut self, plugin: Box<dyn Plugin>) -> &mut Self {
+        if !self.plugins.insert(plugin.name()) && plugin.is_unique() {
+            panic!("Plugin {} was already added", plugin.name());
+        }
+
+        plugin.build(self);
+        self
+    }
+
     pub fn add_phase(&mut self, phase: impl Phase) -> &mut Self {
         self.schedule.add_phase(phase);
         self
@@ -80,13 +234,75 @@ impl AppBuilder {
         self
     }
 
+    /// Adds a schedule that systems run on demand with [`World::run_schedule`]. It's built along
+    /// with the app's schedule.
+    pub fn add_schedule(&mut self, label: impl Phase, mut schedule: Schedule) -> &mut Self {
+        let name = label.name();
+        schedule.add_phase(label);
+        self.schedules.push((name, schedule));
+        self
+    }
+
+    /// Replaces the [`default_runner`], for integrations that drive the loop themselves like a
+    /// window event loop or a headless server.
+    pub fn set_runner(&mut self, runner: AppRunner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Times every system and phase into a [`DiagnosticsStore`] resource.
+    pub fn enable_diagnostics(&mut self) -> &mut Self {
+        self.world.add_resource(DiagnosticsStore::new());
+        self
+    }
+
+    /// Replaces [`log_error`] as the handler of errors returned by systems, the built in policies
+    /// are [`log_error`], [`panic_on_error`](crate::system::error::panic_on_error) and
+    /// [`skip_error`](crate::system::error::skip_error).
+    pub fn set_error_handler(&mut self, handler: ErrorHandler) -> &mut Self {
+        self.error_handler = handler;
+        self
+    }
+
+    /// Adds a sub app that is updated after the main app every frame, replacing a sub app with
+    /// the same label.
+    pub fn add_sub_app(&mut self, label: impl AppLabel, sub_app: SubApp) -> &mut Self {
+        let name = label.name();
+        match self.sub_apps.iter_mut().find(|(label, _)| *label == name) {
+            Some((_, existing)) => *existing = sub_app,
+            None => self.sub_apps.push((name, sub_app)),
+        }
+        self
+    }
+
+    /// Builds the app and hands it to the runner.
+    pub fn run(&mut self) {
+        let runner = self.runner;
+        runner(self.build());
+    }
+
     pub fn build(&mut self) -> App {
         let mut app = std::mem::take(self);
         let systems = app.schedule.build(&mut app.world).unwrap();
+        for (name, schedule) in app.schedules {
+            let systems = schedule.build(&mut app.world).unwrap();
+            app.world.insert_schedule(name, systems);
+        }
+
+        let (reader, cursor) = app.world.resource_mut::<Events<AppExit>>().add_reader();
 
         App {
             world: app.world,
             systems,
+            states: app.states,
+            entered: false,
+            exit: (reader, cursor),
+            error_handler: app.error_handler,
+            sub_apps: app.sub_apps,
+            #[cfg(feature = "alloc-audit")]
+            allocation_audit: app.allocation_audit,
+            #[cfg(feature = "alloc-audit")]
+            allocation_violations: Vec::new(),
         }
     }
 }
@@ -97,9 +313,90 @@ impl Default for AppBuilder {
     }
 }
 
+/// A reusable piece of app setup, like the systems and resources of an engine feature.
+pub trait Plugin: 'static {
+    fn build(&self, app: &mut AppBuilder);
+
+    /// Identifies the plugin when detecting duplicates.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Whether adding the plugin a second time is an error.
+    fn is_unique(&self) -> bool {
+        true
+    }
+}
+
+/// Plugins that are added together, see [`AppBuilder::add_plugins`].
+pub trait PluginGroup {
+    fn build(self) -> PluginGroupBuilder;
+}
+
+/// Ordered plugins of a [`PluginGroup`], individual plugins can be disabled before adding.
+#[derive(Default)]
+pub struct PluginGroupBuilder {
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl PluginGroupBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the plugin, replacing a plugin of the same name already in the group.
+    pub fn add_plugin(mut self, plugin: impl Plugin) -> Self {
+        self.insert(Box::new(plugin));
+        self
+    }
+
+    /// Adds every plugin of another group.
+    pub fn add_plugins(mut self, group: impl PluginGroup) -> Self {
+        for plugin in group.build().plugins {
+            self.insert(plugin);
+        }
+        self
+    }
+
+    pub fn disable<P: Plugin>(mut self) -> Self {
+        let name = std::any::type_name::<P>();
+        self.plugins.retain(|plugin| plugin.name() != name);
+        self
+    }
+
+    pub fn contains<P: Plugin>(&self) -> bool {
+        let name = std::any::type_name::<P>();
+        self.plugins.iter().any(|plugin| plugin.name() == name)
+    }
+
+    fn insert(&mut self, plugin: Box<dyn Plugin>) {
+        match self.plugins.iter().position(|p| p.name() == plugin.name()) {
+            Some(index) => self.plugins[index] = plugin,
+            None => self.plugins.push(plugin),
+        }
+    }
+}
+
+impl PluginGroup for PluginGroupBuilder {
+    fn build(self) -> PluginGroupBuilder {
+        self
+    }
+}
+
 pub struct App {
     world: World,
     systems: Systems,
+    states: Vec<StateTransition>,
+    /// Whether the initial states have been entered.
+    entered: bool,
+    /// Reader index and cursor of the [`AppExit`] events.
+    exit: (usize, u64),
+    error_handler: ErrorHandler,
+    sub_apps: Vec<(&'static str, SubApp)>,
+    #[cfg(feature = "alloc-audit")]
+    allocation_audit: Option<u32>,
+    #[cfg(feature = "alloc-audit")]
+    allocation_violations: Vec<AllocationViolation>,
 }
 
 impl App {
@@ -111,6 +408,10 @@ impl App {
         &self.world
     }
 
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
     pub fn systems(&self) -> &Systems {
         &self.systems
     }
@@ -119,9 +420,817 @@ impl App {
         self.world.resources().is_send()
     }
 
-    pub fn run(&mut self, phase: impl Phase) -> &mut Self {
-        self.systems.run(&mut self.world, phase);
+    /// Captures the world frame, the systems' last run frames and saved arguments, and the event
+    /// sequences. Pair it with a world snapshot taken between frames.
+    pub fn save_runtime_state(&self) -> RuntimeState {
+        RuntimeState {
+            frame: self.world.frame(),
+            systems: self.systems.save_runtime(),
+            events: self.world.save_event_state(),
+        }
+    }
+
+    /// Restores state captured by [`App::save_runtime_state`] into a freshly built app, after the
+    /// world snapshot has been restored. Systems are matched by phase and name, saved systems that
+    /// no longer exist are skipped and listed in the report.
+    pub fn restore_runtime_state(&mut self, state: &RuntimeState) -> RuntimeRestoreReport {
+        let mut report = RuntimeRestoreReport::default();
+        self.world.restore_frame(state.frame);
+        report.missing_events = self.world.restore_event_state(&state.events);
+        self.systems
+            .restore_runtime(&mut self.world, &state.systems, &mut report);
+        report
+    }
+
+    /// Returns a handle that can cancel the currently running phase from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.world.cancel_handle().clone()
+    }
+
+    /// Allocations recorded by [`AppBuilder::forbid_steady_state_allocations`] in release builds.
+    #[cfg(feature = "alloc-audit")]
+    pub fn allocation_violations(&self) -> &[AllocationViolation] {
+        &self.allocation_violations
+    }
+
+    pub fn run(&mut self, phase: impl Phase) -> PhaseRunResult {
+        if !self.entered {
+            self.entered = true;
+            for transition in &self.states {
+                transition(&mut self.world, &self.systems, true);
+            }
+        }
+
+        #[cfg(feature = "alloc-audit")]
+        let guard = self
+            .allocation_audit
+            .filter(|after_frame| self.world.frame().get() > *after_frame)
+            .map(|_| self.world.allocation_guard());
+
+        let name = phase.name();
+        let start = Instant::now();
+        let result = self.systems.run(&mut self.world, phase);
+        let elapsed = start.elapsed();
+
+        #[cfg(feature = "alloc-audit")]
+        if let Some(allocations) = guard.map(|guard| guard.allocations()).filter(|a| *a > 0) {
+            let violation = AllocationViolation {
+                frame: self.world.frame(),
+                phase: name,
+                allocations,
+            };
+
+            if cfg!(debug_assertions) {
+                panic!("Steady state allocation detected: {:?}", violation);
+            }
+
+            self.allocation_violations.push(violation);
+        }
+
+        for transition in &self.states {
+            transition(&mut self.world, &self.systems, false);
+        }
+
+        let stats = self
+            .world
+            .try_resource::<DiagnosticsStore>()
+            .map(|_| self.world.stats());
+        if let Some((store, stats)) = self.world.try_resource_mut::<DiagnosticsStore>().zip(stats) {
+            store.record_phase(name, elapsed);
+            store.record_world(stats);
+            for (phase, system) in self.systems.iter() {
+                let meta = system.meta();
+                let diagnostics = SystemDiagnostics {
+                    name: meta.name.clone(),
+                    phase,
+                    timing: meta.timing,
+                };
+                store.record_system(meta.id, diagnostics);
+            }
+        }
+
+        for failure in self.world.system_errors().take() {
+            (self.error_handler)(&mut self.world, failure);
+        }
+
+        let last_check = self.world.last_change_check();
         self.world.update();
-        self
+        if self.world.last_change_check() != last_check {
+            let frame = self.world.frame();
+            self.systems.check_change_frames(frame);
+            if let Some(schedules) = self.world.try_non_send_resource_mut::<Schedules>() {
+                schedules.check_change_frames(frame);
+            }
+        }
+
+        result
+    }
+
+    pub fn sub_app(&self, label: impl AppLabel) -> Option<&SubApp> {
+        let name = label.name();
+        self.sub_apps
+            .iter()
+            .find_map(|(label, sub_app)| (*label == name).then_some(sub_app))
+    }
+
+    pub fn sub_app_mut(&mut self, label: impl AppLabel) -> Option<&mut SubApp> {
+        let name = label.name();
+        self.sub_apps
+            .iter_mut()
+            .find_map(|(label, sub_app)| (*label == name).then_some(sub_app))
+    }
+
+    /// Extracts into and updates every sub app, in the order they were added. Called by the
+    /// [`default_runner`] at the end of every frame. Commands routed to a sub app are applied
+    /// after its extraction, the ones it routes to [`MainWorld`] right after its update.
+    pub fn update_sub_apps(&mut self) {
+        for (label, sub_app) in &mut self.sub_apps {
+            sub_app.extract(&mut self.world);
+            self.world.apply_routed_commands(label, sub_app.world_mut());
+            sub_app.update();
+            sub_app
+                .world_mut()
+                .apply_routed_commands(MainWorld.name(), &mut self.world);
+        }
+
+        self.drop_unrouted_commands();
+    }
+
+    /// Applies the commands queued with [`Commands::for_world`](crate::world::Commands::for_world),
+    /// first from the main world to every sub app, then from every sub app to the main world.
+    /// The entities spawned in a world are sent back to the world that queued them as a
+    /// [`CrossWorldSpawned`](crate::world::CrossWorldSpawned) event. Commands for a label without a sub app are dropped.
+    pub fn route_commands(&mut self) {
+        for (label, sub_app) in &mut self.sub_apps {
+            self.world.apply_routed_commands(label, sub_app.world_mut());
+        }
+
+        for (_, sub_app) in &mut self.sub_apps {
+            sub_app
+                .world_mut()
+                .apply_routed_commands(MainWorld.name(), &mut self.world);
+        }
+
+        self.drop_unrouted_commands();
+    }
+
+    fn drop_unrouted_commands(&mut self) {
+        let sub_apps = &self.sub_apps;
+        self.world
+            .retain_routed_commands(|label| sub_apps.iter().any(|(name, _)| *name == label));
+        for (_, sub_app) in &mut self.sub_apps {
+            sub_app
+                .world_mut()
+                .retain_routed_commands(|label| label == MainWorld.name());
+        }
+    }
+
+    /// Returns the first [`AppExit`] sent since the last call.
+    pub fn exit_requested(&mut self) -> Option<AppExit> {
+        let (reader, cursor) = &mut self.exit;
+        let events = self.world.resource_mut::<Events<AppExit>>();
+        let exit = EventReader::new(events, cursor).fold(None, |exit, event| exit.or(Some(*event)));
+        events.set_cursor(*reader, *cursor);
+        exit
+    }
+
+    /// Advances [`Time`] by the frame's `delta` and runs [`FixedUpdate`] once per whole fixed
+    /// timestep accumulated. Returns the number of fixed steps run.
+    pub fn tick(&mut self, delta: Duration) -> u32 {
+        self.world.resource_mut::<Time>().advance(delta);
+        let steps = self.world.resource_mut::<FixedTime>().accumulate(delta);
+        for _ in 0..steps {
+            self.world.resource_mut::<FixedTime>().step();
+            self.run(FixedUpdate);
+        }
+
+        steps
+    }
+}
+
+/// Identifies a [`SubApp`], see [`AppBuilder::add_sub_app`].
+pub trait AppLabel: 'static {
+    fn name(&self) -> &'static str {
+        ext::short_type_name::<Self>()
+    }
+}
+
+/// Label of the main world, to route commands from a [`SubApp`] back to it.
+pub struct MainWorld;
+impl AppLabel for MainWorld {}
+
+/// Copies data from the main world into the world of a [`SubApp`].
+pub type ExtractFn = fn(&mut World, &mut World);
+
+/// An [`App`] with its own world and schedule, like a render world next to the main world.
+/// Every frame, `extract` copies what the sub app needs out of the main world, then `update`
+/// runs the sub app's phases. The sub app's systems never see the main world, extraction is
+/// the only point where the two worlds meet.
+pub struct SubApp {
+    app: App,
+    extract: ExtractFn,
+    update: fn(&mut App),
+}
+
+impl SubApp {
+    pub fn new(mut builder: AppBuilder, extract: ExtractFn, update: fn(&mut App)) -> Self {
+        Self {
+            app: builder.build(),
+            extract,
+            update,
+        }
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    /// Copies data out of the main world with the sub app's [`ExtractFn`].
+    pub fn extract(&mut self, main: &mut World) {
+        (self.extract)(main, &mut self.app.world);
+    }
+
+    /// Runs the sub app's phases for a single frame.
+    pub fn update(&mut self) {
+        (self.update)(&mut self.app);
+    }
+}
+
+/// An [`App`] hosted inside another app, like a game simulation running in an editor.
+/// Add it as a non send resource, an [`App`] owns its executors and can't move between threads,
+/// and drive it with [`run_nested_app_system`].
+pub struct NestedApp {
+    app: App,
+    factory: Box<dyn Fn() -> AppBuilder>,
+}
+
+impl Resource for NestedApp {}
+
+impl NestedApp {
+    pub fn new(factory: impl Fn() -> AppBuilder + 'static) -> Self {
+        Self {
+            app: factory().build(),
+            factory: Box::new(factory),
+        }
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    /// Rebuilds the nested app from its factory. Read only systems built for the previous
+    /// app have to be built again.
+    pub fn reset(&mut self) {
+        self.app = (self.factory)().build();
+    }
+
+    /// Builds read only systems that inspect the nested world.
+    pub fn readonly_systems<M>(
+        &mut self,
+        systems: impl IntoSystemConfigs<M>,
+    ) -> Result<ReadOnlySystems, ReadOnlyViolation> {
+        let mut readonly = ReadOnlySystems::new();
+        readonly.add_systems(&mut self.app.world, systems)?;
+        Ok(readonly)
+    }
+
+    pub fn run_readonly(&self, systems: &mut ReadOnlySystems) {
+        systems.run(&self.app.world);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NestedAppControl {
+    #[default]
+    Play,
+    Pause,
+    /// Advances a single tick, then pauses.
+    Step,
+    /// Rebuilds the nested app, then pauses.
+    Reset,
+}
+
+impl Resource for NestedAppControl {}
+
+/// System that advances the [`NestedApp`] resource by one tick every time it runs, following the
+/// [`NestedAppControl`] resource. `tick` runs the nested app's phases for a single tick.
+pub fn run_nested_app_system(tick: impl Fn(&mut App) + Send + Sync + 'static) -> SystemConfigs {
+    let driver =
+        move |mut nested: NonSendMut<NestedApp>, control: &mut NestedAppControl| match *control {
+            NestedAppControl::Play => tick(nested.app_mut()),
+            NestedAppControl::Pause => {}
+            NestedAppControl::Step => {
+                tick(nested.app_mut());
+                *control = NestedAppControl::Pause;
+            }
+            NestedAppControl::Reset => {
+                nested.reset();
+                *control = NestedAppControl::Pause;
+            }
+        };
+
+    driver.configs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        App, AppBuilder, AppExit, AppLabel, Last, MainWorld, NestedApp, NestedAppControl, Plugin,
+        PluginGroup, PluginGroupBuilder, Start, SubApp, run_nested_app_system,
+    };
+    use crate::{
+        core::Frame,
+        system::{
+            IntoSystemConfigs,
+            arg::Local,
+            diagnostics::DiagnosticsStore,
+            error::{SystemError, SystemFailure, panic_on_error},
+            executor::RunMode,
+            query::Query,
+            schedule::Phase,
+        },
+        world::{
+            Command, Commands, Component, CrossWorldSpawned, Entity, EventReader, EventWriter,
+            Resource, World,
+        },
+    };
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    struct Update;
+    impl Phase for Update {}
+
+    struct Tick;
+    impl Phase for Tick {}
+
+    #[derive(Default)]
+    struct Counter(u32);
+    impl Resource for Counter {}
+
+    fn game() -> AppBuilder {
+        let mut builder = App::new();
+        builder
+            .add_resource(Counter::default())
+            .add_systems(Tick, |counter: &mut Counter| counter.0 += 1);
+        builder
+    }
+
+    #[test]
+    fn nested_app_controls() {
+        let mut builder = App::new();
+        builder
+            .add_resource(Counter::default())
+            .add_resource(NestedAppControl::Play)
+            .add_non_send_resource(NestedApp::new(game))
+            .add_systems(Update, |counter: &mut Counter| counter.0 += 1)
+            .add_systems(
+                Update,
+                run_nested_app_system(|app| {
+                    app.run(Tick);
+                }),
+            );
+        let mut editor = builder.build();
+
+        let nested = |editor: &App| {
+            let nested = editor.world().non_send_resource::<NestedApp>();
+            (
+                nested.world().resource::<Counter>().0,
+                nested.world().frame(),
+            )
+        };
+        let initial = nested(&editor).1;
+
+        for _ in 0..3 {
+            editor.run(Update);
+        }
+        let (count, frame) = nested(&editor);
+        assert_eq!(count, 3);
+
+        *editor.world_mut().resource_mut::<NestedAppControl>() = NestedAppControl::Pause;
+        editor.run(Update);
+        editor.run(Update);
+        assert_eq!(nested(&editor), (3, frame));
+        assert_eq!(editor.world().resource::<Counter>().0, 5);
+
+        *editor.world_mut().resource_mut::<NestedAppControl>() = NestedAppControl::Step;
+        editor.run(Update);
+        editor.run(Update);
+        assert_eq!(nested(&editor).0, 4);
+        assert_eq!(
+            *editor.world().resource::<NestedAppControl>(),
+            NestedAppControl::Pause
+        );
+
+        *editor.world_mut().resource_mut::<NestedAppControl>() = NestedAppControl::Reset;
+        editor.run(Update);
+        assert_eq!(nested(&editor), (0, initial));
+        assert_eq!(editor.world().resource::<Counter>().0, 8);
+    }
+
+    #[test]
+    fn nested_app_inspection() {
+        let mut nested = NestedApp::new(game);
+        nested.app_mut().run(Tick);
+        nested.app_mut().run(Tick);
+
+        let observed = Arc::new(AtomicU32::new(0));
+        let sink = observed.clone();
+        let mut inspect = nested
+            .readonly_systems(move |counter: &Counter| sink.store(counter.0, Ordering::Relaxed))
+            .unwrap();
+        nested.run_readonly(&mut inspect);
+        assert_eq!(observed.load(Ordering::Relaxed), 2);
+
+        let mutate = |counter: &mut Counter| counter.0 += 1;
+        assert!(nested.readonly_systems(mutate).is_err());
+    }
+
+    struct CounterPlugin;
+    impl Plugin for CounterPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_resource(Counter::default())
+                .add_systems(Update, |counter: &mut Counter| counter.0 += 1);
+        }
+    }
+
+    struct DoublePlugin;
+    impl Plugin for DoublePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_systems(Update, |counter: &mut Counter| counter.0 *= 2);
+        }
+
+        fn is_unique(&self) -> bool {
+            false
+        }
+    }
+
+    struct GamePlugins;
+    impl PluginGroup for GamePlugins {
+        fn build(self) -> PluginGroupBuilder {
+            PluginGroupBuilder::new()
+                .add_plugin(CounterPlugin)
+                .add_plugin(DoublePlugin)
+        }
+    }
+
+    #[test]
+    fn plugins() {
+        let mut builder = App::new();
+        builder.add_plugins(GamePlugins).add_plugin(DoublePlugin);
+        assert!(builder.has_plugin::<CounterPlugin>());
+
+        let mut app = builder.build();
+        app.run(Update);
+        assert_eq!(app.world().resource::<Counter>().0, 4);
+
+        let group = GamePlugins.build().disable::<DoublePlugin>();
+        assert!(!group.contains::<DoublePlugin>());
+        let mut app = App::new().add_plugins(group).build();
+        app.run(Update);
+        assert_eq!(app.world().resource::<Counter>().0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "already added")]
+    fn duplicate_plugin() {
+        App::new()
+            .add_plugin(CounterPlugin)
+            .add_plugin(CounterPlugin);
+    }
+
+    #[test]
+    fn default_runner() {
+        static STARTS: AtomicU32 = AtomicU32::new(0);
+        static UPDATES: AtomicU32 = AtomicU32::new(0);
+        static LASTS: AtomicU32 = AtomicU32::new(0);
+
+        App::new()
+            .add_systems(Start, || {
+                STARTS.fetch_add(1, Ordering::Relaxed);
+            })
+            .add_systems(Update, |mut exit: EventWriter<AppExit>| {
+                if UPDATES.fetch_add(1, Ordering::Relaxed) == 2 {
+                    exit.send(AppExit::Success);
+                }
+            })
+            .add_systems(Last, || {
+                LASTS.fetch_add(1, Ordering::Relaxed);
+            })
+            .run();
+
+        assert_eq!(STARTS.load(Ordering::Relaxed), 1);
+        assert_eq!(UPDATES.load(Ordering::Relaxed), 3);
+        assert_eq!(LASTS.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn custom_runner() {
+        static TICKS: AtomicU32 = AtomicU32::new(0);
+
+        fn runner(mut app: App) {
+            for _ in 0..4 {
+                app.run(Tick);
+            }
+            assert!(app.exit_requested().is_none());
+            TICKS.store(app.world().resource::<Counter>().0, Ordering::Relaxed);
+        }
+
+        App::new()
+            .add_resource(Counter::default())
+            .add_systems(Tick, |counter: &mut Counter| counter.0 += 1)
+            .set_runner(runner)
+            .run();
+
+        assert_eq!(TICKS.load(Ordering::Relaxed), 4);
+    }
+
+    #[derive(Default)]
+    struct Failures(Vec<String>);
+    impl Resource for Failures {}
+
+    fn record(world: &mut World, failure: SystemFailure) {
+        world
+            .resource_mut::<Failures>()
+            .0
+            .push(failure.error.to_string());
+    }
+
+    fn fallible(counter: &mut Counter) -> Result<(), SystemError> {
+        counter.0 += 1;
+        match counter.0 % 2 {
+            0 => Err(SystemError::new(format!("even {}", counter.0))),
+            _ => Ok(()),
+        }
+    }
+
+    #[test]
+    fn fallible_systems() {
+        for mode in [RunMode::Sequential, RunMode::Parallel] {
+            let mut builder = App::new();
+            builder.schedule_mut().set_mode(mode);
+            let mut app = builder
+                .add_resource(Counter::default())
+                .add_resource(Failures::default())
+                .add_systems(Update, fallible)
+                .add_systems(Update, || -> Result<(), SystemError> {
+                    Err(std::fmt::Error.into())
+                })
+                .set_error_handler(record)
+                .build();
+
+            app.run(Update);
+            app.run(Update);
+
+            let failures = &app.world().resource::<Failures>().0;
+            assert_eq!(failures.len(), 3);
+            assert!(failures.contains(&"even 2".to_string()));
+            assert!(app.world().system_errors().is_empty());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "even 2")]
+    fn panic_on_system_error() {
+        let mut app = App::new()
+            .add_resource(Counter(1))
+            .add_systems(Update, fallible)
+            .set_error_handler(panic_on_error)
+            .build();
+
+        app.run(Update);
+    }
+
+    fn sleep() {
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let mut app = App::new().add_systems(Update, sleep).build();
+        app.run(Update);
+        let (_, system) = app.systems().iter().next().unwrap();
+        assert_eq!(system.meta().timing.runs, 0);
+
+        let mut app = App::new()
+            .add_systems(Update, sleep)
+            .enable_diagnostics()
+            .build();
+        for _ in 0..3 {
+            app.run(Update);
+        }
+
+        let store = app.world().resource::<DiagnosticsStore>();
+        let (_, system) = store.systems().next().unwrap();
+        assert_eq!(system.phase, "Update");
+        assert_eq!(system.timing.runs, 3);
+        assert!(system.timing.average() >= std::time::Duration::from_millis(2));
+
+        let phase = store.phase("Update").unwrap();
+        assert_eq!(phase.runs, 3);
+        assert!(phase.last >= system.timing.last);
+
+        let stats = store.world().unwrap();
+        assert_eq!(stats, &app.world().stats());
+    }
+
+    #[test]
+    fn system_ids_per_world() {
+        fn first() {}
+        fn second() {}
+
+        let build = || {
+            App::new()
+                .add_systems(Update, first.before(second))
+                .add_systems(Update, sleep)
+                .build()
+        };
+        let ids = |app: &App| {
+            let mut ids = app
+                .systems()
+                .iter()
+                .map(|(_, system)| system.meta().id.0)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        // Ids only depend on the order systems are built in, not on other worlds.
+        let (app, other) = (build(), build());
+        assert_eq!(ids(&app), [0, 1, 2]);
+        assert_eq!(ids(&other), [0, 1, 2]);
+    }
+
+    #[test]
+    fn sub_app_extracts_every_frame() {
+        struct RenderApp;
+        impl AppLabel for RenderApp {}
+
+        struct Render;
+        impl Phase for Render {}
+
+        #[derive(Default)]
+        struct Rendered(Vec<u32>);
+        impl Resource for Rendered {}
+
+        fn extract(main: &mut World, render: &mut World) {
+            let count = main.resource::<Counter>().0;
+            render.resource_mut::<Counter>().0 = count;
+        }
+
+        let mut render = App::new();
+        render
+            .add_resource(Counter::default())
+            .add_resource(Rendered::default())
+            .add_systems(Render, |counter: &Counter, rendered: &mut Rendered| {
+                rendered.0.push(counter.0)
+            });
+
+        let mut builder = game();
+        builder.add_sub_app(
+            RenderApp,
+            SubApp::new(render, extract, |app| {
+                app.run(Render);
+            }),
+        );
+        let mut app = builder.build();
+
+        let frame = app.sub_app(RenderApp).unwrap().world().frame();
+        for _ in 0..3 {
+            app.run(Tick);
+            app.update_sub_apps();
+        }
+
+        let render = app.sub_app(RenderApp).unwrap();
+        assert_eq!(render.world().resource::<Rendered>().0, [1, 2, 3]);
+        assert_eq!(render.world().frame(), frame + Frame(3));
+        assert!(app.world().try_resource::<Rendered>().is_none());
+    }
+
+    #[test]
+    fn sub_app_routes_commands() {
+        struct RenderApp;
+        impl AppLabel for RenderApp {}
+
+        struct Render;
+        impl Phase for Render {}
+
+        struct Sprite(u32);
+        impl Component for Sprite {}
+
+        #[derive(Default)]
+        struct Rendered(Vec<u32>);
+        impl Resource for Rendered {}
+
+        #[derive(Default)]
+        struct Spawned(Vec<(Entity, Entity)>);
+        impl Resource for Spawned {}
+
+        struct Count(u32);
+        impl Command for Count {
+            fn execute(self, world: &mut World) {
+                world.resource_mut::<Counter>().0 += self.0;
+            }
+        }
+
+        fn spawn(mut commands: Commands, mut spawned: Local<bool>) {
+            if !std::mem::replace(&mut *spawned, true) {
+                let source = commands.spawn().id();
+                commands.for_world(RenderApp).spawn(source, (Sprite(7),));
+            }
+        }
+
+        fn render(query: Query<&Sprite>, rendered: &mut Rendered, mut commands: Commands) {
+            rendered.0.extend(query.iter().map(|sprite| sprite.0));
+            commands.for_world(MainWorld).add(Count(10));
+        }
+
+        fn read(mut events: EventReader<CrossWorldSpawned>, spawned: &mut Spawned) {
+            for event in events.by_ref() {
+                assert_eq!(event.world, RenderApp.name());
+                spawned.0.extend(event.entities.iter());
+            }
+        }
+
+        let mut render_app = App::new();
+        render_app
+            .register::<Sprite>()
+            .add_resource(Counter::default())
+            .add_resource(Rendered::default())
+            .add_systems(Render, render);
+
+        let mut builder = game();
+        builder
+            .add_resource(Spawned::default())
+            .add_systems(Update, spawn)
+            .add_systems(Last, read)
+            .add_sub_app(
+                RenderApp,
+                SubApp::new(
+                    render_app,
+                    |_, _| {},
+                    |app| {
+                        app.run(Render);
+                    },
+                ),
+            );
+        let mut app = builder.build();
+
+        // The spawn reaches the render world before its update, the count comes back right after.
+        app.run(Update);
+        app.run(Last);
+        app.update_sub_apps();
+        let render = app.sub_app(RenderApp).unwrap();
+        assert_eq!(render.world().resource::<Rendered>().0, [7]);
+        assert_eq!(app.world().resource::<Counter>().0, 10);
+        assert!(app.world().resource::<Spawned>().0.is_empty());
+
+        // The mapping is published with the main world's next update.
+        app.run(Update);
+        app.run(Last);
+        app.update_sub_apps();
+        let spawned = app.world().resource::<Spawned>().0.clone();
+        assert_eq!(spawned.len(), 1);
+        assert_eq!(app.world().resource::<Counter>().0, 20);
+
+        let render = app.sub_app_mut(RenderApp).unwrap().world_mut();
+        let entities = render.query::<Entity>().iter().collect::<Vec<_>>();
+        assert_eq!(entities, [spawned[0].1]);
+        assert!(app.world().entities().is_alive(spawned[0].0));
+
+        // Routed outside of the frame, commands for unknown worlds are dropped.
+        struct OtherApp;
+        impl AppLabel for OtherApp {}
+
+        app.world_mut().for_world(OtherApp).add(Count(1));
+        app.sub_app_mut(RenderApp)
+            .unwrap()
+            .world_mut()
+            .for_world(MainWorld)
+            .add(Count(100));
+        app.route_commands();
+        assert_eq!(app.world().resource::<Counter>().0, 120);
+        assert!(app.world_mut().for_world(OtherApp).is_empty());
     }
 }
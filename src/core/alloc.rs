@@ -0,0 +1,97 @@
+use super::Frame;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+fn record_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+/// Number of allocations made on the current thread since it started.
+/// Always zero unless [`CountingAllocator`] is installed as the global allocator.
+pub fn allocation_count() -> u64 {
+    ALLOCATIONS.try_with(Cell::get).unwrap_or(0)
+}
+
+/// Global allocator wrapper that counts allocations per thread.
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+/// ```
+pub struct CountingAllocator<A: GlobalAlloc = System>(A);
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        Self(System)
+    }
+}
+
+impl<A: GlobalAlloc> CountingAllocator<A> {
+    pub const fn wrap(allocator: A) -> Self {
+        Self(allocator)
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        unsafe { self.0.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        unsafe { self.0.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+        unsafe { self.0.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.0.dealloc(ptr, layout) }
+    }
+}
+
+/// Counts the allocations made on the current thread while it is alive.
+/// Allocations on other threads, like the parallel executor's workers, are not observed.
+pub struct AllocationGuard {
+    start: u64,
+}
+
+impl AllocationGuard {
+    pub fn new() -> Self {
+        Self {
+            start: allocation_count(),
+        }
+    }
+
+    pub fn allocations(&self) -> u64 {
+        allocation_count() - self.start
+    }
+}
+
+impl Default for AllocationGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationViolation {
+    pub frame: Frame,
+    pub phase: &'static str,
+    pub allocations: u64,
+}
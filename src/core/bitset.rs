@@ -33,10 +33,10 @@ impl AccessBitset {
     /// Sets the read bit for the given index.
     /// Returns `true` if the read bit was successfully set, otherwise `false`.
     pub fn read(&mut self, index: usize) -> bool {
-        if self.bits[index + 1] {
+        if self.writes(index) {
             return false;
         } else {
-            self.bits.set(index, true);
+            self.bits.set(index * 2, true);
             return true;
         }
     }
@@ -48,7 +48,7 @@ impl AccessBitset {
         if read || write {
             return false;
         } else {
-            self.bits.set(index + 1, true);
+            self.bits.set(index * 2 + 1, true);
             return true;
         }
     }
@@ -74,6 +74,15 @@ impl AccessBitset {
         false
     }
 
+    /// Indices written by one of the sets and accessed by the other.
+    pub fn conflicting<'a>(&'a self, other: &'a AccessBitset) -> impl Iterator<Item = usize> + 'a {
+        (0..self.len().min(other.len())).filter(|i| {
+            let (read, write) = self.get(*i);
+            let (other_read, other_write) = other.get(*i);
+            ((read || write) && other_write) || (other_read && write)
+        })
+    }
+
     pub fn iter(&self) -> AccessBitsetIter {
         AccessBitsetIter {
             bits: self,
@@ -1,7 +1,8 @@
 use std::{
     alloc::Layout,
     marker::PhantomData,
-    ptr::{self},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,6 +24,11 @@ impl TypeMeta {
         }
     }
 
+    /// Zero sized values take no bytes in a [`Blob`], only their count is tracked.
+    pub fn is_zero_sized(&self) -> bool {
+        self.layout.size() == 0
+    }
+
     fn drop<T>(data: *mut u8) {
         unsafe {
             let raw = data as *mut T;
@@ -31,24 +37,170 @@ impl TypeMeta {
     }
 }
 
+/// Growable bytes allocated with the alignment of the values stored in them, which a
+/// `Vec<u8>` can't guarantee.
+struct AlignedBytes {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+    align: usize,
+}
+
+// Owns its bytes like a `Vec<u8>`.
+unsafe impl Send for AlignedBytes {}
+unsafe impl Sync for AlignedBytes {}
+
+impl AlignedBytes {
+    fn new(align: usize) -> Self {
+        Self {
+            ptr: Self::dangling(align),
+            len: 0,
+            capacity: 0,
+            align,
+        }
+    }
+
+    fn from_slice(bytes: &[u8], align: usize) -> Self {
+        let mut data = Self::new(align);
+        data.extend_from_slice(bytes);
+        data
+    }
+
+    /// Well aligned pointer for empty buffers and zero sized values.
+    fn dangling(align: usize) -> NonNull<u8> {
+        NonNull::new(ptr::without_provenance_mut(align)).unwrap()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("Capacity overflow");
+        if required > self.capacity {
+            self.set_capacity(required.max(self.capacity * 2));
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        if self.capacity > self.len {
+            self.set_capacity(self.len);
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        let old = Layout::from_size_align(self.capacity, self.align).unwrap();
+        let new = Layout::from_size_align(capacity, self.align).expect("Capacity overflow");
+        let ptr = match (self.capacity, capacity) {
+            (_, 0) => {
+                unsafe { std::alloc::dealloc(self.ptr.as_ptr(), old) };
+                Self::dangling(self.align).as_ptr()
+            }
+            (0, _) => unsafe { std::alloc::alloc(new) },
+            _ => unsafe { std::alloc::realloc(self.ptr.as_ptr(), old, capacity) },
+        };
+
+        self.ptr = NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(new));
+        self.capacity = capacity;
+    }
+
+    /// Grows or shrinks to `len` bytes, zeroing the new ones.
+    fn resize(&mut self, len: usize) {
+        if len > self.len {
+            self.reserve(len - self.len);
+            unsafe {
+                self.ptr
+                    .as_ptr()
+                    .add(self.len)
+                    .write_bytes(0, len - self.len)
+            };
+        }
+
+        self.len = len;
+    }
+
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+        unsafe {
+            let dst = self.ptr.as_ptr().add(self.len);
+            ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+        }
+
+        self.len += bytes.len();
+    }
+
+    /// Removes `count` bytes at `offset`, shifting the bytes after them.
+    fn drain(&mut self, offset: usize, count: usize) -> Vec<u8> {
+        let bytes = self[offset..offset + count].to_vec();
+        self.copy_within(offset + count.., offset);
+        self.len -= count;
+
+        bytes
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Deref for AlignedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for AlignedBytes {
+    fn drop(&mut self) {
+        if self.capacity > 0 {
+            let layout = Layout::from_size_align(self.capacity, self.align).unwrap();
+            unsafe { std::alloc::dealloc(self.ptr.as_ptr(), layout) };
+        }
+    }
+}
+
+/// Type erased vector of values sharing a [`TypeMeta`]. The element count is tracked
+/// separately from the bytes so zero sized types are counted too.
 pub struct Blob {
-    data: Vec<u8>,
+    data: AlignedBytes,
+    len: usize,
     meta: TypeMeta,
 }
 
 impl Blob {
     pub fn new<T: 'static>() -> Self {
-        let meta = TypeMeta::new::<T>();
-
-        Self { data: vec![], meta }
+        Self::with_meta(TypeMeta::new::<T>())
     }
 
-    pub unsafe fn from_raw(data: Vec<u8>, meta: TypeMeta) -> Self {
-        Self { data, meta }
+    /// # Safety
+    /// `data` must contain `len` valid values of the blob's type.
+    pub unsafe fn from_raw(data: Vec<u8>, len: usize, meta: TypeMeta) -> Self {
+        assert_eq!(data.len(), len * meta.layout.size());
+
+        Self {
+            data: AlignedBytes::from_slice(&data, meta.layout.align()),
+            len,
+            meta,
+        }
     }
 
     pub fn with_meta(meta: TypeMeta) -> Self {
-        Self { data: vec![], meta }
+        Self {
+            data: AlignedBytes::new(meta.layout.align()),
+            len: 0,
+            meta,
+        }
     }
 
     pub fn data(&self) -> &[u8] {
@@ -59,264 +211,336 @@ impl Blob {
         &self.meta
     }
 
+    /// The initialized region of the blob, `len() * layout.size()` bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len * self.meta.layout.size()]
+    }
+
     pub fn get<T: 'static>(&self, index: usize) -> Option<&T> {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
 
-        let offset = index * self.meta.layout.size();
-        if self.data.is_empty() || offset > self.data.len() - self.meta.layout.size() {
+        if index >= self.len {
             return None;
         }
 
-        unsafe { (self.data.as_ptr().add(offset) as *const T).as_ref() }
+        unsafe { (self.value_ptr(index) as *const T).as_ref() }
     }
 
     pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
 
-        let offset = index * self.meta.layout.size();
-        if self.data.is_empty() || offset > self.data.len() - self.meta.layout.size() {
+        if index >= self.len {
             return None;
         }
 
-        unsafe { (self.data.as_mut_ptr().add(offset) as *mut T).as_mut() }
+        unsafe { (self.value_ptr(index) as *mut T).as_mut() }
+    }
+
+    /// Type erased pointer to the value, `None` if the index is out of bounds.
+    pub fn get_raw(&self, index: usize) -> Option<NonNull<u8>> {
+        (index < self.len).then(|| unsafe { NonNull::new_unchecked(self.value_ptr(index)) })
     }
 
     pub fn push<T: 'static>(&mut self, value: T) {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
 
-        let offset = self.data.len();
-        self.data
-            .resize(self.data.len() + self.meta.layout.size(), 0);
+        unsafe { self.push_with(|dst| ptr::write(dst as *mut T, value)) };
+    }
 
-        unsafe {
-            let dst = self.data.as_mut_ptr().add(offset);
-            ptr::write(dst as *mut T, value);
-        };
+    /// Builds a value in place at the end of the blob.
+    ///
+    /// # Safety
+    /// `init` must write a valid value of the blob's type to the pointer.
+    pub unsafe fn push_with(&mut self, init: impl FnOnce(*mut u8)) {
+        if !self.meta.is_zero_sized() {
+            self.data.resize(self.data.len() + self.meta.layout.size());
+        }
+
+        init(self.value_ptr(self.len));
+        self.len += 1;
     }
 
+    /// Inserts the value at `index`, shifting the values after it. `index` may equal `len()`.
     pub fn insert<T: 'static>(&mut self, index: usize, value: T) {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
 
-        let offset = index * self.meta.layout.size();
-        let bounds = self.data.len() - self.meta.layout.size();
-        if offset > bounds {
-            panic!("Index out of bounds: {}", index);
-        }
-        self.data
-            .resize(self.data.len() + self.meta.layout.size(), 0);
-
         unsafe {
-            let src = self.data.as_ptr().add(offset);
-            let dst = self.data.as_mut_ptr().add(offset + self.meta.layout.size());
-
-            ptr::copy(src, dst, self.data.len() - offset);
-            ptr::write(src as *mut T, value);
+            let dst = self.shift_right(index);
+            ptr::write(dst as *mut T, value);
         }
     }
 
-    pub fn append<T: 'static>(&mut self, values: Vec<T>) {
+    pub fn append<T: 'static>(&mut self, mut values: Vec<T>) {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
 
         let offset = self.data.len();
         self.data
-            .resize(offset + self.meta.layout.size() * values.len(), 0);
+            .resize(offset + self.meta.layout.size() * values.len());
 
         unsafe {
-            let src = values.as_ptr() as *mut T;
+            let src = values.as_ptr();
             let dst = self.data.as_mut_ptr().add(offset) as *mut T;
 
             ptr::copy_nonoverlapping(src, dst, values.len());
-
-            std::mem::forget(values);
         }
+
+        // The values are moved, only the allocation is left to free.
+        self.len += values.len();
+        unsafe { values.set_len(0) };
     }
 
     pub fn remove<T: 'static>(&mut self, index: usize) -> T {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
+        self.check_bounds(index);
 
-        let offset = index * self.meta.layout.size();
-        if self.data.is_empty() || offset > self.data.len() - self.meta.layout.size() {
-            panic!("Index out of bounds: {}", index);
-        }
+        let value = unsafe { ptr::read(self.value_ptr(index) as *const T) };
 
-        unsafe {
-            let src = self.data.as_ptr().add(offset) as *const T;
-            let value = ptr::read::<T>(src);
-
-            self.data.drain(offset..offset + self.meta.layout.size());
+        self.data
+            .drain(index * self.meta.layout.size(), self.meta.layout.size());
+        self.len -= 1;
 
-            value
-        }
+        value
     }
 
     pub fn swap_remove<T: 'static>(&mut self, index: usize) -> T {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
+        self.check_bounds(index);
 
-        let offset = index * self.meta.layout.size();
-        let bounds = self.data.len() - self.meta.layout.size();
+        let value = unsafe { ptr::read(self.value_ptr(index) as *const T) };
+        self.swap_last_into(index);
 
-        if offset > bounds {
-            panic!("Index out of bounds: {}", index);
-        }
-
-        unsafe {
-            let dst = self.data.as_mut_ptr().add(offset) as *mut T;
-            let src = self.data.as_ptr().add(bounds) as *const T;
-
-            let value = ptr::read(dst);
-            if offset != bounds {
-                ptr::copy_nonoverlapping(src, dst, 1);
-            }
-
-            self.data.set_len(bounds);
+        value
+    }
 
-            value
-        }
+    /// # Safety
+    /// `value` must contain `len` valid values of the blob's type, which are moved into the blob.
+    pub unsafe fn append_raw(&mut self, value: Vec<u8>, len: usize) {
+        unsafe { self.extend_raw(&value, len) };
     }
 
-    pub unsafe fn append_raw(&mut self, value: Vec<u8>) {
-        assert!(value.len() % self.meta.layout.size() == 0);
+    /// # Safety
+    /// `bytes` must contain `len` valid values of the blob's type, which are moved into the blob.
+    pub unsafe fn extend_raw(&mut self, bytes: &[u8], len: usize) {
+        assert_eq!(bytes.len(), len * self.meta.layout.size());
 
-        self.data.extend(value);
+        self.data.extend_from_slice(bytes);
+        self.len += len;
     }
 
+    /// # Safety
+    /// `value` must be a single valid value of the blob's type, which is moved into the blob.
     pub unsafe fn insert_raw(&mut self, index: usize, value: Vec<u8>) {
-        let offset = index * self.meta.layout.size();
-        if self.data.is_empty() || offset > self.data.len() - self.meta.layout.size() {
-            panic!("Index out of bounds: {}", index);
-        }
-        self.data.resize(self.data.len() + value.len(), 0);
+        assert_eq!(value.len(), self.meta.layout.size());
 
         unsafe {
-            let src = self.data.as_ptr().add(offset);
-            let dst = self.data.as_mut_ptr().add(offset + self.meta.layout.size());
-
-            ptr::copy(src, dst, self.data.len() - offset);
-            ptr::copy_nonoverlapping(value.as_ptr(), src as *mut u8, value.len());
+            let dst = self.shift_right(index);
+            ptr::copy_nonoverlapping(value.as_ptr(), dst, value.len());
         }
     }
 
+    /// Moves the value out of the blob as bytes.
+    ///
+    /// # Safety
+    /// The caller becomes responsible for dropping the returned value.
     pub unsafe fn remove_raw(&mut self, index: usize) -> Vec<u8> {
+        self.check_bounds(index);
+
         let offset = index * self.meta.layout.size();
-        if self.data.is_empty() || offset > self.data.len() - self.meta.layout.size() {
-            panic!("Index out of bounds: {}", index);
-        }
+        let bytes = self.data.drain(offset, self.meta.layout.size());
+        self.len -= 1;
 
-        self.data
-            .drain(offset..offset + self.meta.layout.size())
-            .collect()
+        bytes
     }
 
+    /// Moves the value out of the blob as bytes.
+    ///
+    /// # Safety
+    /// The caller becomes responsible for dropping the returned value.
     pub unsafe fn swap_remove_raw(&mut self, index: usize) -> Vec<u8> {
+        self.check_bounds(index);
+
         let offset = index * self.meta.layout.size();
-        if self.data.is_empty() || offset > self.data.len() - self.meta.layout.size() {
-            panic!("Index out of bounds: {}", index);
-        }
+        let bytes = self.data[offset..offset + self.meta.layout.size()].to_vec();
+        self.swap_last_into(index);
 
-        unsafe {
-            let mut bytes = vec![0u8; self.meta.layout.size()];
-            let src = self
-                .data
-                .as_ptr()
-                .add(self.data.len() - self.meta.layout.size());
-            ptr::copy_nonoverlapping(src, bytes.as_mut_ptr(), bytes.len());
+        bytes
+    }
 
-            let bytes = self
-                .data
-                .splice(offset..offset + self.meta.layout.size(), bytes)
-                .collect::<Vec<_>>();
+    pub unsafe fn ptr<T: 'static>(&self) -> Ptr<'_, T> {
+        unsafe { Ptr::new(self.value_ptr(0) as *mut T, self.len) }
+    }
 
-            self.data.set_len(self.data.len() - self.meta.layout.size());
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-            bytes
-        }
+    /// Shortens the blob to `len` values, forgetting the rest rather than dropping them.
+    ///
+    /// # Safety
+    /// `len` must not exceed [`Blob::len`], and the forgotten values must be owned elsewhere.
+    pub unsafe fn set_len(&mut self, len: usize) {
+        self.data.truncate(len * self.meta.layout.size());
+        self.len = len;
     }
 
-    pub unsafe fn ptr<T: 'static>(&self) -> Ptr<'_, T> {
-        unsafe { Ptr::new(self.data.as_ptr() as *mut T) }
+    /// Allocated capacity in bytes.
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
     }
 
-    pub fn len(&self) -> usize {
-        self.data.len() / self.meta.layout.size()
+    /// Reserves capacity for at least `additional` more values, zero sized values never
+    /// allocate.
+    pub fn reserve(&mut self, additional: usize) {
+        if !self.meta.is_zero_sized() {
+            self.data.reserve(additional * self.meta.layout.size());
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
     }
 
     pub fn is_empty(&self) -> bool {
-        self.data.len() == 0
+        self.len == 0
     }
 
+    /// Drops every value in the blob, keeping the allocation.
     pub fn clear(&mut self) {
+        if let Some(drop) = self.meta.drop {
+            // Forget the values first so a panicking drop can't lead to a double drop.
+            let len = std::mem::take(&mut self.len);
+            for index in 0..len {
+                drop(self.value_ptr(index));
+            }
+        }
+
+        self.len = 0;
         self.data.clear();
     }
 
+    /// Hands the bytes over without dropping the values, the caller becomes responsible for
+    /// them.
     pub fn into_raw(mut self) -> (Vec<u8>, TypeMeta) {
-        (std::mem::take(&mut self.data), self.meta)
+        let data = self.as_bytes().to_vec();
+        self.len = 0;
+        (data, self.meta)
     }
 
-    pub fn to_vec<T: 'static>(self) -> Vec<T> {
+    pub fn to_vec<T: 'static>(mut self) -> Vec<T> {
+        assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
+
+        // The bytes aren't allocated with the layout of `T`, so the values are copied into a
+        // vector of their own rather than adopting the allocation.
+        let mut values = Vec::<T>::with_capacity(self.len);
         unsafe {
-            let values = Vec::from_raw_parts(
-                self.data.as_ptr() as *mut T,
-                self.len(),
-                self.data.capacity() / self.meta.layout.size(),
+            ptr::copy_nonoverlapping(
+                self.data.as_ptr() as *const T,
+                values.as_mut_ptr(),
+                self.len,
             );
+            values.set_len(self.len);
+        }
+
+        self.len = 0;
+        values
+    }
 
-            std::mem::forget(self);
+    /// Pointer to the value at `index`, which may be one past the end. Zero sized values all
+    /// share a dangling aligned pointer, as they don't occupy any bytes.
+    fn value_ptr(&self, index: usize) -> *mut u8 {
+        match self.meta.is_zero_sized() {
+            true => AlignedBytes::dangling(self.meta.layout.align()).as_ptr(),
+            false => unsafe { self.data.ptr.as_ptr().add(index * self.meta.layout.size()) },
+        }
+    }
 
-            values
+    fn check_bounds(&self, index: usize) {
+        if index >= self.len {
+            panic!("Index out of bounds: {}", index);
         }
     }
+
+    /// Makes room for a value at `index`, returning the uninitialized slot.
+    unsafe fn shift_right(&mut self, index: usize) -> *mut u8 {
+        if index > self.len {
+            panic!("Index out of bounds: {}", index);
+        }
+
+        let size = self.meta.layout.size();
+        let (offset, end) = (index * size, self.data.len());
+        self.data.resize(end + size);
+        self.len += 1;
+
+        unsafe {
+            let src = self.data.as_mut_ptr().add(offset);
+            ptr::copy(src, src.add(size), end - offset);
+            src
+        }
+    }
+
+    /// Moves the last value into the slot at `index`, whose value must have been moved out.
+    fn swap_last_into(&mut self, index: usize) {
+        let size = self.meta.layout.size();
+        let last = (self.len - 1) * size;
+        let offset = index * size;
+        if offset != last {
+            self.data.copy_within(last..last + size, offset);
+        }
+
+        self.data.truncate(last);
+        self.len -= 1;
+    }
 }
 
 impl Drop for Blob {
     fn drop(&mut self) {
-        if let Some(drop) = self.meta.drop {
-            for index in 0..self.len() {
-                let offset = index * self.meta.layout.size();
-                let value = unsafe { self.data.as_mut_ptr().add(offset) };
-                drop(value);
-            }
-        }
-
-        self.data.clear();
+        self.clear();
     }
 }
 
 impl From<BlobCell> for Blob {
     fn from(value: BlobCell) -> Self {
-        let blob = Self {
-            data: unsafe {
-                Vec::from_raw_parts(
-                    value.data.as_ptr() as *mut u8,
-                    value.data.len(),
-                    value.data.capacity(),
-                )
-            },
-            meta: value.meta,
-        };
-
-        std::mem::forget(value);
-
-        blob
+        let meta = value.meta;
+        Self {
+            data: value.into_bytes(),
+            len: 1,
+            meta,
+        }
     }
 }
 
+/// A single type erased value, stored with the alignment its [`TypeMeta`] asks for.
 pub struct BlobCell {
-    data: Vec<u8>,
+    data: AlignedBytes,
     meta: TypeMeta,
 }
 
 impl BlobCell {
     pub fn new<T: 'static>(value: T) -> Self {
-        let meta = TypeMeta::new::<T>();
-        let mut data = vec![0u8; meta.layout.size()];
+        unsafe { Self::init(TypeMeta::new::<T>(), |dst| ptr::write(dst as *mut T, value)) }
+    }
 
-        unsafe { ptr::write(data.as_mut_ptr() as *mut T, value) };
+    /// Builds the value in place.
+    ///
+    /// # Safety
+    /// `init` must write a valid value of the type described by `meta` to the pointer.
+    pub unsafe fn init(meta: TypeMeta, init: impl FnOnce(*mut u8)) -> Self {
+        let mut data = AlignedBytes::new(meta.layout.align());
+        data.resize(meta.layout.size());
+        init(data.as_mut_ptr());
 
         Self { data, meta }
     }
 
+    /// # Safety
+    /// `data` must contain a valid value of the type described by `meta`.
     pub unsafe fn from_raw(data: Vec<u8>, meta: TypeMeta) -> Self {
-        Self { data, meta }
+        assert_eq!(data.len(), meta.layout.size());
+
+        Self {
+            data: AlignedBytes::from_slice(&data, meta.layout.align()),
+            meta,
+        }
     }
 
     pub fn data(&self) -> &[u8] {
@@ -339,52 +563,52 @@ impl BlobCell {
         unsafe { (self.data.as_mut_ptr() as *mut T).as_mut().unwrap() }
     }
 
-    pub fn into_raw(mut self) -> (Vec<u8>, TypeMeta) {
-        let data = std::mem::take(&mut self.data);
+    /// Hands the bytes over without dropping the value, the caller becomes responsible for it.
+    pub fn into_raw(self) -> (Vec<u8>, TypeMeta) {
         let meta = self.meta;
-
-        std::mem::forget(self);
-
-        (data, meta)
+        (self.into_bytes().to_vec(), meta)
     }
 
     pub fn into_value<T: 'static>(self) -> T {
         assert_eq!(std::mem::size_of::<T>(), self.meta.layout.size());
 
-        let value = unsafe { std::ptr::read(self.data.as_ptr() as *const T) };
-
-        std::mem::forget(self);
+        let data = self.into_bytes();
+        unsafe { std::ptr::read(data.as_ptr() as *const T) }
+    }
 
-        value
+    /// Moves the bytes out without dropping the value.
+    fn into_bytes(self) -> AlignedBytes {
+        let align = self.meta.layout.align();
+        let mut cell = std::mem::ManuallyDrop::new(self);
+        std::mem::replace(&mut cell.data, AlignedBytes::new(align))
     }
 }
 
 impl Drop for BlobCell {
     fn drop(&mut self) {
         if let Some(drop) = self.meta.drop {
-            let value = self.data.as_mut_ptr();
-            drop(value);
+            drop(self.data.as_mut_ptr());
         }
-
-        self.data.clear();
     }
 }
 
 pub struct Ptr<'a, T: 'static> {
     data: *mut T,
+    len: usize,
     _marker: PhantomData<&'a T>,
 }
 
 impl<'a, T: 'static> Ptr<'a, T> {
-    pub unsafe fn new(data: *mut T) -> Self {
+    pub unsafe fn new(data: *mut T, len: usize) -> Self {
         Self {
             data,
+            len,
             _marker: Default::default(),
         }
     }
 
     pub unsafe fn get(&self, index: usize) -> Option<&'a T> {
-        if index < std::mem::size_of::<T>() {
+        if index < self.len {
             Some(unsafe { &*self.data.add(index) })
         } else {
             None
@@ -392,17 +616,57 @@ impl<'a, T: 'static> Ptr<'a, T> {
     }
 
     pub unsafe fn get_mut(&mut self, index: usize) -> Option<&'a mut T> {
-        if index < std::mem::size_of::<T>() {
+        if index < self.len {
             Some(unsafe { &mut *self.data.add(index) })
         } else {
             None
         }
     }
+
+    /// # Safety
+    /// The pointed values must not be mutated while the slice is alive.
+    pub unsafe fn as_slice(&self) -> &'a [T] {
+        match self.len {
+            0 => &[],
+            len => unsafe { std::slice::from_raw_parts(self.data, len) },
+        }
+    }
+
+    /// # Safety
+    /// No other reference to the pointed values may be alive while the slice is.
+    pub unsafe fn as_mut_slice(&mut self) -> &'a mut [T] {
+        match self.len {
+            0 => &mut [],
+            len => unsafe { std::slice::from_raw_parts_mut(self.data, len) },
+        }
+    }
 }
 
 #[allow(unused_imports)]
 mod tests {
     use super::{Blob, BlobCell, TypeMeta};
+    use std::{cell::Cell, rc::Rc};
+
+    /// Counts how many times values sharing the counter were dropped.
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    thread_local! {
+        static ZST_DROPS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Marker;
+
+    impl Drop for Marker {
+        fn drop(&mut self) {
+            ZST_DROPS.with(|drops| drops.set(drops.get() + 1));
+        }
+    }
 
     #[test]
     fn blob_from_raw() {
@@ -418,7 +682,7 @@ mod tests {
 
         let meta = TypeMeta::new::<i32>();
 
-        let blob = unsafe { Blob::from_raw(bytes, meta) };
+        let blob = unsafe { Blob::from_raw(bytes, values.len(), meta) };
         for (index, value) in values.iter().enumerate() {
             assert_eq!(blob.get::<i32>(index), Some(value));
         }
@@ -501,7 +765,7 @@ mod tests {
         };
 
         let mut blob = Blob::new::<i32>();
-        unsafe { blob.append_raw(bytes) };
+        unsafe { blob.append_raw(bytes, values.len()) };
 
         for (index, value) in values.iter().enumerate() {
             assert_eq!(blob.get::<i32>(index), Some(value));
@@ -607,4 +871,142 @@ mod tests {
 
         assert_eq!(blob.into_value::<i32>(), 10);
     }
+
+    #[test]
+    fn blob_drops_every_value_once() {
+        let drops = Rc::new(Cell::new(0));
+        let mut blob = Blob::new::<Counted>();
+        for _ in 0..5 {
+            blob.push(Counted(drops.clone()));
+        }
+
+        drop(blob.remove::<Counted>(1));
+        drop(blob.swap_remove::<Counted>(0));
+        assert_eq!(drops.get(), 2);
+        assert_eq!(blob.len(), 3);
+
+        blob.clear();
+        assert_eq!(drops.get(), 5);
+        assert!(blob.is_empty());
+
+        blob.append(vec![Counted(drops.clone()), Counted(drops.clone())]);
+        blob.insert(1, Counted(drops.clone()));
+        drop(blob);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn blob_insert_at_bounds() {
+        let mut blob = Blob::new::<String>();
+        blob.insert(0, String::from("b"));
+        blob.insert(0, String::from("a"));
+        blob.insert(2, String::from("d"));
+        blob.insert(2, String::from("c"));
+
+        let values = blob.to_vec::<String>();
+        assert_eq!(values, ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds: 2")]
+    fn blob_insert_out_of_bounds() {
+        let mut blob = Blob::new::<String>();
+        blob.push(String::from("a"));
+        blob.insert(2, String::from("b"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds: 0")]
+    fn blob_remove_from_empty() {
+        let mut blob = Blob::new::<String>();
+        blob.swap_remove::<String>(0);
+    }
+
+    #[test]
+    fn blob_raw_moves_drop_once() {
+        let drops = Rc::new(Cell::new(0));
+        let meta = TypeMeta::new::<Counted>();
+        let mut blob = Blob::new::<Counted>();
+        for _ in 0..3 {
+            blob.push(Counted(drops.clone()));
+        }
+
+        let removed = unsafe { blob.remove_raw(0) };
+        let swapped = unsafe { blob.swap_remove_raw(0) };
+        assert_eq!(drops.get(), 0);
+        assert_eq!(blob.len(), 1);
+
+        drop(unsafe { BlobCell::from_raw(removed, meta) });
+        assert_eq!(drops.get(), 1);
+
+        unsafe { blob.insert_raw(1, swapped) };
+        let (bytes, _) = BlobCell::new(Counted(drops.clone())).into_raw();
+        unsafe { blob.append_raw(bytes, 1) };
+        assert_eq!(blob.len(), 3);
+
+        let mut other = Blob::new::<Counted>();
+        unsafe { other.extend_raw(blob.as_bytes(), blob.len()) };
+        let (_, meta) = blob.into_raw();
+        assert_eq!(drops.get(), 1);
+
+        drop(other);
+        assert_eq!(drops.get(), 4);
+        assert!(meta.drop.is_some());
+    }
+
+    #[test]
+    fn blob_to_vec_moves_values() {
+        let drops = Rc::new(Cell::new(0));
+        let mut blob = Blob::new::<Counted>();
+        blob.append((0..4).map(|_| Counted(drops.clone())).collect());
+
+        let values = blob.to_vec::<Counted>();
+        assert_eq!(drops.get(), 0);
+        drop(values);
+        assert_eq!(drops.get(), 4);
+
+        let cell = BlobCell::new(Counted(drops.clone()));
+        drop(Blob::from(cell));
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn blob_zero_sized_values() {
+        let mut blob = Blob::new::<Marker>();
+        blob.push(Marker);
+        blob.push(Marker);
+        blob.insert(1, Marker);
+        assert_eq!(blob.len(), 3);
+        assert!(blob.get::<Marker>(2).is_some());
+        assert!(blob.get::<Marker>(3).is_none());
+
+        drop(blob.swap_remove::<Marker>(0));
+        assert_eq!(ZST_DROPS.with(Cell::get), 1);
+        assert_eq!(blob.len(), 2);
+
+        drop(blob);
+        assert_eq!(ZST_DROPS.with(Cell::get), 3);
+    }
+
+    #[test]
+    fn blob_aligned_values() {
+        #[repr(align(64))]
+        struct Aligned(u8);
+
+        let mut blob = Blob::new::<Aligned>();
+        for value in 0..8 {
+            blob.insert(0, Aligned(value));
+        }
+
+        let ptr = blob.get::<Aligned>(3).unwrap() as *const Aligned;
+        assert_eq!(ptr as usize % 64, 0);
+        assert_eq!(blob.remove::<Aligned>(0).0, 7);
+
+        let cell = BlobCell::new(Aligned(1));
+        assert_eq!(cell.data().as_ptr() as usize % 64, 0);
+        let (bytes, meta) = cell.into_raw();
+        let cell = unsafe { BlobCell::from_raw(bytes, meta) };
+        assert_eq!(cell.get::<Aligned>() as *const Aligned as usize % 64, 0);
+        assert_eq!(cell.into_value::<Aligned>().0, 1);
+    }
 }
@@ -1,7 +1,69 @@
 use fixedbitset::FixedBitSet;
 
+/// Nodes of a cycle in order, each one is a dependency of the next. The first node is repeated
+/// at the end to close the cycle.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CyclicDependency(pub Vec<usize>);
+
+/// Orders the nodes so every node comes after its dependencies.
+fn sort(dependents: &[FixedBitSet]) -> Result<Vec<usize>, CyclicDependency> {
+    let mut order = vec![];
+    let mut visited = vec![false; dependents.len()];
+    let mut recursion_stack = vec![false; dependents.len()];
+
+    fn visit(
+        index: usize,
+        dependents: &[FixedBitSet],
+        visited: &mut Vec<bool>,
+        recursion_stack: &mut Vec<bool>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), Vec<usize>> {
+        if recursion_stack[index] {
+            return Err(vec![index]);
+        }
+
+        if visited[index] {
+            return Ok(());
+        }
+
+        visited[index] = true;
+        recursion_stack[index] = true;
+
+        for dependent in dependents[index].ones() {
+            if let Err(mut cycle) = visit(dependent, dependents, visited, recursion_stack, order) {
+                cycle.push(index);
+                return Err(cycle);
+            }
+        }
+
+        recursion_stack[index] = false;
+        order.push(index);
+        Ok(())
+    }
+
+    for index in 0..dependents.len() {
+        if !visited[index] {
+            if let Err(mut cycle) = visit(
+                index,
+                dependents,
+                &mut visited,
+                &mut recursion_stack,
+                &mut order,
+            ) {
+                // The path also holds the nodes leading into the cycle, which start before
+                // the first visit of the repeated node.
+                cycle.reverse();
+                let repeated = cycle[cycle.len() - 1];
+                let start = cycle.iter().position(|index| *index == repeated).unwrap();
+                return Err(CyclicDependency(cycle.split_off(start)));
+            }
+        }
+    }
+
+    order.reverse();
+    Ok(order)
+}
+
 pub struct IndexDag<N> {
     nodes: Vec<N>,
     dependents: Vec<FixedBitSet>,
@@ -87,64 +149,18 @@ impl<N> IndexDag<N> {
 
     pub fn build(&mut self) -> Result<&[usize], CyclicDependency> {
         if self.is_dirty {
-            let mut order = vec![];
-            let mut visited = vec![false; self.nodes.len()];
-            let mut recursion_stack = vec![false; self.nodes.len()];
-
-            fn visit(
-                index: usize,
-                dependents: &Vec<FixedBitSet>,
-                visited: &mut Vec<bool>,
-                recursion_stack: &mut Vec<bool>,
-                order: &mut Vec<usize>,
-            ) -> Result<(), Vec<usize>> {
-                if recursion_stack[index] {
-                    return Err(vec![index]);
-                }
-
-                if visited[index] {
-                    return Ok(());
-                }
-
-                visited[index] = true;
-                recursion_stack[index] = true;
-
-                for dependent in dependents[index].ones() {
-                    if let Err(mut cycle) =
-                        visit(dependent, dependents, visited, recursion_stack, order)
-                    {
-                        cycle.push(index);
-                        return Err(cycle);
-                    }
-                }
-
-                recursion_stack[index] = false;
-                order.push(index);
-                Ok(())
-            }
-
-            for index in 0..self.nodes.len() {
-                if !visited[index] {
-                    if let Err(mut cycle) = visit(
-                        index,
-                        &self.dependents,
-                        &mut visited,
-                        &mut recursion_stack,
-                        &mut order,
-                    ) {
-                        cycle.reverse();
-                        return Err(CyclicDependency(cycle));
-                    }
-                }
-            }
-
-            order.reverse();
-            self.topology = order;
+            self.topology = sort(&self.dependents)?;
         }
 
         Ok(&self.topology)
     }
 
+    /// Checks for cycles without storing the order, so the dag can be checked through a shared
+    /// reference.
+    pub fn check(&self) -> Result<(), CyclicDependency> {
+        sort(&self.dependents).map(|_| ())
+    }
+
     pub fn into_immutable(self) -> ImmutableIndexDag<N> {
         ImmutableIndexDag {
             nodes: self.nodes.into_boxed_slice(),
@@ -273,6 +289,27 @@ mod tests {
         assert!(cycle.0.contains(&node3));
     }
 
+    #[test]
+    fn cycle_path() {
+        let mut dag = super::IndexDag::new();
+        let nodes = (0..5).map(|i| dag.add_node(i)).collect::<Vec<_>>();
+
+        // Node0 and Node1 lead into the cycle without being part of it.
+        dag.add_dependency(nodes[0], nodes[1]);
+        dag.add_dependency(nodes[1], nodes[2]);
+        dag.add_dependency(nodes[2], nodes[3]);
+        dag.add_dependency(nodes[3], nodes[4]);
+        dag.add_dependency(nodes[4], nodes[2]);
+
+        let cycle = dag.check().unwrap_err();
+        assert_eq!(cycle.0, [nodes[2], nodes[3], nodes[4], nodes[2]]);
+        assert_eq!(dag.build().unwrap_err(), cycle);
+
+        dag.remove_dependency(nodes[4], nodes[2]);
+        assert!(dag.check().is_ok());
+        assert_eq!(dag.build().unwrap(), &nodes[..]);
+    }
+
     #[test]
     fn no_dependencies() {
         let mut dag = super::IndexDag::new();
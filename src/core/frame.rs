@@ -1,3 +1,12 @@
+/// Number of frames between two [`World::check_change_frames`](crate::world::World::check_change_frames)
+/// passes run by [`World::update`](crate::world::World::update).
+pub const CHECK_FRAME_THRESHOLD: u32 = 518_400_000;
+
+/// Oldest a frame can get before it's clamped. Frames are compared relative to the current frame,
+/// so clamping at least every [`CHECK_FRAME_THRESHOLD`] frames keeps every frame within half the
+/// frame range of the current one, which [`Frame::latest`] relies on.
+pub const MAX_FRAME_AGE: u32 = i32::MAX as u32 - (2 * CHECK_FRAME_THRESHOLD - 1);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Frame(pub u32);
 
@@ -12,14 +21,40 @@ impl Frame {
         self.0 = value;
     }
 
+    /// Number of frames from `other` to this frame, wrapping around.
+    pub fn relative_to(self, other: Self) -> Self {
+        Self(self.0.wrapping_sub(other.0))
+    }
+
     /// Checks if the current frame is newer than the target frame using the global frame as a reference.
+    /// Both ages are capped at [`MAX_FRAME_AGE`], so frames clamped by [`Frame::check`] stay ordered.
     pub fn is_newer(self, current: Self, last: Self) -> bool {
-        let last_diff = current.0.wrapping_sub(last.0);
-        let target_diff = current.0.wrapping_sub(self.0);
+        let last_diff = current.relative_to(last).0.min(MAX_FRAME_AGE);
+        let target_diff = current.relative_to(self).0.min(MAX_FRAME_AGE);
 
         target_diff < last_diff
     }
 
+    /// Clamps the frame to at most [`MAX_FRAME_AGE`] frames before `current`.
+    /// Returns true if it was clamped.
+    pub fn check(&mut self, current: Self) -> bool {
+        if current.relative_to(*self).0 > MAX_FRAME_AGE {
+            *self = Self(current.0.wrapping_sub(MAX_FRAME_AGE));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The more recent of the two frames, assuming they're less than half the frame range apart.
+    pub fn latest(self, other: Self) -> Self {
+        if (other.0.wrapping_sub(self.0) as i32) > 0 {
+            other
+        } else {
+            self
+        }
+    }
+
     pub fn previous(self) -> Self {
         Self(self.0.wrapping_sub(1))
     }
@@ -41,19 +76,19 @@ impl std::ops::Add for Frame {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0)
+        Self(self.0.wrapping_add(rhs.0))
     }
 }
 
 impl std::ops::AddAssign<Self> for Frame {
     fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
+        self.0 = self.0.wrapping_add(rhs.0);
     }
 }
 
 impl std::ops::AddAssign<u32> for Frame {
     fn add_assign(&mut self, rhs: u32) {
-        self.0 += rhs;
+        self.0 = self.0.wrapping_add(rhs);
     }
 }
 
@@ -61,19 +96,19 @@ impl std::ops::Sub for Frame {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0)
+        Self(self.0.wrapping_sub(rhs.0))
     }
 }
 
 impl std::ops::SubAssign<Self> for Frame {
     fn sub_assign(&mut self, rhs: Self) {
-        self.0 -= rhs.0;
+        self.0 = self.0.wrapping_sub(rhs.0);
     }
 }
 
 impl std::ops::SubAssign<u32> for Frame {
     fn sub_assign(&mut self, rhs: u32) {
-        self.0 -= rhs;
+        self.0 = self.0.wrapping_sub(rhs);
     }
 }
 
@@ -114,4 +149,42 @@ impl ObjectStatus {
             modified: Frame::ZERO,
         }
     }
+
+    /// Clamps both frames, see [`Frame::check`].
+    pub fn check(&mut self, current: Frame) {
+        self.added.check(current);
+        self.modified.check(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Frame, MAX_FRAME_AGE};
+
+    #[test]
+    fn is_newer_wraps() {
+        let current = Frame(5);
+        let last = Frame(u32::MAX - 2);
+
+        assert!(Frame(1).is_newer(current, last));
+        assert!(Frame(u32::MAX).is_newer(current, last));
+        assert!(!Frame(u32::MAX - 5).is_newer(current, last));
+        assert!(!Frame(6).is_newer(current, last));
+    }
+
+    #[test]
+    fn check_clamps_old_frames() {
+        let current = Frame(10);
+        let mut recent = Frame(u32::MAX - 10);
+        assert!(!recent.check(current));
+        assert_eq!(recent, Frame(u32::MAX - 10));
+
+        let mut stale = Frame(20);
+        assert!(stale.check(current));
+        assert_eq!(current.relative_to(stale).get(), MAX_FRAME_AGE);
+
+        // Clamped frames are still older than any frame a system could have last run at.
+        assert!(!stale.is_newer(current, Frame(15)));
+        assert!(Frame(9).is_newer(current, Frame(15)));
+    }
 }
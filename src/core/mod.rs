@@ -1,12 +1,24 @@
+#[cfg(feature = "alloc-audit")]
+pub mod alloc;
 pub mod bitset;
 pub mod blob;
 pub mod dag;
 pub mod frame;
+pub mod hash;
+pub mod rng;
+pub mod runtime;
 pub mod sparse;
+pub mod task;
 
+#[cfg(feature = "alloc-audit")]
+pub use alloc::*;
 pub use bitset::*;
 pub use blob::*;
 pub use dag::*;
 pub use frame::*;
+pub use hash::*;
 pub use indexmap::*;
+pub use rng::*;
+pub use runtime::*;
 pub use sparse::*;
+pub use task::*;
@@ -0,0 +1,42 @@
+/// Source of random numbers for sampling. Implemented by [`SplitMix64`] for deterministic runs.
+pub trait RngSource {
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform value in `0..bound`. `bound` must not be zero.
+    fn below(&mut self, bound: u64) -> u64 {
+        // Rejects the values past the largest multiple of `bound` to avoid modulo bias.
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+        loop {
+            let value = self.next_u64();
+            if value <= zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl<R: RngSource + ?Sized> RngSource for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// Small seeded generator that produces the same sequence on every platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+impl RngSource for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+}
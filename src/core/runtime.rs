@@ -0,0 +1,37 @@
+use serde::{Serialize, de::DeserializeOwned};
+use serde_value::Value;
+
+/// Serializes a value carried across process restarts by
+/// [`App::save_runtime_state`](crate::app::App::save_runtime_state), `None` if it can't be.
+pub fn save_runtime_value<T: Serialize>(value: &T) -> Option<Value> {
+    serde_value::to_value(value).ok()
+}
+
+/// Reads a value written by [`save_runtime_value`], `None` if it doesn't hold one.
+pub fn load_runtime_value<T: DeserializeOwned>(value: &Value) -> Option<T> {
+    value.clone().deserialize_into().ok()
+}
+
+/// Sequence of optional values, read back with [`decode_parts`].
+pub fn encode_parts(parts: Vec<Option<Value>>) -> Value {
+    Value::Seq(
+        parts
+            .into_iter()
+            .map(|part| Value::Option(part.map(Box::new)))
+            .collect(),
+    )
+}
+
+pub fn decode_parts(value: &Value) -> Option<Vec<Option<&Value>>> {
+    let Value::Seq(parts) = value else {
+        return None;
+    };
+
+    parts
+        .iter()
+        .map(|part| match part {
+            Value::Option(part) => Some(part.as_deref()),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,181 @@
+use std::{
+    any::Any,
+    marker::PhantomData,
+    panic::AssertUnwindSafe,
+    sync::{
+        Arc, Condvar, Mutex,
+        mpsc::{Receiver, Sender, channel},
+    },
+    thread::JoinHandle,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Worker threads that are kept alive and reused by every [`TaskPool::scope`].
+pub struct TaskPool {
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl TaskPool {
+    /// Spawns `threads` workers, at least one.
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|index| {
+                let receiver = receiver.clone();
+                std::thread::Builder::new()
+                    .name(format!("task-pool-{}", index))
+                    .spawn(move || Self::work(&receiver))
+                    .unwrap()
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    fn work(receiver: &Mutex<Receiver<Job>>) {
+        loop {
+            let job = receiver.lock().unwrap().recv();
+            match job {
+                Ok(job) => job(),
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Runs `f`, which can spawn tasks borrowing from outside the scope. Returns once every
+    /// spawned task finished, resuming the first panic of a task.
+    pub fn scope<'env, F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState::default()),
+            _scope: PhantomData,
+            _env: PhantomData,
+        };
+
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        scope.state.wait();
+
+        if let Some(panic) = scope.state.panic.lock().unwrap().take() {
+            std::panic::resume_unwind(panic);
+        }
+
+        match result {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+impl Drop for TaskPool {
+    fn drop(&mut self) {
+        // Closing the channel stops the workers once the queued jobs ran.
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[derive(Default)]
+struct ScopeState {
+    pending: Mutex<usize>,
+    done: Condvar,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl ScopeState {
+    fn wait(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        while *pending > 0 {
+            pending = self.done.wait(pending).unwrap();
+        }
+    }
+}
+
+/// Spawns tasks on a [`TaskPool`], see [`TaskPool::scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope TaskPool,
+    state: Arc<ScopeState>,
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    pub fn spawn(&self, f: impl FnOnce() + Send + 'scope) {
+        *self.state.pending.lock().unwrap() += 1;
+
+        let state = self.state.clone();
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(panic) = std::panic::catch_unwind(AssertUnwindSafe(f)) {
+                state.panic.lock().unwrap().get_or_insert(panic);
+            }
+
+            let mut pending = state.pending.lock().unwrap();
+            *pending -= 1;
+            if *pending == 0 {
+                state.done.notify_all();
+            }
+        });
+
+        // The scope waits for every job before returning, so nothing borrowed outlives the job.
+        let job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+        self.pool.sender.as_ref().unwrap().send(job).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaskPool;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn scope_borrows() {
+        let pool = TaskPool::new(3);
+        let count = AtomicUsize::new(0);
+        for _ in 0..4 {
+            pool.scope(|scope| {
+                for _ in 0..8 {
+                    scope.spawn(|| {
+                        count.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+            });
+        }
+
+        assert_eq!(count.load(Ordering::Relaxed), 32);
+        assert_eq!(pool.threads(), 3);
+    }
+
+    #[test]
+    fn scope_resumes_panic() {
+        let pool = TaskPool::new(2);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.scope(|scope| scope.spawn(|| panic!("task failed")))
+        }));
+
+        let panic = result.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"task failed"));
+
+        // The worker survives the panic.
+        let count = AtomicUsize::new(0);
+        pool.scope(|scope| {
+            scope.spawn(|| {
+                count.fetch_add(1, Ordering::Relaxed);
+            })
+        });
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+    }
+}
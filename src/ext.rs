@@ -1,7 +1,25 @@
+use std::{collections::HashSet, sync::Mutex};
+
 pub fn short_type_name<T: ?Sized>() -> &'static str {
     let name = std::any::type_name::<T>();
     match name.rfind(":") {
-        Some(index) => &name[index..],
+        Some(index) => &name[index + 1..],
         None => name,
     }
 }
+
+/// Returns a `'static` copy of the name, every distinct name is only leaked once.
+pub fn intern_name(name: String) -> &'static str {
+    static NAMES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+    let mut names = NAMES.lock().unwrap();
+    let names = names.get_or_insert_with(HashSet::new);
+    match names.get(name.as_str()) {
+        Some(name) => name,
+        None => {
+            let name: &'static str = Box::leak(name.into_boxed_str());
+            names.insert(name);
+            name
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod app;
+pub mod core;
+pub mod ext;
+pub mod system;
+#[cfg(feature = "transform")]
+pub mod transform;
+pub mod world;
@@ -1,40 +1,39 @@
-use core::Frame;
-
-use app::App;
-use system::{
-    query::{Added, Query},
-    schedule::Phase,
-};
-use world::{
-    Command, CommandBuffer, Component, Event, EventReader, EventWriter, Resource, Resources,
-    Spawner, World,
+use hive_ecs::{
+    app::App,
+    core::Frame,
+    system::{
+        query::{Added, Query},
+        schedule::Phase,
+    },
+    world::{
+        self, Command, CommandQueue, Component, Event, EventReader, EventWriter, Resource,
+        Resources, Spawner, World,
+    },
 };
 
-pub mod app;
-pub mod core;
-pub mod ext;
-pub mod system;
-pub mod world;
-
 fn main() {
-    // App::new()
-    //     .register::<Name>()
-    //     .register::<Age>()
-    //     .add_systems(Start, |mut events: EventWriter<TestEvent>| {
-    //         events.send(TestEvent);
-    //     })
-    //     .add_systems(Update, |events: EventReader<TestEvent>| {
-    //         for event in events {
-    //             println!("{:?}", event);
-    //         }
-    //     })
-    //     .build()
-    //     .run(Start)
-    //     .run(Update);
+    let mut builder = App::new();
+    builder
+        .register::<Name>()
+        .register::<Age>()
+        .add_systems(Start, |mut events: EventWriter<TestEvent>| {
+            events.send(TestEvent);
+        })
+        .add_systems(Update, |events: EventReader<TestEvent>| {
+            for event in events {
+                println!("{:?}", event);
+            }
+        });
+
+    let mut app = builder.build();
+    app.run(Start);
+    app.run(Update);
 
     let mut resources = Resources::new();
-    let age = resources.add::<true, _>(Age(30));
-    let name = resources.add::<true, _>(Name("John"));
+    let age = resources.register::<true, Age>();
+    let name = resources.register::<true, Name>();
+    resources.add::<true, _>(Age(30));
+    resources.add::<true, _>(Name("John"));
 
     let age = resources.get::<Age>(age).unwrap();
     println!("{:?}", age);
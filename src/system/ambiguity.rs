@@ -0,0 +1,107 @@
+use super::{SystemName, SystemNode};
+use crate::{
+    core::{FixedBitSet, SparseIndex},
+    world::{ComponentId, ResourceId},
+};
+use std::collections::HashMap;
+
+/// How [`Schedule::build`](super::schedule::Schedule::build) reports ambiguities.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmbiguityDetection {
+    #[default]
+    Ignore,
+    /// Prints the ambiguities to stderr.
+    Warn,
+    /// Fails the build with [`ScheduleBuildError::Ambiguous`](super::schedule::ScheduleBuildError::Ambiguous).
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContestedAccess {
+    Component(ComponentId),
+    Resource(ResourceId),
+}
+
+/// Two systems of a phase that access the same data, at least one of them mutably, without
+/// being ordered by [`before`](super::IntoSystemConfigs::before) or
+/// [`after`](super::IntoSystemConfigs::after). They run in the order they were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ambiguity {
+    pub phase: &'static str,
+    pub systems: [Option<SystemName>; 2],
+    pub access: Vec<ContestedAccess>,
+}
+
+impl std::fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [a, b] = &self.systems;
+        write!(
+            f,
+            "Systems {} and {} in phase {} both access {:?} without an order",
+            a.as_deref().unwrap_or("unknown"),
+            b.as_deref().unwrap_or("unknown"),
+            self.phase,
+            self.access
+        )
+    }
+}
+
+/// Finds the conflicting pairs of systems that aren't ordered by their explicit dependencies.
+/// Exclusive systems are always ordered by insertion and never reported.
+pub(crate) fn find_ambiguities(phase: &'static str, nodes: &[SystemNode]) -> Vec<Ambiguity> {
+    let indices = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.system.meta.id, index))
+        .collect::<HashMap<_, _>>();
+
+    // Systems each system runs after, directly or through other systems.
+    let mut ancestors = vec![FixedBitSet::with_capacity(nodes.len()); nodes.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, node) in nodes.iter().enumerate() {
+            for dependency in node.dependencies.iter().filter_map(|id| indices.get(id)) {
+                let mut reachable = ancestors[*dependency].clone();
+                reachable.insert(*dependency);
+                if !reachable.is_subset(&ancestors[index]) {
+                    ancestors[index].union_with(&reachable);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut ambiguities = vec![];
+    for (index, node) in nodes.iter().enumerate() {
+        for (other_index, other) in nodes.iter().enumerate().skip(index + 1) {
+            let (meta, other_meta) = (&node.system.meta, &other.system.meta);
+            if meta.exclusive
+                || other_meta.exclusive
+                || ancestors[index].contains(other_index)
+                || ancestors[other_index].contains(index)
+                || node.allows_ambiguity(other)
+                || other.allows_ambiguity(node)
+            {
+                continue;
+            }
+
+            let components = meta.components.conflicting(&other_meta.components);
+            let resources = meta.resources.conflicting(&other_meta.resources);
+            let access = components
+                .map(|id| ContestedAccess::Component(ComponentId::from_usize(id)))
+                .chain(resources.map(|id| ContestedAccess::Resource(ResourceId::from_usize(id))))
+                .collect::<Vec<_>>();
+
+            if !access.is_empty() {
+                ambiguities.push(Ambiguity {
+                    phase,
+                    systems: [meta.name.clone(), other_meta.name.clone()],
+                    access,
+                });
+            }
+        }
+    }
+
+    ambiguities
+}
@@ -1,9 +1,18 @@
-use super::{IntoSystemConfigs, SystemConfig, SystemConfigs, SystemId, SystemMeta};
+use super::{
+    IntoSystemConfigs, InvalidArgs, SystemConfig, SystemConfigs, SystemId, SystemMeta,
+    error::SystemOutput, readonly::check_read_only,
+};
 use crate::{
+    core::{decode_parts, encode_parts, load_runtime_value, save_runtime_value},
     system::{Access, SystemAccess},
-    world::{Entities, NonSend, NonSendMut, Resource, ResourceId, World, WorldCell},
+    world::{
+        Entities, NonSend, NonSendMut, Res, ResMut, ResVersioned, Resource, ResourceId,
+        TryResVersioned, World, WorldCell,
+    },
 };
-use std::any::Any;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_value::Value;
+use std::{any::Any, ptr::NonNull};
 
 #[allow(unused_variables)]
 pub unsafe trait SystemArg: Sized {
@@ -32,11 +41,24 @@ pub unsafe trait SystemArg: Sized {
         true
     }
 
+    /// The argument queues world mutations that are only applied after the system runs.
+    fn deferred() -> bool {
+        false
+    }
+
     fn apply(state: &mut Self::State, world: &mut World) {}
 
     fn access(state: &Self::State) -> Vec<SystemAccess> {
         vec![]
     }
+
+    /// State carried across restarts by [`App::save_runtime_state`](crate::app::App::save_runtime_state).
+    fn save(state: &Self::State) -> Option<Value> {
+        None
+    }
+
+    /// Restores state returned by [`SystemArg::save`].
+    fn restore(state: &mut Self::State, world: &mut World, saved: &Value) {}
 }
 
 pub type ArgItem<'world, 'state, A> = <A as SystemArg>::Item<'world, 'state>;
@@ -81,6 +103,31 @@ unsafe impl SystemArg for &World {
     }
 }
 
+/// Runs the system alone on the main thread, no other system of the phase runs alongside it.
+unsafe impl SystemArg for &mut World {
+    type Item<'world, 'state> = &'world mut World;
+
+    type State = ();
+
+    fn init(_: &mut World) -> Self::State {}
+
+    unsafe fn get<'world, 'state>(
+        _state: &'state mut Self::State,
+        mut world: WorldCell<'world>,
+        _system: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        unsafe { world.get_mut() }
+    }
+
+    fn exclusive() -> bool {
+        true
+    }
+
+    fn send() -> bool {
+        false
+    }
+}
+
 unsafe impl SystemArg for &Entities {
     type Item<'world, 'state> = &'world Entities;
 
@@ -99,6 +146,22 @@ unsafe impl SystemArg for &Entities {
     }
 }
 
+/// Resolves a resource through the id cached in the argument state, so the runtime path
+/// neither hashes the resource type nor borrows the world mutably.
+fn resource_ptr<R: Resource>(world: &World, id: ResourceId, kind: &str) -> NonNull<R> {
+    world
+        .resources()
+        .get_ptr_by_id(id)
+        .unwrap_or_else(|| panic!("{} not found: {}", kind, std::any::type_name::<R>()))
+        .cast()
+}
+
+fn resource_ptr_mut<R: Resource>(world: &World, id: ResourceId, kind: &str) -> NonNull<R> {
+    let ptr = resource_ptr(world, id, kind);
+    world.resources().modify(id, world.frame());
+    ptr
+}
+
 unsafe impl<R: Resource + Send> SystemArg for &R {
     type Item<'world, 'state> = &'world R;
 
@@ -108,12 +171,17 @@ unsafe impl<R: Resource + Send> SystemArg for &R {
         world.register_resource::<R>()
     }
 
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
     unsafe fn get<'world, 'state>(
-        _state: &'state mut Self::State,
+        state: &'state mut Self::State,
         world: WorldCell<'world>,
         _system: &SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        unsafe { world.get().resource() }
+        unsafe { resource_ptr::<R>(world.get(), *state, "Resource").as_ref() }
     }
 
     fn access(state: &Self::State) -> Vec<SystemAccess> {
@@ -130,12 +198,79 @@ unsafe impl<R: Resource + Send> SystemArg for &mut R {
         world.register_resource::<R>()
     }
 
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
     unsafe fn get<'world, 'state>(
-        _state: &'state mut Self::State,
-        mut world: WorldCell<'world>,
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
         _system: &SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        unsafe { world.get_mut().resource_mut() }
+        unsafe { resource_ptr_mut::<R>(world.get(), *state, "Resource").as_mut() }
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        vec![SystemAccess::resource(*state, Access::Write)]
+    }
+}
+
+unsafe impl<R: Resource + Send> SystemArg for Res<'_, R> {
+    type Item<'world, 'state> = Res<'world, R>;
+
+    type State = ResourceId;
+
+    fn init(world: &mut World) -> Self::State {
+        world.register_resource::<R>()
+    }
+
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        system: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        let world = unsafe { world.get() };
+        let resource = unsafe { resource_ptr::<R>(world, *state, "Resource").as_ref() };
+        let meta = world.resources().get_meta(*state).unwrap();
+
+        Res::new(resource, meta, system.frame, world.frame())
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        vec![SystemAccess::resource(*state, Access::Read)]
+    }
+}
+
+unsafe impl<R: Resource + Send> SystemArg for ResMut<'_, R> {
+    type Item<'world, 'state> = ResMut<'world, R>;
+
+    type State = ResourceId;
+
+    fn init(world: &mut World) -> Self::State {
+        world.register_resource::<R>()
+    }
+
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        system: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        let world = unsafe { world.get() };
+        let resource = unsafe { resource_ptr::<R>(world, *state, "Resource").as_mut() };
+        let meta = world.resources().get_meta(*state).unwrap();
+
+        ResMut::new(resource, meta, system.frame, world.frame())
     }
 
     fn access(state: &Self::State) -> Vec<SystemAccess> {
@@ -152,12 +287,18 @@ unsafe impl<R: Resource> SystemArg for NonSend<'_, R> {
         world.register_non_send_resource::<R>()
     }
 
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
     unsafe fn get<'world, 'state>(
-        _state: &'state mut Self::State,
+        state: &'state mut Self::State,
         world: WorldCell<'world>,
         _system: &SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        let resource = unsafe { world.get().non_send_resource::<R>() };
+        let resource =
+            unsafe { resource_ptr::<R>(world.get(), *state, "Non Send Resource").as_ref() };
 
         NonSend::new(resource)
     }
@@ -180,12 +321,18 @@ unsafe impl<R: Resource> SystemArg for NonSendMut<'_, R> {
         world.register_non_send_resource::<R>()
     }
 
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
     unsafe fn get<'world, 'state>(
-        _state: &'state mut Self::State,
-        mut world: WorldCell<'world>,
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
         _system: &SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        let resource = unsafe { world.get_mut().non_send_resource_mut::<R>() };
+        let resource =
+            unsafe { resource_ptr_mut::<R>(world.get(), *state, "Non Send Resource").as_mut() };
 
         NonSendMut::new(resource)
     }
@@ -199,6 +346,70 @@ unsafe impl<R: Resource> SystemArg for NonSendMut<'_, R> {
     }
 }
 
+unsafe impl<R: Resource + Send> SystemArg for ResVersioned<'_, R> {
+    type Item<'world, 'state> = ResVersioned<'world, R>;
+
+    type State = ResourceId;
+
+    fn init(world: &mut World) -> Self::State {
+        world.register_resource::<R>()
+    }
+
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<R>(*state).is_some()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        _system: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        let resources = unsafe { world.get().resources() };
+        let resource = resources
+            .get::<R>(*state)
+            .unwrap_or_else(|| panic!("Resource not found: {}", std::any::type_name::<R>()));
+
+        ResVersioned::new(resource, resources.get_meta(*state).unwrap())
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        vec![SystemAccess::resource(*state, Access::Read)]
+    }
+}
+
+unsafe impl<R: Resource + Send> SystemArg for TryResVersioned<'_, R> {
+    type Item<'world, 'state> = TryResVersioned<'world, R>;
+
+    type State = ResourceId;
+
+    fn init(world: &mut World) -> Self::State {
+        world.register_resource::<R>()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        _system: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        let resources = unsafe { world.get().resources() };
+        let meta = resources.get_meta(*state).unwrap();
+        match resources.get::<R>(*state) {
+            Some(resource) => TryResVersioned::Present(ResVersioned::new(resource, meta)),
+            None if meta.generation() > 0 => TryResVersioned::Removed {
+                generation: meta.generation(),
+            },
+            None => TryResVersioned::Absent,
+        }
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        vec![SystemAccess::resource(*state, Access::Read)]
+    }
+}
+
+/// `None` when the argument fails validation, so the system still runs. Lets systems handle
+/// resources that may not exist yet, like `Option<Res<R>>` or `Option<NonSendMut<R>>`.
 unsafe impl<A: SystemArg> SystemArg for Option<A> {
     type Item<'world, 'state> = Option<A::Item<'world, 'state>>;
 
@@ -208,10 +419,6 @@ unsafe impl<A: SystemArg> SystemArg for Option<A> {
         A::init(world)
     }
 
-    unsafe fn validate(state: &Self::State, world: WorldCell, system: &SystemMeta) -> bool {
-        unsafe { A::validate(state, world, system) }
-    }
-
     unsafe fn get<'world, 'state>(
         state: &'state mut Self::State,
         world: WorldCell<'world>,
@@ -226,37 +433,290 @@ unsafe impl<A: SystemArg> SystemArg for Option<A> {
         }
     }
 
+    fn exclusive() -> bool {
+        A::exclusive()
+    }
+
     fn send() -> bool {
         A::send()
     }
 
+    fn deferred() -> bool {
+        A::deferred()
+    }
+
+    fn apply(state: &mut Self::State, world: &mut World) {
+        A::apply(state, world)
+    }
+
     fn access(state: &Self::State) -> Vec<SystemAccess> {
         A::access(state)
     }
+
+    fn save(state: &Self::State) -> Option<Value> {
+        A::save(state)
+    }
+
+    fn restore(state: &mut Self::State, world: &mut World, saved: &Value) {
+        A::restore(state, world, saved)
+    }
+}
+
+/// Per system value that persists between runs, starting at `T::default()`.
+/// Every system gets its own value and no world access is registered.
+pub struct Local<'state, T: Default + Send + Sync + 'static>(&'state mut T);
+
+impl<T: Default + Send + Sync + 'static> std::ops::Deref for Local<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<T: Default + Send + Sync + 'static> std::ops::DerefMut for Local<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+unsafe impl<T: Default + Send + Sync + 'static> SystemArg for Local<'_, T> {
+    type Item<'world, 'state> = Local<'state, T>;
+
+    type State = T;
+
+    fn init(_: &mut World) -> Self::State {
+        T::default()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        _: WorldCell<'world>,
+        _: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        Local(state)
+    }
+}
+
+/// Per system value that is carried across restarts by
+/// [`App::save_runtime_state`](crate::app::App::save_runtime_state).
+pub struct SerializableLocal<
+    'state,
+    T: Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+>(&'state mut T);
+
+impl<T: Serialize + DeserializeOwned + Default + Send + Sync + 'static> std::ops::Deref
+    for SerializableLocal<'_, T>
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
 }
 
+impl<T: Serialize + DeserializeOwned + Default + Send + Sync + 'static> std::ops::DerefMut
+    for SerializableLocal<'_, T>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+unsafe impl<T: Serialize + DeserializeOwned + Default + Send + Sync + 'static> SystemArg
+    for SerializableLocal<'_, T>
+{
+    type Item<'world, 'state> = SerializableLocal<'state, T>;
+
+    type State = T;
+
+    fn init(_: &mut World) -> Self::State {
+        T::default()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        _: WorldCell<'world>,
+        _: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        SerializableLocal(state)
+    }
+
+    fn save(state: &Self::State) -> Option<Value> {
+        save_runtime_value(state)
+    }
+
+    fn restore(state: &mut Self::State, _: &mut World, saved: &Value) {
+        if let Some(value) = load_runtime_value(saved) {
+            *state = value;
+        }
+    }
+}
+
+/// Per system buffer of deferred world mutations, applied after the system's phase.
+pub trait SystemBuffer: Default + Send + Sync + 'static {
+    fn apply(&mut self, world: &mut World);
+}
+
+/// Gives a system exclusive access to its own instance of `T`.
+/// Buffers don't register any access, so systems using the same buffer type can run in parallel.
+pub struct Deferred<'state, T: SystemBuffer>(&'state mut T);
+
+impl<'state, T: SystemBuffer> Deferred<'state, T> {
+    pub fn new(buffer: &'state mut T) -> Self {
+        Self(buffer)
+    }
+
+    pub fn into_inner(self) -> &'state mut T {
+        self.0
+    }
+}
+
+impl<'state, T: SystemBuffer> std::ops::Deref for Deferred<'state, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'state, T: SystemBuffer> std::ops::DerefMut for Deferred<'state, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+unsafe impl<T: SystemBuffer> SystemArg for Deferred<'_, T> {
+    type Item<'world, 'state> = Deferred<'state, T>;
+
+    type State = T;
+
+    fn init(_: &mut World) -> Self::State {
+        T::default()
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        _: WorldCell<'world>,
+        _: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        Deferred::new(state)
+    }
+
+    fn deferred() -> bool {
+        true
+    }
+
+    fn apply(state: &mut Self::State, world: &mut World) {
+        state.apply(world);
+    }
+}
+
+/// Arguments with conflicting access, like two queries writing the same component, that are
+/// handed out one at a time through `p0`, `p1`, ... The set reports the access of every member.
+/// Queries in the set name their component references as `'static`, like
+/// `ParamSet<(Query<&'static mut Position>, Query<&'static Position>)>`, because the query's
+/// argument implementation requires it.
+pub struct ParamSet<'world, 'state, T: SystemArg> {
+    state: &'state mut T::State,
+    world: WorldCell<'world>,
+    /// The system's meta outlives the run that created the set.
+    meta: NonNull<SystemMeta>,
+}
+
+unsafe impl<T: SystemArg> SystemArg for ParamSet<'_, '_, T> {
+    type Item<'world, 'state> = ParamSet<'world, 'state, T>;
+
+    type State = T::State;
+
+    fn init(world: &mut World) -> Self::State {
+        T::init(world)
+    }
+
+    unsafe fn validate(state: &Self::State, world: WorldCell, system: &SystemMeta) -> bool {
+        unsafe { T::validate(state, world, system) }
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        system: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        ParamSet {
+            state,
+            world,
+            meta: NonNull::from(system),
+        }
+    }
+
+    fn exclusive() -> bool {
+        T::exclusive()
+    }
+
+    fn send() -> bool {
+        T::send()
+    }
+
+    fn deferred() -> bool {
+        T::deferred()
+    }
+
+    fn apply(state: &mut Self::State, world: &mut World) {
+        T::apply(state, world);
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        T::access(state)
+    }
+
+    fn save(state: &Self::State) -> Option<Value> {
+        T::save(state)
+    }
+
+    fn restore(state: &mut Self::State, world: &mut World, saved: &Value) {
+        T::restore(state, world, saved)
+    }
+}
+
+macro_rules! impl_param_set {
+    ($(($index:tt, $get:ident, $arg:ident)),*) => {
+        impl<$($arg: SystemArg),*> ParamSet<'_, '_, ($($arg,)*)> {
+            $(
+                /// Borrowing the set mutably keeps the other members inaccessible.
+                pub fn $get(&mut self) -> $arg::Item<'_, '_> {
+                    unsafe { $arg::get(&mut self.state.$index, self.world, self.meta.as_ref()) }
+                }
+            )*
+        }
+    };
+}
+
+impl_param_set!((0, p0, A), (1, p1, B));
+impl_param_set!((0, p0, A), (1, p1, B), (2, p2, C));
+impl_param_set!((0, p0, A), (1, p1, B), (2, p2, C), (3, p3, D));
+
 macro_rules! impl_into_system_configs {
     ($($arg:ident),*) => {
     #[allow(non_snake_case)]
-    impl<F, $($arg: SystemArg),*> IntoSystemConfigs<(F, $($arg),*)> for F
+    impl<F, O: SystemOutput, $($arg: SystemArg),*> IntoSystemConfigs<(F, $($arg),*)> for F
         where
-            for<'world, 'state> F: Fn($($arg),*) + Fn($(ArgItem<'world,'state, $arg>),*) + Send + Sync + 'static,
+            for<'world, 'state> F: Fn($($arg),*) -> O + Fn($(ArgItem<'world,'state, $arg>),*) -> O + Send + Sync + 'static,
         {
 
             fn configs(self) -> SystemConfigs {
                 let name = std::any::type_name::<F>();
 
-                let init = |world: &mut World| {
+                let init = Box::new(|world: &mut World| {
                     let ($($arg,)*) = ($($arg::init(world),)*);
                     let state = ($($arg,)*);
                     Box::new(state) as Box<dyn Any + Send + Sync>
-                };
+                });
 
                 let execute = move |state: &mut Box<dyn Any + Send + Sync>, world: WorldCell, system: &SystemMeta| {
                     let ($($arg,)*) = state.downcast_mut::<($($arg::State,)*)>().unwrap();
                     let ($($arg,)*) = unsafe {($($arg::get($arg, world, system),)*)};
 
-                    self($($arg,)*);
+                    self($($arg,)*).into_result()
                 };
 
                 let apply = move |state: &mut Box<dyn Any + Send + Sync>, world: &mut World| {
@@ -271,6 +731,32 @@ macro_rules! impl_into_system_configs {
                     access
                 };
 
+                let read_only = |state: &Box<dyn Any + Send + Sync>| {
+                    let ($($arg,)*) = state.downcast_ref::<($($arg::State,)*)>().unwrap();
+                    $(check_read_only::<$arg>($arg)?;)*
+                    Ok(())
+                };
+
+                let save = |state: &Box<dyn Any + Send + Sync>| {
+                    let ($($arg,)*) = state.downcast_ref::<($($arg::State,)*)>().unwrap();
+                    vec![$($arg::save($arg),)*]
+                };
+
+                let restore = |state: &mut Box<dyn Any + Send + Sync>, world: &mut World, saved: &[Option<Value>]| {
+                    let ($($arg,)*) = state.downcast_mut::<($($arg::State,)*)>().unwrap();
+                    let mut saved = saved.iter();
+                    $(
+                        if let Some(Some(value)) = saved.next() {
+                            $arg::restore($arg, world, value);
+                        }
+                    )*
+                };
+
+                let validate = |state: &Box<dyn Any + Send + Sync>, world: WorldCell, system: &SystemMeta| {
+                    let ($($arg,)*) = state.downcast_ref::<($($arg::State,)*)>().unwrap();
+                    unsafe { $($arg::validate($arg, world, system) &&)* true }
+                };
+
                 let send = ($($arg::send() &&)* true);
                 let exclusive = ($($arg::exclusive() ||)* false);
 
@@ -283,7 +769,14 @@ macro_rules! impl_into_system_configs {
                     init,
                     run: Box::new(execute),
                     apply: Box::new(apply),
-                    access
+                    access,
+                    read_only,
+                    save,
+                    restore,
+                    validate: Box::new(validate),
+                    conditions: vec![],
+                    ambiguous_with: std::collections::HashSet::new(),
+                    invalid_args: InvalidArgs::Skip,
                 })
             }
 
@@ -323,6 +816,11 @@ macro_rules! impl_into_system_configs {
                 ($($arg,)*)
             }
 
+            unsafe fn validate(state: &Self::State, world: WorldCell, system: &SystemMeta) -> bool {
+                let ($($arg,)*) = state;
+                unsafe { $($arg::validate($arg, world, system) &&)* true }
+            }
+
             unsafe fn get<'world, 'state>(state: &'state mut Self::State, world: WorldCell<'world>, system: &SystemMeta,) -> Self::Item<'world, 'state> {
                 let ($($arg,)*) = state;
                 let ($($arg,)*) = unsafe {($($arg::get($arg, world, system),)*)};
@@ -337,6 +835,10 @@ macro_rules! impl_into_system_configs {
                 ($($arg::send() &&)* true)
             }
 
+            fn deferred() -> bool {
+                ($($arg::deferred() ||)* false)
+            }
+
             fn apply(state: &mut Self::State, world: &mut World) {
                 let ($($arg,)*) = state;
                 $($arg::apply($arg, world);)*
@@ -348,6 +850,26 @@ macro_rules! impl_into_system_configs {
                 $(access.extend($arg::access($arg));)*
                 access
             }
+
+            fn save(state: &Self::State) -> Option<Value> {
+                let ($($arg,)*) = state;
+                let parts = vec![$($arg::save($arg),)*];
+                parts.iter().any(Option::is_some).then(|| encode_parts(parts))
+            }
+
+            fn restore(state: &mut Self::State, world: &mut World, saved: &Value) {
+                let ($($arg,)*) = state;
+                let Some(parts) = decode_parts(saved) else {
+                    return;
+                };
+
+                let mut parts = parts.into_iter();
+                $(
+                    if let Some(Some(value)) = parts.next() {
+                        $arg::restore($arg, world, value);
+                    }
+                )*
+            }
         }
     };
 }
@@ -362,3 +884,348 @@ impl_into_system_configs!(A, B, C, D, E, F2, G);
 impl_into_system_configs!(A, B, C, D, E, F2, G, H);
 impl_into_system_configs!(A, B, C, D, E, F2, G, H, I);
 impl_into_system_configs!(A, B, C, D, E, F2, G, H, I, J);
+
+#[cfg(test)]
+mod tests {
+    use super::{Deferred, Local, ParamSet, SystemArg, SystemBuffer};
+    use crate::{
+        app::App,
+        system::{
+            Access, IntoSystemConfigs, InvalidArgs, SystemAccess, error::SystemFailure,
+            executor::RunMode, query::Query, query::QueryState, schedule::Phase,
+        },
+        world::{Component, NonSend, NonSendMut, Res, ResMut, Resource, World},
+    };
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Default)]
+    struct Order(Vec<&'static str>);
+    impl Resource for Order {}
+
+    #[derive(Default)]
+    struct Log {
+        entries: Vec<&'static str>,
+    }
+
+    impl SystemBuffer for Log {
+        fn apply(&mut self, world: &mut World) {
+            world.resource_mut::<Order>().0.append(&mut self.entries);
+        }
+    }
+
+    struct Value(u32);
+    impl Component for Value {}
+
+    #[derive(Default)]
+    struct Spawns {
+        values: Vec<u32>,
+    }
+
+    impl SystemBuffer for Spawns {
+        fn apply(&mut self, world: &mut World) {
+            for value in self.values.drain(..) {
+                let entity = world.spawn();
+                world.add_component(entity, Value(value));
+            }
+        }
+    }
+
+    #[test]
+    fn deferred_parallel_buffers() {
+        let mut app = App::new();
+        app.schedule_mut().set_mode(RunMode::Parallel);
+        let mut app = app
+            .add_resource(Order::default())
+            .add_systems(Update, |mut log: Deferred<Log>| log.entries.push("a"))
+            .add_systems(Update, |mut log: Deferred<Log>| log.entries.push("b"))
+            .build();
+
+        app.run(Update);
+        let first = app.world().resource::<Order>().0.clone();
+        assert_eq!(first.len(), 2);
+
+        app.run(Update);
+        let order = &app.world().resource::<Order>().0;
+        assert_eq!(order[..2], order[2..]);
+    }
+
+    #[test]
+    fn deferred_spawn() {
+        let mut app = App::new()
+            .register::<Value>()
+            .add_systems(Update, |mut spawns: Deferred<Spawns>| {
+                spawns.values.extend([1, 2, 3])
+            })
+            .build();
+
+        app.run(Update);
+
+        let state = QueryState::<&Value>::new(app.world());
+        let query = Query::new(app.world(), &state);
+        let sum = query.iter().map(|value| value.0).sum::<u32>();
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn local_state() {
+        #[derive(Default)]
+        struct Runs(Vec<(u32, u32)>);
+        impl Resource for Runs {}
+
+        let mut app = App::new()
+            .add_resource(Runs::default())
+            .add_systems(
+                Update,
+                |mut count: Local<u32>, mut doubled: Local<u32>, runs: &mut Runs| {
+                    *count += 1;
+                    *doubled += 2;
+                    runs.0.push((*count, *doubled));
+                },
+            )
+            .add_systems(Update, |mut count: Local<u32>, runs: &mut Runs| {
+                *count += 10;
+                runs.0.push((*count, 0));
+            })
+            .build();
+
+        app.run(Update);
+        app.run(Update);
+        assert_eq!(
+            app.world().resource::<Runs>().0,
+            [(1, 2), (10, 0), (2, 4), (20, 0)]
+        );
+        assert!(Local::<u32>::access(&0).is_empty());
+    }
+
+    #[test]
+    fn param_set_conflicting_queries() {
+        struct Player(#[allow(dead_code)] u8);
+        impl Component for Player {}
+
+        struct Camera(#[allow(dead_code)] u8);
+        impl Component for Camera {}
+
+        #[derive(Default)]
+        struct Total(u32);
+        impl Resource for Total {}
+
+        fn follow(
+            mut set: ParamSet<(
+                Query<(&'static mut Value, &'static Player)>,
+                Query<(&'static mut Value, &'static Camera)>,
+                &mut Total,
+            )>,
+        ) {
+            let mut target = 0;
+            for (mut value, _) in set.p0().iter_mut() {
+                value.0 += 1;
+                target = value.0;
+            }
+
+            for (mut value, _) in set.p1().iter_mut() {
+                value.0 = target;
+            }
+
+            set.p2().0 += target;
+        }
+
+        let mut builder = App::new();
+        builder
+            .register::<Value>()
+            .register::<Player>()
+            .register::<Camera>()
+            .add_resource(Total::default());
+
+        let world = builder.world_mut();
+        let player = world.spawn();
+        world.add_component(player, Value(4));
+        world.add_component(player, Player(0));
+        let camera = world.spawn();
+        world.add_component(camera, Value(0));
+        world.add_component(camera, Camera(0));
+
+        let mut app = builder.add_systems(Update, follow).build();
+        app.run(Update);
+
+        let world = app.world();
+        assert_eq!(world.get_component::<Value>(player).unwrap().0, 5);
+        assert_eq!(world.get_component::<Value>(camera).unwrap().0, 5);
+        assert_eq!(world.resource::<Total>().0, 5);
+
+        type Set<'w, 's> = ParamSet<'w, 's, (Query<'w, 's, &'static mut Value>, &'w mut Total)>;
+        let world = app.world_mut();
+        let state = Set::init(world);
+        let value = world.components().get_id::<Value>().unwrap();
+        let total = world.resources().get_id::<Total>().unwrap();
+        assert_eq!(
+            Set::access(&state),
+            vec![
+                SystemAccess::component(value, Access::Write),
+                SystemAccess::resource(total, Access::Write)
+            ]
+        );
+    }
+
+    fn missing_resources(mode: RunMode) {
+        #[derive(Default)]
+        struct Missing;
+        impl Resource for Missing {}
+
+        #[derive(Default)]
+        struct Failures(Vec<String>);
+        impl Resource for Failures {}
+
+        fn record(world: &mut World, failure: SystemFailure) {
+            world
+                .resource_mut::<Failures>()
+                .0
+                .push(failure.error.to_string());
+        }
+
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(mode);
+        let mut app = builder
+            .add_resource(Order::default())
+            .add_resource(Failures::default())
+            .set_error_handler(record)
+            .add_systems(Update, |_: Res<Missing>, order: &mut Order| {
+                order.0.push("res")
+            })
+            .add_systems(Update, |_: &Missing, order: &mut Order| order.0.push("ref"))
+            .add_systems(Update, |_: NonSend<Missing>, order: &mut Order| {
+                order.0.push("non_send")
+            })
+            .add_systems(
+                Update,
+                (|_: ResMut<Missing>, order: &mut Order| order.0.push("reported"))
+                    .on_invalid_args(InvalidArgs::Error),
+            )
+            .add_systems(
+                Update,
+                |missing: Option<Res<Missing>>, order: &mut Order| {
+                    order
+                        .0
+                        .push(if missing.is_some() { "some" } else { "none" })
+                },
+            )
+            .build();
+
+        let run = |app: &mut App| {
+            app.run(Update);
+            let mut order = std::mem::take(&mut app.world_mut().resource_mut::<Order>().0);
+            order.sort();
+            order
+        };
+
+        assert_eq!(run(&mut app), ["none"]);
+        assert_eq!(
+            app.world().resource::<Failures>().0,
+            ["Arguments failed validation"]
+        );
+
+        app.world_mut().add_resource(Missing);
+        app.world_mut().add_non_send_resource(Missing);
+        assert_eq!(
+            run(&mut app),
+            ["non_send", "ref", "reported", "res", "some"]
+        );
+        assert_eq!(app.world().resource::<Failures>().0.len(), 1);
+    }
+
+    #[test]
+    fn sequential_missing_resources() {
+        missing_resources(RunMode::Sequential);
+    }
+
+    #[test]
+    fn parallel_missing_resources() {
+        missing_resources(RunMode::Parallel);
+    }
+
+    #[test]
+    fn optional_resources() {
+        #[derive(Default)]
+        struct Score(u32);
+        impl Resource for Score {}
+
+        #[derive(Default)]
+        struct Window(u32);
+        impl Resource for Window {}
+
+        fn update(
+            score: Option<ResMut<Score>>,
+            window: Option<NonSendMut<Window>>,
+            order: &mut Order,
+        ) {
+            if let Some(mut score) = score {
+                order.0.push(if score.is_added() {
+                    "score added"
+                } else {
+                    "score"
+                });
+                score.0 += 1;
+            }
+            if let Some(mut window) = window {
+                window.0 += 1;
+                order.0.push("window");
+            }
+        }
+
+        fn read(score: Option<Res<Score>>, window: Option<NonSend<Window>>, order: &mut Order) {
+            if score.is_none() && window.is_none() {
+                order.0.push("nothing");
+            }
+        }
+
+        let mut app = App::new()
+            .add_resource(Order::default())
+            .add_systems(Update, update.before(read))
+            .build();
+        let run = |app: &mut App| {
+            app.run(Update);
+            std::mem::take(&mut app.world_mut().resource_mut::<Order>().0)
+        };
+
+        assert_eq!(run(&mut app), ["nothing"]);
+
+        app.world_mut().add_resource(Score(0));
+        assert_eq!(run(&mut app), ["score added"]);
+        assert_eq!(run(&mut app), ["score"]);
+
+        app.world_mut().add_non_send_resource(Window(0));
+        assert_eq!(run(&mut app), ["score", "window"]);
+        assert_eq!(app.world().resource::<Score>().0, 3);
+        assert_eq!(app.world().non_send_resource::<Window>().0, 1);
+    }
+
+    #[test]
+    fn optional_deferred_applies() {
+        let mut app = App::new()
+            .add_resource(Order::default())
+            .add_systems(Update, |log: Option<Deferred<Log>>| {
+                log.unwrap().entries.push("optional")
+            })
+            .build();
+
+        app.run(Update);
+        assert_eq!(app.world().resource::<Order>().0, ["optional"]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "was built for")]
+    fn system_rejects_other_world() {
+        let mut world = World::new();
+        let mut system = (|_: Local<u32>| {})
+            .configs()
+            .single()
+            .into_system_node(&mut world)
+            .system;
+
+        let other = World::new();
+        let cell = unsafe { other.cell() };
+        system.should_run(cell);
+    }
+}
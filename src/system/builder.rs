@@ -0,0 +1,370 @@
+use super::{Access, IntoSystemConfigs, SystemAccess, SystemConfigs, SystemMeta, arg::SystemArg};
+use crate::world::{Resource, ResourceId, World, WorldCell};
+use std::{any::TypeId, ptr::NonNull};
+
+/// Creates the state of a system argument, in place of [`SystemArg::init`]. Lets generic code
+/// decide at runtime what an argument accesses, see [`SystemBuilder`].
+pub trait SystemArgBuilder<A: SystemArg>: Send + Sync + 'static {
+    fn build(self, world: &mut World) -> A::State;
+}
+
+/// Initializes the argument like a regular system would.
+pub struct ArgBuilder;
+
+impl<A: SystemArg> SystemArgBuilder<A> for ArgBuilder {
+    fn build(self, world: &mut World) -> A::State {
+        A::init(world)
+    }
+}
+
+impl<A: SystemArg, F> SystemArgBuilder<A> for F
+where
+    F: FnOnce(&mut World) -> A::State + Send + Sync + 'static,
+{
+    fn build(self, world: &mut World) -> A::State {
+        self(world)
+    }
+}
+
+/// Marker of a function system, naming the tuple of its arguments.
+pub trait FunctionArgs {
+    type Args: SystemArg;
+}
+
+/// Builds a function system whose argument states are created by builders instead of
+/// [`SystemArg::init`], one builder per argument in the order of the function's arguments.
+///
+/// ```ignore
+/// let resources = DynamicResourcesBuilder::new().read::<Score>();
+/// let system = SystemBuilder::new((ArgBuilder, resources)).build(report);
+/// ```
+pub struct SystemBuilder<B>(B);
+
+impl<B> SystemBuilder<B> {
+    pub fn new(builders: B) -> Self {
+        Self(builders)
+    }
+
+    pub fn build<M: FunctionArgs>(self, system: impl IntoSystemConfigs<M>) -> SystemConfigs
+    where
+        B: SystemArgBuilder<M::Args>,
+    {
+        let builders = self.0;
+        let mut config = system.configs().single();
+        config.init = Box::new(move |world| Box::new(builders.build(world)));
+        SystemConfigs::Config(config)
+    }
+}
+
+macro_rules! impl_system_arg_builder {
+    ($(($arg:ident, $builder:ident)),*) => {
+        #[allow(non_snake_case)]
+        impl<$($arg: SystemArg, $builder: SystemArgBuilder<$arg>),*> SystemArgBuilder<($($arg,)*)>
+            for ($($builder,)*)
+        {
+            fn build(self, world: &mut World) -> <($($arg,)*) as SystemArg>::State {
+                let ($($builder,)*) = self;
+                ($($builder.build(world),)*)
+            }
+        }
+
+        impl<F, $($arg: SystemArg),*> FunctionArgs for (F, $($arg),*) {
+            type Args = ($($arg,)*);
+        }
+    };
+}
+
+impl_system_arg_builder!((A, BA));
+impl_system_arg_builder!((A, BA), (B, BB));
+impl_system_arg_builder!((A, BA), (B, BB), (C, BC));
+impl_system_arg_builder!((A, BA), (B, BB), (C, BC), (D, BD));
+impl_system_arg_builder!((A, BA), (B, BB), (C, BC), (D, BD), (E, BE));
+impl_system_arg_builder!((A, BA), (B, BB), (C, BC), (D, BD), (E, BE), (F2, BF));
+impl_system_arg_builder!(
+    (A, BA),
+    (B, BB),
+    (C, BC),
+    (D, BD),
+    (E, BE),
+    (F2, BF),
+    (G, BG)
+);
+impl_system_arg_builder!(
+    (A, BA),
+    (B, BB),
+    (C, BC),
+    (D, BD),
+    (E, BE),
+    (F2, BF),
+    (G, BG),
+    (H, BH)
+);
+impl_system_arg_builder!(
+    (A, BA),
+    (B, BB),
+    (C, BC),
+    (D, BD),
+    (E, BE),
+    (F2, BF),
+    (G, BG),
+    (H, BH),
+    (I, BI)
+);
+impl_system_arg_builder!(
+    (A, BA),
+    (B, BB),
+    (C, BC),
+    (D, BD),
+    (E, BE),
+    (F2, BF),
+    (G, BG),
+    (H, BH),
+    (I, BI),
+    (J, BJ)
+);
+
+/// Resources chosen when the system is built with a [`DynamicResourcesBuilder`]. Without a
+/// builder the set is empty.
+pub struct DynamicResources<'world, 'state> {
+    world: &'world World,
+    resources: &'state [DynamicResource],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicResource {
+    id: ResourceId,
+    ty: Option<TypeId>,
+    access: Access,
+}
+
+impl<'world> DynamicResources<'world, '_> {
+    pub fn ids(&self) -> impl Iterator<Item = ResourceId> + '_ {
+        self.resources.iter().map(|resource| resource.id)
+    }
+
+    pub fn contains(&self, id: ResourceId) -> bool {
+        self.resource(id).is_some()
+    }
+
+    /// The resource, if it's part of the set.
+    pub fn get<R: Resource + Send>(&self) -> Option<&'world R> {
+        let resource = self.typed::<R>()?;
+        self.get_ptr(resource.id)
+            .map(|ptr| unsafe { ptr.cast::<R>().as_ref() })
+    }
+
+    /// The resource, if it's part of the set with write access. Marks it modified.
+    pub fn get_mut<R: Resource + Send>(&mut self) -> Option<&'world mut R> {
+        let resource = self.typed::<R>()?;
+        self.get_mut_ptr(resource.id)
+            .map(|ptr| unsafe { ptr.cast::<R>().as_mut() })
+    }
+
+    /// Untyped pointer to a resource of the set, only valid for reads.
+    pub fn get_ptr(&self, id: ResourceId) -> Option<NonNull<u8>> {
+        self.resource(id)?;
+        self.world.resources().get_ptr_by_id(id)
+    }
+
+    /// Untyped pointer to a resource of the set with write access. Marks it modified.
+    pub fn get_mut_ptr(&mut self, id: ResourceId) -> Option<NonNull<u8>> {
+        let resource = self.resource(id)?;
+        if resource.access != Access::Write {
+            return None;
+        }
+
+        let ptr = self.world.resources().get_ptr_by_id(id)?;
+        self.world.resources().modify(id, self.world.frame());
+        Some(ptr)
+    }
+
+    fn resource(&self, id: ResourceId) -> Option<&DynamicResource> {
+        self.resources.iter().find(|resource| resource.id == id)
+    }
+
+    fn typed<R: Resource>(&self) -> Option<&DynamicResource> {
+        let ty = Some(TypeId::of::<R>());
+        self.resources.iter().find(|resource| resource.ty == ty)
+    }
+}
+
+unsafe impl SystemArg for DynamicResources<'_, '_> {
+    type Item<'world, 'state> = DynamicResources<'world, 'state>;
+
+    type State = Vec<DynamicResource>;
+
+    fn init(_: &mut World) -> Self::State {
+        Vec::new()
+    }
+
+    /// Every resource of the set has to exist and be sendable.
+    unsafe fn validate(state: &Self::State, world: WorldCell, _: &SystemMeta) -> bool {
+        let resources = unsafe { world.get().resources() };
+        state.iter().all(|resource| {
+            resources
+                .get_meta(resource.id)
+                .is_some_and(|meta| meta.exists() && meta.send())
+        })
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        _: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        DynamicResources {
+            world: unsafe { world.get() },
+            resources: state,
+        }
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        state
+            .iter()
+            .map(|resource| SystemAccess::resource(resource.id, resource.access))
+            .collect()
+    }
+}
+
+/// Resource of a [`DynamicResourcesBuilder`], typed ones are registered when the system is built.
+enum ResourceTerm {
+    Typed(TypeId, fn(&mut World) -> ResourceId),
+    Id(ResourceId),
+}
+
+/// Chooses the resources of a [`DynamicResources`] argument.
+#[derive(Default)]
+pub struct DynamicResourcesBuilder {
+    terms: Vec<(ResourceTerm, Access)>,
+}
+
+impl DynamicResourcesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read<R: Resource + Send>(self) -> Self {
+        self.typed::<R>(Access::Read)
+    }
+
+    pub fn write<R: Resource + Send>(self) -> Self {
+        self.typed::<R>(Access::Write)
+    }
+
+    /// Reads a resource by id, only reachable through [`DynamicResources::get_ptr`].
+    pub fn read_id(self, id: ResourceId) -> Self {
+        self.term(ResourceTerm::Id(id), Access::Read)
+    }
+
+    /// Writes a resource by id, only reachable through [`DynamicResources::get_mut_ptr`].
+    pub fn write_id(self, id: ResourceId) -> Self {
+        self.term(ResourceTerm::Id(id), Access::Write)
+    }
+
+    fn typed<R: Resource + Send>(self, access: Access) -> Self {
+        let term = ResourceTerm::Typed(TypeId::of::<R>(), World::register_resource::<R>);
+        self.term(term, access)
+    }
+
+    fn term(mut self, term: ResourceTerm, access: Access) -> Self {
+        self.terms.push((term, access));
+        self
+    }
+}
+
+impl<'w, 's> SystemArgBuilder<DynamicResources<'w, 's>> for DynamicResourcesBuilder {
+    fn build(self, world: &mut World) -> Vec<DynamicResource> {
+        let mut resources = Vec::<DynamicResource>::new();
+        for (term, access) in self.terms {
+            let (id, ty) = match term {
+                ResourceTerm::Typed(ty, register) => (register(world), Some(ty)),
+                ResourceTerm::Id(id) => (id, None),
+            };
+
+            match resources.iter_mut().find(|resource| resource.id == id) {
+                Some(current) => {
+                    current.ty = current.ty.or(ty);
+                    if access == Access::Write {
+                        current.access = access;
+                    }
+                }
+                None => resources.push(DynamicResource { id, ty, access }),
+            }
+        }
+
+        resources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgBuilder, DynamicResources, DynamicResourcesBuilder, SystemBuilder};
+    use crate::{
+        core::SparseIndex,
+        system::{arg::Local, query::Query},
+        world::{Component, Resource, World},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Score(u32);
+    impl Resource for Score {}
+
+    #[derive(Debug, PartialEq)]
+    struct Bonus(u32);
+    impl Resource for Bonus {}
+
+    struct Points(u32);
+    impl Component for Points {}
+
+    fn award(query: Query<&Points>, mut resources: DynamicResources, mut step: Local<u32>) {
+        *step += 1;
+        let bonus = resources.get::<Bonus>().map_or(0, |bonus| bonus.0);
+        let points = query.iter().map(|points| points.0).sum::<u32>();
+        if let Some(score) = resources.get_mut::<Score>() {
+            score.0 += points * *step + bonus;
+        }
+    }
+
+    #[test]
+    fn system_builder() {
+        let mut world = World::new();
+        world.add_resource(Score(0));
+        world.add_resource(Bonus(5));
+        world.register::<Points>();
+        world.spawn_bundle((Points(2),));
+        world.spawn_bundle((Points(3),));
+
+        let resources = DynamicResourcesBuilder::new()
+            .read::<Bonus>()
+            .write::<Score>();
+        let config = SystemBuilder::new((ArgBuilder, resources, |_: &mut World| 10))
+            .build(award)
+            .single();
+
+        let node = config.into_system_node(&mut world);
+        let score = world.resources().get_id::<Score>().unwrap();
+        let bonus = world.resources().get_id::<Bonus>().unwrap();
+        let resources = &node.system.meta().resources;
+        assert!(resources.writes(score.to_usize()));
+        assert!(resources.reads(bonus.to_usize()) && !resources.writes(bonus.to_usize()));
+
+        let id = world.register_system(
+            SystemBuilder::new((
+                ArgBuilder,
+                DynamicResourcesBuilder::new().read::<Score>(),
+                ArgBuilder,
+            ))
+            .build(award),
+        );
+        world.run_system(id).unwrap();
+        assert_eq!(world.resource::<Score>(), &Score(0));
+
+        let resources = DynamicResourcesBuilder::new().write::<Score>();
+        let id = world.register_system(
+            SystemBuilder::new((ArgBuilder, resources, |_: &mut World| 1)).build(award),
+        );
+        world.run_system(id).unwrap();
+        world.run_system(id).unwrap();
+        assert_eq!(world.resource::<Score>(), &Score(5 * 2 + 5 * 3));
+    }
+}
@@ -0,0 +1,202 @@
+use super::{
+    SystemAccess, SystemMeta, SystemState,
+    arg::{ArgItem, SystemArg},
+};
+use crate::world::{Res, Resource, World, WorldCell};
+use std::{any::Any, sync::Arc};
+
+type ConditionRun = Arc<dyn Fn(&mut SystemState, WorldCell, &SystemMeta) -> bool + Send + Sync>;
+
+/// A system returning `bool` that decides whether the system it's attached to runs, see
+/// [`IntoSystemConfigs::run_if`](super::IntoSystemConfigs::run_if).
+/// Conditions should only read the world, deferred mutations they queue are never applied.
+#[derive(Clone)]
+pub struct Condition(ConditionKind);
+
+#[derive(Clone)]
+enum ConditionKind {
+    System {
+        init: fn(&mut World) -> SystemState,
+        access: fn(&SystemState) -> Vec<SystemAccess>,
+        send: bool,
+        exclusive: bool,
+        run: ConditionRun,
+    },
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub(crate) fn send(&self) -> bool {
+        match &self.0 {
+            ConditionKind::System { send, .. } => *send,
+            ConditionKind::And(a, b) | ConditionKind::Or(a, b) => a.send() && b.send(),
+            ConditionKind::Not(condition) => condition.send(),
+        }
+    }
+
+    pub(crate) fn exclusive(&self) -> bool {
+        match &self.0 {
+            ConditionKind::System { exclusive, .. } => *exclusive,
+            ConditionKind::And(a, b) | ConditionKind::Or(a, b) => a.exclusive() || b.exclusive(),
+            ConditionKind::Not(condition) => condition.exclusive(),
+        }
+    }
+
+    pub(crate) fn init(self, world: &mut World) -> ConditionState {
+        match self.0 {
+            ConditionKind::System {
+                init, access, run, ..
+            } => ConditionState::System {
+                state: init(world),
+                access,
+                run,
+            },
+            ConditionKind::And(a, b) => {
+                ConditionState::And(Box::new(a.init(world)), Box::new(b.init(world)))
+            }
+            ConditionKind::Or(a, b) => {
+                ConditionState::Or(Box::new(a.init(world)), Box::new(b.init(world)))
+            }
+            ConditionKind::Not(condition) => ConditionState::Not(Box::new(condition.init(world))),
+        }
+    }
+}
+
+/// An initialized [`Condition`] owned by the system it guards.
+pub(crate) enum ConditionState {
+    System {
+        state: SystemState,
+        access: fn(&SystemState) -> Vec<SystemAccess>,
+        run: ConditionRun,
+    },
+    And(Box<ConditionState>, Box<ConditionState>),
+    Or(Box<ConditionState>, Box<ConditionState>),
+    Not(Box<ConditionState>),
+}
+
+impl ConditionState {
+    /// Evaluates the condition, `and` and `or` short circuit like their boolean operators.
+    pub fn evaluate(&mut self, world: WorldCell, system: &SystemMeta) -> bool {
+        match self {
+            ConditionState::System { state, run, .. } => run(state, world, system),
+            ConditionState::And(a, b) => a.evaluate(world, system) && b.evaluate(world, system),
+            ConditionState::Or(a, b) => a.evaluate(world, system) || b.evaluate(world, system),
+            ConditionState::Not(condition) => !condition.evaluate(world, system),
+        }
+    }
+
+    pub fn access(&self) -> Vec<SystemAccess> {
+        match self {
+            ConditionState::System { state, access, .. } => access(state),
+            ConditionState::And(a, b) | ConditionState::Or(a, b) => {
+                let mut access = a.access();
+                access.extend(b.access());
+                access
+            }
+            ConditionState::Not(condition) => condition.access(),
+        }
+    }
+}
+
+pub trait IntoCondition<M> {
+    fn into_condition(self) -> Condition;
+
+    fn and<Marker>(self, other: impl IntoCondition<Marker>) -> Condition
+    where
+        Self: Sized,
+    {
+        Condition(ConditionKind::And(
+            Box::new(self.into_condition()),
+            Box::new(other.into_condition()),
+        ))
+    }
+
+    fn or<Marker>(self, other: impl IntoCondition<Marker>) -> Condition
+    where
+        Self: Sized,
+    {
+        Condition(ConditionKind::Or(
+            Box::new(self.into_condition()),
+            Box::new(other.into_condition()),
+        ))
+    }
+
+    fn not(self) -> Condition
+    where
+        Self: Sized,
+    {
+        Condition(ConditionKind::Not(Box::new(self.into_condition())))
+    }
+}
+
+impl IntoCondition<()> for Condition {
+    fn into_condition(self) -> Condition {
+        self
+    }
+}
+
+/// Passes when the resource was inserted or mutated since the guarded system last ran.
+pub fn resource_changed<R: Resource + Send>() -> Condition {
+    (|resource: Option<Res<R>>| resource.is_some_and(|resource| resource.is_changed()))
+        .into_condition()
+}
+
+/// Passes when the resource was inserted since the guarded system last ran.
+pub fn resource_added<R: Resource + Send>() -> Condition {
+    (|resource: Option<Res<R>>| resource.is_some_and(|resource| resource.is_added()))
+        .into_condition()
+}
+
+macro_rules! impl_into_condition {
+    ($($arg:ident),*) => {
+        #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe, clippy::unused_unit)]
+        impl<F, $($arg: SystemArg),*> IntoCondition<(F, $($arg),*)> for F
+        where
+            for<'world, 'state> F: Fn($($arg),*) -> bool
+                + Fn($(ArgItem<'world, 'state, $arg>),*) -> bool
+                + Send
+                + Sync
+                + 'static,
+        {
+            fn into_condition(self) -> Condition {
+                let init = |world: &mut World| {
+                    let state = ($($arg::init(world),)*);
+                    Box::new(state) as Box<dyn Any + Send + Sync>
+                };
+
+                let run = move |state: &mut SystemState, world: WorldCell, system: &SystemMeta| {
+                    let ($($arg,)*) = state.downcast_mut::<($($arg::State,)*)>().unwrap();
+                    let ($($arg,)*) = unsafe { ($($arg::get($arg, world, system),)*) };
+                    self($($arg,)*)
+                };
+
+                let access = |state: &SystemState| {
+                    let ($($arg,)*) = state.downcast_ref::<($($arg::State,)*)>().unwrap();
+                    let mut access = Vec::new();
+                    $(access.extend($arg::access($arg));)*
+                    access
+                };
+
+                Condition(ConditionKind::System {
+                    init,
+                    access,
+                    send: ($($arg::send() &&)* true),
+                    exclusive: ($($arg::exclusive() ||)* false),
+                    run: Arc::new(run),
+                })
+            }
+        }
+    };
+}
+
+impl_into_condition!();
+impl_into_condition!(A);
+impl_into_condition!(A, B);
+impl_into_condition!(A, B, C);
+impl_into_condition!(A, B, C, D);
+impl_into_condition!(A, B, C, D, E);
+impl_into_condition!(A, B, C, D, E, F2);
+impl_into_condition!(A, B, C, D, E, F2, G);
+impl_into_condition!(A, B, C, D, E, F2, G, H);
@@ -0,0 +1,80 @@
+use super::{SystemId, SystemName};
+use crate::world::{Resource, WorldStats};
+use std::{collections::HashMap, time::Duration};
+
+/// Execution times of a system or phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    pub last: Duration,
+    pub total: Duration,
+    pub runs: u32,
+}
+
+impl Timing {
+    pub fn record(&mut self, duration: Duration) {
+        self.last = duration;
+        self.total += duration;
+        self.runs += 1;
+    }
+
+    pub fn average(&self) -> Duration {
+        match self.runs {
+            0 => Duration::ZERO,
+            runs => self.total / runs,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemDiagnostics {
+    pub name: Option<SystemName>,
+    pub phase: &'static str,
+    pub timing: Timing,
+}
+
+/// Opts into profiling, systems are only timed while the resource exists. Updated by
+/// [`App::run`](crate::app::App::run) after every phase, along with the [`WorldStats`].
+#[derive(Debug, Default)]
+pub struct DiagnosticsStore {
+    systems: HashMap<SystemId, SystemDiagnostics>,
+    phases: HashMap<&'static str, Timing>,
+    world: Option<WorldStats>,
+}
+
+impl Resource for DiagnosticsStore {}
+
+impl DiagnosticsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn system(&self, id: SystemId) -> Option<&SystemDiagnostics> {
+        self.systems.get(&id)
+    }
+
+    pub fn systems(&self) -> impl Iterator<Item = (&SystemId, &SystemDiagnostics)> {
+        self.systems.iter()
+    }
+
+    /// Timing of a phase, including its sub phases.
+    pub fn phase(&self, name: &str) -> Option<&Timing> {
+        self.phases.get(name)
+    }
+
+    /// [`WorldStats`] as of the end of the last phase.
+    pub fn world(&self) -> Option<&WorldStats> {
+        self.world.as_ref()
+    }
+
+    pub fn record_phase(&mut self, phase: &'static str, duration: Duration) {
+        self.phases.entry(phase).or_default().record(duration);
+    }
+
+    pub fn record_system(&mut self, id: SystemId, diagnostics: SystemDiagnostics) {
+        self.systems.insert(id, diagnostics);
+    }
+
+    pub fn record_world(&mut self, stats: WorldStats) {
+        self.world = Some(stats);
+    }
+}
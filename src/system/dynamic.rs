@@ -0,0 +1,236 @@
+use super::{Access, SystemAccess};
+use crate::core::{Frame, ObjectStatus};
+use crate::world::{ComponentId, Entity, World, archetype::ArchetypeQuery};
+use std::{marker::PhantomData, ptr::NonNull};
+
+/// Builds a [`DynamicQuery`] from component ids known only at runtime.
+#[derive(Default)]
+pub struct QueryBuilder {
+    query: ArchetypeQuery,
+    terms: Vec<(ComponentId, Access)>,
+}
+
+impl QueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetches the component for reading, entities without it are skipped.
+    pub fn read(self, id: ComponentId) -> Self {
+        self.term(id, Access::Read)
+    }
+
+    /// Fetches the component for writing, entities without it are skipped.
+    pub fn write(self, id: ComponentId) -> Self {
+        self.term(id, Access::Write)
+    }
+
+    /// Only matches entities with the component, without fetching it.
+    pub fn with(mut self, id: ComponentId) -> Self {
+        self.query.include(id);
+        self
+    }
+
+    /// Only matches entities without the component.
+    pub fn without(mut self, id: ComponentId) -> Self {
+        self.query.exclude(id);
+        self
+    }
+
+    pub fn build(self) -> DynamicQuery {
+        DynamicQuery {
+            query: self.query,
+            terms: self.terms,
+        }
+    }
+
+    fn term(mut self, id: ComponentId, access: Access) -> Self {
+        self.query.include(id);
+        match self.terms.iter_mut().find(|(term, _)| *term == id) {
+            Some((_, current)) if access == Access::Write => *current = access,
+            Some(_) => {}
+            None => self.terms.push((id, access)),
+        }
+
+        self
+    }
+}
+
+/// Query over component ids, yielding untyped [`DynamicItem`]s.
+pub struct DynamicQuery {
+    query: ArchetypeQuery,
+    terms: Vec<(ComponentId, Access)>,
+}
+
+impl DynamicQuery {
+    /// Fetched components in the order they were added to the builder.
+    pub fn terms(&self) -> &[(ComponentId, Access)] {
+        &self.terms
+    }
+
+    pub fn access(&self) -> Vec<SystemAccess> {
+        self.terms
+            .iter()
+            .map(|(id, access)| SystemAccess::component(*id, *access))
+            .collect()
+    }
+
+    pub fn iter<'w>(&'w self, world: &'w mut World) -> impl Iterator<Item = DynamicItem<'w>> + 'w {
+        let archetypes = world
+            .archetypes()
+            .query_iter(&self.query)
+            .map(|archetype| archetype.id())
+            .collect::<Vec<_>>();
+
+        // Writes need per row frames, so frozen columns are thawed up front.
+        let frame = world.frame();
+        for archetype in &archetypes {
+            let table = world.archetypes_mut()[*archetype].table_mut();
+            for (id, _) in self.terms.iter().filter(|(_, a)| *a == Access::Write) {
+                if let Some(column) = table.get_column_mut(*id) {
+                    column.thaw();
+                    column.mark_modified(frame);
+                }
+            }
+        }
+
+        let world: &'w World = world;
+        archetypes.into_iter().flat_map(move |archetype| {
+            let table = world.archetypes()[archetype].table();
+            table.entities().enumerate().map(move |(row, entity)| {
+                let components = self
+                    .terms
+                    .iter()
+                    .map(|(id, access)| {
+                        let column = table.get_column(*id).unwrap();
+                        let (ptr, status) = column.get_raw(row).unwrap();
+                        DynamicComponent {
+                            id: *id,
+                            access: *access,
+                            ptr,
+                            status,
+                        }
+                    })
+                    .collect();
+
+                DynamicItem {
+                    entity: *entity,
+                    components,
+                    frame,
+                    _marker: PhantomData,
+                }
+            })
+        })
+    }
+}
+
+struct DynamicComponent {
+    id: ComponentId,
+    access: Access,
+    ptr: NonNull<u8>,
+    status: Option<NonNull<ObjectStatus>>,
+}
+
+/// Components of one entity matched by a [`DynamicQuery`]. Pointers are untyped, use the
+/// component's meta in [`World::components`] to interpret them.
+pub struct DynamicItem<'w> {
+    entity: Entity,
+    components: Vec<DynamicComponent>,
+    frame: Frame,
+    _marker: PhantomData<&'w mut World>,
+}
+
+impl DynamicItem<'_> {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.components.iter().map(|component| component.id)
+    }
+
+    /// Pointer to a fetched component, only valid for reads.
+    pub fn get(&self, id: ComponentId) -> Option<NonNull<u8>> {
+        self.component(id).map(|component| component.ptr)
+    }
+
+    /// Pointer to a component fetched with [`QueryBuilder::write`], marking it modified.
+    pub fn get_mut(&mut self, id: ComponentId) -> Option<NonNull<u8>> {
+        let frame = self.frame;
+        let component = self.component(id)?;
+        if component.access != Access::Write {
+            return None;
+        }
+
+        if let Some(mut status) = component.status {
+            unsafe { status.as_mut().modified = frame };
+        }
+
+        Some(component.ptr)
+    }
+
+    fn component(&self, id: ComponentId) -> Option<&DynamicComponent> {
+        self.components.iter().find(|component| component.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryBuilder;
+    use crate::system::query::{Modified, Query, QueryState};
+    use crate::world::{Component, Entity, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Debug, PartialEq)]
+    struct Armor(u32);
+    impl Component for Armor {}
+
+    struct Dead(bool);
+    impl Component for Dead {}
+
+    #[test]
+    fn dynamic_query() {
+        let mut world = World::new();
+        let health = world.register::<Health>();
+        let armor = world.register::<Armor>();
+        let dead = world.register::<Dead>();
+
+        let a = world.spawn_bundle((Health(10), Armor(2)));
+        let b = world.spawn_bundle((Health(20),));
+        world.spawn_bundle((Health(30), Armor(4), Dead(true)));
+
+        let last_run = world.frame();
+        world.update();
+
+        let query = QueryBuilder::new()
+            .write(health)
+            .read(armor)
+            .without(dead)
+            .build();
+        let mut visited = vec![];
+        for mut item in query.iter(&mut world) {
+            assert!(item.get_mut(armor).is_none());
+            let armor = unsafe { item.get(armor).unwrap().cast::<Armor>().as_ref().0 };
+            let health = unsafe { item.get_mut(health).unwrap().cast::<Health>().as_mut() };
+            health.0 -= armor;
+            visited.push(item.entity());
+        }
+
+        assert_eq!(visited, [a]);
+        assert_eq!(world.get_component::<Health>(a), Some(&Health(8)));
+
+        let query = QueryBuilder::new().read(health).without(armor).build();
+        let items = query.iter(&mut world).map(|item| item.entity());
+        assert_eq!(items.collect::<Vec<_>>(), [b]);
+
+        let query = QueryBuilder::new().read(armor).with(dead).build();
+        assert_eq!(query.iter(&mut world).count(), 1);
+
+        let state = QueryState::<Entity, Modified<Health>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().collect::<Vec<_>>(), [a]);
+    }
+}
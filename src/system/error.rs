@@ -0,0 +1,100 @@
+use super::SystemName;
+use crate::world::World;
+use std::{error::Error, fmt::Display, sync::Mutex};
+
+/// An error returned by a fallible system.
+pub struct SystemError(Box<dyn Error + Send + Sync>);
+
+impl SystemError {
+    pub fn new(message: impl Display) -> Self {
+        Self(message.to_string().into())
+    }
+
+    pub fn inner(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.0.as_ref()
+    }
+}
+
+impl<E: Error + Send + Sync + 'static> From<E> for SystemError {
+    fn from(error: E) -> Self {
+        Self(Box::new(error))
+    }
+}
+
+impl std::fmt::Debug for SystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for SystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Return types a system function can have.
+pub trait SystemOutput {
+    fn into_result(self) -> Result<(), SystemError>;
+}
+
+impl SystemOutput for () {
+    fn into_result(self) -> Result<(), SystemError> {
+        Ok(())
+    }
+}
+
+impl SystemOutput for Result<(), SystemError> {
+    fn into_result(self) -> Result<(), SystemError> {
+        self
+    }
+}
+
+/// An error returned by a system, with the name of the system that failed.
+#[derive(Debug)]
+pub struct SystemFailure {
+    pub system: Option<SystemName>,
+    pub error: SystemError,
+}
+
+impl Display for SystemFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.system {
+            Some(name) => write!(f, "System {} failed: {}", name, self.error),
+            None => write!(f, "System failed: {}", self.error),
+        }
+    }
+}
+
+/// Handles the errors returned by systems, see
+/// [`AppBuilder::set_error_handler`](crate::app::AppBuilder::set_error_handler).
+pub type ErrorHandler = fn(&mut World, SystemFailure);
+
+/// The default [`ErrorHandler`], prints the failure to stderr.
+pub fn log_error(_: &mut World, failure: SystemFailure) {
+    eprintln!("{}", failure);
+}
+
+pub fn panic_on_error(_: &mut World, failure: SystemFailure) {
+    panic!("{}", failure);
+}
+
+pub fn skip_error(_: &mut World, _: SystemFailure) {}
+
+/// Failures collected by the executors until the app handles them.
+#[derive(Default)]
+pub struct SystemErrors(Mutex<Vec<SystemFailure>>);
+
+impl SystemErrors {
+    pub fn push(&self, failure: SystemFailure) {
+        self.0.lock().unwrap().push(failure);
+    }
+
+    pub fn take(&self) -> Vec<SystemFailure> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().is_empty()
+    }
+}
@@ -1,27 +1,649 @@
-use super::SystemCell;
-use crate::{core::IndexDag, world::WorldCell};
+use super::{SystemCell, SystemMeta, arg::SystemArg};
+use crate::{
+    core::IndexDag,
+    world::{Resource, World, WorldCell},
+};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
 
 pub mod parallel;
 pub mod sequential;
+#[cfg(test)]
+pub mod test;
 
 pub use parallel::*;
 pub use sequential::*;
 
 pub trait SystemExecutor: 'static {
-    fn execute(&self, world: WorldCell);
+    fn execute(&self, world: WorldCell) -> PhaseRunResult;
+
+    fn systems(&self) -> &[SystemCell];
+
+    fn systems_mut(&mut self) -> &mut [SystemCell];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhaseRunResult {
+    Completed,
+    /// Execution was cancelled before every system could run.
+    /// `completed` systems ran and applied their commands, `skipped` systems never started.
+    /// Both count the systems of the phase and of its sub phases.
+    Cancelled {
+        completed: usize,
+        skipped: usize,
+    },
+}
+
+impl PhaseRunResult {
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, PhaseRunResult::Cancelled { .. })
+    }
+}
+
+/// Cooperative cancellation flag checked by the executors between systems.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn reset(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// System argument for long running systems that want to poll for cancellation.
+pub struct Cancelled<'w>(&'w CancelHandle);
+
+impl<'w> Cancelled<'w> {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+}
+
+unsafe impl SystemArg for Cancelled<'_> {
+    type Item<'world, 'state> = Cancelled<'world>;
+
+    type State = ();
+
+    fn init(_: &mut World) -> Self::State {}
+
+    unsafe fn get<'world, 'state>(
+        _: &'state mut Self::State,
+        world: WorldCell<'world>,
+        _: &SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        Cancelled(unsafe { world.get().cancel_handle() })
+    }
+}
+
+/// Configures the [`TaskPool`](crate::core::TaskPool) the parallel executor runs systems on. Read
+/// when the world's pool is first used, so add it before the first parallel phase runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutorConfig {
+    /// Worker threads of the pool, at least one.
+    pub threads: usize,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+}
+
+impl Resource for ExecutorConfig {}
+
+/// When the parallel executor applies the deferred commands of finished systems. The
+/// sequential executor applies them after every system regardless.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncPoints {
+    /// Once every system of the phase finished.
+    #[default]
+    Phase,
+    /// After each dependency level, so systems see the commands of the systems they run after.
+    /// Systems of the next level only start once the previous level is applied.
+    Levels,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum RunMode {
     Sequential,
     Parallel,
+    /// Runs systems in parallel, dispatched in a stable order, see
+    /// [`ParallelExecutor::deterministic`].
+    DeterministicParallel,
+    /// Runs the sequential or parallel executor through a [`test::TestExecutor`].
+    #[cfg(test)]
+    Test {
+        parallel: bool,
+    },
 }
 
 impl RunMode {
-    pub fn create_executor(&self, systems: IndexDag<SystemCell>) -> Box<dyn SystemExecutor> {
+    pub fn create_executor(
+        &self,
+        systems: IndexDag<SystemCell>,
+        sync: SyncPoints,
+    ) -> Box<dyn SystemExecutor> {
         match self {
             RunMode::Sequential => Box::new(SequentialExecutor::new(systems)),
-            RunMode::Parallel => Box::new(ParallelExecutor::new(systems)),
+            RunMode::Parallel => Box::new(ParallelExecutor::new(systems, sync)),
+            RunMode::DeterministicParallel => {
+                Box::new(ParallelExecutor::new(systems, sync).deterministic())
+            }
+            #[cfg(test)]
+            RunMode::Test { parallel } => {
+                Box::new(test::TestExecutor::new(*parallel, sync, systems))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Cancelled, ExecutorConfig, ParallelExecutor, PhaseRunResult, RunMode, SyncPoints,
+        SystemExecutor,
+    };
+    use crate::{
+        app::App,
+        core::{IndexDag, SparseIndex},
+        system::{
+            IntoSystemConfigs, SystemCell, condition::IntoCondition, query::Query, schedule::Phase,
+        },
+        world::{Commands, Component, NonSend, NonSendMut, Resource, World, WorldCell},
+    };
+    use std::{
+        collections::HashSet,
+        sync::{
+            Mutex,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+        },
+        thread::ThreadId,
+        time::Duration,
+    };
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Default)]
+    struct Counter(usize);
+    impl Resource for Counter {}
+
+    fn cancel_mid_phase(mode: RunMode, sync: SyncPoints) {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(mode);
+        builder.schedule_mut().set_sync_points(sync);
+
+        let handle = builder.world().cancel_handle().clone();
+        let fired = AtomicBool::new(false);
+        let cancel = move |cancelled: Cancelled| {
+            if fired.swap(true, Ordering::Relaxed) {
+                return;
+            }
+
+            let handle = handle.clone();
+            std::thread::spawn(move || handle.cancel()).join().unwrap();
+            assert!(cancelled.is_cancelled());
+        };
+        let count = |counter: &mut Counter| counter.0 += 1;
+
+        let mut app = builder
+            .add_resource(Counter::default())
+            .add_systems(Update, cancel.before(count))
+            .build();
+
+        let result = app.run(Update);
+        assert_eq!(
+            result,
+            PhaseRunResult::Cancelled {
+                completed: 1,
+                skipped: 1
+            }
+        );
+        assert_eq!(app.world().resource::<Counter>().0, 0);
+        assert!(!app.cancel_handle().is_cancelled());
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().resource::<Counter>().0, 1);
+    }
+
+    #[test]
+    fn sequential_cancel() {
+        cancel_mid_phase(RunMode::Sequential, SyncPoints::Phase);
+    }
+
+    #[test]
+    fn parallel_cancel() {
+        cancel_mid_phase(RunMode::Parallel, SyncPoints::Phase);
+        cancel_mid_phase(RunMode::Parallel, SyncPoints::Levels);
+        cancel_mid_phase(RunMode::DeterministicParallel, SyncPoints::Phase);
+        cancel_mid_phase(RunMode::DeterministicParallel, SyncPoints::Levels);
+    }
+
+    #[test]
+    fn cancel_counts_sub_phases() {
+        struct Late;
+        impl Phase for Late {}
+
+        let mut builder = App::new();
+        let handle = builder.world().cancel_handle().clone();
+        let cancel = move || handle.cancel();
+        let count = |counter: &mut Counter| counter.0 += 1;
+
+        let mut app = builder
+            .add_resource(Counter::default())
+            .add_sub_phase(Update, Late)
+            .add_systems(Update, (count, cancel, count).chain())
+            .add_systems(Late, (count, count))
+            .build();
+
+        let result = app.run(Update);
+        assert_eq!(
+            result,
+            PhaseRunResult::Cancelled {
+                completed: 2,
+                skipped: 3
+            }
+        );
+        assert_eq!(app.world().resource::<Counter>().0, 1);
+    }
+
+    #[test]
+    fn cancel_outside_of_run() {
+        let mut builder = App::new();
+        let handle = builder.world().cancel_handle().clone();
+        let late = move || handle.cancel();
+        let count = |counter: &mut Counter| counter.0 += 1;
+
+        let mut app = builder
+            .add_resource(Counter::default())
+            .add_systems(Update, count.before(late))
+            .build();
+
+        // Cancelling from the last system leaves nothing to skip.
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert!(!app.cancel_handle().is_cancelled());
+
+        app.cancel_handle().cancel();
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().resource::<Counter>().0, 2);
+    }
+
+    struct Marker(#[allow(dead_code)] u8);
+    impl Component for Marker {}
+
+    fn spawn(mut commands: Commands) {
+        commands.spawn().insert(Marker(0));
+    }
+
+    fn observe(markers: Query<&Marker>, counter: &mut Counter) {
+        counter.0 = markers.iter().count();
+    }
+
+    fn observed(sync: SyncPoints) -> usize {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(RunMode::Parallel);
+        builder.schedule_mut().set_sync_points(sync);
+        builder.register::<Marker>();
+
+        let mut app = builder
+            .add_resource(Counter::default())
+            .add_systems(Update, spawn.before(observe))
+            .add_systems(Update, spawn)
+            .build();
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().archetypes().archetypes().len(), 2);
+        app.world().resource::<Counter>().0
+    }
+
+    #[test]
+    fn parallel_sync_points() {
+        assert_eq!(observed(SyncPoints::Phase), 0);
+        assert_eq!(observed(SyncPoints::Levels), 2);
+    }
+
+    #[derive(Default)]
+    struct Running {
+        count: AtomicUsize,
+        exclusive: AtomicBool,
+        overlapped: AtomicBool,
+        log: Mutex<Vec<&'static str>>,
+    }
+    impl Resource for Running {}
+
+    impl Running {
+        fn run(&self, name: &'static str) {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(5));
+            if self.exclusive.load(Ordering::SeqCst) {
+                self.overlapped.store(true, Ordering::SeqCst);
+            }
+            self.log.lock().unwrap().push(name);
+            self.count.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    fn first(running: &Running) {
+        running.run("first");
+    }
+
+    fn second(running: &Running) {
+        running.run("second");
+    }
+
+    fn last(running: &Running) {
+        running.run("last");
+    }
+
+    fn exclusive(world: &mut World) {
+        let running = world.resource::<Running>();
+        running.exclusive.store(true, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(5));
+        if running.count.load(Ordering::SeqCst) > 0 {
+            running.overlapped.store(true, Ordering::SeqCst);
+        }
+        running.log.lock().unwrap().push("exclusive");
+        running.exclusive.store(false, Ordering::SeqCst);
+        world.resource_mut::<Counter>().0 += 1;
+    }
+
+    #[test]
+    fn parallel_exclusive_runs_alone() {
+        for sync in [SyncPoints::Phase, SyncPoints::Levels] {
+            let mut builder = App::new();
+            builder.schedule_mut().set_mode(RunMode::Parallel);
+            builder.schedule_mut().set_sync_points(sync);
+
+            let mut app = builder
+                .add_resource(Counter::default())
+                .add_resource(Running::default())
+                .add_systems(Update, first)
+                .add_systems(Update, second)
+                .add_systems(Update, exclusive)
+                .add_systems(Update, last)
+                .build();
+
+            for run in 1..=3 {
+                assert_eq!(app.run(Update), PhaseRunResult::Completed);
+                assert_eq!(app.world().resource::<Counter>().0, run);
+            }
+
+            let log = app
+                .world()
+                .resource::<Running>()
+                .log
+                .lock()
+                .unwrap()
+                .clone();
+            for run in log.chunks(4) {
+                let mut before = run[..2].to_vec();
+                before.sort();
+                assert_eq!(before, ["first", "second"]);
+                assert_eq!(run[2..], ["exclusive", "last"]);
+            }
+        }
+    }
+
+    #[test]
+    fn exclusive_ordering() {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(RunMode::Parallel);
+
+        let mut app = builder
+            .add_resource(Counter::default())
+            .add_resource(Running::default())
+            .add_systems(Update, last.after(exclusive).after(first))
+            .build();
+
+        app.run(Update);
+        let log = app
+            .world()
+            .resource::<Running>()
+            .log
+            .lock()
+            .unwrap()
+            .clone();
+        assert_eq!(log, ["first", "exclusive", "last"]);
+    }
+
+    #[test]
+    fn parallel_executor_isolates_exclusive() {
+        let mut world = World::new();
+        world.add_resource(Counter::default());
+        world.add_resource(Running::default());
+
+        // No dependencies between the systems, the executor alone keeps the exclusive one apart.
+        let mut systems = IndexDag::new();
+        for config in [
+            first.configs(),
+            exclusive.configs(),
+            second.configs(),
+            last.configs(),
+        ] {
+            systems.add_node(SystemCell::from(
+                config.single().into_system_node(&mut world),
+            ));
+        }
+        systems.build().unwrap();
+
+        let executor = ParallelExecutor::new(systems, SyncPoints::Phase);
+        for run in 1..=5 {
+            let result = executor.execute(unsafe { WorldCell::new_mut(&mut world) });
+            assert_eq!(result, PhaseRunResult::Completed);
+            assert_eq!(world.resource::<Counter>().0, run);
+        }
+
+        let running = world.resource::<Running>();
+        assert!(!running.overlapped.load(Ordering::SeqCst));
+        assert_eq!(running.log.lock().unwrap().len(), 20);
+    }
+
+    struct Enabled(bool);
+    impl Resource for Enabled {}
+
+    fn enabled(enabled: &Enabled) -> bool {
+        enabled.0
+    }
+
+    fn ones(counter: &mut Counter) {
+        counter.0 += 1;
+    }
+
+    fn tens(counter: &mut Counter) {
+        counter.0 += 10;
+    }
+
+    fn hundreds(counter: &mut Counter) {
+        counter.0 += 100;
+    }
+
+    fn run_conditions(mode: RunMode) {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(mode);
+
+        let mut app = builder
+            .add_resource(Counter::default())
+            .add_resource(Enabled(false))
+            .add_systems(Update, ones.run_if(enabled))
+            .add_systems(Update, tens.run_if(enabled.not()))
+            .add_systems(Update, hundreds.run_if((|| false).or(enabled)))
+            .add_systems(Update, hundreds.run_if(enabled.and(|| false)))
+            .build();
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().resource::<Counter>().0, 10);
+
+        app.world_mut().resource_mut::<Enabled>().0 = true;
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().resource::<Counter>().0, 111);
+    }
+
+    #[test]
+    fn sequential_run_conditions() {
+        run_conditions(RunMode::Sequential);
+    }
+
+    #[test]
+    fn parallel_run_conditions() {
+        run_conditions(RunMode::Parallel);
+        run_conditions(RunMode::DeterministicParallel);
+    }
+
+    #[test]
+    fn run_condition_access() {
+        let mut world = World::new();
+        world.add_resource(Enabled(true));
+
+        let id = world.resources().get_id::<Enabled>().unwrap();
+        let system = (|| {})
+            .run_if(enabled)
+            .single()
+            .into_system_node(&mut world);
+        assert!(system.system.meta().resources.reads(id.to_usize()));
+    }
+
+    #[derive(Default)]
+    struct Threads(Mutex<HashSet<String>>);
+    impl Resource for Threads {}
+
+    fn record_thread(threads: &Threads) {
+        let name = std::thread::current()
+            .name()
+            .unwrap_or_default()
+            .to_string();
+        threads.0.lock().unwrap().insert(name);
+    }
+
+    #[test]
+    fn parallel_executor_reuses_pool() {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(RunMode::Parallel);
+
+        let mut app = builder
+            .add_resource(ExecutorConfig { threads: 2 })
+            .add_resource(Threads::default())
+            .add_systems(Update, record_thread)
+            .add_systems(Update, record_thread)
+            .add_systems(Update, record_thread)
+            .build();
+
+        for _ in 0..10 {
+            assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        }
+
+        assert_eq!(app.world().task_pool().threads(), 2);
+        let threads = app.world().resource::<Threads>().0.lock().unwrap();
+        assert!(threads.len() <= 2);
+        assert!(threads.iter().all(|name| name.starts_with("task-pool-")));
+    }
+
+    #[derive(Default)]
+    struct LocalThreads(Vec<ThreadId>);
+    impl Resource for LocalThreads {}
+
+    fn record_local(mut threads: NonSendMut<LocalThreads>) {
+        threads.0.push(std::thread::current().id());
+    }
+
+    fn local_condition(threads: NonSend<LocalThreads>) -> bool {
+        threads.0.len() < 100
+    }
+
+    #[test]
+    fn parallel_non_send_main_thread() {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(RunMode::Parallel);
+
+        let mut app = builder
+            .add_resource(ExecutorConfig { threads: 2 })
+            .add_resource(Threads::default())
+            .add_non_send_resource(LocalThreads::default())
+            .add_systems(Update, record_thread)
+            .add_systems(Update, record_local)
+            .add_systems(Update, record_thread)
+            .add_systems(Update, (|| {}).run_if(local_condition))
+            .add_systems(Update, record_local.run_if(local_condition))
+            .build();
+
+        for _ in 0..10 {
+            assert_eq!(app.run(Update), PhaseRunResult::Completed);
         }
+
+        let main = std::thread::current().id();
+        let locals = &app.world().non_send_resource::<LocalThreads>().0;
+        assert_eq!(locals.len(), 20);
+        assert!(locals.iter().all(|thread| *thread == main));
+
+        let threads = app.world().resource::<Threads>().0.lock().unwrap();
+        assert!(threads.iter().all(|name| name.starts_with("task-pool-")));
+    }
+
+    #[derive(Default)]
+    struct Log(Mutex<Vec<&'static str>>);
+    impl Resource for Log {}
+
+    fn slow(log: &Log) {
+        std::thread::sleep(Duration::from_millis(30));
+        log.0.lock().unwrap().push("slow");
+    }
+
+    fn fast(log: &Log) {
+        log.0.lock().unwrap().push("fast");
+    }
+
+    fn after_slow(log: &Log) {
+        log.0.lock().unwrap().push("after_slow");
+    }
+
+    fn after_fast(log: &Log) {
+        std::thread::sleep(Duration::from_millis(10));
+        log.0.lock().unwrap().push("after_fast");
+    }
+
+    fn dispatch_order(mode: RunMode) -> Vec<&'static str> {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(mode);
+
+        let mut app = builder
+            .add_resource(ExecutorConfig { threads: 4 })
+            .add_resource(Log::default())
+            .add_systems(Update, slow.before(after_slow))
+            .add_systems(Update, fast.before(after_fast))
+            .build();
+
+        app.run(Update);
+        let log = app.world().resource::<Log>().0.lock().unwrap();
+        log.iter()
+            .copied()
+            .filter(|name| name.starts_with("after"))
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_dispatch_order() {
+        assert_eq!(
+            dispatch_order(RunMode::Parallel),
+            vec!["after_fast", "after_slow"]
+        );
+        assert_eq!(
+            dispatch_order(RunMode::DeterministicParallel),
+            vec!["after_slow", "after_fast"]
+        );
     }
 }
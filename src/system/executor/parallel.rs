@@ -1,26 +1,58 @@
-use super::SystemExecutor;
+use super::{CancelHandle, PhaseRunResult, SyncPoints, SystemExecutor};
 use crate::{
-    core::{ImmutableIndexDag, IndexDag},
+    core::{ImmutableIndexDag, IndexDag, Scope},
     system::SystemCell,
     world::WorldCell,
 };
 use fixedbitset::FixedBitSet;
 use std::{
+    panic::AssertUnwindSafe,
     sync::{
         Arc, Mutex, MutexGuard,
         mpsc::{Sender, channel},
     },
-    thread::Scope,
+    thread::ThreadId,
 };
 
 pub struct ParallelExecutor {
     state: Arc<Mutex<ExecutionState>>,
     systems: ImmutableIndexDag<SystemCell>,
     initial_systems: FixedBitSet,
+    /// Dependency level of each system, only set with [`SyncPoints::Levels`].
+    levels: Option<Levels>,
+    /// Order ready systems are dispatched in, only set in deterministic mode.
+    order: Option<Box<[usize]>>,
+}
+
+/// Systems grouped by their longest chain of dependencies.
+pub struct Levels {
+    level: Box<[usize]>,
+    sizes: Box<[usize]>,
+}
+
+impl Levels {
+    fn new(systems: &ImmutableIndexDag<SystemCell>) -> Self {
+        let mut level = vec![0; systems.len()];
+        for index in systems.topology() {
+            for dependent in systems.dependents()[*index].ones() {
+                level[dependent] = level[dependent].max(level[*index] + 1);
+            }
+        }
+
+        let mut sizes = vec![0; level.iter().max().map_or(0, |max| max + 1)];
+        for level in &level {
+            sizes[*level] += 1;
+        }
+
+        Self {
+            level: level.into_boxed_slice(),
+            sizes: sizes.into_boxed_slice(),
+        }
+    }
 }
 
 impl ParallelExecutor {
-    pub fn new(systems: IndexDag<SystemCell>) -> Self {
+    pub fn new(systems: IndexDag<SystemCell>, sync: SyncPoints) -> Self {
         let systems = systems.into_immutable();
 
         let mut initial_systems = FixedBitSet::with_capacity(systems.len());
@@ -28,59 +60,129 @@ impl ParallelExecutor {
             initial_systems.set(index, *deps == 0);
         }
 
+        let levels = match sync {
+            SyncPoints::Phase => None,
+            SyncPoints::Levels => Some(Levels::new(&systems)),
+        };
+
         let state = ExecutionState {
             dependencies: systems.dependencies().to_vec(),
             queue: initial_systems.clone(),
             completed: FixedBitSet::with_capacity(systems.len()),
+            applied: FixedBitSet::with_capacity(systems.len()),
+            running: 0,
+            exclusive: false,
+            level: 0,
+            next: 0,
+            remaining: levels
+                .as_ref()
+                .map_or(vec![], |levels| levels.sizes.to_vec()),
         };
 
         Self {
             state: Arc::new(Mutex::new(state)),
             systems,
             initial_systems,
+            levels,
+            order: None,
         }
     }
 
+    /// Dispatches systems one after the other in a stable order, by dependency level and then
+    /// [`SystemId`](crate::system::SystemId), so runs with identical inputs start systems in the
+    /// same order. Systems still overlap once dispatched.
+    pub fn deterministic(mut self) -> Self {
+        let levels = Levels::new(&self.systems);
+        let mut order = (0..self.systems.len()).collect::<Vec<_>>();
+        order.sort_by_key(|index| {
+            (
+                levels.level[*index],
+                self.systems.nodes()[*index].get().meta.id,
+            )
+        });
+        self.order = Some(order.into_boxed_slice());
+        self
+    }
+
     fn reset(&self) {
         let mut state = self.state.lock().unwrap();
         state.completed.clear();
+        state.applied.clear();
+        state.running = 0;
+        state.exclusive = false;
         state.queue = self.initial_systems.clone();
         state.dependencies = self.systems.dependencies().to_vec();
+        state.level = 0;
+        state.next = 0;
+        if let Some(levels) = &self.levels {
+            state.remaining.copy_from_slice(&levels.sizes);
+        }
+    }
+
+    /// Applies the commands of the completed systems that weren't applied yet, in dependency order.
+    fn apply(&self, mut world: WorldCell) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        for index in self.systems.topology() {
+            if !state.completed[*index] || state.applied[*index] {
+                continue;
+            }
+
+            state.applied.set(*index, true);
+            unsafe {
+                self.systems.nodes()[*index]
+                    .cast_mut()
+                    .apply(world.get_mut())
+            };
+        }
     }
 }
 
 impl SystemExecutor for ParallelExecutor {
-    fn execute(&self, mut world: WorldCell) {
+    fn execute(&self, world: WorldCell) -> PhaseRunResult {
+        // Reset up front so a run that unwound from a panicking system leaves no stale state.
+        self.reset();
         let (sender, receiver) = channel::<ExecutionResult>();
+        let cancel = unsafe { world.get().cancel_handle().clone() };
+        let pool = unsafe { world.get().task_pool() };
 
-        std::thread::scope(|scope| {
-            let ctx = Arc::new(ExecutionContext::new(
-                world,
-                &self.systems,
-                scope,
-                &sender,
-                self.state.clone(),
-            ));
+        pool.scope(|scope| {
+            let ctx = Arc::new(ExecutionContext::new(world, self, scope, &sender, cancel));
 
             ctx.execute();
 
             for result in receiver.iter() {
                 match result {
-                    ExecutionResult::Run(index) => ctx.run_system(index),
+                    ExecutionResult::RunLocal(index) => ctx.run_local(index),
+                    ExecutionResult::Sync => {
+                        // No system is running until the next level is started.
+                        self.apply(world);
+                        self.state.lock().unwrap().level += 1;
+                        ctx.execute();
+                    }
                     ExecutionResult::Done => break,
                 }
             }
         });
 
-        for index in self.systems.topology() {
-            unsafe {
-                self.systems.nodes()[*index]
-                    .cast_mut()
-                    .apply(world.get_mut())
-            };
+        self.apply(world);
+        let completed = self.state.lock().unwrap().completed.clone();
+
+        match completed.is_full() {
+            true => PhaseRunResult::Completed,
+            false => PhaseRunResult::Cancelled {
+                completed: completed.count_ones(..),
+                skipped: completed.count_zeroes(..),
+            },
         }
+    }
 
-        self.reset();
+    fn systems(&self) -> &[SystemCell] {
+        self.systems.nodes()
+    }
+
+    fn systems_mut(&mut self) -> &mut [SystemCell] {
+        self.systems.nodes_mut()
     }
 }
 
@@ -88,6 +190,16 @@ pub struct ExecutionState {
     dependencies: Vec<usize>,
     queue: FixedBitSet,
     completed: FixedBitSet,
+    applied: FixedBitSet,
+    running: usize,
+    /// An exclusive system is running, nothing else may start.
+    exclusive: bool,
+    /// Level currently running with [`SyncPoints::Levels`].
+    level: usize,
+    /// Systems of each level that haven't completed yet.
+    remaining: Vec<usize>,
+    /// Position in [`ParallelExecutor::deterministic`] order of the next system to dispatch.
+    next: usize,
 }
 
 impl Default for ExecutionState {
@@ -96,12 +208,21 @@ impl Default for ExecutionState {
             dependencies: Default::default(),
             queue: Default::default(),
             completed: Default::default(),
+            applied: Default::default(),
+            running: 0,
+            exclusive: false,
+            level: 0,
+            remaining: Default::default(),
+            next: 0,
         }
     }
 }
 
 pub enum ExecutionResult {
-    Run(usize),
+    /// Runs a non send or exclusive system on the thread that called [`SystemExecutor::execute`].
+    RunLocal(usize),
+    /// Every system of the current level completed, apply their commands before the next one.
+    Sync,
     Done,
 }
 
@@ -111,22 +232,32 @@ pub struct ExecutionContext<'scope, 'env: 'scope> {
     scope: &'scope Scope<'scope, 'env>,
     sender: &'env Sender<ExecutionResult>,
     state: Arc<Mutex<ExecutionState>>,
+    cancel: CancelHandle,
+    levels: Option<&'scope Levels>,
+    order: Option<&'scope [usize]>,
+    /// The thread that called [`SystemExecutor::execute`], the only one allowed to run non send
+    /// systems.
+    main_thread: ThreadId,
 }
 
 impl<'scope, 'env: 'scope> ExecutionContext<'scope, 'env> {
     pub fn new(
         world: WorldCell<'scope>,
-        systems: &'scope ImmutableIndexDag<SystemCell>,
+        executor: &'scope ParallelExecutor,
         scope: &'scope Scope<'scope, 'env>,
         sender: &'env Sender<ExecutionResult>,
-        state: Arc<Mutex<ExecutionState>>,
+        cancel: CancelHandle,
     ) -> Self {
         Self {
             world,
-            systems,
+            systems: &executor.systems,
             scope,
             sender,
-            state,
+            state: executor.state.clone(),
+            cancel,
+            levels: executor.levels.as_ref(),
+            order: executor.order.as_deref(),
+            main_thread: std::thread::current().id(),
         }
     }
 
@@ -136,6 +267,7 @@ impl<'scope, 'env: 'scope> ExecutionContext<'scope, 'env> {
         let scope = self.scope;
         let sender = self.sender;
         let state = self.state.clone();
+        let cancel = self.cancel.clone();
 
         Self {
             world,
@@ -143,6 +275,10 @@ impl<'scope, 'env: 'scope> ExecutionContext<'scope, 'env> {
             scope,
             sender,
             state,
+            cancel,
+            levels: self.levels,
+            order: self.order,
+            main_thread: self.main_thread,
         }
     }
 
@@ -151,8 +287,17 @@ impl<'scope, 'env: 'scope> ExecutionContext<'scope, 'env> {
         scoped.scope.spawn(move || scoped.run_system(index));
     }
 
-    fn spawn_non_send(&self, index: usize) {
-        self.sender.send(ExecutionResult::Run(index)).unwrap();
+    fn spawn_local(&self, index: usize) {
+        self.sender.send(ExecutionResult::RunLocal(index)).unwrap();
+    }
+
+    fn run_local(&self, index: usize) {
+        assert_eq!(
+            std::thread::current().id(),
+            self.main_thread,
+            "Local systems must run on the thread that started the phase"
+        );
+        self.run_system(index);
     }
 
     fn execute(&self) {
@@ -166,25 +311,129 @@ impl<'scope, 'env: 'scope> ExecutionContext<'scope, 'env> {
             return;
         }
 
-        for index in state.queue.clone().into_ones() {
+        if self.levels.is_some() && state.remaining[state.level] == 0 {
+            let _ = self.sender.send(ExecutionResult::Sync);
+            return;
+        }
+
+        if let Some(order) = self.order {
+            if !state.exclusive {
+                self.spawn_ordered(&mut state, order);
+            }
+
+            if self.cancel.is_cancelled() && state.running == 0 {
+                let _ = self.sender.send(ExecutionResult::Done);
+            }
+            return;
+        }
+
+        // A queued exclusive system holds back new systems until the running ones finish,
+        // then runs alone on the main thread.
+        let exclusive = state
+            .queue
+            .ones()
+            .filter(|index| self.is_current_level(&state, *index))
+            .find(|index| self.systems.nodes()[*index].get().meta.exclusive);
+
+        if state.exclusive || (exclusive.is_some() && state.running > 0) {
+            return;
+        }
+
+        if let Some(index) = exclusive {
+            if !self.cancel.is_cancelled() {
+                state.queue.set(index, false);
+                state.running += 1;
+                state.exclusive = true;
+                self.spawn_local(index);
+            }
+        } else {
+            for index in state.queue.clone().into_ones() {
+                if self.cancel.is_cancelled() {
+                    break;
+                }
+
+                if !self.is_current_level(&state, index) {
+                    continue;
+                }
+
+                state.queue.set(index, false);
+                state.running += 1;
+                if self.systems.nodes()[index].get().meta.send {
+                    self.spawn(index);
+                } else {
+                    self.spawn_local(index);
+                }
+            }
+        }
+
+        if self.cancel.is_cancelled() && state.running == 0 {
+            let _ = self.sender.send(ExecutionResult::Done);
+        }
+    }
+
+    /// Dispatches systems in order until one isn't ready, an exclusive system waits for the
+    /// running ones and then runs alone.
+    fn spawn_ordered(&self, state: &mut ExecutionState, order: &[usize]) {
+        while let Some(&index) = order.get(state.next) {
+            if self.cancel.is_cancelled()
+                || !state.queue[index]
+                || !self.is_current_level(state, index)
+            {
+                break;
+            }
+
+            let meta = &self.systems.nodes()[index].get().meta;
+            if meta.exclusive && state.running > 0 {
+                break;
+            }
+
             state.queue.set(index, false);
-            if self.systems.nodes()[index].get().meta.send {
+            state.running += 1;
+            state.next += 1;
+            if meta.exclusive {
+                state.exclusive = true;
+                self.spawn_local(index);
+                break;
+            } else if meta.send {
                 self.spawn(index);
             } else {
-                self.spawn_non_send(index);
+                self.spawn_local(index);
             }
         }
     }
 
+    fn is_current_level(&self, state: &ExecutionState, index: usize) -> bool {
+        self.levels
+            .is_none_or(|levels| levels.level[index] == state.level)
+    }
+
     fn run_system(&self, index: usize) {
-        unsafe { self.systems.nodes()[index].cast_mut().run(self.world) };
+        // A panicking system still has to be marked done, otherwise the phase waits on it forever.
+        let system = &self.systems.nodes()[index];
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+            let system = system.cast_mut();
+            if system.should_run(self.world)
+                && let Err(failure) = system.run(self.world)
+            {
+                self.world.get().system_errors().push(failure);
+            }
+        }));
+
         self.system_done(index);
+        if let Err(panic) = result {
+            std::panic::resume_unwind(panic);
+        }
     }
 
     fn system_done(&self, index: usize) {
         let mut state = self.state.lock().unwrap();
 
         state.completed.set(index, true);
+        state.running -= 1;
+        state.exclusive = false;
+        if let Some(levels) = self.levels {
+            state.remaining[levels.level[index]] -= 1;
+        }
 
         for dependent in self.systems.dependents()[index].ones() {
             state.dependencies[dependent] -= 1;
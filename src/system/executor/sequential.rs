@@ -1,4 +1,4 @@
-use super::SystemExecutor;
+use super::{PhaseRunResult, SystemExecutor};
 use crate::{
     core::{DagValues, IndexDag},
     system::SystemCell,
@@ -23,13 +23,37 @@ impl SequentialExecutor {
 }
 
 impl SystemExecutor for SequentialExecutor {
-    fn execute(&self, mut world: crate::world::WorldCell) {
-        for index in &self.order {
+    fn execute(&self, mut world: crate::world::WorldCell) -> PhaseRunResult {
+        let cancel = unsafe { world.get().cancel_handle().clone() };
+
+        for (completed, index) in self.order.iter().enumerate() {
+            if cancel.is_cancelled() {
+                return PhaseRunResult::Cancelled {
+                    completed,
+                    skipped: self.order.len() - completed,
+                };
+            }
+
             let system = &self.systems[*index];
             unsafe {
-                system.cast_mut().run(world);
-                system.cast_mut().apply(world.get_mut())
+                let system = system.cast_mut();
+                if system.should_run(world) {
+                    if let Err(failure) = system.run(world) {
+                        world.get().system_errors().push(failure);
+                    }
+                    system.apply(world.get_mut());
+                }
             };
         }
+
+        PhaseRunResult::Completed
+    }
+
+    fn systems(&self) -> &[SystemCell] {
+        &self.systems
+    }
+
+    fn systems_mut(&mut self) -> &mut [SystemCell] {
+        &mut self.systems
     }
 }
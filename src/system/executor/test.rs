@@ -0,0 +1,506 @@
+use super::{PhaseRunResult, RunMode, SyncPoints, SystemExecutor};
+use crate::{
+    core::IndexDag,
+    system::{SystemCell, SystemId, SystemMeta},
+    world::{Resource, World, WorldCell},
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+/// A fault injected by the [`TestExecutor`]. Systems are matched by the last segment of their name.
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Panics before the system starts its `run`th run, counting from 1.
+    PanicBefore { system: &'static str, run: usize },
+    /// Cancels the phase once `systems` systems have run in total.
+    CancelAfter { systems: usize },
+    /// Fails the argument validation of the system while the world is at `frame`, the system is
+    /// then skipped or reported according to its [`InvalidArgs`](crate::system::InvalidArgs).
+    FailValidation { system: &'static str, frame: u32 },
+    /// Sleeps before running the system, to force a specific wave composition.
+    Delay {
+        system: &'static str,
+        duration: Duration,
+    },
+}
+
+/// Faults applied by the [`TestExecutor`], read from the world every time a system runs.
+#[derive(Default)]
+pub struct FaultPlan {
+    faults: Vec<Fault>,
+    runs: Mutex<HashMap<SystemId, usize>>,
+    total: AtomicUsize,
+}
+
+impl Resource for FaultPlan {}
+
+impl FaultPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    fn matches(meta: &SystemMeta, system: &str) -> bool {
+        let name = meta.name.as_deref().unwrap_or_default();
+        name.rsplit("::").next() == Some(system)
+    }
+
+    fn before(&self, meta: &SystemMeta) {
+        let run = {
+            let mut runs = self.runs.lock().unwrap();
+            let run = runs.entry(meta.id).or_default();
+            *run += 1;
+            *run
+        };
+
+        for fault in &self.faults {
+            match fault {
+                Fault::PanicBefore { system, run: at }
+                    if *at == run && Self::matches(meta, system) =>
+                {
+                    panic!("Injected panic before {} run {}", system, run);
+                }
+                Fault::Delay { system, duration } if Self::matches(meta, system) => {
+                    std::thread::sleep(*duration);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn after(&self, world: &World) {
+        let total = self.total.fetch_add(1, Ordering::AcqRel) + 1;
+        for fault in &self.faults {
+            if let Fault::CancelAfter { systems } = fault
+                && *systems == total
+            {
+                world.cancel_handle().cancel();
+            }
+        }
+    }
+
+    fn fails_validation(&self, meta: &SystemMeta, world: &World) -> bool {
+        self.faults.iter().any(|fault| match fault {
+            Fault::FailValidation { system, frame } => {
+                world.frame().get() == *frame && Self::matches(meta, system)
+            }
+            _ => false,
+        })
+    }
+}
+
+/// Wraps the sequential or parallel executor and applies the world's [`FaultPlan`] around every
+/// system, so the real executor's panic, validation and cancellation handling is what gets
+/// exercised.
+pub struct TestExecutor(Box<dyn SystemExecutor>);
+
+impl TestExecutor {
+    pub fn new(parallel: bool, sync: SyncPoints, mut systems: IndexDag<SystemCell>) -> Self {
+        for cell in systems.nodes_mut() {
+            let system = cell.get_mut();
+            let validate = std::mem::replace(&mut system.validate, Box::new(|_, _, _| true));
+            system.validate = Box::new(move |state, world: WorldCell, meta| {
+                let plan = unsafe { world.get() }.try_resource::<FaultPlan>();
+                if plan.is_some_and(|plan| plan.fails_validation(meta, unsafe { world.get() })) {
+                    return false;
+                }
+
+                validate(state, world, meta)
+            });
+
+            let run = std::mem::replace(&mut system.run, Box::new(|_, _, _| Ok(())));
+            system.run = Box::new(move |state, world: WorldCell, meta| {
+                let plan = unsafe { world.get() }.try_resource::<FaultPlan>();
+                if let Some(plan) = plan {
+                    plan.before(meta);
+                }
+
+                let result = run(state, world, meta);
+
+                if let Some(plan) = plan {
+                    plan.after(unsafe { world.get() });
+                }
+
+                result
+            });
+        }
+
+        let mode = match parallel {
+            true => RunMode::Parallel,
+            false => RunMode::Sequential,
+        };
+
+        Self(mode.create_executor(systems, sync))
+    }
+}
+
+impl SystemExecutor for TestExecutor {
+    fn execute(&self, world: WorldCell) -> PhaseRunResult {
+        self.0.execute(world)
+    }
+
+    fn systems(&self) -> &[SystemCell] {
+        self.0.systems()
+    }
+
+    fn systems_mut(&mut self) -> &mut [SystemCell] {
+        self.0.systems_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fault, FaultPlan};
+    use crate::{
+        app::App,
+        system::{
+            IntoSystemConfigs, InvalidArgs, SystemId,
+            error::{SystemError, SystemFailure},
+            executor::{ExecutorConfig, PhaseRunResult, RunMode},
+            schedule::Phase,
+        },
+        world::{Commands, Resource, World},
+    };
+    use std::{panic::AssertUnwindSafe, sync::Mutex, time::Duration};
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Default)]
+    struct Log(Mutex<Vec<&'static str>>);
+    impl Resource for Log {}
+
+    impl Log {
+        fn entries(&self) -> Vec<&'static str> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn first(log: &Log) {
+        log.0.lock().unwrap().push("first");
+    }
+
+    fn second(log: &Log) {
+        log.0.lock().unwrap().push("second");
+    }
+
+    fn third(log: &Log) {
+        log.0.lock().unwrap().push("third");
+    }
+
+    fn fourth(log: &Log) {
+        log.0.lock().unwrap().push("fourth");
+    }
+
+    fn fails(log: &Log) -> Result<(), SystemError> {
+        log.0.lock().unwrap().push("fails");
+        Err(SystemError::new("fails"))
+    }
+
+    /// Failures handed to the app's error handler, by the last segment of the system name.
+    #[derive(Default)]
+    struct Failures(Vec<(Option<String>, String)>);
+    impl Resource for Failures {}
+
+    /// An error handler that records the failure and lets the app continue.
+    fn record(world: &mut World, failure: SystemFailure) {
+        let system = failure
+            .system
+            .as_deref()
+            .and_then(|name| name.rsplit("::").next())
+            .map(str::to_string);
+        let failures = &mut world.resource_mut::<Failures>().0;
+        failures.push((system, failure.error.to_string()));
+    }
+
+    fn app(parallel: bool, plan: FaultPlan) -> crate::app::AppBuilder {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(RunMode::Test { parallel });
+        // Faults like delays need systems to actually overlap, even on a single core machine.
+        builder
+            .add_resource(Log::default())
+            .add_resource(plan)
+            .add_resource(Failures::default())
+            .add_resource(ExecutorConfig { threads: 4 })
+            .set_error_handler(record);
+        builder
+    }
+
+    fn panic_before_nth_run(parallel: bool) {
+        let plan = FaultPlan::new().with(Fault::PanicBefore {
+            system: "second",
+            run: 3,
+        });
+
+        let mut app = app(parallel, plan)
+            .add_systems(Update, first.before(second))
+            .build();
+
+        app.run(Update);
+        app.run(Update);
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| app.run(Update)));
+        assert!(result.is_err());
+
+        let log = app.world().resource::<Log>();
+        assert_eq!(log.entries().len(), 5);
+        assert_eq!(log.entries().last(), Some(&"first"));
+
+        // The executor recovers and the next run completes.
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().resource::<Log>().entries().len(), 7);
+    }
+
+    #[test]
+    fn fault_panic_before_nth_run_sequential() {
+        panic_before_nth_run(false);
+    }
+
+    #[test]
+    fn fault_panic_before_nth_run_parallel() {
+        panic_before_nth_run(true);
+    }
+
+    fn cancel_after_systems(parallel: bool) {
+        let plan = FaultPlan::new().with(Fault::CancelAfter { systems: 2 });
+        let mut app = app(parallel, plan)
+            .add_systems(Update, first.before(second).before(third).before(fourth))
+            .build();
+
+        let result = app.run(Update);
+        assert_eq!(
+            result,
+            PhaseRunResult::Cancelled {
+                completed: 2,
+                skipped: 2
+            }
+        );
+        assert_eq!(
+            app.world().resource::<Log>().entries(),
+            vec!["first", "second"]
+        );
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.world().resource::<Log>().entries().len(), 6);
+    }
+
+    #[test]
+    fn fault_cancel_after_systems_sequential() {
+        cancel_after_systems(false);
+    }
+
+    #[test]
+    fn fault_cancel_after_systems_parallel() {
+        cancel_after_systems(true);
+    }
+
+    #[test]
+    fn fault_delay_reorders_wave() {
+        let plan = FaultPlan::new().with(Fault::Delay {
+            system: "first",
+            duration: Duration::from_millis(50),
+        });
+
+        let mut app = app(true, plan)
+            .add_systems(Update, first)
+            .add_systems(Update, second)
+            .build();
+
+        app.run(Update);
+        assert_eq!(
+            app.world().resource::<Log>().entries(),
+            vec!["second", "first"]
+        );
+    }
+
+    fn panic_with_continue(parallel: bool) {
+        let plan = FaultPlan::new().with(Fault::PanicBefore {
+            system: "second",
+            run: 2,
+        });
+
+        let mut app = app(parallel, plan)
+            .add_systems(Update, (fails, second, third).chain())
+            .build();
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| app.run(Update)));
+        assert!(result.is_err());
+
+        // The failure reported before the panic is kept and handled by the next run.
+        assert_eq!(app.world().resource::<Failures>().0.len(), 1);
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+
+        let failures = &app.world().resource::<Failures>().0;
+        let fails = (Some("fails".to_string()), "fails".to_string());
+        assert_eq!(failures, &vec![fails; 3]);
+
+        // The parallel executor finishes the phase around the panicking system before the panic
+        // resumes, the sequential executor unwinds right away.
+        let panicked = match parallel {
+            true => vec!["fails", "third"],
+            false => vec!["fails"],
+        };
+        let mut expected = vec!["fails", "second", "third"];
+        expected.extend(panicked);
+        expected.extend(["fails", "second", "third"]);
+        assert_eq!(app.world().resource::<Log>().entries(), expected);
+    }
+
+    #[test]
+    fn fault_panic_with_continue_sequential() {
+        panic_with_continue(false);
+    }
+
+    #[test]
+    fn fault_panic_with_continue_parallel() {
+        panic_with_continue(true);
+    }
+
+    fn skipped_dependencies(parallel: bool) {
+        let mut app = app(parallel, FaultPlan::new())
+            .add_systems(
+                Update,
+                (first, second.on_invalid_args(InvalidArgs::Error), third).chain(),
+            )
+            .build();
+
+        // Fail the validation of the second system on the next frame only.
+        let frame = app.world().frame().get();
+        let plan = FaultPlan::new().with(Fault::FailValidation {
+            system: "second",
+            frame: frame + 1,
+        });
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        app.world_mut().add_resource(plan);
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+
+        // Dependents of a skipped system still run, in order.
+        assert_eq!(
+            app.world().resource::<Log>().entries(),
+            vec![
+                "first", "second", "third", "first", "third", "first", "second", "third"
+            ]
+        );
+
+        let failures = &app.world().resource::<Failures>().0;
+        let skipped = (
+            Some("second".to_string()),
+            "Arguments failed validation".to_string(),
+        );
+        assert_eq!(failures, &vec![skipped]);
+    }
+
+    #[test]
+    fn fault_skipped_dependencies_sequential() {
+        skipped_dependencies(false);
+    }
+
+    #[test]
+    fn fault_skipped_dependencies_parallel() {
+        skipped_dependencies(true);
+    }
+
+    fn skipped_systems_count_as_completed(parallel: bool) {
+        let mut app = app(parallel, FaultPlan::new())
+            .add_systems(Update, (first, second, third, fourth).chain())
+            .build();
+
+        let frame = app.world().frame().get();
+        let plan = FaultPlan::new()
+            .with(Fault::FailValidation {
+                system: "second",
+                frame,
+            })
+            .with(Fault::CancelAfter { systems: 2 });
+        app.world_mut().add_resource(plan);
+
+        // The skipped system doesn't run, so it doesn't count toward the cancellation, but the
+        // executor did process it and reports it as completed.
+        assert_eq!(
+            app.run(Update),
+            PhaseRunResult::Cancelled {
+                completed: 3,
+                skipped: 1
+            }
+        );
+        assert_eq!(
+            app.world().resource::<Log>().entries(),
+            vec!["first", "third"]
+        );
+    }
+
+    #[test]
+    fn fault_skipped_systems_count_as_completed_sequential() {
+        skipped_systems_count_as_completed(false);
+    }
+
+    #[test]
+    fn fault_skipped_systems_count_as_completed_parallel() {
+        skipped_systems_count_as_completed(true);
+    }
+
+    /// One-shot systems run by [`queue_runs`].
+    struct OneShots {
+        registered: SystemId,
+        missing: SystemId,
+    }
+    impl Resource for OneShots {}
+
+    fn queue_runs(mut commands: Commands, systems: &OneShots) {
+        commands.run_system(systems.registered);
+        commands.run_system(systems.missing);
+    }
+
+    fn command_errors(parallel: bool) {
+        let mut builder = app(parallel, FaultPlan::new());
+        let registered = builder.world_mut().register_system(fails);
+        let missing = builder.world_mut().register_system(first);
+        builder.world_mut().unregister_system(missing);
+
+        let mut app = builder
+            .add_resource(OneShots {
+                registered,
+                missing,
+            })
+            .add_systems(Update, (queue_runs, second))
+            .build();
+
+        assert_eq!(app.run(Update), PhaseRunResult::Completed);
+
+        // Failures of systems run by commands are attributed to that system, commands that
+        // can't run a system report a failure without one.
+        let failures = &app.world().resource::<Failures>().0;
+        assert_eq!(failures.len(), 2);
+        assert_eq!(
+            failures[0],
+            (Some("fails".to_string()), "fails".to_string())
+        );
+        assert_eq!(failures[1].0, None);
+        assert_eq!(
+            app.world().resource::<Log>().entries(),
+            vec!["second", "fails"]
+        );
+    }
+
+    #[test]
+    fn fault_command_errors_sequential() {
+        command_errors(false);
+    }
+
+    #[test]
+    fn fault_command_errors_parallel() {
+        command_errors(true);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::core::{CyclicDependency, IndexDag};
+
+/// Builds the dependency graph the executors run a phase from. Explicit dependencies become
+/// edges as given. Conflicting nodes, like systems writing the same component, are ordered along
+/// the explicit dependencies and otherwise by insertion, so conflicts never add a cycle.
+pub struct DependencyGraphBuilder<N> {
+    nodes: Vec<N>,
+    /// Explicit `(dependency, dependent)` edges.
+    edges: Vec<(usize, usize)>,
+}
+
+impl<N> DependencyGraphBuilder<N> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            edges: vec![],
+        }
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn add_node(&mut self, node: N) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// `dependency` runs before `index`.
+    pub fn add_dependency(&mut self, dependency: usize, index: usize) {
+        self.edges.push((dependency, index));
+    }
+
+    /// Fails if the explicit dependencies form a cycle. The conflicts are checked for every
+    /// pair of nodes once, with the node that runs first as the first argument.
+    pub fn build(
+        self,
+        conflicts: impl Fn(&N, &N) -> bool,
+    ) -> Result<IndexDag<N>, CyclicDependency> {
+        let mut explicit = IndexDag::new();
+        for index in 0..self.nodes.len() {
+            explicit.add_node(index);
+        }
+
+        for (dependency, index) in self.edges {
+            add_edge(&mut explicit, dependency, index);
+        }
+
+        explicit.check()?;
+        let order = stable_order(&explicit);
+
+        let mut graph = IndexDag::new();
+        for node in self.nodes {
+            graph.add_node(node);
+        }
+
+        for (dependency, dependents) in explicit.dependents().iter().enumerate() {
+            for index in dependents.ones() {
+                add_edge(&mut graph, dependency, index);
+            }
+        }
+
+        for (position, &first) in order.iter().enumerate() {
+            for &second in &order[position + 1..] {
+                if conflicts(&graph.nodes()[first], &graph.nodes()[second]) {
+                    add_edge(&mut graph, first, second);
+                }
+            }
+        }
+
+        graph.build()?;
+        Ok(graph)
+    }
+}
+
+impl<N> Default for DependencyGraphBuilder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adds the edge once, [`IndexDag`] counts every call as a dependency.
+fn add_edge<N>(dag: &mut IndexDag<N>, dependency: usize, index: usize) {
+    let dependents = &dag.dependents()[dependency];
+    if dependency != index && !(index < dependents.len() && dependents.contains(index)) {
+        dag.add_dependency(dependency, index);
+    }
+}
+
+/// Topological order that keeps the insertion order, except that the dependencies of a node
+/// are pulled in right before it. The dag must not have cycles.
+fn stable_order<N>(dag: &IndexDag<N>) -> Vec<usize> {
+    fn visit(
+        index: usize,
+        dependencies: &[Vec<usize>],
+        placed: &mut [bool],
+        order: &mut Vec<usize>,
+    ) {
+        if std::mem::replace(&mut placed[index], true) {
+            return;
+        }
+
+        for dependency in &dependencies[index] {
+            visit(*dependency, dependencies, placed, order);
+        }
+
+        order.push(index);
+    }
+
+    let mut dependencies = vec![vec![]; dag.len()];
+    for (dependency, dependents) in dag.dependents().iter().enumerate() {
+        for index in dependents.ones() {
+            dependencies[index].push(dependency);
+        }
+    }
+
+    let mut placed = vec![false; dag.len()];
+    let mut order = Vec::with_capacity(dag.len());
+    for index in 0..dag.len() {
+        visit(index, &dependencies, &mut placed, &mut order);
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DependencyGraphBuilder;
+    use crate::core::{IndexDag, RngSource, SplitMix64};
+
+    /// Node writing to the bits of `access`.
+    #[derive(Debug, Clone, Copy)]
+    struct Node {
+        access: u8,
+    }
+
+    fn conflicts(a: &Node, b: &Node) -> bool {
+        a.access & b.access != 0
+    }
+
+    fn reaches<N>(dag: &IndexDag<N>, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut seen = vec![false; dag.len()];
+        while let Some(index) = stack.pop() {
+            if index == to {
+                return true;
+            }
+
+            if !std::mem::replace(&mut seen[index], true) {
+                stack.extend(dag.dependents()[index].ones());
+            }
+        }
+
+        false
+    }
+
+    #[test]
+    fn conflicts_follow_insertion_order() {
+        let mut builder = DependencyGraphBuilder::new();
+        let a = builder.add_node(Node { access: 1 });
+        let b = builder.add_node(Node { access: 2 });
+        let c = builder.add_node(Node { access: 3 });
+
+        let graph = builder.build(conflicts).unwrap();
+        assert!(graph.dependents()[a].contains(c));
+        assert!(graph.dependents()[b].contains(c));
+        assert!(!reaches(&graph, a, b) && !reaches(&graph, b, a));
+        assert_eq!(graph.dependencies(), [0, 0, 2]);
+    }
+
+    #[test]
+    fn explicit_dependencies_on_later_nodes() {
+        let mut builder = DependencyGraphBuilder::new();
+        let a = builder.add_node(Node { access: 1 });
+        let b = builder.add_node(Node { access: 1 });
+        let c = builder.add_node(Node { access: 1 });
+
+        // `a` runs after `c`, which moves `c` ahead of `b` as well.
+        builder.add_dependency(c, a);
+        builder.add_dependency(c, a);
+
+        let graph = builder.build(conflicts).unwrap();
+        assert!(reaches(&graph, c, a));
+        assert!(reaches(&graph, a, b));
+        assert_eq!(graph.dependencies(), [1, 2, 0]);
+        assert_eq!(graph.topology(), [c, a, b]);
+    }
+
+    #[test]
+    fn explicit_cycles_fail() {
+        let mut builder = DependencyGraphBuilder::new();
+        let a = builder.add_node(Node { access: 0 });
+        let b = builder.add_node(Node { access: 0 });
+        builder.add_dependency(a, b);
+        builder.add_dependency(b, a);
+
+        let Err(cycle) = builder.build(conflicts) else {
+            panic!("Expected a cycle");
+        };
+        assert_eq!(cycle.0, [a, b, a]);
+    }
+
+    #[test]
+    fn random_graphs_are_ordered() {
+        let mut rng = SplitMix64::new(7);
+        for _ in 0..500 {
+            let len = 1 + rng.below(12) as usize;
+            let nodes = (0..len)
+                .map(|_| Node {
+                    access: rng.below(16) as u8,
+                })
+                .collect::<Vec<_>>();
+
+            // Edges between random nodes along a shuffled order can't form a cycle.
+            let mut rank = (0..len).collect::<Vec<_>>();
+            for index in (1..len).rev() {
+                rank.swap(index, rng.below(index as u64 + 1) as usize);
+            }
+
+            let mut edges = vec![];
+            for _ in 0..rng.below(len as u64 * 2) {
+                let (a, b) = (
+                    rng.below(len as u64) as usize,
+                    rng.below(len as u64) as usize,
+                );
+                if rank[a] < rank[b] {
+                    edges.push((a, b));
+                }
+            }
+
+            let mut builder = DependencyGraphBuilder::new();
+            nodes.iter().for_each(|node| _ = builder.add_node(*node));
+            edges
+                .iter()
+                .for_each(|(a, b)| builder.add_dependency(*a, *b));
+            let mut graph = builder.build(conflicts).unwrap();
+
+            let topology = graph.build().unwrap().to_vec();
+            let position = |index| topology.iter().position(|i| *i == index).unwrap();
+            for (a, b) in &edges {
+                assert!(position(*a) < position(*b));
+            }
+
+            for a in 0..len {
+                let count = (0..len)
+                    .filter(|b| a < graph.dependents()[*b].len() && graph.dependents()[*b][a])
+                    .count();
+                assert_eq!(graph.dependencies()[a], count);
+
+                for b in a + 1..len {
+                    if conflicts(&nodes[a], &nodes[b]) {
+                        assert!(reaches(&graph, a, b) || reaches(&graph, b, a));
+                    }
+
+                    if edges.is_empty() && conflicts(&nodes[a], &nodes[b]) {
+                        assert!(graph.dependents()[a].contains(b));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,26 +1,51 @@
 use crate::{
     core::{AccessBitset, Frame, SparseIndex},
-    world::{ComponentId, ResourceId, World, cell::WorldCell},
+    system::condition::{Condition, ConditionState, IntoCondition},
+    system::diagnostics::{DiagnosticsStore, Timing},
+    system::error::{SystemError, SystemFailure, SystemOutput},
+    system::readonly::ReadOnlyViolation,
+    system::stepping::Stepping,
+    world::{ComponentId, ResourceId, World, WorldId, cell::WorldCell},
+};
+use serde_value::Value;
+use std::{
+    any::Any,
+    borrow::Cow,
+    cell::UnsafeCell,
+    collections::HashSet,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Instant,
 };
-use std::{any::Any, borrow::Cow, cell::UnsafeCell, collections::HashSet};
 
+pub mod ambiguity;
 pub mod arg;
+pub mod builder;
+pub mod condition;
+pub mod diagnostics;
+pub mod dynamic;
+pub mod error;
 pub mod executor;
+pub mod graph;
+pub mod one_shot;
+pub mod pipe;
 pub mod query;
+pub mod readonly;
+pub mod runtime;
 pub mod schedule;
+pub mod state;
+pub mod stepping;
+pub mod time;
 
 pub type SystemName = Cow<'static, str>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct SystemId(u32);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemId(pub(crate) u32);
 impl SystemId {
+    /// Unique id of a config. Systems are renumbered by their world when they're built, see
+    /// [`World::next_system_id`].
     fn new() -> Self {
-        static mut ID: u32 = 0;
-        unsafe {
-            let id = ID;
-            ID += 1;
-            SystemId(id)
-        }
+        static ID: AtomicU32 = AtomicU32::new(0);
+        SystemId(ID.fetch_add(1, Ordering::Relaxed))
     }
 }
 
@@ -59,6 +84,8 @@ pub struct SystemMeta {
     pub exclusive: bool,
     /// The frame in which the system was last executed.
     pub frame: Frame,
+    /// Execution times, only recorded while the world has a [`DiagnosticsStore`].
+    pub timing: Timing,
 }
 
 pub struct SystemConfig {
@@ -67,19 +94,54 @@ pub struct SystemConfig {
     exclusive: bool,
     send: bool,
     dependencies: HashSet<SystemId>,
-    init: fn(&mut World) -> Box<dyn Any + Send + Sync>,
+    init: SystemInit,
     access: fn(&Box<dyn Any + Send + Sync>) -> Vec<SystemAccess>,
+    read_only: fn(&Box<dyn Any + Send + Sync>) -> Result<(), ReadOnlyViolation>,
+    save: SystemSave,
+    restore: SystemRestore,
+    validate: SystemValidate,
     run: SystemRun,
     apply: SystemApply,
+    conditions: Vec<Condition>,
+    ambiguous_with: HashSet<SystemName>,
+    invalid_args: InvalidArgs,
 }
 
 impl SystemConfig {
-    pub fn into_system_node(self, world: &mut World) -> SystemNode {
-        let state = (self.init)(world);
+    pub fn into_system_node(mut self, world: &mut World) -> SystemNode {
+        let state = self.init(world);
+        self.build(world, state)
+    }
+
+    /// Builds the system, rejecting it if any of its arguments could mutate the world.
+    pub fn into_read_only(mut self, world: &mut World) -> Result<System, ReadOnlyViolation> {
+        let state = self.init(world);
+        if let Err(mut violation) = (self.read_only)(&state) {
+            violation.system = self.name;
+            return Err(violation);
+        }
+
+        Ok(self.build(world, state).system)
+    }
+
+    fn init(&mut self, world: &mut World) -> SystemState {
+        let init = std::mem::replace(&mut self.init, Box::new(|_| Box::new(())));
+        init(world)
+    }
+
+    fn build(self, world: &mut World, state: SystemState) -> SystemNode {
+        let conditions = self
+            .conditions
+            .iter()
+            .map(|condition| condition.clone().init(world))
+            .collect::<Vec<_>>();
+
         let mut components = AccessBitset::with_capacity(world.components().len());
         let mut resources = AccessBitset::with_capacity(world.resources().len());
 
-        for access in (self.access)(&state) {
+        let access = (self.access)(&state);
+        let condition_access = conditions.iter().flat_map(|condition| condition.access());
+        for access in access.into_iter().chain(condition_access) {
             match access {
                 SystemAccess::Component { id, access } => match access {
                     Access::Read => components.read(id.to_usize()),
@@ -93,18 +155,30 @@ impl SystemConfig {
         }
 
         let meta = SystemMeta {
-            id: self.id,
+            id: world.next_system_id(),
             name: self.name,
             components,
             resources,
-            send: self.send,
-            exclusive: self.exclusive,
+            send: self.send && self.conditions.iter().all(Condition::send),
+            exclusive: self.exclusive || self.conditions.iter().any(Condition::exclusive),
             frame: Frame::ZERO,
+            timing: Timing::default(),
         };
 
+        let mut system = System::new(meta, state, self.run, self.apply);
+        system.save = self.save;
+        system.restore = self.restore;
+        system.validate = self.validate;
+        system.invalid_args = self.invalid_args;
+        system.conditions = conditions;
+        system.stepping = Some(world.resources_mut().register::<true, Stepping>());
+        system.diagnostics = Some(world.resources_mut().register::<true, DiagnosticsStore>());
+        system.world = Some(world.id());
+
         SystemNode {
-            system: System::new(meta, state, self.run, self.apply),
+            system,
             dependencies: self.dependencies,
+            ambiguous_with: self.ambiguous_with,
         }
     }
 }
@@ -112,11 +186,24 @@ impl SystemConfig {
 pub struct SystemNode {
     pub system: System,
     pub dependencies: HashSet<SystemId>,
+    /// Names of the systems this one isn't reported as ambiguous with.
+    pub ambiguous_with: HashSet<SystemName>,
 }
 
 impl SystemNode {
-    pub fn has_dependency(&self, other: &SystemNode) -> bool {
-        self.dependencies.contains(&other.system.meta.id)
+    fn allows_ambiguity(&self, other: &SystemNode) -> bool {
+        other
+            .system
+            .meta
+            .name
+            .as_ref()
+            .is_some_and(|name| self.ambiguous_with.contains(name))
+    }
+
+    /// Exclusive systems conflict with every other system, so they keep their insertion order.
+    pub fn conflicts(&self, other: &SystemNode) -> bool {
+        self.system.meta.exclusive
+            || other.system.meta.exclusive
             || self
                 .system
                 .meta
@@ -130,6 +217,7 @@ impl SystemNode {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 pub enum SystemConfigs {
     Config(SystemConfig),
     Configs(Vec<SystemConfig>),
@@ -195,6 +283,95 @@ pub trait IntoSystemConfigs<M> {
     {
         configs.before(self)
     }
+
+    /// Only runs the systems when the condition returns `true`.
+    /// The condition is checked right before each system is dispatched, so skipped systems keep
+    /// their last run frame.
+    fn run_if<Marker>(self, condition: impl IntoCondition<Marker>) -> SystemConfigs
+    where
+        Self: Sized,
+    {
+        let condition = condition.into_condition();
+        let mut configs = self.configs();
+        match &mut configs {
+            SystemConfigs::Config(config) => config.conditions.push(condition),
+            SystemConfigs::Configs(configs) => configs
+                .iter_mut()
+                .for_each(|config| config.conditions.push(condition.clone())),
+        }
+
+        configs
+    }
+
+    /// Sets what happens when the arguments of the systems fail validation, like a resource
+    /// that doesn't exist. See [`InvalidArgs`].
+    fn on_invalid_args(self, policy: InvalidArgs) -> SystemConfigs
+    where
+        Self: Sized,
+    {
+        let mut configs = self.configs();
+        match &mut configs {
+            SystemConfigs::Config(config) => config.invalid_args = policy,
+            SystemConfigs::Configs(configs) => configs
+                .iter_mut()
+                .for_each(|config| config.invalid_args = policy),
+        }
+
+        configs
+    }
+
+    /// Stops reporting the systems as ambiguous with the given systems, which are only used for
+    /// their names. See [`AmbiguityDetection`](ambiguity::AmbiguityDetection).
+    fn ambiguous_with<Marker>(self, systems: impl IntoSystemConfigs<Marker>) -> SystemConfigs
+    where
+        Self: Sized,
+    {
+        let names = systems
+            .configs()
+            .flatten()
+            .into_iter()
+            .filter_map(|config| config.name)
+            .collect::<Vec<_>>();
+
+        let mut configs = self.configs();
+        match &mut configs {
+            SystemConfigs::Config(config) => config.ambiguous_with.extend(names),
+            SystemConfigs::Configs(configs) => configs
+                .iter_mut()
+                .for_each(|config| config.ambiguous_with.extend(names.iter().cloned())),
+        }
+
+        configs
+    }
+
+    /// Runs the systems one after another, in the order they were given.
+    fn chain(self) -> SystemConfigs
+    where
+        Self: Sized,
+    {
+        let groups = self
+            .configs()
+            .flatten()
+            .into_iter()
+            .map(|config| vec![config]);
+        chain_groups(groups)
+    }
+}
+
+/// Makes every config of a group depend on every config of the group before it.
+fn chain_groups(groups: impl IntoIterator<Item = Vec<SystemConfig>>) -> SystemConfigs {
+    let mut configs = vec![];
+    let mut previous = vec![];
+    for mut group in groups {
+        for config in &mut group {
+            config.dependencies.extend(previous.iter().copied());
+        }
+
+        previous = group.iter().map(|config| config.id).collect();
+        configs.extend(group);
+    }
+
+    SystemConfigs::Configs(configs)
 }
 
 impl IntoSystemConfigs<()> for SystemConfigs {
@@ -233,7 +410,7 @@ impl IntoSystemConfigs<()> for SystemConfigs {
     }
 }
 
-impl<F: Fn() + Send + Sync + 'static> IntoSystemConfigs<()> for F {
+impl<F: Fn() -> O + Send + Sync + 'static, O: SystemOutput> IntoSystemConfigs<()> for F {
     fn configs(self) -> SystemConfigs {
         SystemConfigs::Config(SystemConfig {
             id: SystemId::new(),
@@ -241,12 +418,17 @@ impl<F: Fn() + Send + Sync + 'static> IntoSystemConfigs<()> for F {
             exclusive: false,
             send: true,
             dependencies: HashSet::new(),
-            init: |_| Box::new(()),
+            init: Box::new(|_| Box::new(())),
             access: |_| vec![],
-            run: Box::new(move |_, _, _| {
-                self();
-            }),
+            read_only: |_| Ok(()),
+            save: |_| vec![],
+            restore: |_, _, _| {},
+            validate: Box::new(|_, _, _| true),
+            run: Box::new(move |_, _, _| self().into_result()),
             apply: Box::new(|_, _| {}),
+            conditions: vec![],
+            ambiguous_with: HashSet::new(),
+            invalid_args: InvalidArgs::Skip,
         })
     }
 
@@ -255,16 +437,128 @@ impl<F: Fn() + Send + Sync + 'static> IntoSystemConfigs<()> for F {
     }
 }
 
+/// Marker of [`IntoSystemConfigs`] for tuples of systems.
+pub struct SystemTuple;
+
+macro_rules! impl_into_system_configs_tuple {
+    ($(($system:ident, $marker:ident)),*) => {
+        #[allow(non_snake_case)]
+        impl<$($system: IntoSystemConfigs<$marker>, $marker),*>
+            IntoSystemConfigs<(SystemTuple, $($marker,)*)> for ($($system,)*)
+        {
+            fn configs(self) -> SystemConfigs {
+                let ($($system,)*) = self;
+                let mut configs = vec![];
+                $(configs.extend($system.configs().flatten());)*
+                SystemConfigs::Configs(configs)
+            }
+
+            fn before<Marker>(self, configs: impl IntoSystemConfigs<Marker>) -> SystemConfigs {
+                self.configs().before(configs)
+            }
+
+            /// Runs every system of an element after every system of the element before it,
+            /// keeping the order within each element.
+            fn chain(self) -> SystemConfigs {
+                let ($($system,)*) = self;
+                chain_groups([$($system.configs().flatten()),*])
+            }
+        }
+    };
+}
+
+impl_into_system_configs_tuple!((A, MA));
+impl_into_system_configs_tuple!((A, MA), (B, MB));
+impl_into_system_configs_tuple!((A, MA), (B, MB), (C, MC));
+impl_into_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD));
+impl_into_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD), (E, ME));
+impl_into_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD), (E, ME), (F, MF));
+impl_into_system_configs_tuple!(
+    (A, MA),
+    (B, MB),
+    (C, MC),
+    (D, MD),
+    (E, ME),
+    (F, MF),
+    (G, MG)
+);
+impl_into_system_configs_tuple!(
+    (A, MA),
+    (B, MB),
+    (C, MC),
+    (D, MD),
+    (E, ME),
+    (F, MF),
+    (G, MG),
+    (H, MH)
+);
+impl_into_system_configs_tuple!(
+    (A, MA),
+    (B, MB),
+    (C, MC),
+    (D, MD),
+    (E, ME),
+    (F, MF),
+    (G, MG),
+    (H, MH),
+    (I, MI)
+);
+impl_into_system_configs_tuple!(
+    (A, MA),
+    (B, MB),
+    (C, MC),
+    (D, MD),
+    (E, ME),
+    (F, MF),
+    (G, MG),
+    (H, MH),
+    (I, MI),
+    (J, MJ)
+);
+
 pub type SystemState = Box<dyn Any + Send + Sync>;
-pub type SystemRun =
-    Box<dyn Fn(&mut Box<dyn Any + Send + Sync>, WorldCell, &SystemMeta) + Send + Sync>;
+/// Creates the state of every argument, see [`builder::SystemBuilder`] to create it from
+/// values known at runtime.
+pub type SystemInit = Box<dyn FnOnce(&mut World) -> SystemState + Send + Sync>;
+pub type SystemRun = Box<
+    dyn Fn(&mut Box<dyn Any + Send + Sync>, WorldCell, &SystemMeta) -> Result<(), SystemError>
+        + Send
+        + Sync,
+>;
 pub type SystemApply = Box<dyn Fn(&mut Box<dyn Any + Send + Sync>, &mut World) + Send + Sync>;
+/// Saved state of each argument, see [`SystemArg::save`](arg::SystemArg::save).
+pub type SystemSave = fn(&SystemState) -> Vec<Option<Value>>;
+pub type SystemRestore = fn(&mut SystemState, &mut World, &[Option<Value>]);
+/// Checks every argument with [`SystemArg::validate`](arg::SystemArg::validate).
+pub type SystemValidate = Box<dyn Fn(&SystemState, WorldCell, &SystemMeta) -> bool + Send + Sync>;
+
+/// What happens to a system whose arguments fail [`SystemArg::validate`](arg::SystemArg::validate),
+/// like a resource that doesn't exist.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidArgs {
+    /// Skips the system, it keeps its last run frame.
+    #[default]
+    Skip,
+    /// Skips the system and reports a [`SystemFailure`] to the app's error handler.
+    Error,
+}
 
 pub struct System {
     meta: SystemMeta,
     state: SystemState,
     run: SystemRun,
     apply: SystemApply,
+    save: SystemSave,
+    restore: SystemRestore,
+    validate: SystemValidate,
+    invalid_args: InvalidArgs,
+    conditions: Vec<ConditionState>,
+    /// Id of the [`Stepping`] resource, registered up front so dispatching doesn't look it up.
+    stepping: Option<ResourceId>,
+    /// Id of the [`DiagnosticsStore`] resource, the system is timed while it exists.
+    diagnostics: Option<ResourceId>,
+    /// World the state of the system was initialized in, its ids aren't valid in other worlds.
+    world: Option<WorldId>,
 }
 
 impl System {
@@ -274,17 +568,125 @@ impl System {
             state,
             run,
             apply,
+            save: |_| vec![],
+            restore: |_, _, _| {},
+            validate: Box::new(|_, _, _| true),
+            invalid_args: InvalidArgs::Skip,
+            conditions: vec![],
+            stepping: None,
+            diagnostics: None,
+            world: None,
+        }
+    }
+
+    pub fn meta(&self) -> &SystemMeta {
+        &self.meta
+    }
+
+    /// State of each argument that is carried across restarts.
+    pub fn save_state(&self) -> Vec<Option<Value>> {
+        (self.save)(&self.state)
+    }
+
+    /// Restores the last run frame and the argument state returned by [`System::save_state`].
+    pub fn restore_state(&mut self, world: &mut World, frame: Frame, args: &[Option<Value>]) {
+        self.meta.frame = frame;
+        (self.restore)(&mut self.state, world, args);
+    }
+
+    /// Clamps the last run frame, see [`Frame::check`].
+    pub fn check_change_frames(&mut self, current: Frame) {
+        self.meta.frame.check(current);
+    }
+
+    /// Evaluates the run conditions of the system, see [`IntoSystemConfigs::run_if`], then
+    /// validates its arguments, see [`InvalidArgs`], and then consults the world's [`Stepping`]
+    /// if there is one.
+    pub fn should_run(&mut self, world: WorldCell) -> bool {
+        self.check_world(unsafe { world.get() });
+        let meta = &self.meta;
+        self.conditions
+            .iter_mut()
+            .all(|condition| condition.evaluate(world, meta))
+            && self.validate(world)
+            && self.stepping.is_none_or(|id| {
+                let resources = unsafe { world.get() }.resources();
+                resources
+                    .get::<Stepping>(id)
+                    .is_none_or(|stepping| stepping.should_run(meta))
+            })
+    }
+
+    /// Whether every argument of the system can be fetched, reporting a failure if the system
+    /// errors on invalid arguments.
+    pub fn validate(&self, world: WorldCell) -> bool {
+        if (self.validate)(&self.state, world, &self.meta) {
+            return true;
         }
+
+        if self.invalid_args == InvalidArgs::Error {
+            let failure = SystemFailure {
+                system: self.meta.name.clone(),
+                error: SystemError::new("Arguments failed validation"),
+            };
+            unsafe { world.get() }.system_errors().push(failure);
+        }
+
+        false
     }
 
-    pub fn run(&mut self, world: WorldCell) {
-        (self.run)(&mut self.state, world, &self.meta);
-        self.meta.frame = unsafe { world.get().frame() }
+    /// Runs the system, returning the error of a fallible system along with its name.
+    pub fn run(&mut self, world: WorldCell) -> Result<(), SystemFailure> {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!("system", name = self.meta.name.as_deref()).entered();
+
+        self.check_world(unsafe { world.get() });
+
+        let resources = unsafe { world.get() }.resources();
+        let start = self
+            .diagnostics
+            .filter(|id| resources.get::<DiagnosticsStore>(*id).is_some())
+            .map(|_| Instant::now());
+
+        // Debug builds catch systems whose access metadata doesn't match what they touch.
+        let borrow = cfg!(debug_assertions).then(|| {
+            let world = unsafe { world.get() };
+            world.borrows().acquire(&self.meta)
+        });
+        let result = (self.run)(&mut self.state, world, &self.meta);
+        drop(borrow);
+
+        if let Some(start) = start {
+            self.meta.timing.record(start.elapsed());
+        }
+
+        self.meta.frame = unsafe { world.get().frame() };
+        result.map_err(|error| SystemFailure {
+            system: self.meta.name.clone(),
+            error,
+        })
     }
 
     pub fn apply(&mut self, world: &mut World) {
+        self.check_world(world);
         (self.apply)(&mut self.state, world);
     }
+
+    /// Debug builds panic when the system runs against another world than the one it was
+    /// built for.
+    fn check_world(&self, world: &World) {
+        if cfg!(debug_assertions)
+            && let Some(owner) = self.world
+            && owner != world.id()
+        {
+            panic!(
+                "System {} was built for {:?} but ran against {:?}",
+                self.meta.name.as_deref().unwrap_or("unknown"),
+                owner,
+                world.id()
+            );
+        }
+    }
 }
 
 impl From<SystemNode> for System {
@@ -317,6 +719,7 @@ impl SystemCell {
     }
 
     /// The caller must ensure that the system is not borrowed elsewhere.
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn cast_mut(&self) -> &mut System {
         unsafe { &mut *self.0.get() }
     }
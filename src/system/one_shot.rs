@@ -0,0 +1,170 @@
+use super::{IntoSystemConfigs, System, SystemId};
+use crate::world::{World, WorldExtension, cell::WorldCell};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunSystemError {
+    /// The id wasn't returned by [`World::register_system`], or the system was unregistered.
+    NotRegistered(SystemId),
+    /// The system is already running, one-shot systems can't run themselves.
+    Running(SystemId),
+}
+
+impl std::fmt::Display for RunSystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunSystemError::NotRegistered(id) => write!(f, "System {:?} isn't registered", id),
+            RunSystemError::Running(id) => write!(f, "System {:?} is already running", id),
+        }
+    }
+}
+
+impl std::error::Error for RunSystemError {}
+
+/// Systems registered with [`World::register_system`]. A system is taken out of its slot while
+/// it runs.
+#[derive(Default)]
+struct OneShotSystems {
+    systems: HashMap<SystemId, Option<System>>,
+}
+
+impl WorldExtension for OneShotSystems {}
+
+impl World {
+    /// Builds the system and runs it once outside of the schedule, applying its deferred
+    /// arguments right after. Failures are reported like those of scheduled systems.
+    pub fn run_system_once<M>(&mut self, system: impl IntoSystemConfigs<M>) {
+        let mut system = system.configs().single().into_system_node(self).system;
+        run_system(self, &mut system);
+    }
+
+    /// Builds the system and keeps it, along with its state, to be run with
+    /// [`World::run_system`].
+    pub fn register_system<M>(&mut self, system: impl IntoSystemConfigs<M>) -> SystemId {
+        let system = system.configs().single().into_system_node(self).system;
+        let id = system.meta().id;
+        if self.extension::<OneShotSystems>().is_none() {
+            self.insert_extension(OneShotSystems::default());
+        }
+
+        let systems = self.extension_mut::<OneShotSystems>().unwrap();
+        systems.systems.insert(id, Some(system));
+        id
+    }
+
+    /// Runs a system registered with [`World::register_system`]. Its last run frame is kept
+    /// between runs, so change detection sees everything since it last ran.
+    pub fn run_system(&mut self, id: SystemId) -> Result<(), RunSystemError> {
+        let slot = self
+            .extension_mut::<OneShotSystems>()
+            .and_then(|systems| systems.systems.get_mut(&id))
+            .ok_or(RunSystemError::NotRegistered(id))?;
+        let mut system = slot.take().ok_or(RunSystemError::Running(id))?;
+
+        run_system(self, &mut system);
+
+        // The system may have unregistered itself while it ran.
+        if let Some(slot) = self
+            .extension_mut::<OneShotSystems>()
+            .and_then(|systems| systems.systems.get_mut(&id))
+        {
+            *slot = Some(system);
+        }
+
+        Ok(())
+    }
+
+    /// Drops a system registered with [`World::register_system`], returning whether it existed.
+    pub fn unregister_system(&mut self, id: SystemId) -> bool {
+        self.extension_mut::<OneShotSystems>()
+            .is_some_and(|systems| systems.systems.remove(&id).is_some())
+    }
+}
+
+fn run_system(world: &mut World, system: &mut System) {
+    world.with_nested_borrows(|world| {
+        let cell = unsafe { WorldCell::new_mut(world) };
+        if system.should_run(cell) {
+            if let Err(failure) = system.run(cell) {
+                world.system_errors().push(failure);
+            }
+
+            system.apply(world);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RunSystemError;
+    use crate::{
+        system::{SystemId, arg::Local, query::Query},
+        world::{Commands, Component, Resource, World},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Default)]
+    struct Runs(u32);
+    impl Resource for Runs {}
+
+    struct Heal(SystemId);
+    impl Resource for Heal {}
+
+    fn spawn(mut commands: Commands) {
+        commands.spawn().insert(Health(10));
+    }
+
+    fn heal(mut query: Query<&mut Health>, runs: &mut Runs, mut count: Local<u32>) {
+        *count += 1;
+        runs.0 = *count;
+        for mut health in query.iter_mut() {
+            health.0 += 1;
+        }
+    }
+
+    fn queue_heal(heal: &Heal, mut commands: Commands) {
+        commands.run_system(heal.0);
+    }
+
+    fn recurse(world: &mut World) {
+        let id = world.resource::<Heal>().0;
+        assert_eq!(world.run_system(id), Err(RunSystemError::Running(id)));
+    }
+
+    #[test]
+    fn one_shot_systems() {
+        let mut world = World::new();
+        world.register::<Health>();
+        world.add_resource(Runs::default());
+
+        world.run_system_once(spawn);
+        let health = |world: &mut World| world.query::<&Health>().iter().next().map(|h| h.0);
+        assert_eq!(health(&mut world), Some(10));
+
+        // Registered systems keep their state between runs.
+        let id = world.register_system(heal);
+        world.run_system(id).unwrap();
+        world.run_system(id).unwrap();
+        assert_eq!(world.resource::<Runs>().0, 2);
+        assert_eq!(health(&mut world), Some(12));
+
+        world.add_resource(Heal(id));
+        world.run_system_once(queue_heal);
+        assert_eq!(world.resource::<Runs>().0, 3);
+
+        let id = world.register_system(recurse);
+        world.resource_mut::<Heal>().0 = id;
+        world.run_system(id).unwrap();
+
+        assert!(world.unregister_system(id));
+        assert_eq!(world.run_system(id), Err(RunSystemError::NotRegistered(id)));
+        assert!(world.system_errors().is_empty());
+
+        world.add_resource(Heal(id));
+        world.run_system_once(queue_heal);
+        assert_eq!(world.system_errors().take().len(), 1);
+    }
+}
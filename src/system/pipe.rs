@@ -0,0 +1,420 @@
+use super::{
+    IntoSystemConfigs, InvalidArgs, SystemAccess, SystemConfig, SystemConfigs, SystemId,
+    SystemMeta, SystemName, SystemState,
+    arg::{ArgItem, SystemArg},
+    error::SystemOutput,
+    readonly::{ReadOnlyViolation, check_read_only},
+};
+use crate::world::{World, WorldCell};
+use serde_value::Value;
+use std::{collections::HashSet, marker::PhantomData};
+
+/// Output of the previous system in a pipe, must be the first argument of the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct In<T>(pub T);
+
+impl<T> std::ops::Deref for In<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for In<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A system taking an input and returning an output, see [`IntoPipeSystem::pipe`].
+pub trait PipeSystem: Send + Sync + 'static {
+    type In;
+    type Out;
+    type State: Send + Sync + 'static;
+
+    fn name(&self) -> SystemName;
+
+    fn init(world: &mut World) -> Self::State;
+
+    fn run(
+        &self,
+        state: &mut Self::State,
+        world: WorldCell,
+        system: &SystemMeta,
+        input: Self::In,
+    ) -> Self::Out;
+
+    fn apply(state: &mut Self::State, world: &mut World);
+
+    fn access(state: &Self::State) -> Vec<SystemAccess>;
+
+    fn read_only(state: &Self::State) -> Result<(), ReadOnlyViolation>;
+
+    fn save(state: &Self::State) -> Vec<Option<Value>>;
+
+    fn restore(state: &mut Self::State, world: &mut World, saved: &[Option<Value>]);
+
+    /// # Safety
+    /// See [`SystemArg::validate`].
+    unsafe fn validate(state: &Self::State, world: WorldCell, system: &SystemMeta) -> bool;
+
+    fn send() -> bool;
+
+    fn exclusive() -> bool;
+}
+
+pub trait IntoPipeSystem<M> {
+    type System: PipeSystem;
+
+    fn into_pipe_system(self) -> Self::System;
+
+    /// Runs `next` right after this system, passing it this system's output as [`In`].
+    fn pipe<Marker, B>(self, next: B) -> Pipe<Self::System, B::System>
+    where
+        Self: Sized,
+        B: IntoPipeSystem<Marker>,
+        B::System: PipeSystem<In = <Self::System as PipeSystem>::Out>,
+    {
+        Pipe(self.into_pipe_system(), next.into_pipe_system())
+    }
+}
+
+impl<P: PipeSystem> IntoPipeSystem<()> for P {
+    type System = P;
+
+    fn into_pipe_system(self) -> Self::System {
+        self
+    }
+}
+
+/// Two systems run back to back as one, the first one's output is the second one's input.
+/// The pipe is scheduled with the combined access of both systems.
+pub struct Pipe<A, B>(A, B);
+
+impl<A, B> PipeSystem for Pipe<A, B>
+where
+    A: PipeSystem,
+    B: PipeSystem<In = A::Out>,
+{
+    type In = A::In;
+    type Out = B::Out;
+    type State = (A::State, B::State);
+
+    fn name(&self) -> SystemName {
+        format!("{} | {}", self.0.name(), self.1.name()).into()
+    }
+
+    fn init(world: &mut World) -> Self::State {
+        (A::init(world), B::init(world))
+    }
+
+    fn run(
+        &self,
+        (a, b): &mut Self::State,
+        world: WorldCell,
+        system: &SystemMeta,
+        input: Self::In,
+    ) -> Self::Out {
+        let output = self.0.run(a, world, system, input);
+        self.1.run(b, world, system, output)
+    }
+
+    fn apply((a, b): &mut Self::State, world: &mut World) {
+        A::apply(a, world);
+        B::apply(b, world);
+    }
+
+    fn access((a, b): &Self::State) -> Vec<SystemAccess> {
+        let mut access = A::access(a);
+        access.extend(B::access(b));
+        access
+    }
+
+    fn read_only((a, b): &Self::State) -> Result<(), ReadOnlyViolation> {
+        A::read_only(a)?;
+        B::read_only(b)
+    }
+
+    fn save((a, b): &Self::State) -> Vec<Option<Value>> {
+        let mut saved = A::save(a);
+        saved.extend(B::save(b));
+        saved
+    }
+
+    fn restore((a, b): &mut Self::State, world: &mut World, saved: &[Option<Value>]) {
+        // Systems always save one entry per argument, so the first system's count is fixed.
+        let split = A::save(a).len().min(saved.len());
+        A::restore(a, world, &saved[..split]);
+        B::restore(b, world, &saved[split..]);
+    }
+
+    unsafe fn validate((a, b): &Self::State, world: WorldCell, system: &SystemMeta) -> bool {
+        unsafe { A::validate(a, world, system) && B::validate(b, world, system) }
+    }
+
+    fn send() -> bool {
+        A::send() && B::send()
+    }
+
+    fn exclusive() -> bool {
+        A::exclusive() || B::exclusive()
+    }
+}
+
+impl<A, B> IntoSystemConfigs<()> for Pipe<A, B>
+where
+    A: PipeSystem<In = ()>,
+    B: PipeSystem<In = A::Out>,
+    B::Out: SystemOutput,
+{
+    fn configs(self) -> SystemConfigs {
+        let name = self.name();
+        let run = move |state: &mut SystemState, world: WorldCell, system: &SystemMeta| {
+            let state = state.downcast_mut::<<Self as PipeSystem>::State>().unwrap();
+            PipeSystem::run(&self, state, world, system, ()).into_result()
+        };
+
+        SystemConfigs::Config(SystemConfig {
+            id: SystemId::new(),
+            name: Some(name),
+            exclusive: Self::exclusive(),
+            send: Self::send(),
+            dependencies: HashSet::new(),
+            init: Box::new(|world| Box::new(Self::init(world))),
+            access: |state| Self::access(state.downcast_ref().unwrap()),
+            read_only: |state| Self::read_only(state.downcast_ref().unwrap()),
+            save: |state| Self::save(state.downcast_ref().unwrap()),
+            restore: |state, world, saved| {
+                Self::restore(state.downcast_mut().unwrap(), world, saved)
+            },
+            validate: Box::new(|state, world, system| unsafe {
+                Self::validate(state.downcast_ref().unwrap(), world, system)
+            }),
+            run: Box::new(run),
+            apply: Box::new(|state, world| Self::apply(state.downcast_mut().unwrap(), world)),
+            conditions: vec![],
+            ambiguous_with: HashSet::new(),
+            invalid_args: InvalidArgs::Skip,
+        })
+    }
+
+    fn before<Marker>(self, configs: impl IntoSystemConfigs<Marker>) -> SystemConfigs {
+        self.configs().before(configs)
+    }
+}
+
+/// Marks functions without an [`In`] argument.
+pub struct NoInput;
+
+/// A function used as a [`PipeSystem`].
+pub struct FunctionSystem<F, M> {
+    func: F,
+    _marker: PhantomData<fn() -> M>,
+}
+
+macro_rules! impl_pipe_system {
+    ($($arg:ident),*) => {
+        #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe, clippy::unused_unit)]
+        impl<F, O, $($arg: SystemArg + 'static),*> IntoPipeSystem<(NoInput, O, $($arg,)*)> for F
+        where
+            for<'world, 'state> F: Fn($($arg),*) -> O
+                + Fn($(ArgItem<'world, 'state, $arg>),*) -> O
+                + Send
+                + Sync
+                + 'static,
+            O: 'static,
+        {
+            type System = FunctionSystem<F, (NoInput, O, $($arg,)*)>;
+
+            fn into_pipe_system(self) -> Self::System {
+                FunctionSystem { func: self, _marker: PhantomData }
+            }
+        }
+
+        #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe, clippy::unused_unit)]
+        impl<F, O, $($arg: SystemArg + 'static),*> PipeSystem for FunctionSystem<F, (NoInput, O, $($arg,)*)>
+        where
+            for<'world, 'state> F: Fn($($arg),*) -> O
+                + Fn($(ArgItem<'world, 'state, $arg>),*) -> O
+                + Send
+                + Sync
+                + 'static,
+            O: 'static,
+        {
+            type In = ();
+            type Out = O;
+            type State = ($($arg::State,)*);
+
+            fn name(&self) -> SystemName {
+                std::any::type_name::<F>().into()
+            }
+
+            fn init(world: &mut World) -> Self::State {
+                ($($arg::init(world),)*)
+            }
+
+            fn run(&self, state: &mut Self::State, world: WorldCell, system: &SystemMeta, _: ()) -> O {
+                let ($($arg,)*) = state;
+                let ($($arg,)*) = unsafe { ($($arg::get($arg, world, system),)*) };
+                (self.func)($($arg),*)
+            }
+
+            impl_pipe_system!(@common $($arg),*);
+        }
+
+        #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe, clippy::unused_unit)]
+        impl<F, I, O, $($arg: SystemArg + 'static),*> IntoPipeSystem<(In<I>, O, $($arg,)*)> for F
+        where
+            for<'world, 'state> F: Fn(In<I>, $($arg),*) -> O
+                + Fn(In<I>, $(ArgItem<'world, 'state, $arg>),*) -> O
+                + Send
+                + Sync
+                + 'static,
+            I: 'static,
+            O: 'static,
+        {
+            type System = FunctionSystem<F, (In<I>, O, $($arg,)*)>;
+
+            fn into_pipe_system(self) -> Self::System {
+                FunctionSystem { func: self, _marker: PhantomData }
+            }
+        }
+
+        #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe, clippy::unused_unit)]
+        impl<F, I, O, $($arg: SystemArg + 'static),*> PipeSystem for FunctionSystem<F, (In<I>, O, $($arg,)*)>
+        where
+            for<'world, 'state> F: Fn(In<I>, $($arg),*) -> O
+                + Fn(In<I>, $(ArgItem<'world, 'state, $arg>),*) -> O
+                + Send
+                + Sync
+                + 'static,
+            I: 'static,
+            O: 'static,
+        {
+            type In = I;
+            type Out = O;
+            type State = ($($arg::State,)*);
+
+            fn name(&self) -> SystemName {
+                std::any::type_name::<F>().into()
+            }
+
+            fn init(world: &mut World) -> Self::State {
+                ($($arg::init(world),)*)
+            }
+
+            fn run(&self, state: &mut Self::State, world: WorldCell, system: &SystemMeta, input: I) -> O {
+                let ($($arg,)*) = state;
+                let ($($arg,)*) = unsafe { ($($arg::get($arg, world, system),)*) };
+                (self.func)(In(input), $($arg),*)
+            }
+
+            impl_pipe_system!(@common $($arg),*);
+        }
+    };
+    (@common $($arg:ident),*) => {
+        fn apply(state: &mut Self::State, world: &mut World) {
+            let ($($arg,)*) = state;
+            $($arg::apply($arg, world);)*
+        }
+
+        fn access(state: &Self::State) -> Vec<SystemAccess> {
+            let ($($arg,)*) = state;
+            let mut access = Vec::new();
+            $(access.extend($arg::access($arg));)*
+            access
+        }
+
+        fn read_only(state: &Self::State) -> Result<(), ReadOnlyViolation> {
+            let ($($arg,)*) = state;
+            $(check_read_only::<$arg>($arg)?;)*
+            Ok(())
+        }
+
+        fn save(state: &Self::State) -> Vec<Option<Value>> {
+            let ($($arg,)*) = state;
+            vec![$($arg::save($arg),)*]
+        }
+
+        fn restore(state: &mut Self::State, world: &mut World, saved: &[Option<Value>]) {
+            let ($($arg,)*) = state;
+            let mut saved = saved.iter();
+            $(
+                if let Some(Some(value)) = saved.next() {
+                    $arg::restore($arg, world, value);
+                }
+            )*
+        }
+
+        unsafe fn validate(state: &Self::State, world: WorldCell, system: &SystemMeta) -> bool {
+            let ($($arg,)*) = state;
+            unsafe { $($arg::validate($arg, world, system) &&)* true }
+        }
+
+        fn send() -> bool {
+            ($($arg::send() &&)* true)
+        }
+
+        fn exclusive() -> bool {
+            ($($arg::exclusive() ||)* false)
+        }
+    };
+}
+
+impl_pipe_system!();
+impl_pipe_system!(A);
+impl_pipe_system!(A, B);
+impl_pipe_system!(A, B, C);
+impl_pipe_system!(A, B, C, D);
+impl_pipe_system!(A, B, C, D, E);
+impl_pipe_system!(A, B, C, D, E, F2);
+impl_pipe_system!(A, B, C, D, E, F2, G);
+impl_pipe_system!(A, B, C, D, E, F2, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::{In, IntoPipeSystem};
+    use crate::{
+        app::{App, Update},
+        world::Resource,
+    };
+
+    struct Config(Option<u32>);
+    impl Resource for Config {}
+
+    #[derive(Default)]
+    struct Log(Vec<String>);
+    impl Resource for Log {}
+
+    fn load_config(config: &Config) -> Result<u32, String> {
+        config.0.ok_or_else(|| "Missing config".to_string())
+    }
+
+    fn double(In(value): In<Result<u32, String>>) -> Result<u32, String> {
+        value.map(|value| value * 2)
+    }
+
+    fn handle_errors(In(result): In<Result<u32, String>>, log: &mut Log) {
+        match result {
+            Ok(value) => log.0.push(format!("Loaded {}", value)),
+            Err(error) => log.0.push(error),
+        }
+    }
+
+    #[test]
+    fn pipe_systems() {
+        let mut app = App::new()
+            .add_resource(Config(Some(21)))
+            .add_resource(Log::default())
+            .add_systems(Update, load_config.pipe(double).pipe(handle_errors))
+            .build();
+
+        app.run(Update);
+        app.world_mut().resource_mut::<Config>().0 = None;
+        app.run(Update);
+
+        let log = &app.world().resource::<Log>().0;
+        assert_eq!(log, &["Loaded 42", "Missing config"]);
+    }
+}
@@ -1,9 +1,11 @@
-use crate::core::{Frame, ObjectStatus, blob::Ptr, sparse::SparseIndex};
+use crate::core::{Frame, ObjectStatus, RngSource, blob::Ptr, sparse::SparseIndex};
 use crate::system::Access;
 use crate::world::{
-    Component, ComponentId, Components, Entity, World,
+    Children, Component, ComponentId, Disabled, Entity, Relationship, RelationshipTarget, Resource,
+    ResourceId, World, WorldId,
     archetype::{
-        Archetype, ArchetypeQuery,
+        Archetype, ArchetypeGeneration, ArchetypeId, ArchetypeQuery, ArchetypeQueryIter,
+        Archetypes, RemovedLog,
         table::{Column, RowIndex},
     },
     cell::WorldCell,
@@ -11,6 +13,10 @@ use crate::world::{
 
 use super::SystemAccess;
 use super::arg::SystemArg;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 pub trait BaseQuery {
     type Item<'w>;
@@ -20,10 +26,18 @@ pub trait BaseQuery {
     /// This is used to create the query state when the query is first created.
     type Data: Send + Sync + Sized;
 
-    fn init(components: &Components, query: &mut ArchetypeQuery) -> Self::Data;
+    /// Whether the query can reject individual entities of the archetypes it matches.
+    const PER_ENTITY: bool = false;
+
+    /// Registers what [`BaseQuery::init`] needs to look up, when the world can be borrowed
+    /// mutably, see [`QueryState::init`].
+    fn register(_: &mut World) {}
+
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data;
 
     fn state<'w>(
         data: &Self::Data,
+        world: &'w World,
         archetype: &'w Archetype,
         current_frame: Frame,
         system_frame: Frame,
@@ -36,10 +50,38 @@ pub trait BaseQuery {
     }
 }
 
+/// Query that can fetch a whole archetype table at once, see [`Query::iter_chunks`].
+pub trait ChunkQuery: BaseQuery {
+    type Chunk<'w>;
+
+    fn chunk<'w>(state: &mut Self::State<'w>) -> Self::Chunk<'w>;
+}
+
 pub trait BaseFilter: for<'w> BaseQuery<Item<'w> = bool> {}
 
 impl<Q: for<'w> BaseQuery<Item<'w> = bool>> BaseFilter for Q {}
 
+/// Query whose items only read, so any number of them can be alive at once and [`Query`] hands
+/// them out through a shared borrow. Items of other queries are fetched through the `_mut`
+/// methods, which borrow the query mutably so two items of the same entity never alias.
+///
+/// # Safety
+/// The items must not give mutable access to anything.
+pub unsafe trait ReadOnlyQuery: BaseQuery {}
+
+unsafe impl ReadOnlyQuery for () {}
+unsafe impl ReadOnlyQuery for Entity {}
+unsafe impl ReadOnlyQuery for IncludeDisabled {}
+unsafe impl<C: Component> ReadOnlyQuery for &C {}
+unsafe impl<C: Component> ReadOnlyQuery for Option<&C> {}
+unsafe impl<C: Component> ReadOnlyQuery for Not<C> {}
+unsafe impl<C: Component> ReadOnlyQuery for With<C> {}
+unsafe impl<C: Component> ReadOnlyQuery for Added<C> {}
+unsafe impl<C: Component> ReadOnlyQuery for Modified<C> {}
+unsafe impl<C: Component> ReadOnlyQuery for Removed<C> {}
+unsafe impl<C: Component + Resource + Send> ReadOnlyQuery for WithFallback<C> {}
+unsafe impl<C: Component + Resource + Send> ReadOnlyQuery for TryWithFallback<C> {}
+
 impl BaseQuery for () {
     type Item<'w> = bool;
 
@@ -47,11 +89,17 @@ impl BaseQuery for () {
 
     type Data = ();
 
-    fn init(_: &Components, _: &mut ArchetypeQuery) -> Self::Data {
+    fn init(_: &World, _: &mut ArchetypeQuery) -> Self::Data {
         ()
     }
 
-    fn state<'w>(_: &Self::Data, _: &'w Archetype, _: Frame, _: Frame) -> Self::State<'w> {
+    fn state<'w>(
+        _: &Self::Data,
+        _: &'w World,
+        _: &'w Archetype,
+        _: Frame,
+        _: Frame,
+    ) -> Self::State<'w> {
         ()
     }
 
@@ -68,16 +116,22 @@ impl<C: Component> BaseQuery for Not<C> {
 
     type Data = ();
 
-    fn init(components: &Components, state: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    fn init(world: &World, state: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
 
         state.exclude(id)
     }
 
-    fn state<'w>(_: &Self::Data, _: &'w Archetype, _: Frame, _: Frame) -> Self::State<'w> {
+    fn state<'w>(
+        _: &Self::Data,
+        _: &'w World,
+        _: &'w Archetype,
+        _: Frame,
+        _: Frame,
+    ) -> Self::State<'w> {
         ()
     }
 
@@ -92,24 +146,66 @@ impl<C: Component> BaseQuery for With<C> {
     type State<'w> = ();
     type Data = ();
 
-    fn init(components: &Components, state: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    fn init(world: &World, state: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
 
         state.include(id)
     }
 
-    fn state<'w>(_: &Self::Data, _: &'w Archetype, _: Frame, _: Frame) -> Self::State<'w> {
+    fn state<'w>(
+        _: &Self::Data,
+        _: &'w World,
+        _: &'w Archetype,
+        _: Frame,
+        _: Frame,
+    ) -> Self::State<'w> {
+        ()
+    }
+
+    fn get<'w>(_: &mut Self::State<'w>, _: Entity, _: RowIndex) -> Self::Item<'w> {
+        true
+    }
+}
+
+/// Opts a query into matching [`Disabled`] entities, which are skipped by default.
+pub struct IncludeDisabled;
+impl BaseQuery for IncludeDisabled {
+    type Item<'w> = bool;
+    type State<'w> = ();
+    type Data = ();
+
+    fn init(world: &World, state: &mut ArchetypeQuery) -> Self::Data {
+        if let Some(id) = world.components().get_id::<Disabled>() {
+            state.allow(id);
+        }
+    }
+
+    fn state<'w>(
+        _: &Self::Data,
+        _: &'w World,
+        _: &'w Archetype,
+        _: Frame,
+        _: Frame,
+    ) -> Self::State<'w> {
         ()
     }
 
     fn get<'w>(_: &mut Self::State<'w>, _: Entity, _: RowIndex) -> Self::Item<'w> {
-        todo!()
+        true
     }
 }
 
+/// Matches entities passing any of the filters in the tuple. Archetypes must match the
+/// include/exclude masks of at least one branch, and per entity only the branches matching the
+/// entity's archetype are checked.
+pub struct Or<F>(std::marker::PhantomData<F>);
+
+/// Matches entities passing every filter in the tuple, letting filters be nested in [`Or`].
+pub struct And<F>(std::marker::PhantomData<F>);
+
 pub struct Added<T: 'static>(std::marker::PhantomData<T>);
 pub struct AddedComponent<'w, C: Component> {
     reader: Option<ReadQuery<'w, C>>,
@@ -122,22 +218,30 @@ impl<C: Component> BaseQuery for Added<C> {
     type State<'w> = AddedComponent<'w, C>;
     type Data = ComponentId;
 
-    fn init(components: &Components, _: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    const PER_ENTITY: bool = true;
+
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
 
+        query.include(id);
         id
     }
 
     fn state<'w>(
         data: &Self::Data,
+        _: &'w World,
         archetype: &'w Archetype,
         current_frame: Frame,
         system_frame: Frame,
     ) -> Self::State<'w> {
-        let components = archetype.table().get_column(*data);
+        // Columns without a row added since the system last ran are skipped entirely.
+        let components = archetype
+            .table()
+            .get_column(*data)
+            .filter(|column| column.changed().added.is_newer(current_frame, system_frame));
         AddedComponent {
             reader: components.map(|components| ReadQuery::from(components)),
             current_frame,
@@ -147,9 +251,14 @@ impl<C: Component> BaseQuery for Added<C> {
 
     fn get<'w>(state: &mut Self::State<'w>, _: Entity, row: RowIndex) -> Self::Item<'w> {
         match state.reader.as_ref() {
-            Some(reader) => reader.components.frames()[row.to_usize()]
-                .added
-                .is_newer(state.current_frame, state.system_frame),
+            Some(reader) => reader
+                .components
+                .status(row.to_usize())
+                .is_some_and(|status| {
+                    status
+                        .added
+                        .is_newer(state.current_frame, state.system_frame)
+                }),
             None => false,
         }
     }
@@ -167,22 +276,31 @@ impl<C: Component> BaseQuery for Modified<C> {
     type State<'w> = ModifiedComponent<'w, C>;
     type Data = ComponentId;
 
-    fn init(components: &Components, _: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    const PER_ENTITY: bool = true;
 
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
+
+        query.include(id);
         id
     }
 
     fn state<'w>(
         data: &Self::Data,
+        _: &'w World,
         archetype: &'w Archetype,
         current_frame: Frame,
         system_frame: Frame,
     ) -> Self::State<'w> {
-        let components = archetype.table().get_column(*data);
+        let components = archetype.table().get_column(*data).filter(|column| {
+            column
+                .changed()
+                .modified
+                .is_newer(current_frame, system_frame)
+        });
         ModifiedComponent {
             reader: components.map(|components| ReadQuery::from(components)),
             current_frame,
@@ -192,15 +310,127 @@ impl<C: Component> BaseQuery for Modified<C> {
 
     fn get<'w>(state: &mut Self::State<'w>, _: Entity, row: RowIndex) -> Self::Item<'w> {
         match state.reader.as_ref() {
-            Some(reader) => {
-                let modified = reader.components.frames()[row.to_usize()].modified;
-                modified.is_newer(state.current_frame, state.system_frame)
-            }
+            Some(reader) => reader
+                .components
+                .status(row.to_usize())
+                .is_some_and(|status| {
+                    status
+                        .modified
+                        .is_newer(state.current_frame, state.system_frame)
+                }),
             None => false,
         }
     }
 }
 
+/// Matches entities that had `C` removed since the system last ran, including entities that
+/// got `C` back. Removals are only kept for the current and previous frame.
+pub struct Removed<T: 'static>(std::marker::PhantomData<T>);
+pub struct RemovedComponent<'w> {
+    log: Option<&'w RemovedLog>,
+    current_frame: Frame,
+    system_frame: Frame,
+}
+
+impl<C: Component> BaseQuery for Removed<C> {
+    type Item<'w> = bool;
+    type State<'w> = RemovedComponent<'w>;
+    type Data = ComponentId;
+
+    const PER_ENTITY: bool = true;
+
+    fn init(world: &World, _: &mut ArchetypeQuery) -> Self::Data {
+        world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()))
+    }
+
+    fn state<'w>(
+        data: &Self::Data,
+        world: &'w World,
+        _: &'w Archetype,
+        current_frame: Frame,
+        system_frame: Frame,
+    ) -> Self::State<'w> {
+        RemovedComponent {
+            log: world.archetypes().removed(*data),
+            current_frame,
+            system_frame,
+        }
+    }
+
+    fn get<'w>(state: &mut Self::State<'w>, entity: Entity, _: RowIndex) -> Self::Item<'w> {
+        state
+            .log
+            .is_some_and(|log| log.contains(entity, state.current_frame, state.system_frame))
+    }
+}
+
+/// Iterates the entities `C` was removed from since the system last ran, despawned entities
+/// included. Like events, removals are only kept for the current and previous frame.
+pub struct RemovedComponents<'w, 's, C: Component> {
+    log: Option<&'w RemovedLog>,
+    cursor: &'s mut u64,
+    _marker: std::marker::PhantomData<C>,
+}
+
+impl<C: Component> RemovedComponents<'_, '_, C> {
+    /// Number of removals that haven't been read yet.
+    pub fn remaining(&self) -> usize {
+        self.log
+            .map_or(0, |log| log.end().saturating_sub(*self.cursor) as usize)
+    }
+}
+
+impl<C: Component> Iterator for RemovedComponents<'_, '_, C> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entity, _) = self.log?.get(*self.cursor)?;
+        *self.cursor += 1;
+        Some(*entity)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+pub struct RemovedComponentsState {
+    id: ComponentId,
+    cursor: u64,
+}
+
+unsafe impl<C: Component> SystemArg for RemovedComponents<'_, '_, C> {
+    type Item<'world, 'state> = RemovedComponents<'world, 'state, C>;
+
+    type State = RemovedComponentsState;
+
+    fn init(world: &mut World) -> Self::State {
+        let id = world.register::<C>();
+        let cursor = world.archetypes().removed(id).map_or(0, |log| log.end());
+        RemovedComponentsState { id, cursor }
+    }
+
+    unsafe fn get<'world, 'state>(
+        state: &'state mut Self::State,
+        world: WorldCell<'world>,
+        _: &super::SystemMeta,
+    ) -> Self::Item<'world, 'state> {
+        let log = unsafe { world.get() }.archetypes().removed(state.id);
+        if let Some(log) = log {
+            state.cursor = state.cursor.max(log.start());
+        }
+
+        RemovedComponents {
+            log,
+            cursor: &mut state.cursor,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
 pub struct ReadQuery<'a, C: Component> {
     components: &'a Column,
     _marker: std::marker::PhantomData<C>,
@@ -222,11 +452,11 @@ impl<C: Component> BaseQuery for &C {
 
     type Data = ComponentId;
 
-    fn init(components: &Components, query: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
 
         query.include(id);
 
@@ -235,14 +465,17 @@ impl<C: Component> BaseQuery for &C {
 
     fn state<'w>(
         data: &Self::Data,
+        _: &'w World,
         archetype: &'w Archetype,
         _: Frame,
         _: Frame,
     ) -> Self::State<'w> {
-        let components = archetype.table().get_column(*data).expect(&format!(
-            "Component not found in archetype: {}",
-            std::any::type_name::<C>()
-        ));
+        let components = archetype.table().get_column(*data).unwrap_or_else(|| {
+            panic!(
+                "Component not found in archetype: {}",
+                std::any::type_name::<C>()
+            )
+        });
 
         ReadQuery::from(components)
     }
@@ -251,7 +484,7 @@ impl<C: Component> BaseQuery for &C {
         state
             .components
             .get(row.to_usize())
-            .expect(&format!("Component not found for entity: {:?}", entity))
+            .unwrap_or_else(|| panic!("Component not found for entity: {:?}", entity))
     }
 
     fn access(data: &Self::Data) -> Vec<SystemAccess> {
@@ -284,31 +517,110 @@ impl<'a, C: Component> WriteQuery<'a, C> {
     }
 }
 
+/// Mutable access to a component that only marks it modified when it's dereferenced mutably.
+pub struct Mut<'w, C> {
+    value: &'w mut C,
+    status: &'w mut ObjectStatus,
+    frame: Frame,
+}
+
+impl<'w, C> Mut<'w, C> {
+    pub fn new(value: &'w mut C, status: &'w mut ObjectStatus, frame: Frame) -> Self {
+        Self {
+            value,
+            status,
+            frame,
+        }
+    }
+
+    /// Replaces the value and marks it modified only if the new value is different.
+    pub fn set_if_neq(&mut self, value: C) -> bool
+    where
+        C: PartialEq,
+    {
+        if *self.value == value {
+            return false;
+        }
+
+        *self.value = value;
+        self.status.modified = self.frame;
+        true
+    }
+
+    /// Mutable access that doesn't mark the component modified.
+    pub fn bypass_change_detection(&mut self) -> &mut C {
+        self.value
+    }
+
+    pub fn status(&self) -> ObjectStatus {
+        *self.status
+    }
+
+    /// Converts into a plain reference, marking the component modified.
+    pub fn into_inner(self) -> &'w mut C {
+        self.status.modified = self.frame;
+        self.value
+    }
+}
+
+impl<C> std::ops::Deref for Mut<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<C> std::ops::DerefMut for Mut<'_, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.status.modified = self.frame;
+        self.value
+    }
+}
+
+impl<C> AsRef<C> for Mut<'_, C> {
+    fn as_ref(&self) -> &C {
+        self.value
+    }
+}
+
+impl<C: std::fmt::Debug> std::fmt::Debug for Mut<'_, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
 impl<C: Component> BaseQuery for &mut C {
-    type Item<'w> = &'w mut C;
+    type Item<'w> = Mut<'w, C>;
 
     type State<'w> = WriteQuery<'w, C>;
 
     type Data = ComponentId;
 
-    fn init(components: &Components, query: &mut ArchetypeQuery) -> Self::Data {
-        <&C as BaseQuery>::init(components, query)
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        <&C as BaseQuery>::init(world, query)
     }
 
     fn state<'w>(
         data: &Self::Data,
+        _: &'w World,
         archetype: &'w Archetype,
         current_frame: Frame,
         _: Frame,
     ) -> Self::State<'w> {
+        // Writes need per row frames, so frozen columns are thawed here.
         let (components, frames) = unsafe {
+            archetype.table().thaw_column(*data);
+            archetype.table().mark_column_modified(*data, current_frame);
             archetype
                 .table()
                 .get_column(*data)
-                .expect(&format!(
-                    "Component not found in archetype: {}",
-                    std::any::type_name::<C>()
-                ))
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Component not found in archetype: {}",
+                        std::any::type_name::<C>()
+                    )
+                })
                 .get_ptr()
         };
 
@@ -316,16 +628,16 @@ impl<C: Component> BaseQuery for &mut C {
     }
 
     fn get<'w>(state: &mut Self::State<'w>, entity: Entity, row: RowIndex) -> Self::Item<'w> {
-        let component = unsafe {
-            state.frames.get_mut(row.0 as usize).unwrap().modified = state.current_frame;
-
-            state
+        let (component, status) = unsafe {
+            let component = state
                 .components
                 .get_mut(row.to_usize())
-                .expect(&format!("Component not found for entity: {:?}", entity))
+                .unwrap_or_else(|| panic!("Component not found for entity: {:?}", entity));
+
+            (component, state.frames.get_mut(row.to_usize()).unwrap())
         };
 
-        component
+        Mut::new(component, status, state.current_frame)
     }
 
     fn access(data: &Self::Data) -> Vec<SystemAccess> {
@@ -336,6 +648,29 @@ impl<C: Component> BaseQuery for &mut C {
     }
 }
 
+impl<C: Component> ChunkQuery for &C {
+    type Chunk<'w> = &'w [C];
+
+    fn chunk<'w>(state: &mut Self::State<'w>) -> Self::Chunk<'w> {
+        state.components.as_slice()
+    }
+}
+
+impl<C: Component> ChunkQuery for &mut C {
+    type Chunk<'w> = &'w mut [C];
+
+    /// Every row of the chunk is marked modified, there's no way to tell which ones are written.
+    fn chunk<'w>(state: &mut Self::State<'w>) -> Self::Chunk<'w> {
+        unsafe {
+            for status in state.frames.as_mut_slice() {
+                status.modified = state.current_frame;
+            }
+
+            state.components.as_mut_slice()
+        }
+    }
+}
+
 impl<C: Component> BaseQuery for Option<&C> {
     type Item<'w> = Option<&'w C>;
 
@@ -343,17 +678,19 @@ impl<C: Component> BaseQuery for Option<&C> {
 
     type Data = ComponentId;
 
-    fn init(components: &Components, _: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
 
+        query.allow(id);
         id
     }
 
     fn state<'w>(
         data: &Self::Data,
+        _: &'w World,
         archetype: &'w Archetype,
         _: Frame,
         _: Frame,
@@ -377,29 +714,35 @@ impl<C: Component> BaseQuery for Option<&C> {
 }
 
 impl<C: Component> BaseQuery for Option<&mut C> {
-    type Item<'w> = Option<&'w mut C>;
+    type Item<'w> = Option<Mut<'w, C>>;
 
     type State<'w> = Option<WriteQuery<'w, C>>;
 
     type Data = ComponentId;
 
-    fn init(components: &Components, _: &mut ArchetypeQuery) -> Self::Data {
-        let id = components.get_id::<C>().expect(&format!(
-            "Component not registered: {}",
-            std::any::type_name::<C>()
-        ));
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        let id = world
+            .components()
+            .get_id::<C>()
+            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()));
 
+        query.allow(id);
         id
     }
 
     fn state<'w>(
         data: &Self::Data,
+        _: &'w World,
         archetype: &'w Archetype,
         current_frame: Frame,
         _: Frame,
     ) -> Self::State<'w> {
         archetype.table().get_column(*data).map(|column| {
-            let (components, frames) = unsafe { column.get_ptr() };
+            let (components, frames) = unsafe {
+                archetype.table().thaw_column(*data);
+                archetype.table().mark_column_modified(*data, current_frame);
+                column.get_ptr()
+            };
             WriteQuery::new(components, frames, current_frame)
         })
     }
@@ -416,6 +759,121 @@ impl<C: Component> BaseQuery for Option<&mut C> {
     }
 }
 
+/// Reads the entity's component, falling back to the global resource of the same type
+/// when the entity doesn't have one. Panics if neither exists, use [`TryWithFallback`]
+/// to get an `Option` instead.
+///
+/// Creating the state panics if neither the component nor the resource is registered.
+/// Once one of them is, systems register the other so both reads are declared, and a
+/// resource added after the system was built is still found.
+pub struct WithFallback<C: Component + Resource + Send>(std::marker::PhantomData<C>);
+
+pub struct FallbackState<'w, C: Component + Resource + Send> {
+    reader: Option<ReadQuery<'w, C>>,
+    fallback: Option<&'w C>,
+}
+
+impl<C: Component + Resource + Send> BaseQuery for WithFallback<C> {
+    type Item<'w> = &'w C;
+
+    type State<'w> = FallbackState<'w, C>;
+
+    type Data = (Option<ComponentId>, Option<ResourceId>);
+
+    fn register(world: &mut World) {
+        TryWithFallback::<C>::register(world)
+    }
+
+    fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+        TryWithFallback::<C>::init(world, query)
+    }
+
+    fn state<'w>(
+        data: &Self::Data,
+        world: &'w World,
+        archetype: &'w Archetype,
+        current_frame: Frame,
+        system_frame: Frame,
+    ) -> Self::State<'w> {
+        TryWithFallback::<C>::state(data, world, archetype, current_frame, system_frame)
+    }
+
+    fn get<'w>(state: &mut Self::State<'w>, entity: Entity, row: RowIndex) -> Self::Item<'w> {
+        TryWithFallback::<C>::get(state, entity, row).unwrap_or_else(|| {
+            panic!(
+                "Neither component nor fallback resource found for entity {:?}: {}",
+                entity,
+                std::any::type_name::<C>()
+            )
+        })
+    }
+
+    fn access(data: &Self::Data) -> Vec<SystemAccess> {
+        TryWithFallback::<C>::access(data)
+    }
+}
+
+/// Same as [`WithFallback`] but yields `None` when neither the component nor the resource exists.
+pub struct TryWithFallback<C: Component + Resource + Send>(std::marker::PhantomData<C>);
+
+impl<C: Component + Resource + Send> BaseQuery for TryWithFallback<C> {
+    type Item<'w> = Option<&'w C>;
+
+    type State<'w> = FallbackState<'w, C>;
+
+    type Data = (Option<ComponentId>, Option<ResourceId>);
+
+    fn register(world: &mut World) {
+        if world.components().get_id::<C>().is_some() || world.resources().get_id::<C>().is_some() {
+            world.register::<C>();
+            world.register_resource::<C>();
+        }
+    }
+
+    fn init(world: &World, _: &mut ArchetypeQuery) -> Self::Data {
+        let component = world.components().get_id::<C>();
+        let resource = world.resources().get_id::<C>();
+        assert!(
+            component.is_some() || resource.is_some(),
+            "Neither component nor fallback resource registered: {}",
+            std::any::type_name::<C>()
+        );
+
+        (component, resource)
+    }
+
+    fn state<'w>(
+        data: &Self::Data,
+        world: &'w World,
+        archetype: &'w Archetype,
+        _: Frame,
+        _: Frame,
+    ) -> Self::State<'w> {
+        let (component, resource) = *data;
+        FallbackState {
+            reader: component
+                .and_then(|id| archetype.table().get_column(id))
+                .map(ReadQuery::from),
+            fallback: resource.and_then(|id| world.resources().get::<C>(id)),
+        }
+    }
+
+    fn get<'w>(state: &mut Self::State<'w>, _: Entity, row: RowIndex) -> Self::Item<'w> {
+        state
+            .reader
+            .as_ref()
+            .and_then(|reader| reader.components.get(row.to_usize()))
+            .or(state.fallback)
+    }
+
+    fn access(data: &Self::Data) -> Vec<SystemAccess> {
+        let (component, resource) = *data;
+        let component = component.map(|id| SystemAccess::component(id, Access::Read));
+        let resource = resource.map(|id| SystemAccess::resource(id, Access::Read));
+        component.into_iter().chain(resource).collect()
+    }
+}
+
 impl BaseQuery for Entity {
     type Item<'w> = Entity;
 
@@ -423,11 +881,17 @@ impl BaseQuery for Entity {
 
     type Data = ();
 
-    fn init(_: &Components, _: &mut ArchetypeQuery) -> Self::Data {
+    fn init(_: &World, _: &mut ArchetypeQuery) -> Self::Data {
         ()
     }
 
-    fn state<'w>(_: &Self::Data, _: &'w Archetype, _: Frame, _: Frame) -> Self::State<'w> {
+    fn state<'w>(
+        _: &Self::Data,
+        _: &'w World,
+        _: &'w Archetype,
+        _: Frame,
+        _: Frame,
+    ) -> Self::State<'w> {
         ()
     }
 
@@ -436,54 +900,622 @@ impl BaseQuery for Entity {
     }
 }
 
+/// Draws spent per requested item by [`Query::sample`] when the filter rejects individual entities.
+pub const SAMPLE_ATTEMPTS: u64 = 32;
+
 pub struct QueryState<Q: BaseQuery, F: BaseFilter = ()> {
     pub(crate) query: ArchetypeQuery,
     pub(crate) data: Q::Data,
     pub(crate) filter_data: F::Data,
+    world: WorldId,
+    /// Matched archetypes created before `generation`.
+    archetypes: Vec<ArchetypeId>,
+    generation: ArchetypeGeneration,
 }
 
 impl<Q: BaseQuery, F: BaseFilter> QueryState<Q, F> {
+    /// Registers what the query needs with [`BaseQuery::register`] before creating the state.
+    /// Systems create their query states this way.
+    pub fn init(world: &mut World) -> Self {
+        Q::register(world);
+        F::register(world);
+        Self::new(world)
+    }
+
     pub fn new(world: &World) -> Self {
         let mut query = ArchetypeQuery::default();
-        let data = Q::init(world.components(), &mut query);
-        let filter_data = F::init(world.components(), &mut query);
+        let data = Q::init(world, &mut query);
+        let filter_data = F::init(world, &mut query);
+        if let Some(id) = world.components().get_id::<Disabled>()
+            && !query.mentions(id)
+        {
+            query.exclude(id);
+        }
 
-        QueryState {
+        let mut state = QueryState {
             query,
             data,
             filter_data,
-        }
+            world: world.id(),
+            archetypes: vec![],
+            generation: ArchetypeGeneration::ZERO,
+        };
+        state.update_archetypes(world);
+        state
     }
-}
-
-pub struct Query<'w, 's, Q: BaseQuery, F: BaseFilter = ()> {
-    world: WorldCell<'w>,
-    state: &'s QueryState<Q, F>,
-    current_frame: Frame,
-    system_frame: Frame,
-}
 
-impl<'w, 's, Q: BaseQuery, F: BaseFilter> Query<'w, 's, Q, F> {
-    pub fn new(world: &'w World, state: &'s QueryState<Q, F>) -> Self {
-        Self {
-            world: unsafe { WorldCell::new(world) },
-            current_frame: world.frame(),
-            system_frame: world.frame().previous(),
-            state,
+    /// Matches the archetypes created since the last update.
+    pub fn update_archetypes(&mut self, world: &World) {
+        self.check_world(world);
+        if self.world != world.id() {
+            self.world = world.id();
+            self.archetypes.clear();
+            self.generation = ArchetypeGeneration::ZERO;
         }
-    }
 
-    pub fn with_frame(world: &'w World, state: &'s QueryState<Q, F>, frame: Frame) -> Self {
-        Self {
-            world: unsafe { WorldCell::new(world) },
-            current_frame: world.frame(),
-            system_frame: frame,
-            state,
+        let archetypes = world.archetypes();
+        if self.generation == archetypes.generation() {
+            return;
         }
-    }
 
-    pub fn iter(&'w self) -> QueryIter<'w, 's, Q, F> {
-        QueryIter::new(&self)
+        let matched = archetypes.query_since(&self.query, self.generation);
+        self.archetypes.extend(matched.map(Archetype::id));
+        self.generation = archetypes.generation();
+    }
+
+    /// The archetypes matching the query. Only the archetypes created since the last
+    /// [`QueryState::update_archetypes`] are checked.
+    pub fn matched_archetypes<'w>(&'w self, world: &'w World) -> MatchedArchetypes<'w> {
+        self.check_world(world);
+        let (cached, generation) = match self.world == world.id() {
+            true => (self.archetypes.as_slice(), self.generation),
+            false => (&[][..], ArchetypeGeneration::ZERO),
+        };
+
+        let archetypes = world.archetypes();
+        MatchedArchetypes {
+            archetypes,
+            cached: cached.iter(),
+            created: archetypes.query_since(&self.query, generation),
+        }
+    }
+
+    /// The item of the entity, `None` if it doesn't match the query.
+    pub fn get<'w>(&self, world: &'w mut World, entity: Entity) -> Option<Q::Item<'w>> {
+        let frame = world.frame();
+        self.fetch(world, entity, frame, frame.previous())
+    }
+
+    fn fetch<'w>(
+        &self,
+        world: &'w World,
+        entity: Entity,
+        current: Frame,
+        system: Frame,
+    ) -> Option<Q::Item<'w>> {
+        self.check_world(world);
+        let archetype = world.archetypes().entity_archetype(entity)?;
+        let archetype = &world.archetypes()[archetype];
+        if !self.query.matches(archetype) {
+            return None;
+        }
+
+        let row = archetype.table().get_entity_row(entity)?;
+        let mut filter = F::state(&self.filter_data, world, archetype, current, system);
+        if !F::get(&mut filter, entity, row) {
+            return None;
+        }
+
+        let mut state = Q::state(&self.data, world, archetype, current, system);
+        Some(Q::get(&mut state, entity, row))
+    }
+
+    /// The component ids of the state are only valid in the world it was created for, debug
+    /// builds panic when it's used with another one. Release builds rescan the archetypes.
+    fn check_world(&self, world: &World) {
+        if cfg!(debug_assertions) && self.world != world.id() {
+            panic!(
+                "QueryState<{}> was created for {:?} but used with {:?}",
+                std::any::type_name::<Q>(),
+                self.world,
+                world.id()
+            );
+        }
+    }
+}
+
+/// Archetypes matched by a [`QueryState`], see [`QueryState::matched_archetypes`].
+pub struct MatchedArchetypes<'w> {
+    archetypes: &'w Archetypes,
+    cached: std::slice::Iter<'w, ArchetypeId>,
+    created: ArchetypeQueryIter<'w>,
+}
+
+impl<'w> Iterator for MatchedArchetypes<'w> {
+    type Item = &'w Archetype;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.cached.next() {
+            Some(id) => Some(&self.archetypes[*id]),
+            None => self.created.next(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySingleError {
+    NoEntities(&'static str),
+    MultipleEntities(&'static str),
+}
+
+impl std::fmt::Display for QuerySingleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuerySingleError::NoEntities(query) => write!(f, "No entities match {}", query),
+            QuerySingleError::MultipleEntities(query) => {
+                write!(f, "Multiple entities match {}", query)
+            }
+        }
+    }
+}
+
+pub struct Query<'w, 's, Q: BaseQuery, F: BaseFilter = ()> {
+    world: WorldCell<'w>,
+    state: &'s QueryState<Q, F>,
+    current_frame: Frame,
+    system_frame: Frame,
+}
+
+impl<'w, 's, Q: BaseQuery, F: BaseFilter> Query<'w, 's, Q, F> {
+    /// Read only query over a shared world. Mutable queries use [`Query::new_mut`].
+    pub fn new(world: &'w World, state: &'s QueryState<Q, F>) -> Self
+    where
+        Q: ReadOnlyQuery,
+    {
+        Self::with_frame(world, state, world.frame().previous())
+    }
+
+    /// Read only query reporting changes since `frame`.
+    pub fn with_frame(world: &'w World, state: &'s QueryState<Q, F>, frame: Frame) -> Self
+    where
+        Q: ReadOnlyQuery,
+    {
+        unsafe { Self::new_unchecked(world, state, frame) }
+    }
+
+    /// Query borrowing the world mutably, so no other query can alias its items.
+    pub fn new_mut(world: &'w mut World, state: &'s QueryState<Q, F>) -> Self {
+        let frame = world.frame().previous();
+        unsafe { Self::new_unchecked(world, state, frame) }
+    }
+
+    /// Query borrowing the world mutably, reporting changes since `frame`.
+    pub fn with_frame_mut(world: &'w mut World, state: &'s QueryState<Q, F>, frame: Frame) -> Self {
+        unsafe { Self::new_unchecked(world, state, frame) }
+    }
+
+    /// # Safety
+    /// Nothing else may access what `Q` writes for as long as the query lives.
+    pub(crate) unsafe fn new_unchecked(
+        world: &'w World,
+        state: &'s QueryState<Q, F>,
+        frame: Frame,
+    ) -> Self {
+        Self {
+            world: unsafe { WorldCell::new(world) },
+            current_frame: world.frame(),
+            system_frame: frame,
+            state,
+        }
+    }
+
+    /// Iterates the items of a read only query. Mutable queries use [`Query::iter_mut`].
+    pub fn iter(&'w self) -> QueryIter<'w, 's, Q, F>
+    where
+        Q: ReadOnlyQuery,
+    {
+        QueryIter::new(self)
+    }
+
+    /// Iterates the items, borrowing the query mutably so the items of two iterations can't
+    /// alias. Items of one iteration can be kept together, every entity is visited once.
+    pub fn iter_mut(&mut self) -> QueryIter<'_, 's, Q, F> {
+        QueryIter::new(self)
+    }
+
+    /// The item of the entity, `None` if it doesn't match the query.
+    pub fn get(&'w self, entity: Entity) -> Option<Q::Item<'w>>
+    where
+        Q: ReadOnlyQuery,
+    {
+        self.fetch(entity)
+    }
+
+    /// Like [`Query::get`] for mutable queries, the query stays borrowed while the item lives.
+    pub fn get_mut(&mut self, entity: Entity) -> Option<Q::Item<'_>> {
+        self.fetch(entity)
+    }
+
+    /// The items of several distinct entities at once. `None` if an entity doesn't match the
+    /// query or is given more than once, so the items never alias.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        entities: [Entity; N],
+    ) -> Option<[Q::Item<'_>; N]> {
+        for (index, entity) in entities.iter().enumerate() {
+            if entities[..index].contains(entity) {
+                return None;
+            }
+        }
+
+        let items = entities.map(|entity| self.fetch(entity));
+        match items.iter().all(Option::is_some) {
+            true => Some(items.map(Option::unwrap)),
+            false => None,
+        }
+    }
+
+    /// Callers must not fetch mutable items that are alive at the same time for the same entity.
+    fn fetch<'a>(&'a self, entity: Entity) -> Option<Q::Item<'a>> {
+        let world: &'a World = unsafe { self.world.get() };
+        self.state
+            .fetch(world, entity, self.current_frame, self.system_frame)
+    }
+
+    /// Iterates the items ordered by their `K` component, entities without it first. The query
+    /// has to access `K` itself, so reading the keys can't race with other systems.
+    pub fn iter_sorted<K: Component + Ord>(&'w self) -> std::vec::IntoIter<Q::Item<'w>>
+    where
+        Q: ReadOnlyQuery,
+    {
+        self.sorted::<K>()
+    }
+
+    /// Like [`Query::iter_sorted`] for mutable queries.
+    pub fn iter_sorted_mut<K: Component + Ord>(&mut self) -> std::vec::IntoIter<Q::Item<'_>> {
+        self.sorted::<K>()
+    }
+
+    fn sorted<'a, K: Component + Ord>(&'a self) -> std::vec::IntoIter<Q::Item<'a>> {
+        let world: &'a World = unsafe { self.world.get() };
+        let key = world.components().get_id::<K>().filter(|key| {
+            Q::access(&self.state.data)
+                .iter()
+                .any(|access| matches!(access, SystemAccess::Component { id, .. } if id == key))
+        });
+        let Some(key) = key else {
+            panic!("Query doesn't access {}", std::any::type_name::<K>());
+        };
+
+        let (current, system) = (self.current_frame, self.system_frame);
+        let archetypes = self.state.matched_archetypes(world).collect::<Vec<_>>();
+        let mut rows = vec![];
+        for (index, archetype) in archetypes.iter().enumerate() {
+            let column = archetype.table().get_column(key);
+            let mut filter = F::state(&self.state.filter_data, world, archetype, current, system);
+            for (row, entity) in archetype.table().entities().enumerate() {
+                let key = column.and_then(|column| column.get::<K>(row));
+                let row = RowIndex(row as u32);
+                if F::get(&mut filter, *entity, row) {
+                    rows.push((key, index, *entity, row));
+                }
+            }
+        }
+
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        // The keys are released before any item can borrow them mutably.
+        let rows = rows
+            .into_iter()
+            .map(|(_, index, entity, row)| (index, entity, row))
+            .collect::<Vec<_>>();
+
+        let mut states = archetypes
+            .iter()
+            .map(|archetype| Q::state(&self.state.data, world, archetype, current, system))
+            .collect::<Vec<_>>();
+        rows.into_iter()
+            .map(|(index, entity, row)| Q::get(&mut states[index], entity, row))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Iterates the matched entities on scoped threads. Each entity is visited by exactly one
+    /// thread, so the items only touch the components the system already declared access to.
+    pub fn par_iter(&'w self) -> ParQueryIter<'w, 's, Q, F>
+    where
+        Q: ReadOnlyQuery,
+    {
+        ParQueryIter {
+            query: self,
+            batch_size: None,
+        }
+    }
+
+    /// Like [`Query::par_iter`] for mutable queries.
+    pub fn par_iter_mut(&mut self) -> ParQueryIter<'_, 's, Q, F> {
+        ParQueryIter {
+            query: self,
+            batch_size: None,
+        }
+    }
+
+    /// Iterates the matched archetype tables, yielding the components of each one as contiguous
+    /// slices instead of per-entity items. Empty tables are skipped.
+    ///
+    /// Chunks can't skip rows, so this panics if the filter rejects individual entities.
+    pub fn iter_chunks(&'w self) -> impl Iterator<Item = Q::Chunk<'w>> + 'w
+    where
+        Q: ChunkQuery + ReadOnlyQuery,
+    {
+        self.chunks()
+    }
+
+    /// Like [`Query::iter_chunks`] for mutable queries.
+    pub fn iter_chunks_mut(&mut self) -> impl Iterator<Item = Q::Chunk<'_>> + '_
+    where
+        Q: ChunkQuery,
+    {
+        self.chunks()
+    }
+
+    fn chunks<'a>(&'a self) -> impl Iterator<Item = Q::Chunk<'a>> + 'a
+    where
+        Q: ChunkQuery,
+    {
+        assert!(
+            !F::PER_ENTITY,
+            "Chunks can't be filtered per entity: {}",
+            std::any::type_name::<F>()
+        );
+
+        let world: &'a World = unsafe { self.world.get() };
+        self.state
+            .matched_archetypes(world)
+            .filter(|archetype| !archetype.table().is_empty())
+            .map(move |archetype| {
+                let (current, system) = (self.current_frame, self.system_frame);
+                let mut state = Q::state(&self.state.data, world, archetype, current, system);
+                Q::chunk(&mut state)
+            })
+    }
+
+    /// The only item of the query, failing when no entity or several entities match.
+    pub fn single(&'w self) -> Result<Q::Item<'w>, QuerySingleError>
+    where
+        Q: ReadOnlyQuery,
+    {
+        Self::only(self.iter())
+    }
+
+    /// Like [`Query::single`], borrowing the query mutably so the item can't alias another one.
+    pub fn single_mut(&mut self) -> Result<Q::Item<'_>, QuerySingleError> {
+        Self::only(self.iter_mut())
+    }
+
+    fn only<I: Iterator>(mut iter: I) -> Result<I::Item, QuerySingleError> {
+        let name = std::any::type_name::<Self>();
+        let item = iter.next().ok_or(QuerySingleError::NoEntities(name))?;
+
+        match iter.next() {
+            Some(_) => Err(QuerySingleError::MultipleEntities(name)),
+            None => Ok(item),
+        }
+    }
+
+    /// Number of archetypes matched by the query.
+    pub fn matched_archetype_count(&self) -> usize {
+        self.archetype_sizes().count()
+    }
+
+    /// Number of entities stored in the matched archetypes, before per-entity filters are applied.
+    pub fn matched_entity_count(&self) -> usize {
+        self.archetype_sizes().sum()
+    }
+
+    /// Entity count of each matched archetype.
+    pub fn archetype_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        let world = unsafe { self.world.get() };
+        self.state
+            .matched_archetypes(world)
+            .map(|archetype| archetype.table().len())
+    }
+
+    /// Picks up to `n` distinct random items without iterating the matched archetypes. Entities are
+    /// drawn uniformly across archetypes using their sizes, so only the chosen rows are fetched.
+    ///
+    /// Per-entity filters like [`Added`] and [`Modified`] can't be counted up front, so filtered
+    /// queries draw rows until `n` pass or `n * SAMPLE_ATTEMPTS` draws are spent, and may return
+    /// fewer than `n` items when few entities pass.
+    pub fn sample(&'w self, rng: &mut impl RngSource, n: usize) -> Vec<Q::Item<'w>>
+    where
+        Q: ReadOnlyQuery,
+    {
+        self.sample_items(rng, n)
+    }
+
+    /// Like [`Query::sample`] for mutable queries. The sampled entities are distinct, so the
+    /// items don't alias.
+    pub fn sample_mut(&mut self, rng: &mut impl RngSource, n: usize) -> Vec<Q::Item<'_>> {
+        self.sample_items(rng, n)
+    }
+
+    fn sample_items<'a>(&'a self, rng: &mut impl RngSource, n: usize) -> Vec<Q::Item<'a>> {
+        let world: &'a World = unsafe { self.world.get() };
+        let rows = self.sample_rows(rng, n);
+        let mut items = Vec::with_capacity(rows.len());
+        let mut current: Option<(&Archetype, Q::State<'a>)> = None;
+        for (archetype, row) in rows {
+            if current
+                .as_ref()
+                .is_none_or(|(current, _)| current.id() != archetype.id())
+            {
+                let state = Q::state(
+                    &self.state.data,
+                    world,
+                    archetype,
+                    self.current_frame,
+                    self.system_frame,
+                );
+                current = Some((archetype, state));
+            }
+
+            let (archetype, state) = current.as_mut().unwrap();
+            let entity = archetype.table().get_entity(row).unwrap();
+            items.push(Q::get(state, entity, row));
+        }
+
+        items
+    }
+
+    /// Like [`Query::sample`], returning only the entities.
+    pub fn sample_entities(&'w self, rng: &mut impl RngSource, n: usize) -> Vec<Entity> {
+        self.sample_rows(rng, n)
+            .into_iter()
+            .map(|(archetype, row)| archetype.table().get_entity(row).unwrap())
+            .collect()
+    }
+
+    /// Sampled rows, ordered by archetype so each archetype's state is built once.
+    fn sample_rows<'a>(
+        &'a self,
+        rng: &mut impl RngSource,
+        n: usize,
+    ) -> Vec<(&'a Archetype, RowIndex)> {
+        let world: &'a World = unsafe { self.world.get() };
+        let mut offsets = vec![];
+        let mut archetypes = vec![];
+        let mut total = 0;
+        for archetype in self.state.matched_archetypes(world) {
+            if !archetype.table().is_empty() {
+                offsets.push(total);
+                archetypes.push(archetype);
+                total += archetype.table().len() as u64;
+            }
+        }
+
+        let locate = |index: u64| {
+            let archetype = offsets.partition_point(|offset| *offset <= index) - 1;
+            let row = RowIndex((index - offsets[archetype]) as u32);
+            (archetype, row)
+        };
+        let rows = |mut indices: Vec<u64>| {
+            indices.sort_unstable();
+            indices
+                .into_iter()
+                .map(|index| {
+                    let (archetype, row) = locate(index);
+                    (archetypes[archetype], row)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let n = n as u64;
+        let mut chosen = HashSet::new();
+        if !F::PER_ENTITY {
+            // Floyd's algorithm picks n distinct indices with n draws.
+            for bound in total.saturating_sub(n)..total {
+                let index = rng.below(bound + 1);
+                if !chosen.insert(index) {
+                    chosen.insert(bound);
+                }
+            }
+
+            return rows(chosen.into_iter().collect());
+        }
+
+        let mut accepted = vec![];
+        let mut filters: HashMap<usize, F::State<'a>> = HashMap::new();
+        let attempts = n.saturating_mul(SAMPLE_ATTEMPTS);
+        for _ in 0..attempts {
+            if total == 0 || accepted.len() as u64 == n.min(total) {
+                break;
+            }
+
+            let index = rng.below(total);
+            if chosen.contains(&index) {
+                continue;
+            }
+
+            let (archetype, row) = locate(index);
+            let filter = filters.entry(archetype).or_insert_with(|| {
+                F::state(
+                    &self.state.filter_data,
+                    world,
+                    archetypes[archetype],
+                    self.current_frame,
+                    self.system_frame,
+                )
+            });
+
+            let entity = archetypes[archetype].table().get_entity(row).unwrap();
+            chosen.insert(index);
+            if F::get(filter, entity, row) {
+                accepted.push(index);
+            }
+        }
+
+        rows(accepted)
+    }
+
+    /// Batch size that splits the matched entities into roughly `target_batches` batches.
+    pub fn suggest_batch_size(&self, target_batches: usize) -> usize {
+        self.matched_entity_count()
+            .div_ceil(target_batches.max(1))
+            .max(1)
+    }
+}
+
+impl<'w, F: BaseFilter> Query<'w, '_, &Children, F> {
+    /// Children of the entity, empty if it has none. The filter isn't applied.
+    pub fn children(&self, entity: Entity) -> &'w [Entity] {
+        let world = unsafe { self.world.get() };
+        world
+            .get_component::<Children>(entity)
+            .map_or(&[], Children::as_slice)
+    }
+
+    /// Every descendant of the entity, depth first in child order. The filter isn't applied.
+    pub fn iter_descendants(&self, entity: Entity) -> Descendants<'w> {
+        let mut stack = self.children(entity).to_vec();
+        stack.reverse();
+        Descendants {
+            world: unsafe { self.world.get() },
+            stack,
+        }
+    }
+}
+
+impl<'w, R: Relationship, F: BaseFilter> Query<'w, '_, &R, F> {
+    /// The entity `R` points at, `None` if the entity has no `R`. The filter isn't applied.
+    pub fn target(&self, source: Entity) -> Option<Entity> {
+        let world = unsafe { self.world.get() };
+        world.get_component::<R>(source).map(R::target)
+    }
+}
+
+impl<'w, R: Relationship, F: BaseFilter> Query<'w, '_, &RelationshipTarget<R>, F> {
+    /// The entities whose `R` points at the target. The filter isn't applied.
+    pub fn related(&self, target: Entity) -> &'w [Entity] {
+        let world = unsafe { self.world.get() };
+        world.related::<R>(target)
+    }
+}
+
+pub struct Descendants<'w> {
+    world: &'w World,
+    stack: Vec<Entity>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entity = self.stack.pop()?;
+        if let Some(children) = self.world.get_component::<Children>(entity) {
+            self.stack.extend(children.as_slice().iter().rev());
+        }
+
+        Some(entity)
     }
 }
 
@@ -493,7 +1525,7 @@ unsafe impl<Q: BaseQuery + 'static, F: BaseFilter + 'static> SystemArg for Query
     type State = QueryState<Q, F>;
 
     fn init(world: &mut World) -> Self::State {
-        QueryState::new(world)
+        QueryState::init(world)
     }
 
     unsafe fn get<'world, 'state>(
@@ -501,7 +1533,9 @@ unsafe impl<Q: BaseQuery + 'static, F: BaseFilter + 'static> SystemArg for Query
         world: WorldCell<'world>,
         system: &super::SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        unsafe { Query::with_frame(world.get(), state, system.frame) }
+        let world = unsafe { world.get() };
+        state.update_archetypes(world);
+        unsafe { Query::new_unchecked(world, state, system.frame) }
     }
 
     fn access(state: &Self::State) -> Vec<super::SystemAccess> {
@@ -511,48 +1545,74 @@ unsafe impl<Q: BaseQuery + 'static, F: BaseFilter + 'static> SystemArg for Query
 
 pub struct QueryIter<'w, 's, Q: BaseQuery, F: BaseFilter = ()> {
     query: &'w Query<'w, 's, Q, F>,
-    archetypes: Vec<&'w Archetype>,
+    archetypes: MatchedArchetypes<'w>,
+    archetype: Option<&'w Archetype>,
     state: Option<Q::State<'w>>,
     filter: Option<F::State<'w>>,
-    entities: Option<indexmap::set::Iter<'w, Entity>>,
-    archetype: usize,
+    entities: Option<std::iter::Enumerate<indexmap::set::Iter<'w, Entity>>>,
 }
 
 impl<'w, 's, Q: BaseQuery, F: BaseFilter> QueryIter<'w, 's, Q, F> {
     pub fn new(query: &'w Query<'w, 's, Q, F>) -> Self {
         let world = unsafe { query.world.get() };
-        let archetypes = world.archetypes().query(&query.state.query);
+        let mut iter = Self {
+            query,
+            archetypes: query.state.matched_archetypes(world),
+            archetype: None,
+            state: None,
+            filter: None,
+            entities: None,
+        };
 
-        let (state, filter_state, entities) = archetypes
-            .get(0)
-            .map(|archetype| {
-                let state = Q::state(
-                    &query.state.data,
-                    archetype,
-                    query.current_frame,
-                    query.system_frame,
-                );
-                let filter_state = F::state(
-                    &query.state.filter_data,
-                    archetype,
-                    query.current_frame,
-                    query.system_frame,
-                );
+        iter.next_archetype();
+        iter
+    }
 
-                let entities = archetype.table().entities();
+    /// The archetype of the most recently yielded item.
+    pub fn current_archetype(&self) -> Option<&'w Archetype> {
+        self.archetype
+    }
 
-                (Some(state), Some(filter_state), Some(entities))
-            })
-            .unwrap_or((None, None, None));
+    /// Collects the remaining items, ordered by the key. The sort is stable.
+    pub fn sort_by_key<K: Ord>(
+        self,
+        f: impl FnMut(&Q::Item<'w>) -> K,
+    ) -> std::vec::IntoIter<Q::Item<'w>> {
+        let mut items = self.collect::<Vec<_>>();
+        items.sort_by_key(f);
+        items.into_iter()
+    }
 
-        Self {
-            query,
-            archetypes,
-            state,
-            filter: filter_state,
-            entities,
-            archetype: 0,
-        }
+    /// Collects the remaining items, ordered by the comparator. The sort is stable.
+    pub fn sort_by(
+        self,
+        compare: impl FnMut(&Q::Item<'w>, &Q::Item<'w>) -> std::cmp::Ordering,
+    ) -> std::vec::IntoIter<Q::Item<'w>> {
+        let mut items = self.collect::<Vec<_>>();
+        items.sort_by(compare);
+        items.into_iter()
+    }
+
+    fn next_archetype(&mut self) {
+        let world = unsafe { self.query.world.get() };
+        self.archetype = self.archetypes.next();
+        self.entities = self.archetype.map(|archetype| {
+            self.state = Some(Q::state(
+                &self.query.state.data,
+                world,
+                archetype,
+                self.query.current_frame,
+                self.query.system_frame,
+            ));
+            self.filter = Some(F::state(
+                &self.query.state.filter_data,
+                world,
+                archetype,
+                self.query.current_frame,
+                self.query.system_frame,
+            ));
+            archetype.table().entities().enumerate()
+        });
     }
 }
 
@@ -560,50 +1620,101 @@ impl<'w, 's, Q: BaseQuery, F: BaseFilter> Iterator for QueryIter<'w, 's, Q, F> {
     type Item = Q::Item<'w>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.archetype >= self.archetypes.len() {
-            None
-        } else if let Some(entity) = self
-            .entities
-            .as_mut()
-            .and_then(|entities| entities.next())
-            .copied()
-        {
-            let row = self.archetypes[self.archetype]
-                .table()
-                .get_entity_row(entity)
-                .unwrap();
+        loop {
+            self.archetype?;
+            // Entities are iterated in table order, so their index is their row.
+            let Some((row, &entity)) = self.entities.as_mut().and_then(|e| e.next()) else {
+                self.next_archetype();
+                continue;
+            };
 
+            let row = RowIndex(row as u32);
             let state = self.state.as_mut()?;
             let filter = match &mut self.filter {
                 Some(state) => F::get(state, entity, row),
                 None => true,
             };
 
-            filter.then_some(Q::get(state, entity, row))
-        } else {
-            self.archetype += 1;
-            self.entities = self.archetypes.get(self.archetype).map(|archetype| {
-                self.state = Some(Q::state(
-                    &self.query.state.data,
-                    archetype,
-                    self.query.current_frame,
-                    self.query.system_frame,
-                ));
-                self.filter = Some(F::state(
-                    &self.query.state.filter_data,
-                    archetype,
-                    self.query.current_frame,
-                    self.query.system_frame,
-                ));
-                archetype.table().entities()
-            });
+            if filter {
+                return Some(Q::get(state, entity, row));
+            }
+        }
+    }
+}
+
+/// Parallel iteration over a [`Query`], archetype tables are split into batches that are
+/// distributed across threads.
+pub struct ParQueryIter<'w, 's, Q: BaseQuery, F: BaseFilter = ()> {
+    query: &'w Query<'w, 's, Q, F>,
+    batch_size: Option<usize>,
+}
+
+impl<'w, 's, Q: BaseQuery, F: BaseFilter> ParQueryIter<'w, 's, Q, F> {
+    /// Entities processed per batch, defaults to spreading the matched entities evenly over the
+    /// available threads.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = Some(size.max(1));
+        self
+    }
+
+    pub fn for_each(self, f: impl Fn(Q::Item<'w>) + Send + Sync) {
+        let query = self.query;
+        let world = unsafe { query.world.get() };
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+        let batches = self.batches(threads);
+
+        let run = |(archetype, rows): &(&'w Archetype, std::ops::Range<usize>)| {
+            let (current, system) = (query.current_frame, query.system_frame);
+            let mut state = Q::state(&query.state.data, world, archetype, current, system);
+            let mut filter = F::state(&query.state.filter_data, world, archetype, current, system);
+            for row in rows.clone() {
+                let row = RowIndex(row as u32);
+                let entity = archetype.table().get_entity(row).unwrap();
+                if F::get(&mut filter, entity, row) {
+                    f(Q::get(&mut state, entity, row));
+                }
+            }
+        };
 
-            self.next()
+        if threads == 1 || batches.len() <= 1 {
+            batches.iter().for_each(run);
+            return;
         }
+
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..threads.min(batches.len()) {
+                scope.spawn(|| {
+                    while let Some(batch) = batches.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        run(batch);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Rows of every matched archetype split into batches of the configured size, or of
+    /// [`Query::suggest_batch_size`] for `threads` batches.
+    fn batches(&self, threads: usize) -> Vec<(&'w Archetype, std::ops::Range<usize>)> {
+        let query = self.query;
+        let world = unsafe { query.world.get() };
+        let batch_size = self
+            .batch_size
+            .unwrap_or_else(|| query.suggest_batch_size(threads));
+
+        query
+            .state
+            .matched_archetypes(world)
+            .flat_map(|archetype| {
+                let len = archetype.table().len();
+                (0..len)
+                    .step_by(batch_size)
+                    .map(move |start| (archetype, start..(start + batch_size).min(len)))
+            })
+            .collect()
     }
 }
 
-#[macro_export]
 macro_rules! impl_base_query_for_tuples {
     ($(($($name:ident),*)),*)  => {
         $(
@@ -615,13 +1726,19 @@ macro_rules! impl_base_query_for_tuples {
 
                 type Data = ($($name::Data), +);
 
-                fn init(components: &Components, query: &mut ArchetypeQuery) -> Self::Data {
-                    ($($name::init(components, query),)*)
+                const PER_ENTITY: bool = $($name::PER_ENTITY)||+;
+
+                fn register(world: &mut World) {
+                    $($name::register(world);)*
                 }
 
-                fn state<'w>(data: &Self::Data, archetype: &'w Archetype, current_frame: Frame, system_frame: Frame) -> Self::State<'w> {
+                fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+                    ($($name::init(world, query),)*)
+                }
+
+                fn state<'w>(data: &Self::Data, world: &'w World, archetype: &'w Archetype, current_frame: Frame, system_frame: Frame) -> Self::State<'w> {
                     let ($($name,)*) = data;
-                    ($($name::state($name, archetype, current_frame, system_frame),)*)
+                    ($($name::state($name, world, archetype, current_frame, system_frame),)*)
                 }
 
                 fn get<'w>(state: &mut Self::State<'w>, entity: Entity, row: RowIndex) -> Self::Item<'w> {
@@ -641,10 +1758,128 @@ macro_rules! impl_base_query_for_tuples {
                     access
                 }
             }
+
+            unsafe impl<$($name: ReadOnlyQuery),+> ReadOnlyQuery for ($($name),+) {}
+
+            #[allow(non_snake_case)]
+            impl<$($name: ChunkQuery),+> ChunkQuery for ($($name),+) {
+                type Chunk<'w> = ($($name::Chunk<'w>), +);
+
+                fn chunk<'w>(state: &mut Self::State<'w>) -> Self::Chunk<'w> {
+                    let ($($name,)*) = state;
+                    ($($name::chunk($name),)*)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_filter_combinators_for_tuples {
+    ($(($($name:ident),*)),*)  => {
+        $(
+            #[allow(non_snake_case)]
+            unsafe impl<$($name: BaseFilter),+> ReadOnlyQuery for Or<($($name,)+)> {}
+
+            #[allow(non_snake_case)]
+            impl<$($name: BaseFilter),+> BaseQuery for Or<($($name,)+)> {
+                type Item<'w> = bool;
+
+                type State<'w> = ($(Option<$name::State<'w>>,)+);
+
+                type Data = ($(($name::Data, ArchetypeQuery),)+);
+
+                const PER_ENTITY: bool = $($name::PER_ENTITY)||+;
+
+                fn register(world: &mut World) {
+                    $($name::register(world);)+
+                }
+
+                fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+                    $(
+                        let mut branch = ArchetypeQuery::default();
+                        let $name = ($name::init(world, &mut branch), branch);
+                    )+
+
+                    query.any(vec![$($name.1.clone()),+]);
+                    ($($name,)+)
+                }
+
+                fn state<'w>(data: &Self::Data, world: &'w World, archetype: &'w Archetype, current_frame: Frame, system_frame: Frame) -> Self::State<'w> {
+                    let ($($name,)+) = data;
+                    ($(
+                        $name.1.matches(archetype).then(|| {
+                            $name::state(&$name.0, world, archetype, current_frame, system_frame)
+                        }),
+                    )+)
+                }
+
+                fn get<'w>(state: &mut Self::State<'w>, entity: Entity, row: RowIndex) -> Self::Item<'w> {
+                    let ($($name,)+) = state;
+                    false $(|| $name.as_mut().is_some_and(|state| $name::get(state, entity, row)))+
+                }
+
+                fn access(data: &Self::Data) -> Vec<SystemAccess> {
+                    let ($($name,)+) = data;
+                    let mut access = vec![];
+                    $(
+                        access.extend($name::access(&$name.0));
+                    )+
+                    access
+                }
+            }
+
+            #[allow(non_snake_case)]
+            unsafe impl<$($name: BaseFilter),+> ReadOnlyQuery for And<($($name,)+)> {}
+
+            #[allow(non_snake_case)]
+            impl<$($name: BaseFilter),+> BaseQuery for And<($($name,)+)> {
+                type Item<'w> = bool;
+
+                type State<'w> = ($($name::State<'w>,)+);
+
+                type Data = ($($name::Data,)+);
+
+                const PER_ENTITY: bool = $($name::PER_ENTITY)||+;
+
+                fn register(world: &mut World) {
+                    $($name::register(world);)+
+                }
+
+                fn init(world: &World, query: &mut ArchetypeQuery) -> Self::Data {
+                    ($($name::init(world, query),)+)
+                }
+
+                fn state<'w>(data: &Self::Data, world: &'w World, archetype: &'w Archetype, current_frame: Frame, system_frame: Frame) -> Self::State<'w> {
+                    let ($($name,)+) = data;
+                    ($($name::state($name, world, archetype, current_frame, system_frame),)+)
+                }
+
+                fn get<'w>(state: &mut Self::State<'w>, entity: Entity, row: RowIndex) -> Self::Item<'w> {
+                    let ($($name,)+) = state;
+                    true $(&& $name::get($name, entity, row))+
+                }
+
+                fn access(data: &Self::Data) -> Vec<SystemAccess> {
+                    let ($($name,)+) = data;
+                    let mut access = vec![];
+                    $(
+                        access.extend($name::access($name));
+                    )+
+                    access
+                }
+            }
         )+
     };
 }
 
+impl_filter_combinators_for_tuples!((A, B));
+impl_filter_combinators_for_tuples!((A, B, C));
+impl_filter_combinators_for_tuples!((A, B, C, D));
+impl_filter_combinators_for_tuples!((A, B, C, D, E));
+impl_filter_combinators_for_tuples!((A, B, C, D, E, F));
+impl_filter_combinators_for_tuples!((A, B, C, D, E, F, G));
+impl_filter_combinators_for_tuples!((A, B, C, D, E, F, G, H));
+
 impl_base_query_for_tuples!((A, B));
 impl_base_query_for_tuples!((A, B, C));
 impl_base_query_for_tuples!((A, B, C, D));
@@ -675,19 +1910,20 @@ mod tests {
 
     use super::*;
 
+    #[derive(PartialEq)]
     struct Age(u32);
     impl Component for Age {}
 
     #[test]
     fn test_modified_filter() {
-        let mut components = Components::new();
+        let mut world = World::new();
         let mut archetype_query = ArchetypeQuery::default();
 
         // Register a component
-        let component_id = components.register::<Age>();
+        let component_id = world.register::<Age>();
 
         // Initialize the Modified filter
-        let modified_filter = Modified::<Age>::init(&components, &mut archetype_query);
+        let modified_filter = Modified::<Age>::init(&world, &mut archetype_query);
 
         let system_frame = Frame(0);
         let current_frame = Frame(1);
@@ -702,9 +1938,946 @@ mod tests {
         );
 
         // Check if the filter detects the modification
-        let mut state =
-            Modified::<Age>::state(&modified_filter, &archetype, current_frame, system_frame);
+        let mut state = Modified::<Age>::state(
+            &modified_filter,
+            &world,
+            &archetype,
+            current_frame,
+            system_frame,
+        );
         let row = RowIndex(0);
         assert!(Modified::<Age>::get(&mut state, Entity::root(0), row));
     }
+
+    #[test]
+    fn query_current_archetype() {
+        let mut world = World::new();
+        world.register::<Age>();
+        world.on_archetype_created(|archetype| archetype.set_userdata(Box::new(archetype.id())));
+
+        let entity = world.spawn();
+        world.add_component(entity, Age(10));
+
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        let mut iter = query.iter();
+
+        assert_eq!(iter.next().map(|age| age.0), Some(10));
+        let archetype = iter.current_archetype().unwrap();
+        assert_eq!(archetype.userdata::<ArchetypeId>(), Some(&archetype.id()));
+    }
+
+    #[test]
+    fn query_distribution() {
+        struct Name(u8);
+        impl Component for Name {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Name>();
+
+        for i in 0..10 {
+            let entity = world.spawn();
+            world.add_component(entity, Age(i));
+            if i % 3 == 0 {
+                world.add_component(entity, Name(0));
+            }
+        }
+
+        let entity = world.spawn();
+        world.add_component(entity, Name(0));
+
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+
+        assert_eq!(query.matched_archetype_count(), 2);
+        assert_eq!(query.matched_entity_count(), query.iter().count());
+
+        let mut sizes = query.archetype_sizes().collect::<Vec<_>>();
+        sizes.sort();
+        assert_eq!(sizes, vec![4, 6]);
+
+        assert_eq!(query.suggest_batch_size(3), 4);
+        assert_eq!(query.suggest_batch_size(0), 10);
+        assert_eq!(query.suggest_batch_size(100), 1);
+    }
+
+    #[test]
+    fn query_with_fallback() {
+        #[derive(Debug, PartialEq)]
+        struct Color(u8);
+        impl Component for Color {}
+        impl Resource for Color {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Color>();
+        world.add_resource(Color(0));
+
+        let plain = world.spawn();
+        world.add_component(plain, Age(1));
+
+        let tinted = world.spawn();
+        world.add_component(tinted, Age(2));
+        world.add_component(tinted, Color(7));
+
+        let state = QueryState::<(Entity, &Age, WithFallback<Color>)>::new(&world);
+        let colors = |world: &World| {
+            let query = Query::new(world, &state);
+            let mut colors = query.iter().map(|(e, _, c)| (e, c.0)).collect::<Vec<_>>();
+            colors.sort_by_key(|(e, _)| *e == tinted);
+            colors
+        };
+
+        assert_eq!(colors(&world), vec![(plain, 0), (tinted, 7)]);
+
+        world.resource_mut::<Color>().0 = 3;
+        assert_eq!(colors(&world), vec![(plain, 3), (tinted, 7)]);
+
+        let access = <WithFallback<Color> as BaseQuery>::access(&state.data.2);
+        assert_eq!(access.len(), 2);
+    }
+
+    #[test]
+    fn query_try_with_fallback() {
+        struct Color(u8);
+        impl Component for Color {}
+        impl Resource for Color {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Color>();
+
+        let entity = world.spawn();
+        world.add_component(entity, Age(1));
+
+        let state = QueryState::<(&Age, TryWithFallback<Color>)>::new(&world);
+        let query = Query::new(&world, &state);
+        assert!(query.iter().all(|(_, color)| color.map(|c| c.0).is_none()));
+    }
+
+    #[test]
+    fn query_fallback_resource_added_later() {
+        #[derive(Default)]
+        struct Seen(Vec<Option<u8>>);
+        impl Resource for Seen {}
+
+        struct Color(u8);
+        impl Component for Color {}
+        impl Resource for Color {}
+
+        fn read(query: Query<TryWithFallback<Color>, With<Age>>, seen: &mut Seen) {
+            seen.0.extend(query.iter().map(|color| color.map(|c| c.0)));
+        }
+
+        // Only the resource is registered, the component isn't.
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register_resource::<Color>();
+        world.add_resource(Seen::default());
+        let entity = world.spawn();
+        world.add_component(entity, Age(1));
+
+        let id = world.register_system(read);
+        world.run_system(id).unwrap();
+
+        // The component gets registered with the system, and both reads are declared.
+        let node = crate::system::IntoSystemConfigs::configs(read)
+            .single()
+            .into_system_node(&mut world);
+        let component = world.components().get_id::<Color>().unwrap();
+        let resource = world.resources().get_id::<Color>().unwrap();
+        assert!(node.system.meta().components.reads(component.to_usize()));
+        assert!(node.system.meta().resources.reads(resource.to_usize()));
+
+        world.add_resource(Color(4));
+        world.run_system(id).unwrap();
+
+        world.add_component(entity, Color(9));
+        world.run_system(id).unwrap();
+        assert_eq!(world.resource::<Seen>().0, [None, Some(4), Some(9)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Neither component nor fallback resource registered")]
+    fn query_fallback_unregistered() {
+        struct Color;
+        impl Component for Color {}
+        impl Resource for Color {}
+
+        let mut world = World::new();
+        world.query::<WithFallback<Color>>();
+    }
+
+    fn sample_world() -> World {
+        struct Name(u8);
+        impl Component for Name {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Name>();
+
+        for i in 0..10 {
+            let entity = world.spawn();
+            world.add_component(entity, Age(i));
+            if i % 3 == 0 {
+                world.add_component(entity, Name(0));
+            }
+        }
+
+        world
+    }
+
+    #[test]
+    fn query_sample_uniform() {
+        let world = sample_world();
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        let mut rng = crate::core::SplitMix64::new(7);
+
+        let draws = 20_000;
+        let mut counts = [0u32; 10];
+        for _ in 0..draws {
+            let sample = query.sample(&mut rng, 1);
+            counts[sample[0].0 as usize] += 1;
+        }
+
+        // Chi-squared with 9 degrees of freedom, 27.88 is the 0.001 critical value.
+        let expected = draws as f64 / 10.0;
+        let chi = counts
+            .iter()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum::<f64>();
+        assert!(chi < 27.88, "{chi} {counts:?}");
+
+        let mut sample = query
+            .sample(&mut rng, 4)
+            .iter()
+            .map(|a| a.0)
+            .collect::<Vec<_>>();
+        sample.dedup();
+        assert_eq!(sample.len(), 4);
+        assert_eq!(query.sample(&mut rng, 20).len(), 10);
+        assert!(query.sample(&mut rng, 0).is_empty());
+    }
+
+    #[test]
+    fn query_sample_per_entity_filter() {
+        let mut world = sample_world();
+        world.update();
+        for i in 10..20 {
+            let entity = world.spawn();
+            world.add_component(entity, Age(i));
+        }
+
+        let state = QueryState::<&Age, Added<Age>>::new(&world);
+        let query = Query::new(&world, &state);
+        let mut rng = crate::core::SplitMix64::new(3);
+
+        for _ in 0..100 {
+            let sample = query.sample(&mut rng, 3);
+            assert_eq!(sample.len(), 3);
+            assert!(sample.iter().all(|age| age.0 >= 10));
+        }
+
+        let mut all = query
+            .sample(&mut rng, 50)
+            .iter()
+            .map(|a| a.0)
+            .collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, (10..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn query_sample_deterministic() {
+        let world = sample_world();
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+
+        let draw = |seed| {
+            let mut rng = crate::core::SplitMix64::new(seed);
+            (0..8)
+                .map(|_| query.sample_entities(&mut rng, 3))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(draw(11), draw(11));
+        assert_ne!(draw(11), draw(12));
+    }
+
+    #[test]
+    fn query_par_iter_visits_each_entity_once() {
+        let mut world = sample_world();
+        for i in 10..5000 {
+            let entity = world.spawn();
+            world.add_component(entity, Age(i));
+        }
+
+        let state = QueryState::<&mut Age>::new(&world);
+        let mut query = Query::new_mut(&mut world, &state);
+        query
+            .par_iter_mut()
+            .batch_size(64)
+            .for_each(|mut age| age.0 *= 2);
+
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        let mut ages = query.iter().map(|age| age.0).collect::<Vec<_>>();
+        ages.sort();
+        assert_eq!(ages, (0..5000).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn query_par_iter_uses_suggested_batch_size() {
+        struct Name(#[allow(dead_code)] u8);
+        impl Component for Name {}
+
+        let mut world = sample_world();
+        world.register::<Name>();
+        world.spawn_batch((10..110).map(|i| (Age(i), Name(0))));
+
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        for threads in [1, 3, 8, 1000] {
+            let size = query.suggest_batch_size(threads);
+            let batches = query.par_iter().batches(threads);
+            let sizes = batches
+                .iter()
+                .map(|(_, rows)| rows.len())
+                .collect::<Vec<_>>();
+
+            assert!(sizes.iter().all(|len| *len <= size));
+            assert_eq!(sizes.iter().sum::<usize>(), 110);
+            for (archetype, rows) in &batches {
+                let len = archetype.table().len();
+                assert!(rows.len() == size || rows.end == len);
+            }
+        }
+
+        let batches = query.par_iter().batch_size(16).batches(3);
+        let expected = query
+            .archetype_sizes()
+            .map(|len| len.div_ceil(16))
+            .sum::<usize>();
+        assert!(batches.iter().all(|(_, rows)| rows.len() <= 16));
+        assert_eq!(batches.len(), expected);
+    }
+
+    #[test]
+    fn query_par_iter_filter() {
+        let mut world = sample_world();
+        world.update();
+        for i in 10..1000 {
+            let entity = world.spawn();
+            world.add_component(entity, Age(i));
+        }
+
+        let state = QueryState::<&Age, Added<Age>>::new(&world);
+        let query = Query::new(&world, &state);
+        let (count, sum) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        query.par_iter().batch_size(16).for_each(|age| {
+            count.fetch_add(1, Ordering::Relaxed);
+            sum.fetch_add(age.0 as usize, Ordering::Relaxed);
+        });
+
+        assert_eq!(count.into_inner(), 990);
+        assert_eq!(sum.into_inner(), (10..1000).sum::<usize>());
+    }
+
+    #[test]
+    fn removed_components_reader() {
+        use crate::{app::App, system::schedule::Phase};
+        use std::sync::Mutex;
+
+        struct Update;
+        impl Phase for Update {}
+
+        #[derive(Default)]
+        struct Seen(Mutex<Vec<Entity>>);
+        impl Resource for Seen {}
+
+        fn collect(removed: RemovedComponents<Age>, seen: &Seen) {
+            seen.0.lock().unwrap().extend(removed);
+        }
+
+        let mut builder = App::new();
+        builder.add_resource(Seen::default());
+        builder.add_systems(Update, collect);
+        let mut app = builder.build();
+
+        let world = app.world_mut();
+        let entities = (0..3)
+            .map(|i| {
+                let entity = world.spawn();
+                world.add_component(entity, Age(i));
+                entity
+            })
+            .collect::<Vec<_>>();
+
+        app.run(Update);
+        app.world_mut().remove_component::<Age>(entities[0]);
+        app.world_mut().despawn(entities[1]);
+        app.run(Update);
+        app.run(Update);
+        assert_eq!(
+            *app.world().resource::<Seen>().0.lock().unwrap(),
+            vec![entities[0], entities[1]]
+        );
+
+        // Removals are dropped once they are two frames old.
+        app.world_mut().remove_component::<Age>(entities[2]);
+        app.world_mut().update();
+        app.world_mut().update();
+        app.run(Update);
+        assert_eq!(app.world().resource::<Seen>().0.lock().unwrap().len(), 2);
+        assert!(
+            app.world()
+                .archetypes()
+                .removed(app.world().components().get_id::<Age>().unwrap())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn removed_filter() {
+        let mut world = sample_world();
+        let entities = (0..4).map(|_| world.spawn()).collect::<Vec<_>>();
+        for entity in &entities {
+            world.add_component(*entity, Age(100));
+        }
+
+        let last_run = world.frame();
+        world.update();
+        world.remove_component::<Age>(entities[0]);
+        world.despawn(entities[1]);
+        world.remove_component::<Age>(entities[2]);
+        world.add_component(entities[2], Age(200));
+
+        let state = QueryState::<Entity, Removed<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        let mut removed = query.iter().collect::<Vec<_>>();
+        removed.sort_by_key(|entity| entity.id());
+        assert_eq!(removed, vec![entities[0], entities[2]]);
+
+        let query = Query::with_frame(&world, &state, world.frame());
+        assert_eq!(query.iter().count(), 0);
+    }
+
+    #[test]
+    fn mut_change_detection() {
+        let mut world = World::new();
+        world.register::<Age>();
+        let entities = (0..4).map(|_| world.spawn()).collect::<Vec<_>>();
+        for (age, entity) in entities.iter().enumerate() {
+            world.add_component(*entity, Age(age as u32));
+        }
+
+        let last_run = world.frame();
+        world.update();
+
+        let state = QueryState::<(Entity, &mut Age)>::new(&world);
+        let mut query = Query::new_mut(&mut world, &state);
+        for (entity, mut age) in query.iter_mut() {
+            match entities.iter().position(|e| *e == entity).unwrap() {
+                0 => assert_eq!(age.0, 0),
+                1 => assert!(!age.set_if_neq(Age(1))),
+                2 => assert!(age.set_if_neq(Age(20))),
+                _ => age.bypass_change_detection().0 = 30,
+            }
+        }
+
+        let state = QueryState::<Entity, Modified<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().collect::<Vec<_>>(), [entities[2]]);
+        assert_eq!(world.get_component::<Age>(entities[3]).unwrap().0, 30);
+    }
+
+    #[test]
+    fn query_single() {
+        let mut world = World::new();
+        world.register::<Age>();
+        let state = QueryState::<&mut Age>::new(&world);
+
+        let mut query = Query::new_mut(&mut world, &state);
+        assert!(matches!(
+            query.single_mut(),
+            Err(QuerySingleError::NoEntities(_))
+        ));
+
+        let entity = world.spawn();
+        world.add_component(entity, Age(1));
+        let mut query = Query::new_mut(&mut world, &state);
+        query.single_mut().unwrap().0 = 2;
+        assert_eq!(world.get_component::<Age>(entity).unwrap().0, 2);
+
+        let other = world.spawn();
+        world.add_component(other, Age(3));
+        let mut query = Query::new_mut(&mut world, &state);
+        assert!(matches!(
+            query.single_mut(),
+            Err(QuerySingleError::MultipleEntities(_))
+        ));
+    }
+
+    #[test]
+    fn query_iter_chunks() {
+        struct Speed(u32);
+        impl Component for Speed {}
+
+        let mut world = sample_world();
+        world.register::<Speed>();
+        world.spawn_batch([(Age(10), Speed(3)), (Age(20), Speed(3))]);
+
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        let chunks = query.iter_chunks().collect::<Vec<_>>();
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            [6, 4, 2]
+        );
+        assert_eq!(
+            chunks[0].iter().map(|a| a.0).sum::<u32>(),
+            1 + 2 + 4 + 5 + 7 + 8
+        );
+
+        let last_run = world.frame();
+        world.update();
+
+        let state = QueryState::<(&mut Age, &Speed)>::new(&world);
+        let mut query = Query::new_mut(&mut world, &state);
+        for (ages, speeds) in query.iter_chunks_mut() {
+            for (age, speed) in ages.iter_mut().zip(speeds) {
+                age.0 += speed.0;
+            }
+        }
+
+        let state = QueryState::<&Age, Modified<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().map(|a| a.0).collect::<Vec<_>>(), [13, 23]);
+    }
+
+    #[test]
+    fn query_zero_sized_components() {
+        #[derive(Debug, PartialEq)]
+        struct Player;
+        impl Component for Player {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Player>();
+        let entities = (0..100)
+            .map(|age| world.spawn_bundle((Age(age), Player)))
+            .collect::<Vec<_>>();
+        world.despawn(entities[0]);
+
+        let archetype = world.archetypes().entity_archetype(entities[1]).unwrap();
+        let player = world.components().get_id::<Player>().unwrap();
+        let column = world.archetypes()[archetype].table().get_column(player);
+        let (bytes, count, _) = column.unwrap().raw_parts();
+        assert_eq!((bytes.len(), count), (0, 99));
+
+        let state = QueryState::<&Age, Added<Player>>::new(&world);
+        assert_eq!(Query::new(&world, &state).iter().count(), 99);
+
+        let state = QueryState::<&Player>::new(&world);
+        let query = Query::new(&world, &state);
+        assert_eq!(query.iter_chunks().map(<[Player]>::len).sum::<usize>(), 99);
+        assert!(query.iter().all(|player| player == &Player));
+
+        let last_run = world.frame();
+        world.update();
+        let state = QueryState::<&mut Player>::new(&world);
+        for mut player in Query::new_mut(&mut world, &state).iter_mut().take(3) {
+            *player = Player;
+        }
+
+        let state = QueryState::<&Age, Modified<Player>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().map(|a| a.0).collect::<Vec<_>>(), [99, 1, 2]);
+    }
+
+    /// Compares positional rows with looking each entity's row up in the table.
+    /// Run with `cargo test --release bench_query_iter -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_query_iter() {
+        const ENTITIES: u32 = 1_000_000;
+        const ROUNDS: u32 = 10;
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.spawn_batch((0..ENTITIES).map(|i| (Age(i),)));
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+
+        let start = std::time::Instant::now();
+        for _ in 0..ROUNDS {
+            let sum = query.iter().map(|age| age.0 as u64).sum::<u64>();
+            std::hint::black_box(sum);
+        }
+        let positional = start.elapsed();
+
+        let start = std::time::Instant::now();
+        for _ in 0..ROUNDS {
+            let mut sum = 0;
+            for archetype in world.archetypes().query_iter(&state.query) {
+                let mut read = <&Age>::state(&state.data, &world, archetype, Frame(0), Frame(0));
+                for entity in archetype.table().entities() {
+                    let row = archetype.table().get_entity_row(*entity).unwrap();
+                    sum += <&Age>::get(&mut read, *entity, row).0 as u64;
+                }
+            }
+            std::hint::black_box(sum);
+        }
+        let lookup = start.elapsed();
+
+        let items = (ENTITIES * ROUNDS) as f64;
+        println!(
+            "positional: {:.2} ns/item, lookup: {:.2} ns/item",
+            positional.as_nanos() as f64 / items,
+            lookup.as_nanos() as f64 / items
+        );
+    }
+
+    fn filtered<F: BaseFilter>(world: &World, frame: Frame) -> Vec<Entity> {
+        let state = QueryState::<Entity, F>::new(world);
+        let query = Query::with_frame(world, &state, frame);
+        let mut entities = query.iter().collect::<Vec<_>>();
+        entities.sort_by_key(|entity| entity.id());
+        entities
+    }
+
+    #[test]
+    fn query_or_filter() {
+        struct Tag(#[allow(dead_code)] u32);
+        impl Component for Tag {}
+
+        struct Hidden(#[allow(dead_code)] u32);
+        impl Component for Hidden {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Tag>();
+        world.register::<Hidden>();
+
+        let a = world.spawn_bundle((Age(1),));
+        let b = world.spawn_bundle((Tag(1),));
+        let c = world.spawn_bundle((Age(2), Tag(2)));
+        let d = world.spawn_bundle((Hidden(1),));
+        let e = world.spawn_bundle((Age(3), Hidden(2)));
+        let frame = world.frame();
+
+        type AgeOrTag = Or<(With<Age>, With<Tag>)>;
+        assert_eq!(filtered::<AgeOrTag>(&world, frame), [a, b, c, e]);
+        let state = QueryState::<Entity, AgeOrTag>::new(&world);
+        assert_eq!(Query::new(&world, &state).matched_archetype_count(), 4);
+
+        type Nested = Or<(And<(With<Age>, Not<Hidden>)>, With<Hidden>)>;
+        assert_eq!(filtered::<Nested>(&world, frame), [a, c, d, e]);
+
+        type Untagged = And<(Or<(With<Tag>, With<Hidden>)>, Not<Age>)>;
+        assert_eq!(filtered::<Untagged>(&world, frame), [b, d]);
+
+        // Archetype level branches don't reject entities, so chunks are allowed.
+        let state = QueryState::<&Age, Or<(With<Tag>, With<Hidden>)>>::new(&world);
+        let query = Query::new(&world, &state);
+        let mut ages = query
+            .iter_chunks()
+            .flat_map(|ages| ages.iter().map(|age| age.0))
+            .collect::<Vec<_>>();
+        ages.sort();
+        assert_eq!(ages, [2, 3]);
+    }
+
+    #[test]
+    fn query_or_change_filters() {
+        struct Speed(#[allow(dead_code)] u32);
+        impl Component for Speed {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Speed>();
+
+        let old = world.spawn_bundle((Age(1), Speed(1)));
+        let fast = world.spawn_bundle((Speed(2),));
+        let last_run = world.frame();
+        world.update();
+
+        let young = world.spawn_bundle((Age(2),));
+        world.add_component(fast, Speed(3));
+
+        assert_eq!(
+            filtered::<Or<(Added<Age>, Modified<Speed>)>>(&world, last_run),
+            [fast, young]
+        );
+
+        // Entities matching the archetype level branch pass regardless of change ticks.
+        assert_eq!(
+            filtered::<Or<(And<(With<Age>, With<Speed>)>, Added<Age>)>>(&world, last_run),
+            [old, young]
+        );
+
+        type SpeedChanged = Or<(And<(With<Speed>, Modified<Speed>)>, Added<Speed>)>;
+        assert_eq!(filtered::<SpeedChanged>(&world, last_run), [fast]);
+    }
+
+    #[test]
+    fn query_change_filters_skip_columns() {
+        struct Speed(#[allow(dead_code)] u32);
+        impl Component for Speed {}
+
+        let mut world = World::new();
+        let age = world.register::<Age>();
+        world.register::<Speed>();
+
+        let a = world.spawn_bundle((Age(1),));
+        let b = world.spawn_bundle((Age(2), Speed(1)));
+        world.spawn_bundle((Speed(2),));
+        let last_run = world.frame();
+        world.update();
+
+        // Archetypes without the component aren't scanned.
+        let state = QueryState::<Entity, Added<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.matched_archetype_count(), 2);
+        assert_eq!(query.iter().count(), 0);
+
+        let [archetype, other] = [a, b].map(|e| world.archetypes().entity_archetype(e).unwrap());
+        let current = world.frame();
+        for id in [archetype, other] {
+            let archetype = &world.archetypes()[id];
+            let state = Added::<Age>::state(&age, &world, archetype, current, last_run);
+            assert!(state.reader.is_none());
+        }
+
+        world.add_component(a, Age(3));
+        let current = world.frame();
+        let (archetype, other) = (&world.archetypes()[archetype], &world.archetypes()[other]);
+        let column = archetype.table().get_column(age).unwrap();
+        assert_eq!(column.changed().modified, current);
+
+        let state = Modified::<Age>::state(&age, &world, archetype, current, last_run);
+        assert!(state.reader.is_some());
+        let state = Modified::<Age>::state(&age, &world, other, current, last_run);
+        assert!(state.reader.is_none());
+
+        let state = QueryState::<Entity, Modified<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().collect::<Vec<_>>(), [a]);
+    }
+
+    #[test]
+    fn query_sorted() {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct ZIndex(i32);
+        impl Component for ZIndex {}
+
+        let mut world = sample_world();
+        world.register::<ZIndex>();
+        for (index, z) in [(1, 5), (4, -2), (6, 9), (7, 0)] {
+            let entity = world.spawn();
+            world.add_component(entity, Age(index));
+            world.add_component(entity, ZIndex(z));
+        }
+
+        let state = QueryState::<(&mut Age, Option<&ZIndex>)>::new(&world);
+        let mut query = Query::new_mut(&mut world, &state);
+        let ages = query
+            .iter_sorted_mut::<ZIndex>()
+            .filter(|(_, z)| z.is_some())
+            .map(|(mut age, _)| {
+                age.0 += 100;
+                age.0
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ages, [104, 107, 101, 106]);
+
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        let ages = query
+            .iter()
+            .sort_by_key(|age| std::cmp::Reverse(age.0))
+            .map(|age| age.0)
+            .take(3)
+            .collect::<Vec<_>>();
+        assert_eq!(ages, [107, 106, 104]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Query doesn't access")]
+    fn query_sorted_requires_access() {
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct ZIndex(i32);
+        impl Component for ZIndex {}
+
+        let mut world = sample_world();
+        world.register::<ZIndex>();
+        let state = QueryState::<&Age>::new(&world);
+        let query = Query::new(&world, &state);
+        let _ = query.iter_sorted::<ZIndex>();
+    }
+
+    #[test]
+    fn change_detection_survives_wraparound() {
+        use crate::core::CHECK_FRAME_THRESHOLD;
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.restore_frame(Frame(10));
+        let entity = world.spawn_bundle((Age(0),));
+
+        // Ages the component by almost a full frame range, checking frames along the way.
+        for step in 1..=8 {
+            world.restore_frame(Frame(10 + step * CHECK_FRAME_THRESHOLD));
+            world.update();
+        }
+
+        world.restore_frame(Frame(20));
+        let last_run = Frame(5);
+
+        let state = QueryState::<Entity, Added<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().count(), 0);
+
+        let state = QueryState::<&mut Age>::new(&world);
+        state.get(&mut world, entity).unwrap().0 = 1;
+
+        let state = QueryState::<Entity, Modified<Age>>::new(&world);
+        let query = Query::with_frame(&world, &state, last_run);
+        assert_eq!(query.iter().collect::<Vec<_>>(), vec![entity]);
+    }
+
+    #[test]
+    fn world_query() {
+        struct Name(#[allow(dead_code)] u8);
+        impl Component for Name {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Name>();
+        let entities = (0..10)
+            .map(|i| match i % 3 {
+                0 => world.spawn_bundle((Age(i), Name(0))),
+                _ => world.spawn_bundle((Age(i),)),
+            })
+            .collect::<Vec<_>>();
+
+        for mut age in world.query::<&mut Age>().iter_mut() {
+            age.0 += 1;
+        }
+
+        let ages = world.query::<&Age>().iter().map(|age| age.0).sum::<u32>();
+        assert_eq!(ages, 55);
+        let named = world.query_filtered::<Entity, With<Name>>().iter().count();
+        assert_eq!(named, 4);
+
+        let state = QueryState::<&mut Age, Not<Name>>::new(&world);
+        assert!(state.get(&mut world, entities[0]).is_none());
+        state.get(&mut world, entities[1]).unwrap().0 = 50;
+        assert_eq!(
+            world.get_component::<Age>(entities[1]).map(|age| age.0),
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn query_state_caches_archetypes() {
+        struct Name(#[allow(dead_code)] u8);
+        impl Component for Name {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        world.register::<Name>();
+        world.spawn_bundle((Age(1),));
+
+        let mut state = QueryState::<&Age>::new(&world);
+        assert_eq!(state.archetypes.len(), 1);
+        assert_eq!(state.generation, world.archetypes().generation());
+
+        // Archetypes created since the last update are still matched.
+        world.spawn_bundle((Age(2), Name(0)));
+        world.spawn_bundle((Name(1),));
+        let query = Query::new(&world, &state);
+        assert_eq!(query.iter().map(|age| age.0).sum::<u32>(), 3);
+
+        state.update_archetypes(&world);
+        assert_eq!(state.archetypes.len(), 2);
+        assert_eq!(state.generation, world.archetypes().generation());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "was created for")]
+    fn query_state_rejects_other_world() {
+        struct Name;
+        impl Component for Name {}
+
+        let mut world = World::new();
+        world.register::<Age>();
+        let state = QueryState::<&Age>::new(&world);
+
+        // Ids of one world mean other components in another.
+        let mut other = World::new();
+        other.register::<Name>();
+        other.register::<Age>();
+        other.spawn_bundle((Age(7),));
+        Query::new(&other, &state).iter().count();
+    }
+
+    #[test]
+    fn query_skips_disabled() {
+        let mut world = World::new();
+        world.register::<Age>();
+        let entities = (0..4)
+            .map(|i| world.spawn_bundle((Age(i),)))
+            .collect::<Vec<_>>();
+        world.disable(entities[1]);
+        world.disable(entities[3]);
+
+        let ages = |world: &mut World| world.query::<&Age>().iter().map(|age| age.0).sum::<u32>();
+        assert_eq!(ages(&mut world), 2);
+        assert!(world.query::<&Age>().get(entities[1]).is_none());
+
+        let disabled = world.query_filtered::<&Age, With<Disabled>>();
+        assert_eq!(disabled.iter().map(|age| age.0).sum::<u32>(), 4);
+        let all = world.query_filtered::<&Age, IncludeDisabled>();
+        assert_eq!(all.iter().count(), 4);
+        let optional = world.query::<(&Age, Option<&Disabled>)>();
+        assert_eq!(optional.iter().filter(|(_, d)| d.is_some()).count(), 2);
+
+        world.enable(entities[1]);
+        assert!(!world.is_disabled(entities[1]));
+        assert_eq!(ages(&mut world), 3);
+    }
+
+    #[test]
+    fn query_get_many_mut() {
+        let mut world = World::new();
+        world.register::<Age>();
+        let entities = (0..3)
+            .map(|i| world.spawn_bundle((Age(i),)))
+            .collect::<Vec<_>>();
+        let dead = world.spawn();
+        world.despawn(dead);
+
+        let state = QueryState::<&mut Age>::new(&world);
+        let mut query = Query::new_mut(&mut world, &state);
+        let [mut a, mut b] = query.get_many_mut([entities[0], entities[2]]).unwrap();
+        std::mem::swap(&mut a.0, &mut b.0);
+        assert!(query.get_many_mut([entities[1], entities[1]]).is_none());
+        assert!(query.get_many_mut([entities[1], dead]).is_none());
+
+        // Every item of a mutable iteration can be held at once.
+        let mut items = query.iter_mut().collect::<Vec<_>>();
+        items.iter_mut().for_each(|age| age.0 += 10);
+        drop(items);
+
+        let ages = entities
+            .iter()
+            .map(|e| world.get_component::<Age>(*e).unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(ages, [12, 11, 10]);
+    }
 }
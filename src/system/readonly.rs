@@ -0,0 +1,245 @@
+use super::{Access, IntoSystemConfigs, System, SystemAccess, SystemName, arg::SystemArg};
+use crate::world::{World, cell::WorldCell};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadOnlyReason {
+    /// The argument writes to a component or resource.
+    Write(SystemAccess),
+    /// The argument needs exclusive access to the world.
+    Exclusive,
+    /// The argument queues mutations that are applied after the system runs.
+    Deferred,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadOnlyViolation {
+    pub system: Option<SystemName>,
+    /// Type name of the offending argument.
+    pub arg: &'static str,
+    pub reason: ReadOnlyReason,
+}
+
+impl std::fmt::Display for ReadOnlyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let system = self.system.as_deref().unwrap_or("<unnamed>");
+        match self.reason {
+            ReadOnlyReason::Write(access) => write!(
+                f,
+                "System {} is not read only: {} writes {:?}",
+                system, self.arg, access
+            ),
+            ReadOnlyReason::Exclusive => write!(
+                f,
+                "System {} is not read only: {} requires exclusive access",
+                system, self.arg
+            ),
+            ReadOnlyReason::Deferred => write!(
+                f,
+                "System {} is not read only: {} defers world mutations",
+                system, self.arg
+            ),
+        }
+    }
+}
+
+pub(crate) fn check_read_only<A: SystemArg>(state: &A::State) -> Result<(), ReadOnlyViolation> {
+    let violation = |reason| ReadOnlyViolation {
+        system: None,
+        arg: std::any::type_name::<A>(),
+        reason,
+    };
+
+    if A::exclusive() {
+        return Err(violation(ReadOnlyReason::Exclusive));
+    }
+
+    if A::deferred() {
+        return Err(violation(ReadOnlyReason::Deferred));
+    }
+
+    let write = A::access(state).into_iter().find(|access| {
+        matches!(
+            access,
+            SystemAccess::Component {
+                access: Access::Write,
+                ..
+            } | SystemAccess::Resource {
+                access: Access::Write,
+                ..
+            }
+        )
+    });
+
+    match write {
+        Some(access) => Err(violation(ReadOnlyReason::Write(access))),
+        None => Ok(()),
+    }
+}
+
+/// Systems validated to only read the world, so they can run against a shared [`World`],
+/// for example from tooling while the main schedule is paused.
+pub struct ReadOnlySystems {
+    systems: Vec<System>,
+}
+
+impl ReadOnlySystems {
+    pub fn new() -> Self {
+        Self { systems: vec![] }
+    }
+
+    pub fn add_systems<M>(
+        &mut self,
+        world: &mut World,
+        systems: impl IntoSystemConfigs<M>,
+    ) -> Result<&mut Self, ReadOnlyViolation> {
+        let systems = systems
+            .configs()
+            .flatten()
+            .into_iter()
+            .map(|config| config.into_read_only(world))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.systems.extend(systems);
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// Runs the systems in the order they were added.
+    pub fn run(&mut self, world: &World) {
+        let cell = unsafe { WorldCell::new(world) };
+        for system in &mut self.systems {
+            if system.should_run(cell)
+                && let Err(failure) = system.run(cell)
+            {
+                world.system_errors().push(failure);
+            }
+        }
+    }
+}
+
+impl Default for ReadOnlySystems {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    /// Builds the system and runs it once, failing if any of its arguments could mutate the world.
+    /// Building registers the system's resources and events, use [`ReadOnlySystems`] to run
+    /// against a shared world.
+    pub fn run_readonly<M>(
+        &mut self,
+        system: impl IntoSystemConfigs<M>,
+    ) -> Result<(), ReadOnlyViolation> {
+        let mut systems = ReadOnlySystems::new();
+        systems.add_systems(self, system)?;
+        systems.run(self);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReadOnlyReason, ReadOnlySystems};
+    use crate::{
+        app::App,
+        system::{Access, SystemAccess, query::Query, schedule::Phase},
+        world::{Commands, Component, Resource, World},
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Update;
+    impl Phase for Update {}
+
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Default)]
+    struct Total(AtomicU32);
+    impl Resource for Total {}
+
+    fn sum(query: Query<&Health>, total: &Total) {
+        let sum = query.iter().map(|health| health.0).sum::<u32>();
+        total.0.fetch_add(sum, Ordering::Relaxed);
+    }
+
+    fn heal(mut query: Query<&mut Health>) {
+        for mut health in query.iter_mut() {
+            health.0 += 1;
+        }
+    }
+
+    fn despawn(_: Commands) {}
+
+    fn setup(world: &mut World) {
+        world.register::<Health>();
+        world.add_resource(Total::default());
+        for i in 0..4 {
+            let entity = world.spawn();
+            world.add_component(entity, Health(i));
+        }
+    }
+
+    #[test]
+    fn run_readonly() {
+        let mut world = World::new();
+        setup(&mut world);
+
+        world.run_readonly(sum).unwrap();
+        assert_eq!(world.resource::<Total>().0.load(Ordering::Relaxed), 6);
+    }
+
+    #[test]
+    fn run_readonly_rejects_mutation() {
+        let mut world = World::new();
+        setup(&mut world);
+
+        let violation = world.run_readonly(heal).unwrap_err();
+        let health = world.components().get_id::<Health>().unwrap();
+        assert_eq!(violation.arg, std::any::type_name::<Query<&mut Health>>());
+        assert_eq!(
+            violation.reason,
+            ReadOnlyReason::Write(SystemAccess::component(health, Access::Write))
+        );
+        assert!(violation.system.unwrap().contains("heal"));
+
+        let violation = world.run_readonly(despawn).unwrap_err();
+        assert_eq!(violation.arg, std::any::type_name::<Commands>());
+        assert_eq!(violation.reason, ReadOnlyReason::Deferred);
+    }
+
+    #[test]
+    fn run_readonly_concurrently() {
+        let mut builder = App::new();
+        builder.add_systems(Update, heal);
+        setup(builder.world_mut());
+
+        let mut systems = (0..4)
+            .map(|_| {
+                let mut systems = ReadOnlySystems::new();
+                systems.add_systems(builder.world_mut(), sum).unwrap();
+                systems
+            })
+            .collect::<Vec<_>>();
+
+        let mut app = builder.build();
+        app.run(Update);
+
+        let world = app.world();
+        std::thread::scope(|scope| {
+            for systems in &mut systems {
+                scope.spawn(|| systems.run(world));
+            }
+        });
+
+        // Every inspector saw the healed values: 1 + 2 + 3 + 4.
+        assert_eq!(world.resource::<Total>().0.load(Ordering::Relaxed), 40);
+    }
+}
@@ -0,0 +1,267 @@
+use super::{SystemName, schedule::Systems};
+use crate::{
+    core::Frame,
+    world::{RuntimeEvents, World},
+};
+use serde_value::Value;
+use std::collections::HashMap;
+
+/// Identity of a system that is stable across process restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SystemKey {
+    pub phase: &'static str,
+    pub name: SystemName,
+    /// Position among the systems of the phase with the same name.
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemRuntimeState {
+    pub key: SystemKey,
+    /// The frame in which the system last ran, the baseline of its change detection.
+    pub frame: Frame,
+    /// Saved state of each argument, see [`SystemArg::save`](super::arg::SystemArg::save).
+    pub args: Vec<Option<Value>>,
+}
+
+/// Schedule internal state captured by [`App::save_runtime_state`](crate::app::App::save_runtime_state),
+/// restored alongside a world snapshot so change detection and event reads continue where they
+/// left off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeState {
+    pub frame: Frame,
+    pub systems: Vec<SystemRuntimeState>,
+    pub events: Vec<RuntimeEvents>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeRestoreReport {
+    /// Saved systems that no longer exist in the schedule.
+    pub skipped: Vec<SystemKey>,
+    /// Systems of the schedule that had no saved state.
+    pub fresh: Vec<SystemKey>,
+    /// Saved event types that aren't registered in the world.
+    pub missing_events: Vec<&'static str>,
+}
+
+impl RuntimeRestoreReport {
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty() && self.fresh.is_empty() && self.missing_events.is_empty()
+    }
+}
+
+fn key(
+    counts: &mut HashMap<(&'static str, SystemName), usize>,
+    phase: &'static str,
+    name: &SystemName,
+) -> SystemKey {
+    let index = counts.entry((phase, name.clone())).or_default();
+    *index += 1;
+    SystemKey {
+        phase,
+        name: name.clone(),
+        index: *index - 1,
+    }
+}
+
+impl Systems {
+    /// Captures the last run frame and the saved argument state of every named system.
+    pub fn save_runtime(&self) -> Vec<SystemRuntimeState> {
+        let mut counts = HashMap::new();
+        self.iter()
+            .filter_map(|(phase, system)| {
+                let name = system.meta().name.as_ref()?;
+                Some(SystemRuntimeState {
+                    key: key(&mut counts, phase, name),
+                    frame: system.meta().frame,
+                    args: system.save_state(),
+                })
+            })
+            .collect()
+    }
+
+    /// Restores state captured by [`Systems::save_runtime`], matching systems by their key.
+    pub fn restore_runtime(
+        &mut self,
+        world: &mut World,
+        saved: &[SystemRuntimeState],
+        report: &mut RuntimeRestoreReport,
+    ) {
+        let mut saved = saved
+            .iter()
+            .map(|state| (&state.key, state))
+            .collect::<HashMap<_, _>>();
+
+        let mut counts = HashMap::new();
+        for (phase, system) in self.iter_mut() {
+            let Some(name) = system.meta().name.clone() else {
+                continue;
+            };
+
+            let key = key(&mut counts, phase, &name);
+            match saved.remove(&key) {
+                Some(state) => system.restore_state(world, state.frame, &state.args),
+                None => report.fresh.push(key),
+            }
+        }
+
+        report.skipped.extend(saved.into_keys().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::App,
+        system::{
+            IntoSystemConfigs,
+            arg::SerializableLocal,
+            query::{Added, Modified, Query},
+            schedule::Phase,
+        },
+        world::{
+            ArchetypeQuery, Component, Entity, Event, EventReader, EventWriter, PodComponent,
+            Resource,
+        },
+    };
+    use serde::{Deserialize, Serialize};
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Position(u32);
+    impl Component for Position {}
+    unsafe impl PodComponent for Position {}
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Moving(#[allow(dead_code)] u8);
+    impl Component for Moving {}
+    unsafe impl PodComponent for Moving {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Ping(u32);
+    impl Event for Ping {}
+
+    /// Per tick: modified entities, added entities and the pings read.
+    #[derive(Default)]
+    struct Log(Vec<(u32, Vec<Entity>, usize, Vec<u32>)>);
+    impl Resource for Log {}
+
+    fn ping(mut tick: SerializableLocal<u32>, mut writer: EventWriter<Ping>) {
+        *tick += 1;
+        writer.send(Ping(*tick));
+    }
+
+    fn mover(mut query: Query<(&mut Position, &Moving)>) {
+        for (mut position, _) in query.iter_mut() {
+            position.0 += 1;
+        }
+    }
+
+    fn watch(
+        mut tick: SerializableLocal<u32>,
+        modified: Query<Entity, Modified<Position>>,
+        added: Query<Entity, Added<Position>>,
+        pings: EventReader<Ping>,
+        log: &mut Log,
+    ) {
+        *tick += 1;
+        let mut modified = modified.iter().collect::<Vec<_>>();
+        modified.sort_by_key(|entity| entity.id());
+        let pings = pings.map(|ping| ping.0).collect();
+        log.0.push((*tick, modified, added.iter().count(), pings));
+    }
+
+    /// Builds the app, spawning the entities only when it isn't restored from a snapshot.
+    fn app(watcher: bool, spawn: bool) -> App {
+        let mut builder = App::new();
+        let world = builder.world_mut();
+        world.register_pod::<Position>();
+        world.register_pod::<Moving>();
+        world.register_serializable_event::<Ping>();
+        world.add_resource(Log::default());
+
+        for i in (0..6).filter(|_| spawn) {
+            let entity = world.spawn();
+            world.add_component(entity, Position(i));
+            if i % 2 == 0 {
+                world.add_component(entity, Moving(0));
+            }
+        }
+
+        match watcher {
+            true => builder.add_systems(Update, ping.before(mover).before(watch)),
+            false => builder.add_systems(Update, ping.before(mover)),
+        };
+
+        builder.build()
+    }
+
+    fn run(app: &mut App, frames: usize) {
+        for _ in 0..frames {
+            app.run(Update);
+        }
+    }
+
+    #[test]
+    fn runtime_state_warm_start() {
+        let mut original = app(true, true);
+        run(&mut original, 5);
+
+        let snapshot = original
+            .world()
+            .snapshot_pod(&ArchetypeQuery::default())
+            .unwrap();
+        let state = original.save_runtime_state();
+
+        // The restarted apps never spawned the entities, the snapshot brings them back.
+        let mut restarted = app(true, false);
+        restarted.world_mut().restore_pod(&snapshot).unwrap();
+        assert_eq!(restarted.world_mut().query::<Entity>().iter().count(), 6);
+        let report = restarted.restore_runtime_state(&state);
+        assert!(report.is_complete(), "{report:?}");
+
+        let mut cold = app(true, false);
+        cold.world_mut().restore_pod(&snapshot).unwrap();
+
+        original.world_mut().resource_mut::<Log>().0.clear();
+        run(&mut original, 5);
+        run(&mut restarted, 5);
+        run(&mut cold, 5);
+
+        let expected = &original.world().resource::<Log>().0;
+        assert_eq!(expected[0].0, 6);
+        assert_eq!(expected[0].1.len(), 3);
+        assert_eq!(expected[0].2, 0);
+        assert_eq!(expected[0].3, vec![5]);
+
+        let restored = &restarted.world().resource::<Log>().0;
+        assert_eq!(restored, expected);
+
+        // Without the runtime state every entity looks added and the local counters restart.
+        let cold = &cold.world().resource::<Log>().0;
+        assert_eq!(cold[0].0, 1);
+        assert_eq!(cold[0].2, 6);
+    }
+
+    #[test]
+    fn runtime_state_reports_missing_systems() {
+        let mut original = app(true, true);
+        run(&mut original, 2);
+        let state = original.save_runtime_state();
+
+        let mut changed = app(false, true);
+        let report = changed.restore_runtime_state(&state);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].name.contains("watch"));
+        assert!(report.fresh.is_empty());
+        assert!(report.missing_events.is_empty());
+
+        let mut original = app(false, true);
+        let report = original.restore_runtime_state(&changed.save_runtime_state());
+        assert!(report.is_complete());
+    }
+}
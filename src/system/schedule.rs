@@ -1,11 +1,13 @@
 use super::{
-    IntoSystemConfigs, SystemCell, SystemConfig,
-    executor::{RunMode, SystemExecutor},
+    IntoSystemConfigs, System, SystemCell, SystemConfig, SystemName, SystemNode,
+    ambiguity::{Ambiguity, AmbiguityDetection, find_ambiguities},
+    executor::{PhaseRunResult, RunMode, SyncPoints, SystemExecutor},
+    graph::DependencyGraphBuilder,
 };
 use crate::{
-    core::{ImmutableIndexDag, IndexDag},
+    core::{CyclicDependency, Frame, ImmutableIndexDag, IndexDag},
     ext::{self},
-    world::{World, WorldCell},
+    world::{Resource, World, WorldCell},
 };
 use std::collections::HashMap;
 
@@ -23,14 +25,14 @@ impl<'a> PhaseContext<'a> {
         self.world
     }
 
-    pub fn execute(&self) {
-        self.executor.execute(self.world);
+    pub fn execute(&self) -> PhaseRunResult {
+        self.executor.execute(self.world)
     }
 }
 
 pub trait Phase: 'static {
-    fn run(&self, ctx: PhaseContext) {
-        ctx.execute();
+    fn run(&self, ctx: PhaseContext) -> PhaseRunResult {
+        ctx.execute()
     }
 
     fn name(&self) -> &'static str {
@@ -61,47 +63,102 @@ impl PhaseConfig {
         self.parent = Some(index)
     }
 
-    pub fn build(self, world: &mut World, mode: RunMode) -> PhaseNode {
-        let mut systems = IndexDag::new();
-        for config in self.configs {
-            systems.add_node(config.into_system_node(world));
+    /// Checks the explicit orders between the systems of the phase for cycles.
+    pub fn validate(&self) -> Result<(), ScheduleBuildError> {
+        let indices = self
+            .configs
+            .iter()
+            .enumerate()
+            .map(|(index, config)| (config.id, index))
+            .collect::<HashMap<_, _>>();
+
+        let mut order = IndexDag::new();
+        for config in &self.configs {
+            order.add_node(config);
         }
 
-        for index in (0..systems.nodes().len()).rev() {
-            for dep_index in (0..systems.nodes().len()).take(index) {
-                if systems.nodes()[index].has_dependency(&systems.nodes()[dep_index]) {
-                    systems.add_dependency(dep_index, index);
-                }
+        for (index, config) in self.configs.iter().enumerate() {
+            for dependency in config.dependencies.iter().filter_map(|id| indices.get(id)) {
+                order.add_dependency(*dependency, index);
             }
         }
 
-        if let Err(error) = systems.build() {
-            let systems = error
+        order.check().map_err(|cycle| {
+            let path = cycle
                 .0
                 .iter()
-                .map(|i| {
-                    systems.nodes()[*i]
-                        .system
-                        .meta
-                        .name
-                        .clone()
-                        .unwrap_or("unknown".into())
+                .map(|index| {
+                    let config = order.nodes()[*index];
+                    config.name.clone().unwrap_or("unknown".into())
                 })
+                .collect();
+
+            ScheduleBuildError::Cycle(DependencyCycle {
+                kind: DependencyKind::System(self.phase.name()),
+                path,
+            })
+        })
+    }
+
+    pub fn build(
+        self,
+        world: &mut World,
+        mode: RunMode,
+        sync: SyncPoints,
+        detection: AmbiguityDetection,
+    ) -> PhaseNode {
+        // Dependencies refer to config ids until the systems are numbered by the world.
+        let mut ids = HashMap::new();
+        let mut nodes = vec![];
+        for config in self.configs {
+            let id = config.id;
+            let node = config.into_system_node(world);
+            ids.insert(id, (node.system.meta.id, nodes.len()));
+            nodes.push(node);
+        }
+
+        let mut graph = DependencyGraphBuilder::new();
+        for mut node in nodes {
+            let dependencies = node
+                .dependencies
+                .iter()
+                .filter_map(|id| ids.get(id).copied())
                 .collect::<Vec<_>>();
+            node.dependencies = dependencies.iter().map(|(id, _)| *id).collect();
+
+            let index = graph.add_node(node);
+            for (_, dependency) in dependencies {
+                graph.add_dependency(dependency, index);
+            }
+        }
+
+        let ambiguities = match detection {
+            AmbiguityDetection::Ignore => vec![],
+            _ => find_ambiguities(self.phase.name(), graph.nodes()),
+        };
 
-            let phase = self.phase.name();
+        let names = graph
+            .nodes()
+            .iter()
+            .map(|node| node.system.meta.name.clone().unwrap_or("unknown".into()))
+            .collect::<Vec<_>>();
 
+        // Schedule::build rejects explicit cycles before building the phases.
+        let systems = graph.build(SystemNode::conflicts).unwrap_or_else(|error| {
+            let systems = error.0.iter().map(|i| &names[*i]).collect::<Vec<_>>();
             panic!(
                 "Cyclic dependency detected in phase {}: {:?}",
-                phase, systems
+                self.phase.name(),
+                systems
             );
-        }
+        });
 
-        let executor = mode.create_executor(systems.map(SystemCell::from));
+        let executor = mode.create_executor(systems.map(SystemCell::from), sync);
 
         PhaseNode {
             phase: self.phase,
             executor,
+            ambiguities,
         }
     }
 }
@@ -109,17 +166,28 @@ impl PhaseConfig {
 pub struct PhaseNode {
     phase: Box<dyn Phase>,
     executor: Box<dyn SystemExecutor>,
+    ambiguities: Vec<Ambiguity>,
 }
 
 impl PhaseNode {
-    pub fn run(&self, world: WorldCell) {
+    /// Number of systems in the phase, without its sub phases.
+    pub(crate) fn len(&self) -> usize {
+        self.executor.systems().len()
+    }
+
+    pub fn run(&self, world: WorldCell) -> PhaseRunResult {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!("phase", name = self.phase.name()).entered();
+
         let ctx = PhaseContext::new(world, self.executor.as_ref());
-        self.phase.run(ctx);
+        self.phase.run(ctx)
     }
 }
 
 pub struct Schedule {
     mode: RunMode,
+    sync: SyncPoints,
+    ambiguity: AmbiguityDetection,
     phases: IndexDag<PhaseConfig>,
     hierarchy: IndexDag<usize>,
     map: HashMap<&'static str, usize>,
@@ -129,6 +197,8 @@ impl Schedule {
     pub fn new(mode: RunMode) -> Self {
         Self {
             mode,
+            sync: SyncPoints::default(),
+            ambiguity: AmbiguityDetection::default(),
             phases: IndexDag::new(),
             hierarchy: IndexDag::new(),
             map: HashMap::new(),
@@ -143,6 +213,23 @@ impl Schedule {
         self.mode = mode;
     }
 
+    pub fn sync_points(&self) -> SyncPoints {
+        self.sync
+    }
+
+    pub fn set_sync_points(&mut self, sync: SyncPoints) {
+        self.sync = sync;
+    }
+
+    pub fn ambiguity_detection(&self) -> AmbiguityDetection {
+        self.ambiguity
+    }
+
+    /// Reports conflicting systems that aren't explicitly ordered when the schedule is built.
+    pub fn set_ambiguity_detection(&mut self, detection: AmbiguityDetection) {
+        self.ambiguity = detection;
+    }
+
     pub fn add_phase(&mut self, phase: impl Phase) -> usize {
         match self.map.get(phase.name()).copied() {
             Some(index) => index,
@@ -190,29 +277,47 @@ impl Schedule {
         self.phases.nodes_mut()[index].add_systems(systems);
     }
 
-    pub fn build(self, world: &mut World) -> Result<Systems, ScheduleBuildError> {
-        let mode = self.mode;
-        let mut hierarchy = self.hierarchy;
-        let mut phases = self.phases;
-
-        if let Err(error) = hierarchy.build() {
-            let names = error
+    /// Checks the phase hierarchy, the phase order and the explicit system orders for cycles
+    /// without building the systems, ambiguities need a world and are only found by
+    /// [`Schedule::build`].
+    pub fn validate(&self) -> Result<(), ScheduleBuildError> {
+        let phase_names = |cycle: CyclicDependency| {
+            cycle
                 .0
                 .iter()
-                .map(|index| phases.nodes()[*index].phase.name())
-                .collect();
-            return Err(ScheduleBuildError::CyclicHierarchy(names));
+                .map(|index| SystemName::from(self.phases.nodes()[*index].phase.name()))
+                .collect()
+        };
+
+        if let Err(cycle) = self.hierarchy.check() {
+            return Err(ScheduleBuildError::Cycle(DependencyCycle {
+                kind: DependencyKind::Hierarchy,
+                path: phase_names(cycle),
+            }));
         }
 
-        if let Err(error) = phases.build() {
-            let names = error
-                .0
-                .iter()
-                .map(|index| phases.nodes()[*index].phase.name())
-                .collect();
-            return Err(ScheduleBuildError::CyclicDependency(names));
+        if let Err(cycle) = self.phases.check() {
+            return Err(ScheduleBuildError::Cycle(DependencyCycle {
+                kind: DependencyKind::Phase,
+                path: phase_names(cycle),
+            }));
         }
 
+        self.phases
+            .nodes()
+            .iter()
+            .try_for_each(PhaseConfig::validate)
+    }
+
+    pub fn build(self, world: &mut World) -> Result<Systems, ScheduleBuildError> {
+        self.validate()?;
+
+        let (mode, sync, detection) = (self.mode, self.sync, self.ambiguity);
+        let mut phases = self.phases;
+        phases
+            .build()
+            .expect("Validated schedules have no phase cycles");
+
         let mut hierarchy = HashMap::new();
         for index in phases.topology() {
             if let Some(parent) = phases.nodes()[*index].parent {
@@ -220,31 +325,87 @@ impl Schedule {
             }
         }
 
-        let phases = phases.map(|config| config.build(world, mode));
+        let orders = self
+            .map
+            .values()
+            .map(|index| (*index, Systems::run_order(&hierarchy, *index)))
+            .collect();
+
+        let phases = phases.map(|config| config.build(world, mode, sync, detection));
+
+        let ambiguities = phases
+            .nodes()
+            .iter()
+            .flat_map(|phase| phase.ambiguities.iter().cloned())
+            .collect::<Vec<_>>();
+
+        match detection {
+            AmbiguityDetection::Error if !ambiguities.is_empty() => {
+                return Err(ScheduleBuildError::Ambiguous(ambiguities));
+            }
+            AmbiguityDetection::Warn => ambiguities
+                .iter()
+                .for_each(|ambiguity| eprintln!("{}", ambiguity)),
+            _ => {}
+        }
 
         Ok(Systems {
             mode,
             phases: phases.into_immutable(),
             hierarchy,
+            orders,
             map: self.map,
         })
     }
 }
 
+/// Ordering that formed a [`DependencyCycle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// Sub phases, see [`Schedule::add_sub_phase`].
+    Hierarchy,
+    /// Phase order, see [`Schedule::run_before`].
+    Phase,
+    /// Explicit system order within the phase, see [`IntoSystemConfigs::before`].
+    System(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyCycle {
+    pub kind: DependencyKind,
+    /// Names of the phases or systems in the cycle, each one ordered before the next. The
+    /// first one is repeated at the end to close the cycle.
+    pub path: Vec<SystemName>,
+}
+
+impl std::fmt::Display for DependencyCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            DependencyKind::Hierarchy => write!(f, "Cyclic phase hierarchy: ")?,
+            DependencyKind::Phase => write!(f, "Cyclic phase order: ")?,
+            DependencyKind::System(phase) => write!(f, "Cyclic system order in {}: ", phase)?,
+        }
+
+        write!(f, "{}", self.path.join(" -> "))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ScheduleBuildError {
-    CyclicDependency(Vec<&'static str>),
-    CyclicHierarchy(Vec<&'static str>),
+    Cycle(DependencyCycle),
+    /// See [`AmbiguityDetection::Error`].
+    Ambiguous(Vec<Ambiguity>),
 }
 
 impl std::fmt::Display for ScheduleBuildError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ScheduleBuildError::CyclicDependency(names) => {
-                write!(f, "Cyclic dependency detected: {:?}", names)
-            }
-            ScheduleBuildError::CyclicHierarchy(names) => {
-                write!(f, "Cyclic hierarchy detected: {:?}", names)
+            ScheduleBuildError::Cycle(cycle) => write!(f, "{}", cycle),
+            ScheduleBuildError::Ambiguous(ambiguities) => {
+                write!(f, "Ambiguous systems detected:")?;
+                ambiguities
+                    .iter()
+                    .try_for_each(|ambiguity| write!(f, "\n{}", ambiguity))
             }
         }
     }
@@ -254,6 +415,8 @@ pub struct Systems {
     mode: RunMode,
     phases: ImmutableIndexDag<PhaseNode>,
     hierarchy: HashMap<usize, Vec<usize>>,
+    /// Precomputed order of each phase and its sub phases, so running doesn't allocate.
+    orders: HashMap<usize, Box<[usize]>>,
     map: HashMap<&'static str, usize>,
 }
 
@@ -262,31 +425,228 @@ impl Systems {
         self.mode
     }
 
-    pub fn run(&self, world: &mut World, phase: impl Phase) {
-        if let Some(index) = self.map.get(phase.name()).copied() {
-            let world = unsafe { WorldCell::new_mut(world) };
+    /// Runs the phase and its sub phases. If execution is cancelled through the world's
+    /// [`CancelHandle`](super::executor::CancelHandle), the remaining phases are skipped. The
+    /// handle is reset when the run starts and ends, so only cancels made while it runs apply.
+    pub fn run(&self, world: &mut World, phase: impl Phase) -> PhaseRunResult {
+        world.cancel_handle().reset();
+        let result = self.run_phase(world, phase.name());
+        world.cancel_handle().reset();
+        result
+    }
+
+    fn run_phase(&self, world: &mut World, name: &str) -> PhaseRunResult {
+        let Some(index) = self.map.get(name) else {
+            return PhaseRunResult::Completed;
+        };
+
+        world.begin_phase();
+        let cell = unsafe { WorldCell::new_mut(world) };
+        let order = &self.orders[index];
+        for (position, index) in order.iter().enumerate() {
+            let result = self.phases.nodes()[*index].run(cell);
+            if let PhaseRunResult::Cancelled { completed, skipped } = result {
+                world.end_phase();
+
+                // The counts cover the whole phase, sub phases included.
+                let len = |index: &usize| self.phases.nodes()[*index].len();
+                return PhaseRunResult::Cancelled {
+                    completed: completed + order[..position].iter().map(len).sum::<usize>(),
+                    skipped: skipped + order[position + 1..].iter().map(len).sum::<usize>(),
+                };
+            }
+        }
+
+        world.end_phase();
+        PhaseRunResult::Completed
+    }
+
+    /// Ambiguities found when the schedule was built, see [`Schedule::set_ambiguity_detection`].
+    pub fn ambiguities(&self) -> impl Iterator<Item = &Ambiguity> {
+        self.phases
+            .nodes()
+            .iter()
+            .flat_map(|node| node.ambiguities.iter())
+    }
+
+    /// Every system with the name of the phase that runs it.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &System)> {
+        self.phases.nodes().iter().flat_map(|node| {
+            let phase = node.phase.name();
+            node.executor
+                .systems()
+                .iter()
+                .map(move |cell| (phase, cell.get()))
+        })
+    }
 
-            let mut stack = vec![index];
-            while let Some(index) = stack.pop() {
-                self.phases.nodes()[index].run(world);
-                if let Some(children) = self.hierarchy.get(&index) {
-                    for child in children.iter().rev() {
-                        stack.insert(0, *child);
-                    }
+    /// Clamps the last run frame of every system, see [`System::check_change_frames`].
+    pub fn check_change_frames(&mut self, current: Frame) {
+        for (_, system) in self.iter_mut() {
+            system.check_change_frames(current);
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&'static str, &mut System)> {
+        self.phases.nodes_mut().iter_mut().flat_map(|node| {
+            let phase = node.phase.name();
+            node.executor
+                .systems_mut()
+                .iter_mut()
+                .map(move |cell| (phase, cell.get_mut()))
+        })
+    }
+
+    fn run_order(hierarchy: &HashMap<usize, Vec<usize>>, index: usize) -> Box<[usize]> {
+        let mut order = vec![];
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            if let Some(children) = hierarchy.get(&index) {
+                for child in children.iter().rev() {
+                    stack.insert(0, *child);
                 }
             }
         }
+
+        order.into_boxed_slice()
+    }
+}
+
+/// Schedules that can be run on demand with [`World::run_schedule`], like a fixed timestep
+/// driver running its steps from a system. Each schedule is keyed by the phase that runs it and
+/// is taken out of the resource while it runs. The schedules aren't send, so this is a non send
+/// resource.
+#[derive(Default)]
+pub struct Schedules {
+    schedules: HashMap<&'static str, Option<Systems>>,
+}
+
+impl Resource for Schedules {}
+
+impl Schedules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the schedule run by `label`, replacing and returning an existing one.
+    pub fn insert(&mut self, label: impl Phase, systems: Systems) -> Option<Systems> {
+        self.insert_named(label.name(), systems)
+    }
+
+    pub(crate) fn insert_named(&mut self, name: &'static str, systems: Systems) -> Option<Systems> {
+        self.schedules.insert(name, Some(systems)).flatten()
+    }
+
+    /// Removes the schedule, `None` if it doesn't exist or is running.
+    pub fn remove(&mut self, label: impl Phase) -> Option<Systems> {
+        self.schedules.remove(label.name()).flatten()
+    }
+
+    /// The schedule, `None` if it doesn't exist or is running.
+    pub fn get(&self, label: impl Phase) -> Option<&Systems> {
+        self.schedules.get(label.name())?.as_ref()
+    }
+
+    pub fn contains(&self, label: impl Phase) -> bool {
+        self.schedules.contains_key(label.name())
+    }
+
+    pub fn len(&self) -> usize {
+        self.schedules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+
+    /// Clamps the last run frame of the systems of every schedule that isn't running, see
+    /// [`System::check_change_frames`].
+    pub fn check_change_frames(&mut self, current: Frame) {
+        for systems in self.schedules.values_mut().flatten() {
+            systems.check_change_frames(current);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunScheduleError {
+    NotFound(&'static str),
+    /// The schedule is already running, schedules can't run themselves.
+    Running(&'static str),
+}
+
+impl std::fmt::Display for RunScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunScheduleError::NotFound(name) => write!(f, "Schedule {} not found", name),
+            RunScheduleError::Running(name) => write!(f, "Schedule {} is already running", name),
+        }
+    }
+}
+
+impl std::error::Error for RunScheduleError {}
+
+impl World {
+    /// Builds the schedule and adds it to the [`Schedules`] resource, to be run by `label` with
+    /// [`World::run_schedule`].
+    pub fn add_schedule(
+        &mut self,
+        label: impl Phase,
+        mut schedule: Schedule,
+    ) -> Result<(), ScheduleBuildError> {
+        let name = label.name();
+        schedule.add_phase(label);
+        let systems = schedule.build(self)?;
+        self.insert_schedule(name, systems);
+        Ok(())
+    }
+
+    pub(crate) fn insert_schedule(&mut self, name: &'static str, systems: Systems) {
+        if self.try_non_send_resource::<Schedules>().is_none() {
+            self.add_non_send_resource(Schedules::new());
+        }
+
+        self.non_send_resource_mut::<Schedules>()
+            .insert_named(name, systems);
+    }
+
+    /// Runs the schedule of the [`Schedules`] resource added for `label` and its sub phases. Can
+    /// be called from exclusive systems, the schedule runs nested in the current phase.
+    pub fn run_schedule(&mut self, label: impl Phase) -> Result<PhaseRunResult, RunScheduleError> {
+        let name = label.name();
+        let slot = self
+            .try_non_send_resource_mut::<Schedules>()
+            .and_then(|schedules| schedules.schedules.get_mut(name))
+            .ok_or(RunScheduleError::NotFound(name))?;
+        let systems = slot.take().ok_or(RunScheduleError::Running(name))?;
+
+        let result = self.with_nested_borrows(|world| systems.run_phase(world, name));
+
+        // The schedule may have been replaced or removed while it ran.
+        if let Some(slot) = self
+            .try_non_send_resource_mut::<Schedules>()
+            .and_then(|schedules| schedules.schedules.get_mut(name))
+            .filter(|slot| slot.is_none())
+        {
+            *slot = Some(systems);
+        }
+
+        Ok(result)
     }
 }
 
 #[allow(unused_imports)]
 mod tests {
     use crate::{
+        app::App,
         system::{
+            IntoSystemConfigs, SystemConfig, SystemConfigs,
+            ambiguity::{AmbiguityDetection, ContestedAccess},
             executor::RunMode,
-            schedule::{Schedule, ScheduleBuildError},
+            schedule::{DependencyKind, RunScheduleError, Schedule, ScheduleBuildError, Schedules},
         },
-        world::World,
+        world::{Resource, World},
     };
 
     #[derive(Clone, Copy, PartialEq, Eq)]
@@ -355,17 +715,19 @@ mod tests {
         schedule.run_before(phase3, phase1); // Phase3 runs before Phase1
         schedule.run_before(phase1, phase2); // Phase1 runs before Phase2 (creates a cycle)
 
+        assert!(schedule.validate().is_err());
         let mut world = World::new();
         let result = schedule.build(&mut world);
 
-        assert!(result.is_err());
-        if let Err(ScheduleBuildError::CyclicDependency(names)) = result {
-            assert!(names.contains(&"Phase1"));
-            assert!(names.contains(&"Phase2"));
-            assert!(names.contains(&"Phase3"));
-        } else {
+        let Err(ScheduleBuildError::Cycle(cycle)) = result else {
             panic!("Expected a cyclic dependency error");
-        }
+        };
+        assert_eq!(cycle.kind, DependencyKind::Phase);
+        assert_eq!(cycle.path, ["Phase2", "Phase3", "Phase1", "Phase2"]);
+        assert_eq!(
+            cycle.to_string(),
+            "Cyclic phase order: Phase2 -> Phase3 -> Phase1 -> Phase2"
+        );
     }
 
     #[test]
@@ -380,12 +742,199 @@ mod tests {
         let mut world = World::new();
         let result = schedule.build(&mut world);
 
-        assert!(result.is_err());
-        if let Err(ScheduleBuildError::CyclicHierarchy(names)) = result {
-            assert!(names.contains(&"MainPhase"));
-            assert!(names.contains(&"SubPhase"));
-        } else {
+        let Err(ScheduleBuildError::Cycle(cycle)) = result else {
             panic!("Expected a cyclic hierarchy error");
+        };
+        assert_eq!(cycle.kind, DependencyKind::Hierarchy);
+        assert_eq!(cycle.path, ["MainPhase", "SubPhase", "MainPhase"]);
+    }
+
+    #[test]
+    fn test_cyclic_system_order_error() {
+        let mut schedule = Schedule::new(RunMode::Sequential);
+        let phase = TestPhase("Phase");
+
+        let mut configs = increment.before(double).flatten();
+        let last = configs[1].id;
+        configs[0].dependencies.insert(last);
+        schedule.add_systems(phase, SystemConfigs::Configs(configs));
+        schedule.add_systems(phase, read);
+
+        let Err(ScheduleBuildError::Cycle(cycle)) = schedule.validate() else {
+            panic!("Expected a cyclic system order error");
+        };
+        assert_eq!(cycle.kind, DependencyKind::System("Phase"));
+        let names = cycle
+            .path
+            .iter()
+            .map(|name| name.rsplit("::").next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["increment", "double", "increment"]);
+
+        let mut world = World::new();
+        assert!(matches!(
+            schedule.build(&mut world),
+            Err(ScheduleBuildError::Cycle(_))
+        ));
+    }
+
+    struct Score(u32);
+    impl Resource for Score {}
+
+    fn increment(score: &mut Score) {
+        score.0 += 1;
+    }
+
+    fn double(score: &mut Score) {
+        score.0 *= 2;
+    }
+
+    fn read(score: &Score) {
+        assert!(score.0 < 100);
+    }
+
+    fn read_ambiguous(score: &Score) {
+        assert!(score.0 < 100);
+    }
+
+    #[test]
+    fn test_ambiguity_detection() {
+        let phase = TestPhase("Update");
+        let mut schedule = Schedule::new(RunMode::Sequential);
+        schedule.set_ambiguity_detection(AmbiguityDetection::Warn);
+        schedule.add_systems(phase, increment.before(double));
+        schedule.add_systems(phase, read);
+        schedule.add_systems(
+            phase,
+            read_ambiguous
+                .ambiguous_with(increment)
+                .ambiguous_with(double),
+        );
+
+        let mut world = World::new();
+        world.add_resource(Score(0));
+        let systems = schedule.build(&mut world).unwrap();
+        let id = world.resources().get_id::<Score>().unwrap();
+
+        let pairs = systems
+            .ambiguities()
+            .map(|ambiguity| {
+                assert_eq!(ambiguity.phase, "Update");
+                assert_eq!(ambiguity.access, vec![ContestedAccess::Resource(id)]);
+                ambiguity.systems.clone().map(|name| {
+                    let name = name.unwrap();
+                    name.rsplit("::").next().unwrap().to_string()
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, vec![["increment", "read"], ["double", "read"]]);
+    }
+
+    #[test]
+    fn test_system_tuples() {
+        let ids =
+            |configs: &[SystemConfig]| configs.iter().map(|config| config.id).collect::<Vec<_>>();
+
+        let configs = (increment, double, read).configs().flatten();
+        assert_eq!(configs.len(), 3);
+        assert!(configs.iter().all(|config| config.dependencies.is_empty()));
+
+        let configs = (increment, double, read).chain().flatten();
+        let [a, b, _] = ids(&configs)[..] else {
+            panic!("Expected three systems");
+        };
+        assert!(configs[0].dependencies.is_empty());
+        assert_eq!(configs[1].dependencies, [a].into());
+        assert_eq!(configs[2].dependencies, [b].into());
+
+        // Elements keep their own order and run after the whole element before them.
+        let configs = ((increment, double.before(read)), read_ambiguous)
+            .chain()
+            .flatten();
+        let [a, b, c, _] = ids(&configs)[..] else {
+            panic!("Expected four systems");
+        };
+        assert!(configs[0].dependencies.is_empty() && configs[1].dependencies.is_empty());
+        assert_eq!(configs[2].dependencies, [b].into());
+        assert_eq!(configs[3].dependencies, [a, b, c].into());
+
+        let phase = TestPhase("Update");
+        let mut schedule = Schedule::new(RunMode::Sequential);
+        schedule.add_systems(phase, (double, increment, read).chain());
+        let mut world = World::new();
+        world.add_resource(Score(1));
+        let systems = schedule.build(&mut world).unwrap();
+        systems.run(&mut world, phase);
+        assert_eq!(world.resource::<Score>().0, 3);
+    }
+
+    #[test]
+    fn test_ambiguity_error() {
+        let phase = TestPhase("Update");
+        let mut schedule = Schedule::new(RunMode::Sequential);
+        schedule.set_ambiguity_detection(AmbiguityDetection::Error);
+        schedule.add_systems(phase, read);
+        schedule.add_systems(phase, increment);
+
+        let mut world = World::new();
+        world.add_resource(Score(0));
+        match schedule.build(&mut world) {
+            Err(ScheduleBuildError::Ambiguous(ambiguities)) => assert_eq!(ambiguities.len(), 1),
+            _ => panic!("Expected an ambiguity error"),
+        }
+    }
+
+    struct Steps(u32);
+    impl Resource for Steps {}
+
+    fn step(steps: &mut Steps) {
+        steps.0 += 1;
+    }
+
+    fn drive(world: &mut World) {
+        for _ in 0..3 {
+            world.run_schedule(TestPhase("Step")).unwrap();
         }
+
+        let result = world.run_schedule(TestPhase("Missing"));
+        assert_eq!(result, Err(RunScheduleError::NotFound("Missing")));
+    }
+
+    fn recurse(world: &mut World) {
+        let result = world.run_schedule(TestPhase("Step"));
+        assert_eq!(result, Err(RunScheduleError::Running("Step")));
+
+        // Removals are deferred until the outermost phase ends.
+        if world.try_resource::<Score>().is_some() {
+            assert!(world.remove_resource::<Score>().is_err());
+            world.remove_resource_deferred::<Score>();
+            assert!(world.try_resource::<Score>().is_some());
+        }
+    }
+
+    #[test]
+    fn test_run_schedule() {
+        let mut step_schedule = Schedule::new(RunMode::Sequential);
+        step_schedule.add_systems(TestPhase("Step"), step.before(recurse));
+
+        let mut app = App::new()
+            .add_resource(Steps(0))
+            .add_resource(Score(0))
+            .add_schedule(TestPhase("Step"), step_schedule)
+            .add_systems(TestPhase("Update"), drive)
+            .build();
+
+        app.run(TestPhase("Update"));
+        assert_eq!(app.world().resource::<Steps>().0, 3);
+        assert!(app.world().try_resource::<Score>().is_none());
+        assert!(
+            app.world()
+                .non_send_resource::<Schedules>()
+                .get(TestPhase("Step"))
+                .is_some()
+        );
+
+        app.world_mut().run_schedule(TestPhase("Step")).unwrap();
+        assert_eq!(app.world().resource::<Steps>().0, 4);
     }
 }
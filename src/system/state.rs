@@ -0,0 +1,188 @@
+use super::{
+    condition::{Condition, IntoCondition},
+    schedule::{Phase, Systems},
+};
+use crate::{ext, world::Resource, world::World};
+use std::fmt::Debug;
+
+/// A finite set of app states, like the main menu, loading and gameplay.
+/// Register it with [`AppBuilder::add_state`](crate::app::AppBuilder::add_state).
+pub trait States: Debug + Clone + PartialEq + Eq + Send + Sync + 'static {}
+
+/// The current value of the state `S`.
+pub struct State<S: States>(S);
+
+impl<S: States> State<S> {
+    pub fn new(state: S) -> Self {
+        Self(state)
+    }
+
+    pub fn get(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: States> Resource for State<S> {}
+
+/// The state `S` switches to at the end of the current [`App::run`](crate::app::App::run).
+pub struct NextState<S: States>(Option<S>);
+
+impl<S: States> NextState<S> {
+    pub fn set(&mut self, state: S) {
+        self.0 = Some(state);
+    }
+
+    pub fn pending(&self) -> Option<&S> {
+        self.0.as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl<S: States> Default for NextState<S> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<S: States> Resource for NextState<S> {}
+
+/// Phase that runs when the state is entered.
+pub struct OnEnter<S: States>(pub S);
+
+impl<S: States> Phase for OnEnter<S> {
+    fn name(&self) -> &'static str {
+        ext::intern_name(format!(
+            "OnEnter<{}>({:?})",
+            std::any::type_name::<S>(),
+            self.0
+        ))
+    }
+}
+
+/// Phase that runs when the state is exited.
+pub struct OnExit<S: States>(pub S);
+
+impl<S: States> Phase for OnExit<S> {
+    fn name(&self) -> &'static str {
+        ext::intern_name(format!(
+            "OnExit<{}>({:?})",
+            std::any::type_name::<S>(),
+            self.0
+        ))
+    }
+}
+
+/// Run condition that passes while the state `S` is `state`.
+pub fn in_state<S: States>(state: S) -> Condition {
+    (move |current: &State<S>| current.0 == state).into_condition()
+}
+
+/// Applies a state's pending transition, or enters its initial value when `initial` is set.
+pub(crate) type StateTransition = fn(&mut World, &Systems, bool);
+
+pub(crate) fn apply_state_transition<S: States>(
+    world: &mut World,
+    systems: &Systems,
+    initial: bool,
+) {
+    if initial {
+        let current = world.resource::<State<S>>().0.clone();
+        systems.run(world, OnEnter(current));
+        return;
+    }
+
+    let Some(next) = world.resource_mut::<NextState<S>>().0.take() else {
+        return;
+    };
+
+    let current = world.resource::<State<S>>().0.clone();
+    if next == current {
+        return;
+    }
+
+    systems.run(world, OnExit(current));
+    world.resource_mut::<State<S>>().0 = next.clone();
+    systems.run(world, OnEnter(next));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NextState, OnEnter, OnExit, State, States, in_state};
+    use crate::{
+        app::App,
+        system::{IntoSystemConfigs, schedule::Phase},
+        world::Resource,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum GameState {
+        Menu,
+        Playing,
+    }
+    impl States for GameState {}
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+    impl Resource for Log {}
+
+    #[test]
+    fn state_transitions() {
+        let mut app = App::new()
+            .add_resource(Log::default())
+            .add_state(GameState::Menu)
+            .add_systems(OnEnter(GameState::Menu), |log: &mut Log| {
+                log.0.push("enter menu")
+            })
+            .add_systems(OnExit(GameState::Menu), |log: &mut Log| {
+                log.0.push("exit menu")
+            })
+            .add_systems(OnEnter(GameState::Playing), |log: &mut Log| {
+                log.0.push("enter playing")
+            })
+            .add_systems(
+                Update,
+                (|log: &mut Log| log.0.push("menu")).run_if(in_state(GameState::Menu)),
+            )
+            .add_systems(
+                Update,
+                (|log: &mut Log| log.0.push("playing")).run_if(in_state(GameState::Playing)),
+            )
+            .build();
+
+        app.run(Update);
+        assert_eq!(app.world().resource::<Log>().0, ["enter menu", "menu"]);
+
+        app.world_mut().resource_mut::<Log>().0.clear();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.run(Update);
+        assert_eq!(
+            app.world().resource::<Log>().0,
+            ["menu", "exit menu", "enter playing"]
+        );
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Playing
+        );
+
+        app.world_mut().resource_mut::<Log>().0.clear();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.run(Update);
+        assert_eq!(app.world().resource::<Log>().0, ["playing"]);
+        assert!(
+            app.world()
+                .resource::<NextState<GameState>>()
+                .pending()
+                .is_none()
+        );
+    }
+}
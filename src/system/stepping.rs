@@ -0,0 +1,190 @@
+use super::{SystemId, SystemMeta};
+use crate::world::Resource;
+use std::{borrow::Cow, collections::HashSet, sync::Mutex};
+
+/// Debugging control over which systems run, consulted by the executors before each system is
+/// dispatched. Systems are skipped by id or by name, matching either the full name or its last
+/// segment. While paused, only systems granted by [`Stepping::step`] run.
+#[derive(Default)]
+pub struct Stepping {
+    skipped: HashSet<SystemId>,
+    skipped_names: HashSet<Cow<'static, str>>,
+    paused: bool,
+    state: Mutex<StepState>,
+}
+
+#[derive(Default)]
+struct StepState {
+    /// Systems that may still run before a new step is needed.
+    steps: usize,
+    /// Systems that ran in the current stepping cycle.
+    stepped: HashSet<SystemId>,
+    /// Systems seen while paused.
+    seen: HashSet<SystemId>,
+}
+
+impl Resource for Stepping {}
+
+impl Stepping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops running systems until the next [`Stepping::step`] or [`Stepping::resume`].
+    pub fn pause(&mut self) -> &mut Self {
+        self.paused = true;
+        self
+    }
+
+    pub fn resume(&mut self) -> &mut Self {
+        self.paused = false;
+        *self.state.get_mut().unwrap() = StepState::default();
+        self
+    }
+
+    /// Runs the next system that hasn't run since every system was stepped through.
+    pub fn step(&mut self) -> &mut Self {
+        self.state.get_mut().unwrap().steps += 1;
+        self
+    }
+
+    pub fn skip(&mut self, system: SystemId) -> &mut Self {
+        self.skipped.insert(system);
+        self
+    }
+
+    pub fn skip_name(&mut self, name: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.skipped_names.insert(name.into());
+        self
+    }
+
+    pub fn unskip(&mut self, system: SystemId) -> &mut Self {
+        self.skipped.remove(&system);
+        self
+    }
+
+    pub fn unskip_name(&mut self, name: &str) -> &mut Self {
+        self.skipped_names.remove(name);
+        self
+    }
+
+    pub fn is_skipped(&self, meta: &SystemMeta) -> bool {
+        if self.skipped.contains(&meta.id) {
+            return true;
+        }
+
+        let name = meta.name.as_deref().unwrap_or_default();
+        let short = name.rsplit("::").next().unwrap_or(name);
+        self.skipped_names.contains(name) || self.skipped_names.contains(short)
+    }
+
+    /// Whether the system may run now, consuming a step while paused.
+    pub fn should_run(&self, meta: &SystemMeta) -> bool {
+        if self.is_skipped(meta) {
+            return false;
+        } else if !self.paused {
+            return true;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.seen.insert(meta.id);
+        if state.steps == 0 {
+            return false;
+        }
+
+        // Every system was stepped through, start over from this one.
+        if state.stepped.contains(&meta.id) && state.seen.is_subset(&state.stepped) {
+            state.stepped.clear();
+        }
+
+        if state.stepped.insert(meta.id) {
+            state.steps -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stepping;
+    use crate::{
+        app::App,
+        system::{IntoSystemConfigs, executor::RunMode, schedule::Phase},
+        world::Resource,
+    };
+    use std::sync::Mutex;
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Default)]
+    struct Log(Mutex<Vec<&'static str>>);
+    impl Resource for Log {}
+
+    fn first(log: &Log) {
+        log.0.lock().unwrap().push("first");
+    }
+
+    fn second(log: &Log) {
+        log.0.lock().unwrap().push("second");
+    }
+
+    fn third(log: &Log) {
+        log.0.lock().unwrap().push("third");
+    }
+
+    fn stepping(mode: RunMode) {
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(mode);
+        let mut app = builder
+            .add_resource(Log::default())
+            .add_resource(Stepping::new())
+            .add_systems(Update, first.before(second).before(third))
+            .build();
+
+        let run = |app: &mut App| {
+            app.run(Update);
+            std::mem::take(&mut *app.world().resource::<Log>().0.lock().unwrap())
+        };
+
+        app.world_mut()
+            .resource_mut::<Stepping>()
+            .skip_name("second");
+        assert_eq!(run(&mut app), vec!["first", "third"]);
+
+        app.world_mut().resource_mut::<Stepping>().pause();
+        assert!(run(&mut app).is_empty());
+
+        app.world_mut().resource_mut::<Stepping>().step();
+        assert_eq!(run(&mut app), vec!["first"]);
+
+        app.world_mut().resource_mut::<Stepping>().step();
+        assert_eq!(run(&mut app), vec!["third"]);
+
+        // Every system was stepped through, the cycle starts over.
+        app.world_mut().resource_mut::<Stepping>().step().step();
+        assert_eq!(run(&mut app), vec!["first", "third"]);
+
+        app.world_mut()
+            .resource_mut::<Stepping>()
+            .unskip_name("second")
+            .resume();
+        assert_eq!(run(&mut app), vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn sequential_stepping() {
+        stepping(RunMode::Sequential);
+    }
+
+    #[test]
+    fn parallel_stepping() {
+        stepping(RunMode::Parallel);
+    }
+}
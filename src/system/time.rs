@@ -0,0 +1,169 @@
+use super::schedule::Phase;
+use crate::world::Resource;
+use std::time::Duration;
+
+/// Wall clock time of the app, advanced once per frame by [`App::tick`](crate::app::App::tick).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl Time {
+    /// Time between the last two frames.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Time since the app started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub(crate) fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+    }
+}
+
+impl Resource for Time {}
+
+/// Accumulator driving [`FixedUpdate`]. Every frame adds its delta, and the phase runs once per
+/// whole `timestep` accumulated, so fixed systems see the same step regardless of frame rate.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedTime {
+    timestep: Duration,
+    accumulator: Duration,
+    max_steps: u32,
+    elapsed: Duration,
+}
+
+impl FixedTime {
+    pub const DEFAULT_TIMESTEP: Duration = Duration::from_micros(15625);
+
+    pub fn new(timestep: Duration) -> Self {
+        assert!(
+            !timestep.is_zero(),
+            "Fixed timestep must be greater than zero"
+        );
+
+        Self {
+            timestep,
+            accumulator: Duration::ZERO,
+            max_steps: 8,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    pub fn timestep_secs(&self) -> f32 {
+        self.timestep.as_secs_f32()
+    }
+
+    /// Fixed time simulated so far, a multiple of the timestep.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Time accumulated towards the next step.
+    pub fn accumulator(&self) -> Duration {
+        self.accumulator
+    }
+
+    /// How far the accumulator is into the next step, from 0 to 1. Useful to interpolate
+    /// rendering between the last two fixed steps.
+    pub fn overstep(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.timestep.as_secs_f32()
+    }
+
+    /// Caps the steps run in a single frame. Time beyond the cap is dropped so a slow frame
+    /// doesn't snowball into ever longer ones.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Adds the frame delta and returns how many steps to run.
+    pub(crate) fn accumulate(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+
+        let mut steps = 0;
+        while self.accumulator >= self.timestep {
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+
+        steps.min(self.max_steps)
+    }
+
+    /// Advances the fixed clock before each step runs.
+    pub(crate) fn step(&mut self) {
+        self.elapsed += self.timestep;
+    }
+}
+
+impl Default for FixedTime {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TIMESTEP)
+    }
+}
+
+impl Resource for FixedTime {}
+
+/// Phase run zero or more times per [`App::tick`](crate::app::App::tick), once per fixed step.
+pub struct FixedUpdate;
+
+impl Phase for FixedUpdate {}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedTime, FixedUpdate, Time};
+    use crate::{app::App, world::Resource};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Steps(Vec<Duration>);
+    impl Resource for Steps {}
+
+    #[test]
+    fn fixed_timestep() {
+        let step = Duration::from_millis(10);
+        let mut app = App::new()
+            .add_resource(Steps::default())
+            .set_fixed_timestep(FixedTime::new(step).with_max_steps(3))
+            .add_systems(FixedUpdate, |time: &FixedTime, steps: &mut Steps| {
+                steps.0.push(time.elapsed())
+            })
+            .build();
+
+        assert_eq!(app.tick(Duration::from_millis(4)), 0);
+        assert_eq!(app.tick(Duration::from_millis(7)), 1);
+        assert_eq!(app.tick(Duration::from_millis(25)), 2);
+        assert_eq!(
+            app.world().resource::<Steps>().0,
+            [step, step * 2, step * 3]
+        );
+
+        let fixed = app.world().resource::<FixedTime>();
+        assert_eq!(fixed.accumulator(), Duration::from_millis(6));
+        assert!((fixed.overstep() - 0.6).abs() < 1e-6);
+
+        // Steps beyond the cap are dropped, the remainder is kept.
+        assert_eq!(app.tick(Duration::from_millis(55)), 3);
+        assert_eq!(
+            app.world().resource::<FixedTime>().accumulator(),
+            Duration::from_millis(1)
+        );
+
+        let time = app.world().resource::<Time>();
+        assert_eq!(time.delta(), Duration::from_millis(55));
+        assert_eq!(time.elapsed(), Duration::from_millis(91));
+    }
+}
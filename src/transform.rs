@@ -0,0 +1,324 @@
+use crate::{
+    app::{AppBuilder, Last, Plugin},
+    system::query::{Added, And, Modified, Not, Or, Query, Removed, With},
+    world::{Children, Component, Entity, Parent},
+};
+use serde::{Deserialize, Serialize};
+
+/// Position, rotation and scale of an entity relative to its [`Parent`], or to the world for
+/// entities without one. The rotation is a unit quaternion in `[x, y, z, w]` order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: [0.0; 3],
+        rotation: [0.0, 0.0, 0.0, 1.0],
+        scale: [1.0; 3],
+    };
+
+    pub fn from_translation(translation: [f32; 3]) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rotation(rotation: [f32; 4]) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_scale(scale: [f32; 3]) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn with_translation(mut self, translation: [f32; 3]) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: [f32; 4]) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: [f32; 3]) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn transform_point(&self, point: [f32; 3]) -> [f32; 3] {
+        let scaled = mul(point, self.scale);
+        add(rotate(self.rotation, scaled), self.translation)
+    }
+
+    /// Applies `child` on top of this transform. Non-uniform scale combined with rotation is
+    /// approximated, as the result can't represent shear.
+    pub fn mul_transform(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(child.translation),
+            rotation: mul_quat(self.rotation, child.rotation),
+            scale: mul(self.scale, child.scale),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Component for Transform {}
+
+/// The [`Transform`] of an entity relative to the world, computed by
+/// [`propagate_transforms`]. Added along with [`Transform`] by the [`TransformPlugin`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalTransform(Transform);
+
+impl GlobalTransform {
+    pub fn translation(&self) -> [f32; 3] {
+        self.0.translation
+    }
+
+    pub fn rotation(&self) -> [f32; 4] {
+        self.0.rotation
+    }
+
+    pub fn scale(&self) -> [f32; 3] {
+        self.0.scale
+    }
+
+    pub fn compute_transform(&self) -> Transform {
+        self.0
+    }
+
+    pub fn transform_point(&self, point: [f32; 3]) -> [f32; 3] {
+        self.0.transform_point(point)
+    }
+
+    pub fn mul_transform(&self, child: &Transform) -> GlobalTransform {
+        GlobalTransform(self.0.mul_transform(child))
+    }
+}
+
+impl From<Transform> for GlobalTransform {
+    fn from(transform: Transform) -> Self {
+        Self(transform)
+    }
+}
+
+impl Component for GlobalTransform {}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn mul(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] * b[0], a[1] * b[1], a[2] * b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn rotate(q: [f32; 4], v: [f32; 3]) -> [f32; 3] {
+    let axis = [q[0], q[1], q[2]];
+    let t = cross(axis, v).map(|x| x * 2.0);
+    add(add(v, t.map(|x| x * q[3])), cross(axis, t))
+}
+
+fn mul_quat(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+        a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+        a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+        a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2],
+    ]
+}
+
+/// Whether the global transform of an entity is stale, not counting its ancestors.
+type TransformChanged = Or<(
+    Added<Transform>,
+    Modified<Transform>,
+    Added<Parent>,
+    Removed<Parent>,
+)>;
+
+/// Entities with a [`Transform`] at the top of the hierarchy.
+type RootQuery<'w, 's> = Query<'w, 's, Entity, And<(With<Transform>, Not<Parent>)>>;
+
+type TransformQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static mut GlobalTransform,
+        TransformChanged,
+    ),
+>;
+
+/// Updates the [`GlobalTransform`] of every entity in the [`Parent`]/[`Children`] hierarchy.
+/// Only entities whose transform or parent changed since the last run are recomputed, along
+/// with their descendants.
+pub fn propagate_transforms(
+    roots: RootQuery,
+    mut transforms: TransformQuery,
+    hierarchy: Query<&Children>,
+) {
+    for root in roots.iter() {
+        propagate(&mut transforms, &hierarchy, root, None, false);
+    }
+}
+
+fn propagate(
+    transforms: &mut TransformQuery,
+    hierarchy: &Query<&Children>,
+    entity: Entity,
+    parent: Option<&GlobalTransform>,
+    parent_changed: bool,
+) {
+    let Some((transform, mut global, changed)) = transforms.get_mut(entity) else {
+        return;
+    };
+
+    let changed = changed || parent_changed;
+    if changed {
+        *global = match parent {
+            Some(parent) => parent.mul_transform(transform),
+            None => GlobalTransform(*transform),
+        };
+    }
+
+    let global = *global;
+    for child in hierarchy.children(entity) {
+        propagate(transforms, hierarchy, *child, Some(&global), changed);
+    }
+}
+
+/// Registers [`Transform`] and [`GlobalTransform`] and propagates transforms in [`Last`].
+pub struct TransformPlugin;
+
+impl Plugin for TransformPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_required::<Transform, GlobalTransform>()
+            .add_systems(Last, propagate_transforms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GlobalTransform, Transform, TransformPlugin};
+    use crate::{
+        app::{App, Last},
+        system::query::QueryState,
+        world::{Entity, World},
+    };
+
+    fn frame(app: &mut App) {
+        app.run(Last);
+        app.world_mut().update();
+    }
+
+    fn global(world: &World, entity: Entity) -> [f32; 3] {
+        world
+            .get_component::<GlobalTransform>(entity)
+            .unwrap()
+            .translation()
+    }
+
+    fn set_translation(world: &mut World, entity: Entity, translation: [f32; 3]) {
+        let state = QueryState::<&mut Transform>::new(world);
+        state.get(world, entity).unwrap().translation = translation;
+    }
+
+    #[test]
+    fn transform_composition() {
+        // Quarter turn around z.
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let parent = Transform::from_translation([1.0, 0.0, 0.0])
+            .with_rotation([0.0, 0.0, half, half])
+            .with_scale([2.0; 3]);
+        let child = Transform::from_translation([1.0, 0.0, 0.0]);
+
+        let point = parent.mul_transform(&child).translation;
+        assert!((point[0] - 1.0).abs() < 1e-5);
+        assert!((point[1] - 2.0).abs() < 1e-5);
+        assert_eq!(parent.mul_transform(&child).scale, [2.0; 3]);
+        assert_eq!(Transform::IDENTITY.mul_transform(&child), child);
+    }
+
+    #[test]
+    fn propagate_hierarchy() {
+        let mut app = App::new().add_plugin(TransformPlugin).build();
+        let world = app.world_mut();
+        let root = world.spawn();
+        let child = world.spawn();
+        let leaf = world.spawn();
+        world.add_component(root, Transform::from_translation([1.0, 0.0, 0.0]));
+        world.add_component(child, Transform::from_translation([0.0, 2.0, 0.0]));
+        world.add_component(leaf, Transform::from_translation([0.0, 0.0, 3.0]));
+        world.set_parent(child, root).unwrap();
+        world.set_parent(leaf, child).unwrap();
+
+        frame(&mut app);
+        assert_eq!(global(app.world(), root), [1.0, 0.0, 0.0]);
+        assert_eq!(global(app.world(), child), [1.0, 2.0, 0.0]);
+        assert_eq!(global(app.world(), leaf), [1.0, 2.0, 3.0]);
+
+        // Moving the root moves its descendants.
+        set_translation(app.world_mut(), root, [5.0, 0.0, 0.0]);
+        frame(&mut app);
+        assert_eq!(global(app.world(), leaf), [5.0, 2.0, 3.0]);
+
+        // Reparenting and detaching are picked up.
+        app.world_mut().set_parent(leaf, root).unwrap();
+        frame(&mut app);
+        assert_eq!(global(app.world(), leaf), [5.0, 0.0, 3.0]);
+
+        app.world_mut().remove_parent(leaf);
+        frame(&mut app);
+        assert_eq!(global(app.world(), leaf), [0.0, 0.0, 3.0]);
+    }
+
+    #[test]
+    fn propagate_skips_unchanged() {
+        let mut app = App::new().add_plugin(TransformPlugin).build();
+        let world = app.world_mut();
+        let (root, child, other) = (world.spawn(), world.spawn(), world.spawn());
+        for entity in [root, child, other] {
+            world.add_component(entity, Transform::from_translation([1.0, 0.0, 0.0]));
+        }
+        world.set_parent(child, root).unwrap();
+        frame(&mut app);
+
+        // Writes that bypass change detection survive as long as nothing upstream changes.
+        for entity in [child, other] {
+            *app.world_mut()
+                .get_component_mut::<GlobalTransform>(entity)
+                .unwrap() = GlobalTransform::default();
+        }
+        frame(&mut app);
+        assert_eq!(global(app.world(), child), [0.0; 3]);
+        assert_eq!(global(app.world(), other), [0.0; 3]);
+
+        set_translation(app.world_mut(), root, [2.0, 0.0, 0.0]);
+        frame(&mut app);
+        assert_eq!(global(app.world(), child), [3.0, 0.0, 0.0]);
+        assert_eq!(global(app.world(), other), [0.0; 3]);
+    }
+}
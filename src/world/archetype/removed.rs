@@ -0,0 +1,95 @@
+use super::{ComponentId, Entity};
+use crate::core::Frame;
+use std::collections::VecDeque;
+
+/// Entities a component was removed from during the current and previous frame.
+/// Every removal gets a sequence number, so readers can keep a cursor like event readers.
+#[derive(Debug, Default)]
+pub struct RemovedLog {
+    entries: VecDeque<(Entity, Frame)>,
+    /// Sequence number of the first entry.
+    start: u64,
+}
+
+impl RemovedLog {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sequence number of the oldest removal still kept.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Sequence number the next removal will get.
+    pub fn end(&self) -> u64 {
+        self.start + self.entries.len() as u64
+    }
+
+    pub fn get(&self, sequence: u64) -> Option<&(Entity, Frame)> {
+        let index = sequence.checked_sub(self.start)?;
+        self.entries.get(index as usize)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Entity, Frame)> {
+        self.entries.iter()
+    }
+
+    /// Whether the component was removed from the entity in a frame newer than `since`.
+    pub fn contains(&self, entity: Entity, current: Frame, since: Frame) -> bool {
+        self.entries
+            .iter()
+            .any(|(e, frame)| *e == entity && frame.is_newer(current, since))
+    }
+
+    fn push(&mut self, entity: Entity, frame: Frame) {
+        self.entries.push_back((entity, frame));
+    }
+
+    /// Drops the removals recorded before `frame`.
+    fn evict(&mut self, frame: Frame) {
+        while let Some((_, removed)) = self.entries.front() {
+            if *removed == frame {
+                break;
+            }
+
+            self.entries.pop_front();
+            self.start += 1;
+        }
+    }
+}
+
+/// Removal logs of every component that has been removed at least once.
+#[derive(Debug, Default)]
+pub struct RemovedComponentLogs {
+    logs: Vec<(ComponentId, RemovedLog)>,
+}
+
+impl RemovedComponentLogs {
+    pub fn get(&self, id: ComponentId) -> Option<&RemovedLog> {
+        self.logs.iter().find(|(i, _)| *i == id).map(|(_, log)| log)
+    }
+
+    pub(crate) fn record(&mut self, entity: Entity, id: ComponentId, frame: Frame) {
+        let index = match self.logs.iter().position(|(i, _)| *i == id) {
+            Some(index) => index,
+            None => {
+                self.logs.push((id, RemovedLog::default()));
+                self.logs.len() - 1
+            }
+        };
+
+        self.logs[index].1.push(entity, frame);
+    }
+
+    /// Keeps only the removals recorded in `frame`, called at the end of every frame.
+    pub(crate) fn update(&mut self, frame: Frame) {
+        for (_, log) in &mut self.logs {
+            log.evict(frame);
+        }
+    }
+}
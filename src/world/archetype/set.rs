@@ -0,0 +1,129 @@
+use super::ComponentId;
+use std::{any::TypeId, collections::HashMap, sync::Arc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SetId(pub u32);
+
+impl SetId {
+    pub const EMPTY: Self = Self(0);
+}
+
+/// Interns sorted component id sets so each distinct set is allocated and hashed once.
+/// Single component additions and removals are cached as edges between sets, and the set of
+/// each bundle type is cached by its type id.
+pub struct ComponentSetInterner {
+    sets: Vec<Arc<[ComponentId]>>,
+    map: HashMap<Arc<[ComponentId]>, SetId>,
+    with: HashMap<(SetId, ComponentId), SetId>,
+    without: HashMap<(SetId, ComponentId), SetId>,
+    bundles: HashMap<TypeId, SetId>,
+}
+
+#[cfg(test)]
+thread_local! {
+    static LOOKUPS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+impl ComponentSetInterner {
+    pub fn new() -> Self {
+        let empty: Arc<[ComponentId]> = Arc::from([]);
+        Self {
+            sets: vec![empty.clone()],
+            map: HashMap::from([(empty, SetId::EMPTY)]),
+            with: HashMap::new(),
+            without: HashMap::new(),
+            bundles: HashMap::new(),
+        }
+    }
+
+    /// Number of set lookups by slice made on the current thread.
+    #[cfg(test)]
+    pub(crate) fn lookups() -> usize {
+        LOOKUPS.with(|lookups| lookups.get())
+    }
+
+    /// Interns the set. The ids must be sorted and contain no duplicates.
+    pub fn intern(&mut self, ids: &[ComponentId]) -> SetId {
+        debug_assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        #[cfg(test)]
+        LOOKUPS.with(|lookups| lookups.set(lookups.get() + 1));
+
+        if let Some(id) = self.map.get(ids).copied() {
+            return id;
+        }
+
+        let id = SetId(self.sets.len() as u32);
+        let set: Arc<[ComponentId]> = Arc::from(ids);
+        self.sets.push(set.clone());
+        self.map.insert(set, id);
+        id
+    }
+
+    pub fn get(&self, id: SetId) -> &[ComponentId] {
+        &self.sets[id.0 as usize]
+    }
+
+    /// The set with `component` added.
+    pub fn with(&mut self, set: SetId, component: ComponentId) -> SetId {
+        if let Some(id) = self.with.get(&(set, component)).copied() {
+            return id;
+        }
+
+        let ids = self.get(set);
+        let id = match ids.binary_search(&component) {
+            Ok(_) => set,
+            Err(index) => {
+                let mut ids = ids.to_vec();
+                ids.insert(index, component);
+                self.intern(&ids)
+            }
+        };
+
+        self.with.insert((set, component), id);
+        id
+    }
+
+    /// The set with `component` removed.
+    pub fn without(&mut self, set: SetId, component: ComponentId) -> SetId {
+        if let Some(id) = self.without.get(&(set, component)).copied() {
+            return id;
+        }
+
+        let ids = self.get(set);
+        let id = match ids.binary_search(&component) {
+            Ok(index) => {
+                let mut ids = ids.to_vec();
+                ids.remove(index);
+                self.intern(&ids)
+            }
+            Err(_) => set,
+        };
+
+        self.without.insert((set, component), id);
+        id
+    }
+
+    /// The set of the bundle type, if it was interned with [`ComponentSetInterner::insert_bundle`].
+    pub fn bundle(&self, bundle: TypeId) -> Option<SetId> {
+        self.bundles.get(&bundle).copied()
+    }
+
+    /// Records the set of the bundle type, so spawning it again skips sorting and hashing its ids.
+    pub fn insert_bundle(&mut self, bundle: TypeId, set: SetId) {
+        self.bundles.insert(bundle, set);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+}
+
+impl Default for ComponentSetInterner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
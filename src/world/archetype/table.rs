@@ -6,7 +6,12 @@ use crate::core::{
     sparse::{ImmutableSparseSet, SparseIndex, SparseSet},
 };
 use indexmap::IndexSet;
-use std::alloc::Layout;
+use std::{
+    alloc::Layout,
+    cell::UnsafeCell,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 pub struct TableCell {
     data: BlobCell,
@@ -72,19 +77,81 @@ impl TableCell {
     }
 }
 
+struct FrameTracking {
+    frames: Vec<ObjectStatus>,
+    /// Frame the column was frozen at. Frozen columns drop their per row frames.
+    frozen: Option<Frame>,
+    /// Latest added and modified frames of any row, never older than the rows' own frames.
+    changed: ObjectStatus,
+}
+
+impl FrameTracking {
+    fn new(frames: Vec<ObjectStatus>) -> Self {
+        let mut tracking = Self {
+            frames: Vec::new(),
+            frozen: None,
+            changed: ObjectStatus::new(),
+        };
+        tracking.extend(&frames);
+        tracking.frames = frames;
+        tracking
+    }
+
+    fn record(&mut self, status: ObjectStatus) {
+        self.changed.added = self.changed.added.latest(status.added);
+        self.changed.modified = self.changed.modified.latest(status.modified);
+    }
+
+    fn extend(&mut self, frames: &[ObjectStatus]) {
+        for status in frames {
+            self.record(*status);
+        }
+    }
+
+    fn thaw(&mut self, len: usize) -> bool {
+        match self.frozen.take() {
+            Some(frame) => {
+                let status = ObjectStatus {
+                    added: frame,
+                    modified: frame,
+                };
+                self.frames = vec![status; len];
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 pub struct Column {
     data: Blob,
-    frames: Vec<ObjectStatus>,
+    tracking: UnsafeCell<FrameTracking>,
 }
 
+// Frame tracking is only mutated through `&mut self` or `thaw_unchecked`,
+// which requires exclusive access to the column.
+unsafe impl Sync for Column {}
+
 impl Column {
     pub fn new<T: Component>() -> Self {
+        Self::with_meta(TypeMeta::new::<T>())
+    }
+
+    pub fn with_meta(meta: TypeMeta) -> Self {
         Self {
-            data: Blob::new::<T>(),
-            frames: Vec::new(),
+            data: Blob::with_meta(meta),
+            tracking: UnsafeCell::new(FrameTracking::new(Vec::new())),
         }
     }
 
+    fn tracking(&self) -> &FrameTracking {
+        unsafe { &*self.tracking.get() }
+    }
+
+    fn tracking_mut(&mut self) -> &mut FrameTracking {
+        self.tracking.get_mut()
+    }
+
     pub fn get<T: Component>(&self, index: usize) -> Option<&T> {
         self.data.get::<T>(index)
     }
@@ -93,32 +160,177 @@ impl Column {
         self.data.get_mut::<T>(index)
     }
 
+    /// Untyped pointers to the value and frame of a row. Frozen columns have no frame pointer.
+    pub fn get_raw(&self, index: usize) -> Option<(NonNull<u8>, Option<NonNull<ObjectStatus>>)> {
+        let value = self.data.get_raw(index)?;
+        let frame = self.tracking().frames.get(index).map(NonNull::from);
+        Some((value, frame))
+    }
+
+    /// Every value of the column, in row order.
+    pub fn as_slice<T: Component>(&self) -> &[T] {
+        assert_eq!(std::mem::size_of::<T>(), self.data.meta().layout.size());
+        unsafe { self.data.ptr::<T>().as_slice() }
+    }
+
+    /// # Safety
+    /// The caller must have exclusive access to the column while the pointers are used.
+    /// Frozen columns return an empty frame pointer, thaw them before writing.
     pub unsafe fn get_ptr<T: Component>(&self) -> (Ptr<'_, T>, Ptr<'_, ObjectStatus>) {
         let components = unsafe { self.data.ptr::<T>() };
-        let frames = self.frames.as_ptr() as *mut ObjectStatus;
+        let frames = &self.tracking().frames;
 
-        (components, unsafe { Ptr::new(frames) })
+        (components, unsafe {
+            Ptr::new(frames.as_ptr() as *mut ObjectStatus, frames.len())
+        })
     }
 
+    /// Per row frames. Empty while the column is frozen, use [`Column::status`] to read
+    /// frames regardless of the storage mode.
     pub fn frames(&self) -> &[ObjectStatus] {
-        &self.frames
+        &self.tracking().frames
+    }
+
+    pub fn status(&self, index: usize) -> Option<ObjectStatus> {
+        let tracking = self.tracking();
+        match tracking.frozen {
+            Some(frame) => (index < self.len()).then_some(ObjectStatus {
+                added: frame,
+                modified: frame,
+            }),
+            None => tracking.frames.get(index).copied(),
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.tracking().frozen.is_some()
+    }
+
+    /// Latest added and modified frames of any row. Can be newer than every row, since
+    /// handing out write access counts as a modification, but never older.
+    pub fn changed(&self) -> ObjectStatus {
+        self.tracking().changed
+    }
+
+    /// Raises the column's modified frame, see [`Column::changed`].
+    pub fn mark_modified(&mut self, frame: Frame) {
+        let changed = &mut self.tracking_mut().changed;
+        changed.modified = changed.modified.latest(frame);
+    }
+
+    /// # Safety
+    /// The caller must have exclusive access to the column.
+    pub(crate) unsafe fn mark_modified_unchecked(&self, frame: Frame) {
+        let changed = unsafe { &mut (*self.tracking.get()).changed };
+        changed.modified = changed.modified.latest(frame);
     }
 
-    pub fn frames_mut(&mut self) -> &mut [ObjectStatus] {
-        &mut self.frames
+    pub fn frozen_at(&self) -> Option<Frame> {
+        self.tracking().frozen
+    }
+
+    /// Heap bytes held by the column.
+    pub fn memory_usage(&self) -> usize {
+        self.data.capacity()
+            + self.tracking().frames.capacity() * std::mem::size_of::<ObjectStatus>()
+    }
+
+    /// Shrinks the column to its exact size and drops the per row frames.
+    /// Every row reports `frame` as its added and modified frame until the column is thawed.
+    pub fn freeze(&mut self, frame: Frame) {
+        self.data.shrink_to_fit();
+        let tracking = self.tracking_mut();
+        tracking.frames = Vec::new();
+        tracking.frozen = Some(frame);
+        tracking.record(ObjectStatus {
+            added: frame,
+            modified: frame,
+        });
+    }
+
+    /// Restores the per row frames of a frozen column. Returns false if it wasn't frozen.
+    pub fn thaw(&mut self) -> bool {
+        let len = self.len();
+        self.tracking_mut().thaw(len)
+    }
+
+    /// Clamps every frame of the column, see [`Frame::check`].
+    pub fn check_change_frames(&mut self, current: Frame) {
+        let tracking = self.tracking_mut();
+        tracking
+            .frames
+            .iter_mut()
+            .for_each(|status| status.check(current));
+        tracking.changed.check(current);
+        if let Some(frame) = &mut tracking.frozen {
+            frame.check(current);
+        }
+    }
+
+    /// # Safety
+    /// The caller must have exclusive access to the column, no other references to its
+    /// frames may be alive.
+    pub(crate) unsafe fn thaw_unchecked(&self) -> bool {
+        let len = self.len();
+        unsafe { (*self.tracking.get()).thaw(len) }
+    }
+
+    /// Returns the initialized bytes of the column, the element count and the element layout.
+    pub fn raw_parts(&self) -> (&[u8], usize, Layout) {
+        (
+            self.data.as_bytes(),
+            self.data.len(),
+            self.data.meta().layout,
+        )
+    }
+
+    /// # Safety
+    /// `bytes` must contain `frames.len()` valid values of the column's type.
+    pub unsafe fn extend_raw(&mut self, bytes: &[u8], frames: &[ObjectStatus]) {
+        self.thaw();
+        unsafe { self.data.extend_raw(bytes, frames.len()) };
+        let tracking = self.tracking_mut();
+        tracking.extend(frames);
+        tracking.frames.extend_from_slice(frames);
+    }
+
+    /// Marks the row modified at `frame`. Returns false if the row doesn't exist.
+    pub fn modify(&mut self, index: usize, frame: Frame) -> bool {
+        self.thaw();
+        let tracking = self.tracking_mut();
+        let Some(status) = tracking.frames.get_mut(index) else {
+            return false;
+        };
+
+        status.modified = frame;
+        tracking.changed.modified = tracking.changed.modified.latest(frame);
+        true
     }
 
     pub fn push<T: Component>(&mut self, value: T) {
+        self.thaw();
         self.data.push(value);
+        let tracking = self.tracking_mut();
+        tracking.record(ObjectStatus::new());
+        tracking.frames.push(ObjectStatus::new());
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+        self.tracking_mut().frames.reserve(additional);
     }
 
     pub fn push_cell(&mut self, cell: TableCell) {
-        unsafe { self.data.append_raw(cell.data.into_raw().0) };
-        self.frames.push(cell.frame);
+        self.thaw();
+        unsafe { self.data.append_raw(cell.data.into_raw().0, 1) };
+        let tracking = self.tracking_mut();
+        tracking.record(cell.frame);
+        tracking.frames.push(cell.frame);
     }
 
     pub fn remove(&mut self, index: usize) -> Option<TableCell> {
-        let frame = self.frames.remove(index);
+        self.thaw();
+        let frame = self.tracking_mut().frames.remove(index);
         unsafe {
             let data = self.data.remove_raw(index);
             Some(TableCell {
@@ -129,7 +341,8 @@ impl Column {
     }
 
     pub fn swap_remove(&mut self, index: usize) -> Option<TableCell> {
-        let frame = self.frames.swap_remove(index);
+        self.thaw();
+        let frame = self.tracking_mut().frames.swap_remove(index);
         unsafe {
             let data = self.data.swap_remove_raw(index);
             Some(TableCell {
@@ -149,6 +362,7 @@ impl Column {
 
     pub fn clear(&mut self) {
         self.data.clear();
+        self.tracking_mut().frames.clear();
     }
 }
 
@@ -156,7 +370,7 @@ impl From<TableCell> for Column {
     fn from(value: TableCell) -> Self {
         Self {
             data: Blob::from(value.data),
-            frames: vec![value.frame],
+            tracking: UnsafeCell::new(FrameTracking::new(vec![value.frame])),
         }
     }
 }
@@ -216,6 +430,10 @@ impl Row {
         self.0.iter()
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ComponentId, &mut TableCell)> {
+        self.0.iter_mut()
+    }
+
     pub fn contains(&self, id: ComponentId) -> bool {
         self.0.contains(id)
     }
@@ -224,6 +442,10 @@ impl Row {
         self.0.clear();
     }
 
+    pub fn into_cells(mut self) -> Vec<(ComponentId, TableCell)> {
+        self.0.drain().collect()
+    }
+
     pub fn into_table(mut self, entity: Entity) -> Table {
         let columns = self
             .0
@@ -237,6 +459,7 @@ impl Row {
         Table {
             entities,
             columns: columns.into(),
+            thaws: AtomicUsize::new(0),
         }
     }
 }
@@ -292,10 +515,16 @@ impl TableBuilder {
         self
     }
 
+    pub fn add_column_with_meta(&mut self, component_id: ComponentId, meta: TypeMeta) -> &mut Self {
+        self.columns.insert(component_id, Column::with_meta(meta));
+        self
+    }
+
     pub fn build(self) -> Table {
         Table {
             entities: IndexSet::new(),
             columns: self.columns.into(),
+            thaws: AtomicUsize::new(0),
         }
     }
 }
@@ -303,24 +532,69 @@ impl TableBuilder {
 pub struct Table {
     entities: IndexSet<Entity>,
     columns: ImmutableSparseSet<Column, ComponentId>,
+    thaws: AtomicUsize,
 }
 
 impl Table {
     pub fn add_entity(&mut self, entity: Entity, mut row: Row) {
+        // Checked up front so a bad row can't leave the columns partially pushed.
+        assert!(
+            !self.entities.contains(&entity),
+            "Entity already in table: {:?}",
+            entity
+        );
+        assert!(
+            self.columns.iter().all(|(id, _)| row.contains(*id)),
+            "Row does not contain all columns for entity: {:?}",
+            entity
+        );
+
+        self.thaw();
         self.entities.insert(entity);
-
         self.columns.iter_mut().for_each(|(id, column)| {
-            if let Some(cell) = row.remove(*id) {
-                column.push_cell(cell);
-            } else {
-                panic!("Row does not contain all columns for entity: {:?}", entity);
-            }
+            column.push_cell(row.remove(*id).unwrap());
         });
+
+        self.debug_check_consistency();
+    }
+
+    /// Reserves capacity for at least `additional` more entities in every column.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entities.reserve(additional);
+        self.columns
+            .iter_mut()
+            .for_each(|(_, column)| column.reserve(additional));
+    }
+
+    /// # Safety
+    /// `columns` must return bytes for `entities.len()` valid values of every column in the table.
+    pub unsafe fn extend_raw<'a>(
+        &mut self,
+        entities: &[Entity],
+        columns: impl Fn(ComponentId) -> (&'a [u8], &'a [ObjectStatus]),
+    ) {
+        self.thaw();
+        self.entities.extend(entities.iter().copied());
+        for (id, column) in self.columns.iter_mut() {
+            let (bytes, frames) = columns(*id);
+            unsafe { column.extend_raw(bytes, frames) };
+        }
+
+        self.debug_check_consistency();
     }
 
     pub fn remove_entity(&mut self, entity: Entity) -> Option<Row> {
         let index = self.entities.get_index_of(&entity)?;
-        self.entities.swap_remove_index(index);
+        self.swap_remove_row(RowIndex(index as u32))
+            .map(|(_, row)| row)
+    }
+
+    /// Removes a row from the entities and every column together. The last row is moved into
+    /// its place, so when removing while iterating rows, the same index must be visited again.
+    pub fn swap_remove_row(&mut self, row: RowIndex) -> Option<(Entity, Row)> {
+        let index = row.to_usize();
+        let entity = self.entities.swap_remove_index(index)?;
+        self.thaw();
 
         let mut row = Row::new();
         self.columns.iter_mut().for_each(|(id, column)| {
@@ -328,7 +602,117 @@ impl Table {
                 row.insert_cell(*id, cell);
             }
         });
-        Some(row)
+
+        self.debug_check_consistency();
+        Some((entity, row))
+    }
+
+    /// Panics if a column or its frames don't hold exactly one value per entity.
+    pub fn check_consistency(&self) {
+        let len = self.entities.len();
+        for (id, column) in self.columns.iter() {
+            assert_eq!(column.len(), len, "Column {:?} has a different length", id);
+            if !column.is_frozen() {
+                assert_eq!(
+                    column.frames().len(),
+                    len,
+                    "Column {:?} has a different frame count",
+                    id
+                );
+            }
+        }
+    }
+
+    fn debug_check_consistency(&self) {
+        if cfg!(debug_assertions) {
+            self.check_consistency();
+        }
+    }
+
+    /// True if the table has columns and all of them are frozen.
+    pub fn is_frozen(&self) -> bool {
+        !self.columns.is_empty() && self.columns.iter().all(|(_, column)| column.is_frozen())
+    }
+
+    /// Freezes every column, see [`Column::freeze`].
+    pub fn freeze(&mut self, frame: Frame) {
+        self.entities.shrink_to_fit();
+        for (_, column) in self.columns.iter_mut() {
+            column.freeze(frame);
+        }
+    }
+
+    /// Thaws every frozen column. Returns false if none were frozen.
+    pub fn thaw(&mut self) -> bool {
+        let mut thawed = false;
+        for (_, column) in self.columns.iter_mut() {
+            thawed |= column.thaw();
+        }
+
+        if thawed {
+            *self.thaws.get_mut() += 1;
+        }
+
+        thawed
+    }
+
+    /// Clamps the frames of every column, see [`Column::check_change_frames`].
+    pub fn check_change_frames(&mut self, current: Frame) {
+        for (_, column) in self.columns.iter_mut() {
+            column.check_change_frames(current);
+        }
+    }
+
+    /// # Safety
+    /// The caller must have exclusive access to the column.
+    pub(crate) unsafe fn thaw_column(&self, component: ComponentId) -> bool {
+        let thawed = self
+            .columns
+            .get(component)
+            .is_some_and(|column| unsafe { column.thaw_unchecked() });
+
+        if thawed {
+            self.thaws.fetch_add(1, Ordering::Relaxed);
+        }
+
+        thawed
+    }
+
+    /// # Safety
+    /// The caller must have exclusive access to the column.
+    pub(crate) unsafe fn mark_column_modified(&self, component: ComponentId, frame: Frame) {
+        if let Some(column) = self.columns.get(component) {
+            unsafe { column.mark_modified_unchecked(frame) };
+        }
+    }
+
+    /// Number of times a frozen column of the table was thawed.
+    pub fn thaws(&self) -> usize {
+        self.thaws.load(Ordering::Relaxed)
+    }
+
+    /// Most recent added or modified frame of the table's columns, read from their summaries
+    /// without looking at rows, see [`Column::changed`]. `None` if the table is empty.
+    pub fn last_changed(&self) -> Option<Frame> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.columns
+            .iter()
+            .map(|(_, column)| column.changed())
+            .map(|changed| changed.added.latest(changed.modified))
+            .reduce(Frame::latest)
+    }
+
+    /// Heap bytes held by the entities and columns of the table.
+    pub fn memory_usage(&self) -> usize {
+        let entities = self.entities.capacity() * std::mem::size_of::<(Entity, usize)>();
+        self.columns
+            .iter()
+            .map(|(_, column)| column.memory_usage())
+            .sum::<usize>()
+            + entities
     }
 
     pub fn get_entity_row(&self, entity: Entity) -> Option<RowIndex> {
@@ -336,10 +720,26 @@ impl Table {
         Some(RowIndex(index as u32))
     }
 
+    pub fn get_entity(&self, row: RowIndex) -> Option<Entity> {
+        self.entities.get_index(row.0 as usize).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
     pub fn entities(&self) -> indexmap::set::Iter<'_, Entity> {
         self.entities.iter()
     }
 
+    pub fn columns(&self) -> impl Iterator<Item = (&ComponentId, &Column)> {
+        self.columns.iter()
+    }
+
     pub fn get_column(&self, component: ComponentId) -> Option<&Column> {
         self.columns.get(component)
     }
@@ -357,10 +757,7 @@ impl Table {
             return;
         };
 
-        column
-            .frames_mut()
-            .get_mut(index)
-            .and_then(|cell| Some(cell.modified = frame));
+        column.modify(index, frame);
     }
 
     pub fn get_component<C: Component>(
@@ -392,11 +789,11 @@ impl Table {
     }
 }
 
-#[allow(unused_imports)]
+#[cfg(test)]
 mod tests {
-    use super::{Row, Table, TableBuilder};
+    use super::{Row, RowIndex, Table, TableBuilder, TableCell};
     use crate::{
-        core::TypeMeta,
+        core::{Frame, TypeMeta},
         world::{Component, ComponentId, Entity},
     };
 
@@ -446,4 +843,141 @@ mod tests {
         let age = row.get::<Age>(id);
         assert_eq!(age, Some(&Age(0)));
     }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Player;
+    impl Component for Player {}
+
+    #[test]
+    fn table_zero_sized_column() {
+        let id = ComponentId(0);
+        let (first, second) = (Entity::root(0), Entity::root(1));
+
+        let mut table = TableBuilder::new().with_column::<Player>(id).build();
+        for entity in [first, second] {
+            let mut row = Row::new();
+            row.insert(id, Player);
+            table.add_entity(entity, row);
+        }
+
+        let row = table.remove_entity(first).unwrap();
+        assert_eq!(row.get::<Player>(id), Some(&Player));
+        assert_eq!(table.get_column(id).unwrap().len(), 1);
+        assert_eq!(table.get_component::<Player>(second, id), Some(&Player));
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Name(u32);
+    impl Component for Name {}
+
+    const AGE: ComponentId = ComponentId(0);
+    const NAME: ComponentId = ComponentId(1);
+
+    /// Entity `i` gets `Age(i)`, `Name(i)` and frame `i`.
+    fn filled_table(len: u32) -> Table {
+        let mut table = TableBuilder::new()
+            .with_column::<Age>(AGE)
+            .with_column::<Name>(NAME)
+            .build();
+
+        for i in 0..len {
+            let mut row = Row::new();
+            row.insert_cell(AGE, TableCell::with_frame(Age(i), Frame(i)));
+            row.insert(NAME, Name(i));
+            table.add_entity(Entity::root(i), row);
+        }
+
+        table
+    }
+
+    /// Every remaining entity still lines up with its own components and frames.
+    fn assert_rows_match(table: &Table) {
+        table.check_consistency();
+        for (index, entity) in table.entities().enumerate() {
+            let id = entity.id();
+            let column = table.get_column(AGE).unwrap();
+            assert_eq!(column.get::<Age>(index), Some(&Age(id)));
+            assert_eq!(column.status(index).unwrap().added, Frame(id));
+            let column = table.get_column(NAME).unwrap();
+            assert_eq!(column.get::<Name>(index), Some(&Name(id)));
+        }
+    }
+
+    #[test]
+    fn table_swap_remove_row() {
+        let mut table = filled_table(4);
+
+        let (entity, row) = table.swap_remove_row(RowIndex(1)).unwrap();
+        assert_eq!(entity, Entity::root(1));
+        assert_eq!(row.get::<Age>(AGE), Some(&Age(1)));
+        assert_eq!(row.get::<Name>(NAME), Some(&Name(1)));
+        assert_eq!(table.get_entity(RowIndex(1)), Some(Entity::root(3)));
+        assert_rows_match(&table);
+
+        // The last row has nothing to swap in.
+        let (entity, _) = table.swap_remove_row(RowIndex(2)).unwrap();
+        assert_eq!(entity, Entity::root(2));
+        assert!(table.swap_remove_row(RowIndex(2)).is_none());
+        assert_eq!(table.len(), 2);
+        assert_rows_match(&table);
+    }
+
+    #[test]
+    fn table_remove_while_iterating() {
+        let mut table = filled_table(10);
+
+        let mut index = 0;
+        while let Some(entity) = table.get_entity(RowIndex(index)) {
+            if entity.id() % 2 == 0 {
+                table.swap_remove_row(RowIndex(index)).unwrap();
+                assert_rows_match(&table);
+            } else {
+                index += 1;
+            }
+        }
+
+        let mut ids = table.entities().map(|e| e.id()).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, [1, 3, 5, 7, 9]);
+
+        // Draining from the front swaps the last row in every time.
+        while table.swap_remove_row(RowIndex(0)).is_some() {
+            assert_rows_match(&table);
+        }
+        assert!(table.is_empty());
+        assert!(table.get_column(AGE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn table_remove_from_frozen() {
+        let mut table = filled_table(3);
+        table.freeze(Frame(7));
+
+        table.remove_entity(Entity::root(0)).unwrap();
+        assert!(!table.is_frozen());
+        table.check_consistency();
+        assert_eq!(table.get_entity(RowIndex(0)), Some(Entity::root(2)));
+        let column = table.get_column(AGE).unwrap();
+        assert_eq!(column.status(0).unwrap().added, Frame(7));
+        assert_eq!(column.get::<Age>(0), Some(&Age(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Row does not contain all columns")]
+    fn table_add_incomplete_row() {
+        let mut table = filled_table(1);
+        let mut row = Row::new();
+        row.insert(AGE, Age(1));
+        table.add_entity(Entity::root(1), row);
+    }
+
+    #[test]
+    #[should_panic(expected = "Entity already in table")]
+    fn table_add_duplicate_entity() {
+        let mut table = filled_table(1);
+        let mut row = Row::new();
+        row.insert(AGE, Age(0));
+        row.insert(NAME, Name(0));
+        table.add_entity(Entity::root(0), row);
+    }
 }
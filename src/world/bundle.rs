@@ -0,0 +1,358 @@
+use super::{
+    Component, ComponentId, Entity, Lifecycle, RequiredFn, World,
+    archetype::{ArchetypeId, table::Row},
+};
+use std::any::TypeId;
+
+/// A set of components inserted together, moving the entity between archetypes only once.
+pub trait Bundle: Send + Sync + 'static {
+    fn component_ids(world: &World) -> Vec<ComponentId>;
+
+    fn into_row(self, world: &World) -> Row;
+}
+
+fn component_id<C: Component>(world: &World) -> ComponentId {
+    world
+        .components()
+        .get_id::<C>()
+        .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()))
+}
+
+macro_rules! impl_bundle_for_tuples {
+    ($($name:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<$($name: Component),+> Bundle for ($($name,)+) {
+            fn component_ids(world: &World) -> Vec<ComponentId> {
+                vec![$(component_id::<$name>(world)),+]
+            }
+
+            fn into_row(self, world: &World) -> Row {
+                let ($($name,)+) = self;
+                let mut row = Row::new();
+                $(row.insert(component_id::<$name>(world), $name);)+
+                row
+            }
+        }
+    };
+}
+
+impl_bundle_for_tuples!(A);
+impl_bundle_for_tuples!(A, B);
+impl_bundle_for_tuples!(A, B, C);
+impl_bundle_for_tuples!(A, B, C, D);
+impl_bundle_for_tuples!(A, B, C, D, E);
+impl_bundle_for_tuples!(A, B, C, D, E, F);
+impl_bundle_for_tuples!(A, B, C, D, E, F, G);
+impl_bundle_for_tuples!(A, B, C, D, E, F, G, H);
+impl_bundle_for_tuples!(A, B, C, D, E, F, G, H, I);
+impl_bundle_for_tuples!(A, B, C, D, E, F, G, H, I, J);
+impl_bundle_for_tuples!(A, B, C, D, E, F, G, H, I, J, K);
+impl_bundle_for_tuples!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl World {
+    /// Spawns an entity directly into the archetype of the bundle.
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> Entity {
+        let (archetype, required) = self.bundle_archetype::<B>();
+        let mut row = bundle.into_row(self);
+        let entity = self.entities.spawn();
+        let (observed, _) = self.observed_insert(entity, row.ids());
+        for (id, constructor) in &required {
+            row.insert_cell(*id, constructor());
+        }
+
+        self.archetypes.spawn_in(archetype, entity, row, self.frame);
+
+        self.trigger_all(Lifecycle::Add, entity, &observed);
+        self.trigger_all(Lifecycle::Insert, entity, &observed);
+        entity
+    }
+
+    /// Spawns an entity for each bundle, resolving the archetype once and reserving its
+    /// storage up front from the iterator's size hint.
+    pub fn spawn_batch<B: Bundle>(&mut self, bundles: impl IntoIterator<Item = B>) -> Vec<Entity> {
+        let (archetype, required) = self.bundle_archetype::<B>();
+        let bundles = bundles.into_iter();
+        let (additional, _) = bundles.size_hint();
+        self.archetypes.reserve(archetype, additional);
+
+        let mut entities = Vec::with_capacity(additional);
+        for bundle in bundles {
+            let mut row = bundle.into_row(self);
+            for (id, constructor) in &required {
+                row.insert_cell(*id, constructor());
+            }
+
+            let entity = self.entities.spawn();
+            self.archetypes.spawn_in(archetype, entity, row, self.frame);
+            entities.push(entity);
+        }
+
+        let set = self.archetypes.archetype_set(archetype).unwrap();
+        let observed = self
+            .archetypes
+            .component_set(set)
+            .iter()
+            .copied()
+            .filter(|component| self.is_observed(*component))
+            .collect::<Vec<_>>();
+        if !observed.is_empty() {
+            for entity in &entities {
+                self.trigger_all(Lifecycle::Add, *entity, &observed);
+                self.trigger_all(Lifecycle::Insert, *entity, &observed);
+            }
+        }
+
+        entities
+    }
+
+    /// Returns the archetype spawning the bundle lands in, required components included,
+    /// creating it eagerly if it doesn't exist yet.
+    pub fn archetype_for_bundle<B: Bundle>(&mut self) -> ArchetypeId {
+        self.bundle_archetype::<B>().0
+    }
+
+    /// The archetype of the bundle and the components it requires. The set of the bundle's own
+    /// components is resolved once per bundle type, required components are added through
+    /// cached set edges since they can be registered later.
+    fn bundle_archetype<B: Bundle>(&mut self) -> (ArchetypeId, Vec<(ComponentId, RequiredFn)>) {
+        let bundle = TypeId::of::<B>();
+        let set = match self.archetypes.sets().bundle(bundle) {
+            Some(set) => set,
+            None => {
+                let components = B::component_ids(self);
+                self.archetypes.intern_bundle(bundle, &components)
+            }
+        };
+
+        let required = self
+            .components()
+            .required_by(self.archetypes.component_set(set));
+        let set = required
+            .iter()
+            .fold(set, |set, (id, _)| self.archetypes.set_with(set, *id));
+
+        (self.archetypes.get_or_create_set(set), required)
+    }
+
+    /// Inserts every component of the bundle with a single archetype move.
+    pub fn add_bundle<B: Bundle>(&mut self, entity: Entity, bundle: B) {
+        let row = bundle.into_row(self);
+        self.add_components(entity, row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::App,
+        system::schedule::Phase,
+        world::{Component, OnAdd, Spawner, Trigger, World},
+    };
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+    impl Component for Position {}
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32);
+    impl Component for Velocity {}
+
+    #[test]
+    fn spawn_bundle() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+
+        let entity = world.spawn_bundle((Position(1), Velocity(2)));
+        assert_eq!(world.get_component::<Position>(entity), Some(&Position(1)));
+        assert_eq!(world.get_component::<Velocity>(entity), Some(&Velocity(2)));
+
+        // Only the empty archetype and the bundle's archetype exist.
+        assert_eq!(world.archetypes().archetypes().len(), 2);
+
+        let other = world.spawn_bundle((Position(3),));
+        world.add_bundle(other, (Velocity(4),));
+        assert_eq!(world.get_component::<Velocity>(other), Some(&Velocity(4)));
+        assert_eq!(world.archetypes().archetypes().len(), 3);
+    }
+
+    #[test]
+    fn spawn_batch() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+
+        let entities = world.spawn_batch((0..100).map(|i| (Velocity(-i), Position(i))));
+        assert_eq!(entities.len(), 100);
+        for (i, entity) in entities.iter().enumerate() {
+            let i = i as i32;
+            assert_eq!(world.get_component::<Position>(*entity), Some(&Position(i)));
+            assert_eq!(
+                world.get_component::<Velocity>(*entity),
+                Some(&Velocity(-i))
+            );
+        }
+
+        assert_eq!(world.archetypes().archetypes().len(), 2);
+        let archetype = world.archetypes().entity_archetype(entities[0]).unwrap();
+        assert_eq!(world.archetypes()[archetype].table().len(), 100);
+
+        // Bundles with the same components in a different order share the archetype.
+        let entity = world.spawn_bundle((Position(7), Velocity(8)));
+        assert_eq!(world.archetypes().entity_archetype(entity), Some(archetype));
+        assert!(
+            world
+                .spawn_batch(std::iter::empty::<(Position,)>())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn archetype_for_bundle() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+
+        let archetype = world.archetype_for_bundle::<(Velocity, Position)>();
+        assert_eq!(world.archetypes().archetypes().len(), 2);
+        assert!(world.archetypes()[archetype].table().is_empty());
+
+        let entity = world.spawn_bundle((Position(1), Velocity(2)));
+        assert_eq!(world.archetypes().entity_archetype(entity), Some(archetype));
+        assert_eq!(
+            world.archetype_for_bundle::<(Position, Velocity)>(),
+            archetype
+        );
+        assert_eq!(world.archetypes().archetypes().len(), 2);
+    }
+
+    #[test]
+    fn bundle_sets_are_cached() {
+        use crate::world::archetype::ComponentSetInterner;
+
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+
+        let entity = world.spawn_bundle((Velocity(1), Position(1)));
+        let archetype = world.archetypes().entity_archetype(entity);
+        let lookups = ComponentSetInterner::lookups();
+
+        for i in 0..10 {
+            let entity = world.spawn_bundle((Velocity(i), Position(i)));
+            assert_eq!(world.archetypes().entity_archetype(entity), archetype);
+        }
+
+        world.spawn_batch((0..10).map(|i| (Velocity(i), Position(i))));
+        world.archetype_for_bundle::<(Velocity, Position)>();
+        assert_eq!(ComponentSetInterner::lookups(), lookups);
+
+        // Requirements registered after the set was cached still apply.
+        world.register_required::<Position, Transform>();
+        let entity = world.spawn_bundle((Velocity(2), Position(2)));
+        assert_eq!(
+            world.get_component::<Transform>(entity),
+            Some(&Transform(0))
+        );
+        assert_eq!(world.get_component::<Velocity>(entity), Some(&Velocity(2)));
+    }
+
+    #[test]
+    fn spawner_spawn_bundle() {
+        let mut builder = App::new();
+        builder.register::<Position>().register::<Velocity>();
+        builder.add_systems(Update, |mut spawner: Spawner| {
+            spawner.spawn_bundle((Position(5), Velocity(6)));
+        });
+
+        let mut app = builder.build();
+        app.run(Update);
+
+        let world = app.world();
+        let archetype = world
+            .archetypes()
+            .archetypes()
+            .iter()
+            .find(|archetype| !archetype.table().is_empty())
+            .unwrap();
+        let entity = *archetype.table().entities().next().unwrap();
+        assert_eq!(world.get_component::<Position>(entity), Some(&Position(5)));
+        assert_eq!(world.get_component::<Velocity>(entity), Some(&Velocity(6)));
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Transform(i32);
+    impl Component for Transform {}
+
+    #[derive(Debug, Default, PartialEq)]
+    struct GlobalTransform(i32);
+    impl Component for GlobalTransform {}
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Visibility(bool);
+    impl Component for Visibility {}
+
+    #[test]
+    fn required_components() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register_required::<Transform, GlobalTransform>();
+        world.register_required::<GlobalTransform, Visibility>();
+        world.register_required::<Visibility, Transform>();
+
+        world.observe(|trigger: Trigger<OnAdd<Visibility>>, world: &mut World| {
+            world
+                .get_component_mut::<Visibility>(trigger.entity())
+                .unwrap()
+                .0 = true;
+        });
+
+        let entity = world.spawn();
+        world.add_component(entity, Transform(1));
+        assert_eq!(
+            world.get_component::<Transform>(entity),
+            Some(&Transform(1))
+        );
+        assert_eq!(
+            world.get_component::<GlobalTransform>(entity),
+            Some(&GlobalTransform(0))
+        );
+        assert_eq!(
+            world.get_component::<Visibility>(entity),
+            Some(&Visibility(true))
+        );
+
+        // Components the entity already has, or that are inserted with it, are kept.
+        world.get_component_mut::<Visibility>(entity).unwrap().0 = false;
+        world.add_component(entity, Transform(2));
+        assert_eq!(
+            world.get_component::<Visibility>(entity),
+            Some(&Visibility(false))
+        );
+
+        let entity = world.spawn_bundle((GlobalTransform(3), Position(0)));
+        assert_eq!(
+            world.get_component::<Transform>(entity),
+            Some(&Transform(0))
+        );
+        assert_eq!(
+            world.get_component::<GlobalTransform>(entity),
+            Some(&GlobalTransform(3))
+        );
+
+        let entities = world.spawn_batch([(Transform(4),), (Transform(5),)]);
+        for entity in entities {
+            assert_eq!(
+                world.get_component::<GlobalTransform>(entity),
+                Some(&GlobalTransform(0))
+            );
+            assert_eq!(
+                world.get_component::<Visibility>(entity),
+                Some(&Visibility(true))
+            );
+        }
+    }
+}
@@ -1,6 +1,16 @@
 use super::World;
-use std::marker::PhantomData;
+use crate::{core::AccessBitset, system::SystemMeta};
+use std::{
+    marker::PhantomData,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU32, Ordering},
+    },
+};
 
+/// Shared pointer to a world that systems access through their declared access. Nothing stops
+/// two holders from aliasing the same data, debug builds validate the borrows of running
+/// systems with [`WorldBorrows`] instead.
 #[derive(Clone, Copy)]
 pub struct WorldCell<'w>(*mut World, PhantomData<&'w mut World>);
 impl<'w> WorldCell<'w> {
@@ -23,3 +33,215 @@ impl<'w> WorldCell<'w> {
 
 unsafe impl<'w> Send for WorldCell<'w> {}
 unsafe impl<'w> Sync for WorldCell<'w> {}
+
+/// Flag value of data that is borrowed mutably, lower values count the readers.
+const WRITE: u32 = u32::MAX;
+
+/// Runtime borrow flags of the components and resources accessed by running systems. If the
+/// access metadata of a system is wrong, conflicting borrows panic instead of aliasing.
+/// Lives outside the world, so exclusive systems can hold a borrow while mutating it.
+#[derive(Default)]
+pub struct WorldBorrows {
+    world: AtomicU32,
+    components: RwLock<Vec<AtomicU32>>,
+    resources: RwLock<Vec<AtomicU32>>,
+}
+
+impl WorldBorrows {
+    /// Borrows everything the system accesses until the returned guard is dropped. Exclusive
+    /// systems borrow the whole world mutably. Panics if any of it is borrowed in a
+    /// conflicting way, releasing what it already borrowed.
+    pub fn acquire<'a>(self: &Arc<Self>, meta: &'a SystemMeta) -> SystemBorrow<'a> {
+        let name = meta.name.as_deref().unwrap_or("unknown");
+        if !Self::borrow(&self.world, meta.exclusive) {
+            panic!(
+                "System {} borrows the world while another system accesses it",
+                name
+            );
+        }
+
+        if !meta.exclusive {
+            if let Some(index) = Self::borrow_all(&self.components, &meta.components) {
+                Self::release(&self.world, false);
+                panic!(
+                    "System {} borrows component {} while another system accesses it",
+                    name, index
+                );
+            }
+
+            if let Some(index) = Self::borrow_all(&self.resources, &meta.resources) {
+                Self::release_all(&self.components, &meta.components, usize::MAX);
+                Self::release(&self.world, false);
+                panic!(
+                    "System {} borrows resource {} while another system accesses it",
+                    name, index
+                );
+            }
+        }
+
+        SystemBorrow {
+            borrows: self.clone(),
+            meta,
+        }
+    }
+
+    /// Returns the first index that is borrowed in a conflicting way, after releasing the
+    /// indices borrowed before it.
+    fn borrow_all(flags: &RwLock<Vec<AtomicU32>>, access: &AccessBitset) -> Option<usize> {
+        if flags.read().unwrap().len() < access.len() {
+            flags
+                .write()
+                .unwrap()
+                .resize_with(access.len(), AtomicU32::default);
+        }
+
+        let conflict = {
+            let flags = flags.read().unwrap();
+            accessed(access).find(|(index, write)| !Self::borrow(&flags[*index], *write))
+        };
+
+        conflict.map(|(index, _)| {
+            Self::release_all(flags, access, index);
+            index
+        })
+    }
+
+    fn borrow(flag: &AtomicU32, write: bool) -> bool {
+        match write {
+            true => flag
+                .compare_exchange(0, WRITE, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok(),
+            false => flag
+                .fetch_update(Ordering::Acquire, Ordering::Relaxed, |readers| {
+                    (readers < WRITE - 1).then(|| readers + 1)
+                })
+                .is_ok(),
+        }
+    }
+
+    /// Releases the borrows of the indices below `end`.
+    fn release_all(flags: &RwLock<Vec<AtomicU32>>, access: &AccessBitset, end: usize) {
+        let flags = flags.read().unwrap();
+        for (index, write) in accessed(access).take_while(|(index, _)| *index < end) {
+            Self::release(&flags[index], write);
+        }
+    }
+
+    fn release(flag: &AtomicU32, write: bool) {
+        match write {
+            true => flag.store(0, Ordering::Release),
+            false => {
+                flag.fetch_sub(1, Ordering::Release);
+            }
+        }
+    }
+}
+
+/// Indices accessed by the set, with whether they are written.
+fn accessed(access: &AccessBitset) -> impl Iterator<Item = (usize, bool)> + '_ {
+    access
+        .iter()
+        .enumerate()
+        .filter(|(_, (read, write))| *read || *write)
+        .map(|(index, (_, write))| (index, write))
+}
+
+/// Borrows of a running system, see [`WorldBorrows::acquire`]. Released on drop, including
+/// when the system panics.
+pub struct SystemBorrow<'a> {
+    borrows: Arc<WorldBorrows>,
+    meta: &'a SystemMeta,
+}
+
+impl Drop for SystemBorrow<'_> {
+    fn drop(&mut self) {
+        WorldBorrows::release(&self.borrows.world, self.meta.exclusive);
+        if !self.meta.exclusive {
+            let (borrows, meta) = (&self.borrows, self.meta);
+            WorldBorrows::release_all(&borrows.components, &meta.components, usize::MAX);
+            WorldBorrows::release_all(&borrows.resources, &meta.resources, usize::MAX);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorldBorrows;
+    use crate::{
+        core::{AccessBitset, Frame},
+        system::{SystemId, SystemMeta, diagnostics::Timing},
+    };
+    use std::{
+        panic::{AssertUnwindSafe, catch_unwind},
+        sync::Arc,
+    };
+
+    fn meta(
+        components: &[(usize, bool)],
+        resources: &[(usize, bool)],
+        exclusive: bool,
+    ) -> SystemMeta {
+        let access = |indices: &[(usize, bool)]| {
+            let mut access = AccessBitset::with_capacity(4);
+            for (index, write) in indices {
+                match write {
+                    true => access.write(*index),
+                    false => access.read(*index),
+                };
+            }
+            access
+        };
+
+        SystemMeta {
+            id: SystemId(0),
+            name: Some("test".into()),
+            components: access(components),
+            resources: access(resources),
+            send: true,
+            exclusive,
+            frame: Frame::ZERO,
+            timing: Timing::default(),
+        }
+    }
+
+    #[test]
+    fn shared_borrows() {
+        let borrows = Arc::new(WorldBorrows::default());
+        let (a, b) = (
+            meta(&[(0, false)], &[(1, false)], false),
+            meta(&[(0, false), (1, true)], &[], false),
+        );
+        let _a = borrows.acquire(&a);
+        let _b = borrows.acquire(&b);
+    }
+
+    #[test]
+    fn conflicting_borrows_panic() {
+        let borrows = Arc::new(WorldBorrows::default());
+        let writer = meta(&[(1, true)], &[(0, false)], false);
+        let reader = meta(&[(0, false), (1, false)], &[], false);
+        let exclusive = meta(&[], &[], true);
+
+        let borrow = borrows.acquire(&writer);
+        let conflict = catch_unwind(AssertUnwindSafe(|| {
+            borrows.acquire(&reader);
+        }));
+        let message = conflict.unwrap_err();
+        assert_eq!(
+            message.downcast_ref::<String>().map(String::as_str),
+            Some("System test borrows component 1 while another system accesses it")
+        );
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| {
+                borrows.acquire(&exclusive);
+            }))
+            .is_err()
+        );
+
+        // Failed acquisitions release what they borrowed.
+        drop(borrow);
+        drop(borrows.acquire(&exclusive));
+        drop(borrows.acquire(&writer));
+        let _reader = borrows.acquire(&reader);
+    }
+}
@@ -1,41 +1,149 @@
-use super::{Component, Entity, Row, World};
-use crate::system::arg::SystemArg;
+use super::{
+    Bundle, Component, ComponentId, CrossWorldQueue, Entities, Entity, Event, Resource,
+    RoutedCommands, Row, TriggerTargets, World,
+};
+use crate::{
+    app::AppLabel,
+    system::{
+        SystemId, SystemName,
+        arg::{Deferred, SystemArg, SystemBuffer},
+        error::{SystemError, SystemFailure},
+    },
+};
 
 pub trait Command: Sized + Send + Sync + 'static {
     fn execute(self, world: &mut World);
+
+    /// Describes the command to [`CommandMiddleware`]. Custom commands are opaque by default.
+    fn kind(&self, _world: &World) -> CommandKind {
+        CommandKind::Opaque
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandKind {
+    Spawn,
+    Despawn(Entity),
+    Insert {
+        entity: Entity,
+        component: ComponentId,
+    },
+    Remove {
+        entity: Entity,
+        component: ComponentId,
+    },
+    Opaque,
+}
+
+/// Command stored behind a box so middleware can replace queued commands.
+pub struct BoxCommand(Box<dyn FnOnce(&mut World) + Send + Sync>);
+
+impl BoxCommand {
+    pub fn new<C: Command>(command: C) -> Self {
+        Self(Box::new(move |world| command.execute(world)))
+    }
+
+    pub fn execute(self, world: &mut World) {
+        (self.0)(world)
+    }
+}
+
+pub enum CommandDecision {
+    Allow,
+    /// Drops the command without executing it.
+    Skip,
+    /// Drops the command and executes the replacement instead.
+    /// Middleware registered after the one that replaced the command doesn't see the replacement.
+    Replace(BoxCommand),
+}
+
+pub struct CommandContext<'a> {
+    kind: CommandKind,
+    system: Option<&'a str>,
+    world: &'a World,
+}
+
+impl<'a> CommandContext<'a> {
+    pub fn kind(&self) -> CommandKind {
+        self.kind
+    }
+
+    /// Name of the system that queued the command.
+    pub fn system(&self) -> Option<&'a str> {
+        self.system
+    }
+
+    pub fn world(&self) -> &'a World {
+        self.world
+    }
+}
+
+/// Runs in registration order for every command applied from a [`CommandQueue`].
+pub type CommandMiddleware = fn(&mut CommandContext) -> CommandDecision;
+
+struct CommandVTable {
+    execute: unsafe fn(&[u8], &mut World),
+    kind: unsafe fn(&[u8], &World) -> CommandKind,
+    drop: unsafe fn(&[u8]),
+    size: usize,
 }
 
-pub type ExecuteCommand = fn(&[u8], &mut World) -> usize;
+impl CommandVTable {
+    const fn of<C: Command>() -> Self {
+        Self {
+            execute: |bytes, world| {
+                let command = unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const C) };
+                command.execute(world);
+            },
+            kind: |bytes, world| {
+                let command = unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const C) };
+                std::mem::ManuallyDrop::new(command).kind(world)
+            },
+            drop: |bytes| {
+                drop(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const C) });
+            },
+            size: std::mem::size_of::<C>(),
+        }
+    }
+}
 
-pub struct CommandBuffer {
+/// Queue of commands stored back to back in a byte buffer, each behind a pointer to its vtable,
+/// so queuing a command doesn't allocate. Systems queue commands through [`Commands`], but queues
+/// can also be built by hand and applied with [`CommandQueue::execute`] or [`Commands::append`].
+pub struct CommandQueue {
     buffer: Vec<u8>,
+    len: usize,
+    system: Option<SystemName>,
+    routed: RoutedCommands,
 }
 
-impl CommandBuffer {
+impl CommandQueue {
     pub fn new() -> Self {
-        Self { buffer: vec![] }
+        Self {
+            buffer: vec![],
+            len: 0,
+            system: None,
+            routed: RoutedCommands::default(),
+        }
+    }
+
+    /// Number of queued commands.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn add<C: Command>(&mut self, command: C) {
         #[repr(C, packed)]
         struct RawCommand<C: Command> {
-            execute: ExecuteCommand,
+            vtable: &'static CommandVTable,
             command: C,
         }
 
-        impl<C: Command> RawCommand<C> {
-            pub fn new(command: C) -> Self {
-                Self {
-                    execute: |bytes, world| {
-                        let command = unsafe { std::ptr::read::<C>(bytes.as_ptr() as *const C) };
-                        command.execute(world);
-
-                        std::mem::size_of::<C>()
-                    },
-                    command,
-                }
-            }
-        }
+        let vtable: &'static CommandVTable = const { &CommandVTable::of::<C>() };
 
         unsafe {
             let offset = self.buffer.len();
@@ -44,98 +152,448 @@ impl CommandBuffer {
             let ptr = self.buffer.as_mut_ptr().add(offset);
 
             ptr.cast::<RawCommand<C>>()
-                .write_unaligned(RawCommand::new(command));
+                .write_unaligned(RawCommand { vtable, command });
 
             self.buffer
                 .set_len(offset + std::mem::size_of::<RawCommand<C>>());
         };
+
+        self.len += 1;
+    }
+
+    /// Commands for the world with the label. They're moved to the world this queue is applied
+    /// to, and from there to the target by [`App::route_commands`](crate::app::App::route_commands).
+    pub fn for_world(&mut self, label: impl AppLabel) -> &mut CrossWorldQueue {
+        self.routed.queue(label.name())
+    }
+
+    /// Moves the commands of `other` to the end of this queue, leaving `other` empty.
+    pub fn append(&mut self, other: &mut CommandQueue) {
+        self.buffer.append(&mut other.buffer);
+        self.len += std::mem::take(&mut other.len);
+        self.routed.append(&mut other.routed);
     }
 
+    /// Executes the queued commands in order, running them through the world's
+    /// [`CommandMiddleware`]. If a command panics, the commands after it are leaked.
     pub fn execute(&mut self, world: &mut World) {
-        let mut start = 0;
-
-        while start < self.buffer.len() {
-            let executor = unsafe {
-                self.buffer[start..start + std::mem::size_of::<ExecuteCommand>()]
-                    .as_ptr()
-                    .cast::<ExecuteCommand>()
-                    .as_ref()
-                    .unwrap_unchecked()
+        let buffer = std::mem::take(&mut self.buffer);
+        self.len = 0;
+
+        for (vtable, command) in RawCommands::new(&buffer) {
+            if world.command_middleware.is_empty() {
+                unsafe { (vtable.execute)(command, world) };
+                continue;
+            }
+
+            let mut context = CommandContext {
+                kind: unsafe { (vtable.kind)(command, world) },
+                system: self.system.as_deref(),
+                world,
             };
 
-            start += std::mem::size_of::<ExecuteCommand>();
+            let mut decision = CommandDecision::Allow;
+            for middleware in &world.command_middleware {
+                decision = middleware(&mut context);
+                if !matches!(decision, CommandDecision::Allow) {
+                    break;
+                }
+            }
 
-            let command = &self.buffer[start..];
-            start += executor(command, world);
+            match decision {
+                CommandDecision::Allow => unsafe { (vtable.execute)(command, world) },
+                CommandDecision::Skip => unsafe { (vtable.drop)(command) },
+                CommandDecision::Replace(replacement) => {
+                    unsafe { (vtable.drop)(command) };
+                    replacement.execute(world);
+                }
+            }
         }
 
+        // Reuse the allocation for the next frame.
+        self.buffer = buffer;
         self.buffer.clear();
+
+        if !self.routed.is_empty() {
+            world.routed_commands().append(&mut self.routed);
+        }
+    }
+}
+
+impl Default for CommandQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for CommandQueue {
+    fn drop(&mut self) {
+        for (vtable, command) in RawCommands::new(&self.buffer) {
+            unsafe { (vtable.drop)(command) };
+        }
+    }
+}
+
+/// Walks the (vtable, payload) pairs of a command buffer.
+struct RawCommands<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> RawCommands<'a> {
+    fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<'a> Iterator for RawCommands<'a> {
+    type Item = (&'static CommandVTable, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        const VTABLE: usize = std::mem::size_of::<&'static CommandVTable>();
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let vtable = unsafe {
+            self.buffer
+                .as_ptr()
+                .cast::<&'static CommandVTable>()
+                .read_unaligned()
+        };
+        let (command, rest) = self.buffer[VTABLE..].split_at(vtable.size);
+        self.buffer = rest;
+        Some((vtable, command))
+    }
+}
+
+impl SystemBuffer for CommandQueue {
+    fn apply(&mut self, world: &mut World) {
+        self.execute(world);
     }
 }
 
 pub struct Commands<'world, 'state> {
-    commands: &'state mut CommandBuffer,
-    _marker: std::marker::PhantomData<&'world ()>,
+    commands: &'state mut CommandQueue,
+    entities: &'world Entities,
 }
 
 impl<'world, 'state> Commands<'world, 'state> {
-    pub fn new(commands: &'state mut CommandBuffer) -> Self {
-        Commands {
-            commands,
-            _marker: std::marker::PhantomData,
-        }
+    pub fn new(commands: &'state mut CommandQueue, entities: &'world Entities) -> Self {
+        Commands { commands, entities }
     }
 
     pub fn add<C: Command>(&mut self, command: C) {
         self.commands.add(command);
     }
+
+    /// Queues the commands of a hand built queue after the ones already queued by the system.
+    pub fn append(&mut self, queue: &mut CommandQueue) {
+        self.commands.append(queue);
+    }
+
+    /// Reserves an entity that is added to the world when the commands are applied.
+    pub fn spawn(&mut self) -> EntityCommands<'_> {
+        let entity = self.entities.reserve();
+        self.commands.add(SpawnReserved(entity));
+        self.entity(entity)
+    }
+
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_> {
+        EntityCommands {
+            entity,
+            commands: self.commands,
+        }
+    }
+
+    pub fn insert_resource<R: Resource + Send + Sync>(&mut self, resource: R) {
+        self.commands.add(InsertResource(resource));
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self) {
+        self.commands.add(RemoveResource::<R>::new());
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.commands.add(Despawn(entity));
+    }
+
+    pub fn insert<C: Component>(&mut self, entity: Entity, component: C) {
+        self.commands.add(Insert { entity, component });
+    }
+
+    pub fn remove<C: Component>(&mut self, entity: Entity) {
+        self.commands.add(Remove::<C>::new(entity));
+    }
+
+    /// Triggers the event for its global observers, see [`World::trigger_event`].
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.commands.add(TriggerEvent(event));
+    }
+
+    /// Triggers the event for each target, see [`World::trigger_targets`].
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: impl TriggerTargets) {
+        self.commands.add(TriggerTargeted { event, targets });
+    }
+
+    /// Runs a one-shot system, see [`World::run_system`].
+    pub fn run_system(&mut self, id: SystemId) {
+        self.commands.add(RunSystem(id));
+    }
+
+    /// Queues commands for another world, see [`CommandQueue::for_world`].
+    pub fn for_world(&mut self, label: impl AppLabel) -> &mut CrossWorldQueue {
+        self.commands.for_world(label)
+    }
 }
 
 unsafe impl SystemArg for Commands<'_, '_> {
     type Item<'world, 'state> = Commands<'world, 'state>;
 
-    type State = CommandBuffer;
+    type State = CommandQueue;
 
-    fn init(_: &mut World) -> Self::State {
-        CommandBuffer::new()
+    fn init(world: &mut World) -> Self::State {
+        Deferred::<CommandQueue>::init(world)
+    }
+
+    fn deferred() -> bool {
+        true
     }
 
     fn apply(state: &mut Self::State, world: &mut World) {
-        state.execute(world);
+        Deferred::<CommandQueue>::apply(state, world);
     }
 
     unsafe fn get<'world, 'state>(
         state: &'state mut Self::State,
-        _: super::WorldCell<'world>,
-        _: &crate::system::SystemMeta,
+        world: super::WorldCell<'world>,
+        system: &crate::system::SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        Commands::new(state)
+        let buffer = unsafe { Deferred::<CommandQueue>::get(state, world, system) }.into_inner();
+        if buffer.system.is_none() {
+            buffer.system = system.name.clone();
+        }
+
+        Commands::new(buffer, unsafe { world.get() }.entities())
     }
 }
 
+/// Queues commands for a single entity, see [`Commands::spawn`] and [`Commands::entity`].
+pub struct EntityCommands<'a> {
+    entity: Entity,
+    commands: &'a mut CommandQueue,
+}
+
+impl EntityCommands<'_> {
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn insert<C: Component>(self, component: C) -> Self {
+        self.commands.add(Insert {
+            entity: self.entity,
+            component,
+        });
+        self
+    }
+
+    pub fn remove<C: Component>(self) -> Self {
+        self.commands.add(Remove::<C>::new(self.entity));
+        self
+    }
+
+    pub fn despawn(self) {
+        self.commands.add(Despawn(self.entity));
+    }
+
+    pub fn add_command<C: Command>(self, command: C) -> Self {
+        self.commands.add(command);
+        self
+    }
+}
+
+/// Spawns an entity with the components of the row.
+pub struct Spawn(pub Row);
+
+impl Command for Spawn {
+    fn execute(self, world: &mut World) {
+        let entity = world.spawn();
+        world.add_components(entity, self.0);
+    }
+
+    fn kind(&self, _: &World) -> CommandKind {
+        CommandKind::Spawn
+    }
+}
+
+/// Adds an entity reserved with [`Entities::reserve`] to the world.
+pub struct SpawnReserved(pub Entity);
+
+impl Command for SpawnReserved {
+    fn execute(self, world: &mut World) {
+        world.entities.flush();
+        if world.entities.is_alive(self.0) {
+            world.archetypes.add_entity(self.0);
+        }
+    }
+
+    fn kind(&self, _: &World) -> CommandKind {
+        CommandKind::Spawn
+    }
+}
+
+pub struct Despawn(pub Entity);
+
+impl Command for Despawn {
+    fn execute(self, world: &mut World) {
+        world.despawn(self.0);
+    }
+
+    fn kind(&self, _: &World) -> CommandKind {
+        CommandKind::Despawn(self.0)
+    }
+}
+
+pub struct Insert<C: Component> {
+    pub entity: Entity,
+    pub component: C,
+}
+
+impl<C: Component> Command for Insert<C> {
+    fn execute(self, world: &mut World) {
+        world.add_component(self.entity, self.component);
+    }
+
+    fn kind(&self, world: &World) -> CommandKind {
+        match world.components().get_id::<C>() {
+            Some(component) => CommandKind::Insert {
+                entity: self.entity,
+                component,
+            },
+            None => CommandKind::Opaque,
+        }
+    }
+}
+
+pub struct Remove<C: Component> {
+    pub entity: Entity,
+    _marker: std::marker::PhantomData<fn(C)>,
+}
+
+impl<C: Component> Remove<C> {
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: Component> Command for Remove<C> {
+    fn execute(self, world: &mut World) {
+        world.remove_component::<C>(self.entity);
+    }
+
+    fn kind(&self, world: &World) -> CommandKind {
+        match world.components().get_id::<C>() {
+            Some(component) => CommandKind::Remove {
+                entity: self.entity,
+                component,
+            },
+            None => CommandKind::Opaque,
+        }
+    }
+}
+
+pub struct InsertResource<R: Resource + Send + Sync>(pub R);
+
+impl<R: Resource + Send + Sync> Command for InsertResource<R> {
+    fn execute(self, world: &mut World) {
+        world.add_resource(self.0);
+    }
+}
+
+pub struct RemoveResource<R: Resource>(std::marker::PhantomData<fn(R)>);
+
+impl<R: Resource> RemoveResource<R> {
+    pub fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+impl<R: Resource> Default for RemoveResource<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Resource> Command for RemoveResource<R> {
+    fn execute(self, world: &mut World) {
+        world.remove_resource_deferred::<R>();
+    }
+}
+
+pub struct TriggerEvent<E: Event>(pub E);
+
+impl<E: Event> Command for TriggerEvent<E> {
+    fn execute(self, world: &mut World) {
+        world.trigger_event(self.0);
+    }
+}
+
+pub struct TriggerTargeted<E: Event, T: TriggerTargets> {
+    pub event: E,
+    pub targets: T,
+}
+
+impl<E: Event, T: TriggerTargets> Command for TriggerTargeted<E, T> {
+    fn execute(self, world: &mut World) {
+        world.trigger_targets(self.event, self.targets);
+    }
+}
+
+/// Runs a one-shot system, reporting a [`SystemFailure`] if it can't be run.
+pub struct RunSystem(pub SystemId);
+
+impl Command for RunSystem {
+    fn execute(self, world: &mut World) {
+        if let Err(error) = world.run_system(self.0) {
+            let failure = SystemFailure {
+                system: None,
+                error: SystemError::from(error),
+            };
+            world.system_errors().push(failure);
+        }
+    }
+}
+
+/// Spawns entities from any system. Entities are reserved right away and added to the world,
+/// along with their components, when the spawner is applied.
 pub struct Spawner<'world, 'state> {
-    world: &'world mut World,
+    world: &'world World,
     entities: &'state mut Vec<(Entity, Row)>,
-    _marker: std::marker::PhantomData<&'state ()>,
 }
 
 impl<'world, 'state> Spawner<'world, 'state> {
-    pub fn new(world: &'world mut World, entities: &'state mut Vec<(Entity, Row)>) -> Self {
-        Spawner {
-            world,
-            entities,
-            _marker: std::marker::PhantomData,
-        }
+    pub fn new(world: &'world World, entities: &'state mut Vec<(Entity, Row)>) -> Self {
+        Spawner { world, entities }
     }
 
     pub fn spawn(&mut self) -> Spawned<'world, 'state, '_> {
-        let id = self.world.spawn();
+        let id = self.world.entities.reserve();
         Spawned {
             id,
             components: Row::new(),
             spawner: self,
         }
     }
+
+    /// Reserves an entity that is added to the bundle's archetype when the spawner is applied.
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> Entity {
+        let row = bundle.into_row(self.world);
+        let id = self.world.entities.reserve();
+        self.entities.push((id, row));
+        id
+    }
 }
 
 unsafe impl SystemArg for Spawner<'_, '_> {
@@ -149,19 +607,25 @@ unsafe impl SystemArg for Spawner<'_, '_> {
 
     unsafe fn get<'world, 'state>(
         state: &'state mut Self::State,
-        mut world: super::WorldCell<'world>,
+        world: super::WorldCell<'world>,
         _: &crate::system::SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        unsafe { Spawner::new(world.get_mut(), state) }
+        unsafe { Spawner::new(world.get(), state) }
     }
 
-    fn exclusive() -> bool {
+    fn deferred() -> bool {
         true
     }
 
     fn apply(state: &mut Self::State, world: &mut World) {
+        world.entities.flush();
         for (entity, components) in state.drain(..) {
-            world.add_components(entity, components);
+            match components.is_empty() {
+                true => {
+                    world.archetypes.add_entity(entity);
+                }
+                false => world.add_components(entity, components),
+            }
         }
     }
 }
@@ -185,3 +649,303 @@ impl<'world, 'state, 'spawner> Spawned<'world, 'state, 'spawner> {
         id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BoxCommand, Command, CommandContext, CommandDecision, CommandKind, CommandQueue, Commands,
+        Insert,
+    };
+    use crate::{
+        app::App,
+        core::Frame,
+        system::{
+            arg::SystemArg,
+            executor::{ExecutorConfig, RunMode},
+            schedule::Phase,
+        },
+        world::{ArchetypeId, Component, Entity, Resource, Spawner, World},
+    };
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    struct Update;
+    impl Phase for Update {}
+
+    struct Protected(#[allow(dead_code)] u8);
+    impl Component for Protected {}
+
+    #[derive(Debug, PartialEq)]
+    struct Legacy(u32);
+    impl Component for Legacy {}
+
+    #[derive(Debug, PartialEq, Default)]
+    struct Modern(u32);
+    impl Component for Modern {}
+
+    struct Targets {
+        protected: Entity,
+        unprotected: Entity,
+    }
+    impl Resource for Targets {}
+
+    fn protect(ctx: &mut CommandContext) -> CommandDecision {
+        match ctx.kind() {
+            CommandKind::Despawn(entity)
+                if ctx.world().get_component::<Protected>(entity).is_some() =>
+            {
+                CommandDecision::Skip
+            }
+            _ => CommandDecision::Allow,
+        }
+    }
+
+    fn upgrade(ctx: &mut CommandContext) -> CommandDecision {
+        let legacy = ctx.world().components().get_id::<Legacy>();
+        match ctx.kind() {
+            CommandKind::Insert { entity, component } if Some(component) == legacy => {
+                assert!(ctx.system().is_some_and(|name| name.contains("queue")));
+                CommandDecision::Replace(BoxCommand::new(Insert {
+                    entity,
+                    component: Modern::default(),
+                }))
+            }
+            _ => CommandDecision::Allow,
+        }
+    }
+
+    fn queue(targets: &Targets, mut commands: Commands) {
+        commands.despawn(targets.protected);
+        commands.despawn(targets.unprotected);
+        commands.insert(targets.protected, Legacy(1));
+    }
+
+    #[test]
+    fn command_middleware() {
+        let mut builder = App::new();
+        builder
+            .register::<Protected>()
+            .register::<Legacy>()
+            .register::<Modern>();
+
+        let world = builder.world_mut();
+        let protected = world.spawn();
+        world.add_component(protected, Protected(0));
+        let unprotected = world.spawn();
+        world.add_component(unprotected, Legacy(0));
+        world.add_command_middleware(protect);
+        world.add_command_middleware(upgrade);
+
+        let mut app = builder
+            .add_resource(Targets {
+                protected,
+                unprotected,
+            })
+            .add_systems(Update, queue)
+            .build();
+        app.run(Update);
+
+        let world = app.world();
+        assert!(world.get_component::<Protected>(protected).is_some());
+        assert!(world.get_component::<Legacy>(protected).is_none());
+        assert_eq!(world.get_component::<Modern>(protected), Some(&Modern(0)));
+        assert!(world.get_component::<Legacy>(unprotected).is_none());
+    }
+
+    struct Cleanup;
+    impl Phase for Cleanup {}
+
+    struct Teardown;
+    impl Phase for Teardown {}
+
+    #[derive(Default)]
+    struct Created(Mutex<Vec<Entity>>);
+    impl Resource for Created {}
+
+    struct Counter(u32);
+    impl Resource for Counter {}
+
+    fn create(mut commands: Commands, created: &Created) {
+        let entity = commands.spawn().insert(Legacy(1)).insert(Modern(2)).id();
+        created.0.lock().unwrap().push(entity);
+        commands.insert_resource(Counter(1));
+    }
+
+    fn cleanup(mut commands: Commands, created: &Created) {
+        for entity in created.0.lock().unwrap().iter() {
+            commands
+                .entity(*entity)
+                .remove::<Legacy>()
+                .insert(Protected(0));
+        }
+        commands.remove_resource::<Counter>();
+    }
+
+    fn teardown(mut commands: Commands, created: &Created) {
+        for entity in created.0.lock().unwrap().drain(..) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    #[test]
+    fn entity_commands() {
+        let mut builder = App::new();
+        builder
+            .register::<Protected>()
+            .register::<Legacy>()
+            .register::<Modern>()
+            .add_resource(Created::default());
+
+        let mut app = builder
+            .add_systems(Update, create)
+            .add_systems(Cleanup, cleanup)
+            .add_systems(Teardown, teardown)
+            .build();
+
+        app.run(Update);
+        let entity = app.world().resource::<Created>().0.lock().unwrap()[0];
+        let world = app.world_mut();
+        assert!(world.entities().is_alive(entity));
+        assert_eq!(world.get_component::<Legacy>(entity), Some(&Legacy(1)));
+        assert_eq!(world.get_component::<Modern>(entity), Some(&Modern(2)));
+        assert_eq!(world.resource::<Counter>().0, 1);
+        assert_ne!(world.spawn(), entity);
+
+        app.run(Cleanup);
+        let world = app.world();
+        assert!(world.get_component::<Legacy>(entity).is_none());
+        assert!(world.get_component::<Protected>(entity).is_some());
+        assert!(world.try_resource::<Counter>().is_none());
+
+        app.run(Teardown);
+        assert!(!app.world().entities().is_alive(entity));
+        assert!(app.world().get_component::<Modern>(entity).is_none());
+    }
+
+    #[test]
+    fn parallel_spawning() {
+        fn spawn_commands(mut commands: Commands) {
+            for i in 0..50 {
+                commands.spawn().insert(Legacy(i));
+            }
+        }
+
+        fn spawn_bundles(mut spawner: Spawner) {
+            for i in 0..50 {
+                spawner.spawn_bundle((Modern(i),));
+            }
+            spawner.spawn().with(Legacy(50)).finish();
+            spawner.spawn().finish();
+        }
+
+        assert!(!<Spawner as SystemArg>::exclusive());
+
+        let mut builder = App::new();
+        builder.schedule_mut().set_mode(RunMode::Parallel);
+        builder.register::<Legacy>().register::<Modern>();
+        let mut app = builder
+            .add_resource(ExecutorConfig { threads: 2 })
+            .add_systems(Update, spawn_commands)
+            .add_systems(Update, spawn_bundles)
+            .build();
+        app.run(Update);
+        app.run(Update);
+
+        let world = app.world_mut();
+        let mut entities = world.query::<Entity>().iter().collect::<Vec<_>>();
+        entities.sort_by_key(|entity| entity.id());
+        entities.dedup();
+        assert_eq!(entities.len(), 2 * 102);
+        assert!(
+            entities
+                .iter()
+                .all(|entity| world.entities().is_alive(*entity))
+        );
+
+        let legacy = world.query::<&Legacy>().iter().count();
+        let modern = world.query::<&Modern>().iter().count();
+        assert_eq!((legacy, modern), (2 * 51, 2 * 50));
+    }
+
+    #[test]
+    fn update_spawns_reserved_entities() {
+        let mut world = World::new();
+        let reserved = world.entities().reserve();
+        assert!(world.archetypes().entity_archetype(reserved).is_none());
+
+        let frame = world.frame();
+        world.update();
+        assert_eq!(world.frame(), frame + Frame(1));
+        assert!(world.entities().is_alive(reserved));
+        assert_eq!(
+            world.archetypes().entity_archetype(reserved),
+            Some(ArchetypeId::EMPTY)
+        );
+
+        let spawned = world.spawn();
+        assert_ne!(spawned, reserved);
+        assert_eq!(world.query::<Entity>().iter().count(), 2);
+    }
+
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    impl Command for Counted {
+        fn execute(self, world: &mut World) {
+            world.spawn();
+        }
+    }
+
+    struct Pending(Mutex<CommandQueue>);
+    impl Resource for Pending {}
+
+    fn queue_commands(pending: &Pending, mut commands: Commands) {
+        commands.append(&mut pending.0.lock().unwrap());
+    }
+
+    #[test]
+    fn command_queue() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut world = World::new();
+        world.register::<Legacy>();
+        let entity = world.spawn();
+
+        let mut queue = CommandQueue::new();
+        queue.add(Counted(drops.clone()));
+        queue.add(Insert {
+            entity,
+            component: Legacy(3),
+        });
+
+        let mut other = CommandQueue::new();
+        other.add(Counted(drops.clone()));
+        queue.append(&mut other);
+        assert_eq!((queue.len(), other.len()), (3, 0));
+
+        queue.execute(&mut world);
+        assert!(queue.is_empty());
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+        assert_eq!(world.get_component::<Legacy>(entity), Some(&Legacy(3)));
+        assert_eq!(world.query::<Entity>().iter().count(), 3);
+
+        // Commands that are never executed are dropped with the queue.
+        queue.add(Counted(drops.clone()));
+        drop(queue);
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+
+        // Hand built queues can be handed to a system's commands.
+        let mut queue = CommandQueue::new();
+        queue.add(Counted(drops.clone()));
+        let mut app = App::new().add_systems(Update, queue_commands).build();
+        app.world_mut().add_resource(Pending(Mutex::new(queue)));
+        app.run(Update);
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+    }
+}
@@ -1,23 +1,157 @@
-use crate::ext;
-use std::{alloc::Layout, any::TypeId, collections::HashMap};
+use super::{
+    ComponentSerde, Entity, Lifecycle, MapEntities, MapEntitiesFn, World,
+    archetype::table::TableCell, merge::map_component_entities,
+};
+use crate::{
+    core::{StableHasher, TypeMeta},
+    ext,
+};
+use std::{
+    alloc::Layout,
+    any::TypeId,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
 
 pub trait Component: Send + Sync + 'static {}
 
+/// Marker for components whose bytes can be copied directly.
+///
+/// # Safety
+/// The type must be `#[repr(C)]`, contain no padding and no pointers, so any copy of its bytes
+/// is a valid value.
+pub unsafe trait PodComponent: Component + Copy {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ComponentId(pub(crate) u32);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentError {
+    /// The component type, by name, was never registered with the world.
+    Unregistered(&'static str),
+}
+
+impl std::fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComponentError::Unregistered(name) => write!(f, "Component not registered: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ComponentError {}
+
+/// Hashes the value behind the pointer with a [`StableHasher`].
+pub type HashFn = unsafe fn(*const u8) -> u64;
+
+pub(crate) unsafe fn hash_value<T: Hash>(ptr: *const u8) -> u64 {
+    let mut hasher = StableHasher::new();
+    unsafe { &*(ptr as *const T) }.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Writes a clone of the value behind the first pointer to the second, uninitialized one.
+pub type CloneFn = unsafe fn(*const u8, *mut u8);
+
+pub(crate) unsafe fn clone_value<T: Clone>(src: *const u8, dst: *mut u8) {
+    unsafe { (dst as *mut T).write((*(src as *const T)).clone()) }
+}
+
+/// Builds the default value of a required component, see [`Components::register_required`].
+pub type RequiredFn = fn() -> TableCell;
+
+fn default_cell<C: Component + Default>() -> TableCell {
+    TableCell::new(C::default())
+}
+
+/// Runs with the entity whose component changed, see [`ComponentHooks`].
+pub type ComponentHook = fn(&mut World, Entity);
+
+/// Callbacks a component runs on its own lifecycle events, registered with
+/// [`World::register_with_hooks`]. Add and insert hooks run after the component is written,
+/// remove and despawn hooks run while it can still be read.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComponentHooks {
+    on_add: Option<ComponentHook>,
+    on_insert: Option<ComponentHook>,
+    on_remove: Option<ComponentHook>,
+    on_despawn: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The entity didn't have the component before.
+    pub fn on_add(mut self, hook: ComponentHook) -> Self {
+        self.on_add = Some(hook);
+        self
+    }
+
+    /// The component was added or replaced.
+    pub fn on_insert(mut self, hook: ComponentHook) -> Self {
+        self.on_insert = Some(hook);
+        self
+    }
+
+    /// The component is removed, including when its entity is despawned.
+    pub fn on_remove(mut self, hook: ComponentHook) -> Self {
+        self.on_remove = Some(hook);
+        self
+    }
+
+    /// The entity holding the component is despawned.
+    pub fn on_despawn(mut self, hook: ComponentHook) -> Self {
+        self.on_despawn = Some(hook);
+        self
+    }
+
+    pub fn get(&self, event: Lifecycle) -> Option<ComponentHook> {
+        match event {
+            Lifecycle::Add => self.on_add,
+            Lifecycle::Insert => self.on_insert,
+            Lifecycle::Remove => self.on_remove,
+            Lifecycle::Despawn => self.on_despawn,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.on_add.is_none()
+            && self.on_insert.is_none()
+            && self.on_remove.is_none()
+            && self.on_despawn.is_none()
+    }
+}
+
 pub struct ComponentMeta {
     id: ComponentId,
+    type_id: TypeId,
     name: &'static str,
-    layout: Layout,
+    meta: TypeMeta,
+    pod: bool,
+    hash: Option<HashFn>,
+    clone: Option<CloneFn>,
+    serde: Option<ComponentSerde>,
+    map_entities: Option<MapEntitiesFn>,
+    hooks: ComponentHooks,
+    required: Vec<(ComponentId, RequiredFn)>,
 }
 
 impl ComponentMeta {
     pub fn new<C: Component>(id: ComponentId) -> Self {
         Self {
             id,
+            type_id: TypeId::of::<C>(),
             name: ext::short_type_name::<C>(),
-            layout: Layout::new::<C>(),
+            meta: TypeMeta::new::<C>(),
+            pod: false,
+            hash: None,
+            clone: None,
+            serde: None,
+            map_entities: None,
+            hooks: ComponentHooks::default(),
+            required: vec![],
         }
     }
 
@@ -25,12 +159,54 @@ impl ComponentMeta {
         self.id
     }
 
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
     pub fn name(&self) -> &'static str {
         self.name
     }
 
     pub fn layout(&self) -> Layout {
-        self.layout
+        self.meta.layout
+    }
+
+    pub fn type_meta(&self) -> &TypeMeta {
+        &self.meta
+    }
+
+    /// Whether the component was registered as a [`PodComponent`].
+    pub fn is_pod(&self) -> bool {
+        self.pod
+    }
+
+    /// Hash function of components registered as hashable.
+    pub fn hash_fn(&self) -> Option<HashFn> {
+        self.hash
+    }
+
+    /// Clone function of components registered as cloneable or POD.
+    pub fn clone_fn(&self) -> Option<CloneFn> {
+        self.clone
+    }
+
+    /// Scene (de)serializers of components registered with serde support.
+    pub fn serde(&self) -> Option<ComponentSerde> {
+        self.serde
+    }
+
+    /// Entity remapping of components registered with [`World::register_map_entities`].
+    pub fn map_entities_fn(&self) -> Option<MapEntitiesFn> {
+        self.map_entities
+    }
+
+    pub fn hooks(&self) -> &ComponentHooks {
+        &self.hooks
+    }
+
+    /// Components inserted with their default value along with this one.
+    pub fn required(&self) -> &[(ComponentId, RequiredFn)] {
+        &self.required
     }
 }
 
@@ -63,6 +239,111 @@ impl Components {
         }
     }
 
+    pub fn register_pod<C: PodComponent>(&mut self) -> ComponentId {
+        let id = self.register::<C>();
+        self.components[id.0 as usize].pod = true;
+        self.components[id.0 as usize].clone = Some(clone_value::<C>);
+        id
+    }
+
+    pub fn register_clone<C: Component + Clone>(&mut self) -> ComponentId {
+        let id = self.register::<C>();
+        self.components[id.0 as usize].clone = Some(clone_value::<C>);
+        id
+    }
+
+    pub fn register_hashable<C: Component + Hash>(&mut self) -> ComponentId {
+        let id = self.register::<C>();
+        self.components[id.0 as usize].hash = Some(hash_value::<C>);
+        id
+    }
+
+    pub fn register_serde<C: Component>(&mut self, serde: ComponentSerde) -> ComponentId {
+        let id = self.register::<C>();
+        self.components[id.0 as usize].serde = Some(serde);
+        id
+    }
+
+    pub fn register_map_entities<C: Component + MapEntities>(&mut self) -> ComponentId {
+        let id = self.register::<C>();
+        self.components[id.0 as usize].map_entities = Some(map_component_entities::<C>);
+        id
+    }
+
+    /// Registers a component described by another world's meta, matching it by type.
+    pub fn register_meta(&mut self, meta: &ComponentMeta) -> ComponentId {
+        if let Some(id) = self.map.get(&meta.type_id) {
+            return *id;
+        }
+
+        let id = ComponentId(self.components.len() as u32);
+        self.components.push(ComponentMeta {
+            id,
+            type_id: meta.type_id,
+            name: meta.name,
+            meta: meta.meta,
+            pod: meta.pod,
+            hash: meta.hash,
+            clone: meta.clone,
+            serde: meta.serde,
+            map_entities: meta.map_entities,
+            hooks: meta.hooks,
+            // Required ids belong to the other world.
+            required: vec![],
+        });
+        self.map.insert(meta.type_id, id);
+
+        id
+    }
+
+    /// Replaces the hooks of the component, registering it if needed.
+    pub fn register_with_hooks<C: Component>(&mut self, hooks: ComponentHooks) -> ComponentId {
+        let id = self.register::<C>();
+        self.components[id.0 as usize].hooks = hooks;
+        id
+    }
+
+    /// Makes inserting `C` also insert `R::default()` if the entity doesn't have `R`.
+    pub fn register_required<C: Component, R: Component + Default>(&mut self) -> ComponentId {
+        let id = self.register::<C>();
+        let required = self.register::<R>();
+        let meta = &mut self.components[id.0 as usize];
+        if id != required && !meta.required.iter().any(|(id, _)| *id == required) {
+            meta.required.push((required, default_cell::<R>));
+        }
+
+        id
+    }
+
+    /// Components required by the given ones, directly or through other required components,
+    /// that aren't part of them.
+    pub fn required_by(&self, components: &[ComponentId]) -> Vec<(ComponentId, RequiredFn)> {
+        let mut required = vec![];
+        if components
+            .iter()
+            .all(|id| self.meta(*id).is_none_or(|meta| meta.required.is_empty()))
+        {
+            return required;
+        }
+
+        let mut pending = components.to_vec();
+
+        while let Some(id) = pending.pop() {
+            let Some(meta) = self.meta(id) else {
+                continue;
+            };
+
+            for (id, constructor) in &meta.required {
+                if !components.contains(id) && !required.iter().any(|(r, _)| r == id) {
+                    required.push((*id, *constructor));
+                    pending.push(*id);
+                }
+            }
+        }
+
+        required
+    }
+
     pub fn get<C: Component>(&self) -> Option<&ComponentMeta> {
         self.map.get(&TypeId::of::<C>()).and_then(|id| {
             self.components
@@ -71,15 +352,23 @@ impl Components {
         })
     }
 
+    pub fn meta(&self, id: ComponentId) -> Option<&ComponentMeta> {
+        self.components.get(id.0 as usize)
+    }
+
     pub fn get_id<C: Component>(&self) -> Option<ComponentId> {
         self.map.get(&TypeId::of::<C>()).copied()
     }
 
+    pub fn try_get_id<C: Component>(&self) -> Result<ComponentId, ComponentError> {
+        self.get_id::<C>()
+            .ok_or(ComponentError::Unregistered(std::any::type_name::<C>()))
+    }
+
+    /// Panics with a [`ComponentError`] if the component isn't registered.
     pub unsafe fn get_id_unchecked<C: Component>(&self) -> ComponentId {
-        self.map
-            .get(&TypeId::of::<C>())
-            .copied()
-            .unwrap_or_else(|| panic!("Component not registered: {}", std::any::type_name::<C>()))
+        self.try_get_id::<C>()
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 
     pub fn metas(&self) -> &[ComponentMeta] {
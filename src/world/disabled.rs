@@ -0,0 +1,25 @@
+use super::{Component, Entity, World};
+use serde::{Deserialize, Serialize};
+
+/// Marks an entity as disabled. Queries skip disabled entities unless they mention `Disabled`
+/// themselves, e.g. `With<Disabled>` or `Option<&Disabled>`, or opt in with
+/// [`IncludeDisabled`](crate::system::query::IncludeDisabled).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Disabled;
+
+impl Component for Disabled {}
+
+impl World {
+    /// Hides the entity from queries without despawning it.
+    pub fn disable(&mut self, entity: Entity) {
+        self.add_component(entity, Disabled);
+    }
+
+    pub fn enable(&mut self, entity: Entity) {
+        self.remove_component::<Disabled>(entity);
+    }
+
+    pub fn is_disabled(&self, entity: Entity) -> bool {
+        self.get_component::<Disabled>(entity).is_some()
+    }
+}
@@ -1,6 +1,11 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Range, RangeFrom},
+    sync::atomic::{AtomicU32, Ordering},
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Entity {
     id: u32,
     generation: u32,
@@ -34,10 +39,54 @@ impl std::fmt::Display for Entity {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnAtError {
+    /// The slot is held by a live entity with a different generation.
+    Occupied(Entity),
+    /// The slot already issued a newer or equal generation.
+    Stale { entity: Entity, generation: u32 },
+}
+
+impl std::fmt::Display for SpawnAtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnAtError::Occupied(entity) => write!(f, "Slot is occupied by {}", entity),
+            SpawnAtError::Stale { entity, generation } => write!(
+                f,
+                "{} is stale, the slot is at generation {}",
+                entity, generation
+            ),
+        }
+    }
+}
+
 pub struct Entities {
     current: u32,
     free: Vec<u32>,
+    /// Fresh indices jumped over by [`Entities::spawn_at`], handed out before new ones. Kept as
+    /// sorted ranges so claiming a far away index doesn't allocate a slot per skipped index.
+    skipped: Vec<Range<u32>>,
     generations: HashMap<u32, u32>,
+    alive: HashSet<u32>,
+    /// First index handed out by [`Entities::spawn`], lower indices are only claimed with
+    /// [`Entities::spawn_at`].
+    local: u32,
+    /// Fresh indices handed out by [`Entities::reserve`] that haven't been flushed yet.
+    reserved: AtomicU32,
+}
+
+impl Clone for Entities {
+    fn clone(&self) -> Self {
+        Self {
+            current: self.current,
+            free: self.free.clone(),
+            skipped: self.skipped.clone(),
+            generations: self.generations.clone(),
+            alive: self.alive.clone(),
+            local: self.local,
+            reserved: AtomicU32::new(self.reserved.load(Ordering::Relaxed)),
+        }
+    }
 }
 
 impl Entities {
@@ -45,16 +94,50 @@ impl Entities {
         Self {
             current: 0,
             free: vec![],
+            skipped: vec![],
             generations: HashMap::new(),
+            alive: HashSet::new(),
+            local: 0,
+            reserved: AtomicU32::new(0),
+        }
+    }
+
+    /// Reserves a fresh entity without mutable access, for commands that spawn entities later.
+    /// The entity is alive from now on, freed slots are never reserved.
+    pub fn reserve(&self) -> Entity {
+        let offset = self.reserved.fetch_add(1, Ordering::Relaxed);
+        Entity::new(self.current + offset, 1)
+    }
+
+    /// Marks the reserved entities as spawned, returning their ids.
+    pub fn flush(&mut self) -> std::ops::Range<u32> {
+        let reserved = std::mem::take(self.reserved.get_mut());
+        let ids = self.current..self.current + reserved;
+        for id in ids.clone() {
+            self.generations.insert(id, 1);
+            self.alive.insert(id);
         }
+
+        self.current += reserved;
+        ids
     }
 
     pub fn spawn(&mut self) -> Entity {
-        if let Some(id) = self.free.pop() {
+        self.flush();
+        let entity = if let Some(id) = self.free.pop() {
             let generation = self.generations.entry(id).or_default();
             *generation += 1;
 
             Entity::new(id, *generation)
+        } else if let Some(range) = self.skipped.last_mut() {
+            let id = range.start;
+            range.start += 1;
+            if range.start == range.end {
+                self.skipped.pop();
+            }
+
+            self.generations.insert(id, 1);
+            Entity::new(id, 1)
         } else {
             let id = self.current;
             let generation = 1;
@@ -62,16 +145,294 @@ impl Entities {
             self.current += 1;
 
             Entity::new(id, generation)
+        };
+
+        self.alive.insert(entity.id);
+        entity
+    }
+
+    /// Claims a specific slot, for entities whose ids are assigned elsewhere like a replication
+    /// server. Spawning an entity that is already alive returns it unchanged.
+    pub fn spawn_at(&mut self, id: u32, generation: u32) -> Result<Entity, SpawnAtError> {
+        self.flush();
+        let entity = Entity::new(id, generation);
+        let current = self.generations.get(&id).copied();
+        if self.alive.contains(&id) {
+            return match current == Some(generation) {
+                true => Ok(entity),
+                false => Err(SpawnAtError::Occupied(Entity::new(id, current.unwrap()))),
+            };
+        }
+
+        if let Some(current) = current.filter(|current| generation <= *current) {
+            return Err(SpawnAtError::Stale {
+                entity,
+                generation: current,
+            });
         }
+
+        if id >= self.local {
+            if id >= self.current {
+                if id > self.current {
+                    self.skipped.push(self.current..id);
+                }
+                self.current = id + 1;
+            } else if current.is_some() {
+                if let Some(index) = self.free.iter().position(|free| *free == id) {
+                    self.free.swap_remove(index);
+                }
+            } else {
+                // Slots that never had a generation were skipped, the ranges are sorted.
+                let index = self.skipped.partition_point(|range| range.end <= id);
+                let range = self.skipped[index].clone();
+                let split = [range.start..id, id + 1..range.end];
+                self.skipped
+                    .splice(index..=index, split.into_iter().filter(|r| r.start < r.end));
+            }
+        }
+
+        self.generations.insert(id, generation);
+        self.alive.insert(id);
+        Ok(entity)
+    }
+
+    /// Restricts [`Entities::spawn`] to indices starting at `range.start`, leaving the indices
+    /// below it to [`Entities::spawn_at`].
+    pub fn set_local_range(&mut self, range: RangeFrom<u32>) {
+        self.flush();
+        self.local = range.start;
+        self.free.retain(|id| *id >= range.start);
+        for skipped in &mut self.skipped {
+            skipped.start = skipped.start.max(range.start);
+        }
+        self.skipped.retain(|skipped| skipped.start < skipped.end);
+        self.current = self.current.max(range.start);
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let reserved = self.current..self.current + self.reserved.load(Ordering::Relaxed);
+        if reserved.contains(&entity.id) {
+            return entity.generation == 1;
+        }
+
+        self.alive.contains(&entity.id)
+            && self.generations.get(&entity.id) == Some(&entity.generation)
+    }
+
+    /// Number of live entities, reserved ones included.
+    pub fn len(&self) -> usize {
+        self.alive.len() + self.reserved.load(Ordering::Relaxed) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     pub fn despawn(&mut self, entity: Entity) {
-        self.free.push(entity.id);
+        self.flush();
+        if !self.is_alive(entity) {
+            return;
+        }
+
+        self.alive.remove(&entity.id);
+        if entity.id >= self.local {
+            self.free.push(entity.id);
+        }
     }
 
+    /// Despawns every entity. Generations are kept, so handles to the cleared entities stay
+    /// dead when their slots are reused.
     pub fn clear(&mut self) {
-        self.current = 0;
-        self.free.clear();
-        self.generations.clear();
+        self.flush();
+        let (local, start) = (self.local, self.free.len());
+        self.free
+            .extend(self.alive.drain().filter(|id| *id >= local));
+
+        // Lowest indices are handed out first, regardless of the set's order.
+        self.free[start..].sort_unstable_by(|a, b| b.cmp(a));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entities, Entity, SpawnAtError};
+    use crate::world::{Component, World};
+    use std::collections::HashSet;
+
+    struct Replicated(u32);
+    impl Component for Replicated {}
+
+    #[test]
+    fn reserve_then_spawn() {
+        let mut entities = Entities::new();
+        let first = entities.spawn();
+        entities.despawn(first);
+
+        let reserved = [entities.reserve(), entities.reserve()];
+        assert!(reserved.iter().all(|entity| entities.is_alive(*entity)));
+        assert_ne!(reserved[0], reserved[1]);
+
+        let spawned = [entities.spawn(), entities.spawn()];
+        assert_eq!(spawned[0].id(), first.id());
+        assert!(!reserved.contains(&spawned[1]));
+        assert!(reserved.iter().all(|entity| entities.is_alive(*entity)));
+
+        entities.despawn(reserved[0]);
+        assert!(!entities.is_alive(reserved[0]));
+        assert!(entities.is_alive(reserved[1]));
+    }
+
+    #[test]
+    fn spawn_at_interleaved_with_local() {
+        let mut entities = Entities::new();
+        entities.set_local_range(1024..);
+
+        let mut alive = HashSet::new();
+        for i in 0..32u32 {
+            let remote = entities.spawn_at(i * 3, 1).unwrap();
+            let local = entities.spawn();
+            assert!(local.id() >= 1024);
+            assert!(alive.insert(remote.id()));
+            assert!(alive.insert(local.id()));
+
+            if i % 4 == 0 {
+                entities.despawn(local);
+                alive.remove(&local.id());
+            }
+        }
+
+        // Local spawns recycle local slots, never the ones claimed with spawn_at.
+        for _ in 0..16 {
+            let local = entities.spawn();
+            assert!(local.id() >= 1024);
+            assert!(alive.insert(local.id()));
+        }
+    }
+
+    #[test]
+    fn spawn_at_skips_claimed_local_slots() {
+        let mut entities = Entities::new();
+        let claimed = entities.spawn_at(4, 1).unwrap();
+
+        let spawned = (0..8).map(|_| entities.spawn()).collect::<Vec<_>>();
+        assert!(spawned.iter().all(|entity| entity.id() != claimed.id()));
+        assert_eq!(
+            spawned.iter().map(|e| e.id()).collect::<HashSet<_>>().len(),
+            8
+        );
+    }
+
+    #[test]
+    fn spawn_at_rejects_stale_and_occupied() {
+        let mut entities = Entities::new();
+        let entity = entities.spawn_at(7, 3).unwrap();
+
+        assert_eq!(entities.spawn_at(7, 3), Ok(entity));
+        assert_eq!(entities.spawn_at(7, 4), Err(SpawnAtError::Occupied(entity)));
+
+        entities.despawn(Entity::new(7, 2));
+        assert!(entities.is_alive(entity));
+
+        entities.despawn(entity);
+        assert_eq!(
+            entities.spawn_at(7, 2),
+            Err(SpawnAtError::Stale {
+                entity: Entity::new(7, 2),
+                generation: 3
+            })
+        );
+        assert_eq!(entities.spawn_at(7, 4), Ok(Entity::new(7, 4)));
+    }
+
+    #[test]
+    fn spawn_at_far_index_skips_lazily() {
+        let mut entities = Entities::new();
+        let first = entities.spawn();
+        let far = entities.spawn_at(1 << 30, 1).unwrap();
+        assert_eq!((entities.free.len(), entities.skipped.len()), (0, 1));
+
+        // Claiming inside the skipped range splits it.
+        let claimed = entities.spawn_at(10, 1).unwrap();
+        assert_eq!(entities.skipped, [1..10, 11..1 << 30]);
+
+        let spawned = (0..20).map(|_| entities.spawn()).collect::<Vec<_>>();
+        let ids = spawned.iter().map(|e| e.id()).collect::<HashSet<_>>();
+        assert_eq!(ids.len(), 20);
+        assert!(
+            ids.iter()
+                .all(|id| *id != first.id() && *id != claimed.id())
+        );
+        assert!(ids.iter().all(|id| *id < 1 << 30));
+        assert!(entities.is_alive(far));
+    }
+
+    #[test]
+    fn clear_keeps_generations() {
+        let mut entities = Entities::new();
+        let stale = [entities.spawn(), entities.spawn()];
+        entities.clear();
+        assert!(entities.is_empty());
+        assert!(stale.iter().all(|entity| !entities.is_alive(*entity)));
+
+        let spawned = [entities.spawn(), entities.spawn()];
+        assert_eq!(spawned.map(|e| e.id()), stale.map(|e| e.id()));
+        assert!(spawned.iter().all(|entity| entity.generation() == 2));
+        assert!(stale.iter().all(|entity| !entities.is_alive(*entity)));
+        assert!(entities.spawn_at(stale[0].id(), 1).is_err());
+    }
+
+    #[test]
+    fn world_spawn_at_round_trip() {
+        let mut world = World::new();
+        world.register::<Replicated>();
+        world.set_local_entity_range(100..);
+
+        let entity = world.spawn_at(Entity::new(5, 1)).unwrap();
+        world.add_component(entity, Replicated(1));
+        assert_eq!(
+            world.get_component::<Replicated>(entity).map(|r| r.0),
+            Some(1)
+        );
+
+        world.despawn(entity);
+        assert!(world.get_component::<Replicated>(entity).is_none());
+
+        let respawned = world.spawn_at(Entity::new(5, 2)).unwrap();
+        world.add_component(respawned, Replicated(2));
+        assert_eq!(
+            world.get_component::<Replicated>(respawned).map(|r| r.0),
+            Some(2)
+        );
+        assert!(world.get_component::<Replicated>(entity).is_none());
+        assert!(world.spawn_at(entity).is_err());
+        assert!(world.spawn().id() >= 100);
+    }
+
+    #[test]
+    fn stale_handles_do_not_alias() {
+        let mut world = World::new();
+        world.register::<Replicated>();
+
+        let stale = world.spawn();
+        world.add_component(stale, Replicated(1));
+        world.despawn(stale);
+
+        let entity = world.spawn();
+        assert_eq!(entity.id(), stale.id());
+        assert_ne!(entity.generation(), stale.generation());
+        assert!(world.is_alive(entity));
+        assert!(!world.is_alive(stale));
+
+        world.add_component(entity, Replicated(2));
+        world.add_component(stale, Replicated(3));
+        assert!(world.get_component::<Replicated>(stale).is_none());
+        assert!(world.archetypes().entity_archetype(stale).is_none());
+
+        world.despawn(stale);
+        assert!(world.is_alive(entity));
+        assert_eq!(
+            world.get_component::<Replicated>(entity).map(|r| r.0),
+            Some(2)
+        );
     }
 }
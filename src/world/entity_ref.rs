@@ -0,0 +1,279 @@
+use super::{ArchetypeId, Bundle, Component, ComponentId, Entity, Lifecycle, Name, World};
+
+/// Read-only view of a live entity, see [`World::entity`].
+#[derive(Clone, Copy)]
+pub struct EntityRef<'w> {
+    world: &'w World,
+    entity: Entity,
+    archetype: ArchetypeId,
+}
+
+impl<'w> EntityRef<'w> {
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn archetype(&self) -> ArchetypeId {
+        self.archetype
+    }
+
+    pub fn world(&self) -> &'w World {
+        self.world
+    }
+
+    pub fn get<C: Component>(&self) -> Option<&'w C> {
+        let id = self.world.components().get_id::<C>()?;
+        self.world.archetypes()[self.archetype]
+            .table()
+            .get_component(self.entity, id)
+    }
+
+    pub fn contains<C: Component>(&self) -> bool {
+        self.world
+            .components()
+            .get_id::<C>()
+            .is_some_and(|id| self.contains_id(id))
+    }
+
+    pub fn contains_id(&self, id: ComponentId) -> bool {
+        self.world.archetypes()[self.archetype].has_component_id(id)
+    }
+}
+
+/// Mutable view of a live entity, see [`World::entity_mut`].
+/// Structural changes keep the view pointed at the entity's new archetype.
+pub struct EntityMut<'w> {
+    world: &'w mut World,
+    entity: Entity,
+    archetype: ArchetypeId,
+}
+
+impl<'w> EntityMut<'w> {
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn archetype(&self) -> ArchetypeId {
+        self.archetype
+    }
+
+    pub fn world(&self) -> &World {
+        self.world
+    }
+
+    pub fn as_readonly(&self) -> EntityRef<'_> {
+        EntityRef {
+            world: self.world,
+            entity: self.entity,
+            archetype: self.archetype,
+        }
+    }
+
+    pub fn get<C: Component>(&self) -> Option<&C> {
+        self.as_readonly().get::<C>()
+    }
+
+    pub fn get_mut<C: Component>(&mut self) -> Option<&mut C> {
+        let id = self.world.components().get_id::<C>()?;
+        self.world.archetypes_mut()[self.archetype]
+            .table_mut()
+            .get_component_mut(self.entity, id)
+    }
+
+    pub fn contains<C: Component>(&self) -> bool {
+        self.as_readonly().contains::<C>()
+    }
+
+    pub fn contains_id(&self, id: ComponentId) -> bool {
+        self.as_readonly().contains_id(id)
+    }
+
+    pub fn insert<C: Component>(&mut self, component: C) -> &mut Self {
+        self.world.add_component(self.entity, component);
+        self.update_archetype()
+    }
+
+    pub fn insert_bundle<B: Bundle>(&mut self, bundle: B) -> &mut Self {
+        self.world.add_bundle(self.entity, bundle);
+        self.update_archetype()
+    }
+
+    /// Removes the component, returning it if the entity had one.
+    pub fn remove<C: Component>(&mut self) -> Option<C> {
+        let id = self.world.components().get_id::<C>()?;
+        if self.world.is_observed(id) && self.contains_id(id) {
+            self.world.trigger(Lifecycle::Remove, self.entity, id);
+        }
+
+        let component = self.world.archetypes.remove_component::<C>(self.entity);
+        if component.is_some() {
+            self.world.record_removed(self.entity, &[id]);
+        }
+
+        self.update_archetype();
+        component
+    }
+
+    pub fn despawn(self) {
+        self.world.despawn(self.entity);
+    }
+
+    fn update_archetype(&mut self) -> &mut Self {
+        self.archetype = self
+            .world
+            .archetypes()
+            .entity_archetype(self.entity)
+            .unwrap_or(ArchetypeId::EMPTY);
+        self
+    }
+}
+
+impl<'w> From<EntityMut<'w>> for EntityRef<'w> {
+    fn from(entity: EntityMut<'w>) -> Self {
+        EntityRef {
+            world: entity.world,
+            entity: entity.entity,
+            archetype: entity.archetype,
+        }
+    }
+}
+
+impl std::fmt::Debug for EntityRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entity = self.id();
+        if let Some(name) = self.get::<Name>() {
+            write!(f, "{} ", name)?;
+        }
+
+        write!(f, "(Entity {}v{}) [", entity.id(), entity.generation())?;
+        let world = self.world();
+        let name = world.components().get_id::<Name>();
+        let components = world.archetypes()[self.archetype()]
+            .table()
+            .columns()
+            .map(|(id, _)| *id)
+            .filter(|id| Some(*id) != name)
+            .filter_map(|id| world.components().meta(id));
+        for (index, meta) in components.enumerate() {
+            match index {
+                0 => write!(f, "{}", meta.name())?,
+                _ => write!(f, ", {}", meta.name())?,
+            }
+        }
+
+        write!(f, "]")
+    }
+}
+
+impl World {
+    /// Panics if the entity is not alive, see [`World::try_entity`].
+    pub fn entity(&self, entity: Entity) -> EntityRef<'_> {
+        self.try_entity(entity)
+            .unwrap_or_else(|| panic!("{} is not alive", entity))
+    }
+
+    /// Panics if the entity is not alive, see [`World::try_entity_mut`].
+    pub fn entity_mut(&mut self, entity: Entity) -> EntityMut<'_> {
+        self.try_entity_mut(entity)
+            .unwrap_or_else(|| panic!("{} is not alive", entity))
+    }
+
+    pub fn try_entity(&self, entity: Entity) -> Option<EntityRef<'_>> {
+        let archetype = self.live_archetype(entity)?;
+        Some(EntityRef {
+            world: self,
+            entity,
+            archetype,
+        })
+    }
+
+    pub fn try_entity_mut(&mut self, entity: Entity) -> Option<EntityMut<'_>> {
+        let archetype = self.live_archetype(entity)?;
+        Some(EntityMut {
+            world: self,
+            entity,
+            archetype,
+        })
+    }
+
+    /// Describes the entity for logs and panics, e.g. `Player (Entity 4v2) [Transform, Health]`.
+    /// The [`Name`] is left out when the entity has none.
+    pub fn entity_debug(&self, entity: Entity) -> String {
+        match self.try_entity(entity) {
+            Some(entity) => format!("{:?}", entity),
+            None => format!("(Entity {}v{}) <dead>", entity.id(), entity.generation()),
+        }
+    }
+
+    fn live_archetype(&self, entity: Entity) -> Option<ArchetypeId> {
+        match self.entities.is_alive(entity) {
+            true => self.archetypes.entity_archetype(entity),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::{ArchetypeId, Component, Name, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Debug, PartialEq)]
+    struct Label(&'static str);
+    impl Component for Label {}
+
+    #[test]
+    fn entity_views() {
+        let mut world = World::new();
+        world.register::<Health>();
+        world.register::<Label>();
+        let entity = world.spawn();
+
+        let mut view = world.entity_mut(entity);
+        assert_eq!(view.archetype(), ArchetypeId::EMPTY);
+        view.insert(Health(10)).insert_bundle((Label("player"),));
+        view.get_mut::<Health>().unwrap().0 -= 3;
+        assert_eq!(view.get::<Health>(), Some(&Health(7)));
+        assert_eq!(view.remove::<Label>(), Some(Label("player")));
+        assert_eq!(view.remove::<Label>(), None);
+
+        let archetype = view.archetype();
+        assert_eq!(world.archetypes().entity_archetype(entity), Some(archetype));
+
+        let view = world.entity(entity);
+        assert!(view.contains::<Health>());
+        assert!(!view.contains::<Label>());
+        assert_eq!(view.get::<Health>(), Some(&Health(7)));
+
+        world.entity_mut(entity).despawn();
+        assert!(world.try_entity(entity).is_none());
+        assert!(world.try_entity_mut(entity).is_none());
+    }
+
+    #[test]
+    fn entity_debug() {
+        let mut world = World::new();
+        world.register::<Health>();
+        world.register::<Label>();
+        let player = world.spawn();
+        world.add_component(player, Health(10));
+        world.add_component(player, Label("player"));
+        assert_eq!(world.entity_debug(player), "(Entity 0v1) [Health, Label]");
+
+        world.add_component(player, Name::new("Player"));
+        assert_eq!(
+            world.entity_debug(player),
+            "Player (Entity 0v1) [Health, Label]"
+        );
+        assert_eq!(
+            format!("{:?}", world.entity(player)),
+            world.entity_debug(player)
+        );
+
+        world.despawn(player);
+        assert_eq!(world.entity_debug(player), "(Entity 0v1) <dead>");
+    }
+}
@@ -1,24 +1,229 @@
-use super::{World, WorldCell, resource::Resource};
-use crate::system::arg::SystemArg;
-use std::{any::TypeId, collections::HashMap};
+use super::{
+    World, WorldCell,
+    resource::{Resource, ResourceId},
+};
+use crate::{
+    core::{load_runtime_value, save_runtime_value},
+    system::{Access, SystemAccess, arg::SystemArg},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_value::Value;
+use std::{
+    any::TypeId,
+    collections::{HashMap, VecDeque},
+};
 
 pub trait Event: Send + Sync + Sized + 'static {}
 
+/// Default number of frames a published batch is kept while a reader hasn't consumed it.
+pub const DEFAULT_EVENT_RETENTION: u32 = 64;
+
+struct EventBatch<E: Event> {
+    /// Sequence number of the first event in the batch.
+    start: u64,
+    events: Vec<E>,
+    /// Number of updates since the batch was published.
+    age: u32,
+}
+
+impl<E: Event> EventBatch<E> {
+    fn end(&self) -> u64 {
+        self.start + self.events.len() as u64
+    }
+}
+
+/// How [`Events::update`] evicts published events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventControl {
+    /// Evicts events once every reader read them or the max retention is reached.
+    #[default]
+    Automatic,
+    /// Keeps events until they are removed with [`Events::drain`] or [`Events::clear`].
+    Manual,
+}
+
+/// Event storage. Events sent during a frame are published by [`Events::update`] and stay
+/// readable until every registered [`EventReader`] has read them, or until they are older than
+/// the max retention, in which case the unread events are counted in [`Events::dropped`].
 pub struct Events<E: Event> {
     write: Vec<E>,
-    read: Vec<E>,
+    batches: VecDeque<EventBatch<E>>,
+    /// Evicted batch buffers, reused for the following writes.
+    free: Vec<Vec<E>>,
+    /// Sequence number of the next published event.
+    next: u64,
+    cursors: Vec<u64>,
+    max_retention: u32,
+    dropped: u64,
+    control: EventControl,
 }
 
 impl<E: Event> Events<E> {
     pub fn new() -> Self {
         Self {
             write: Vec::new(),
-            read: Vec::new(),
+            batches: VecDeque::new(),
+            free: Vec::new(),
+            next: 0,
+            cursors: Vec::new(),
+            max_retention: DEFAULT_EVENT_RETENTION,
+            dropped: 0,
+            control: EventControl::Automatic,
+        }
+    }
+
+    /// Sequence number of the oldest retained event.
+    fn start(&self) -> u64 {
+        self.batches.front().map_or(self.next, |batch| batch.start)
+    }
+
+    fn get(&self, sequence: u64) -> Option<&E> {
+        let batch = self
+            .batches
+            .iter()
+            .find(|batch| sequence >= batch.start && sequence < batch.end())?;
+        batch.events.get((sequence - batch.start) as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        (self.next - self.start()) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> EventIter<'_, E> {
+        EventIter {
+            batches: self.batches.iter(),
+            events: [].iter(),
+        }
+    }
+
+    pub fn max_retention(&self) -> u32 {
+        self.max_retention
+    }
+
+    /// Number of frames a batch stays readable while a reader still hasn't read it.
+    pub fn set_max_retention(&mut self, frames: u32) {
+        self.max_retention = frames.max(1);
+    }
+
+    /// Events evicted by the max retention before every reader read them.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn control(&self) -> EventControl {
+        self.control
+    }
+
+    pub fn set_control(&mut self, control: EventControl) {
+        self.control = control;
+    }
+
+    /// Sends an event that is published by the next [`Events::update`].
+    pub fn send(&mut self, event: E) {
+        self.write.push(event);
+    }
+
+    /// Events sent this frame that haven't been published yet.
+    pub fn iter_current_update_events(&self) -> std::slice::Iter<'_, E> {
+        self.write.iter()
+    }
+
+    /// Removes the published events and then the events sent this frame, readers skip the
+    /// removed events.
+    pub fn drain(&mut self) -> impl Iterator<Item = E> + '_ {
+        self.batches
+            .drain(..)
+            .flat_map(|batch| batch.events)
+            .chain(self.write.drain(..))
+    }
+
+    /// Drops the published events and the events sent this frame.
+    pub fn clear(&mut self) {
+        for mut batch in self.batches.drain(..) {
+            batch.events.clear();
+            self.free.push(batch.events);
         }
+
+        self.write.clear();
+    }
+
+    pub(crate) fn add_reader(&mut self) -> (usize, u64) {
+        let cursor = self.start();
+        self.cursors.push(cursor);
+        (self.cursors.len() - 1, cursor)
+    }
+
+    pub(crate) fn set_cursor(&mut self, reader: usize, cursor: u64) {
+        self.cursors[reader] = cursor;
     }
 
+    /// Replaces the retained events with `events`, numbered from `start`.
+    fn restore(&mut self, start: u64, events: Vec<E>) {
+        for mut batch in self.batches.drain(..) {
+            batch.events.clear();
+            self.free.push(batch.events);
+        }
+
+        self.next = start + events.len() as u64;
+        if !events.is_empty() {
+            self.batches.push_back(EventBatch {
+                start,
+                events,
+                age: 0,
+            });
+        }
+    }
+
+    fn evict(&mut self) {
+        let oldest = self.cursors.iter().copied().min().unwrap_or(self.next);
+        while let Some(batch) = self.batches.front() {
+            let read = oldest >= batch.end();
+            if !read && batch.age < self.max_retention {
+                break;
+            }
+
+            if !read {
+                self.dropped += batch.end() - oldest.max(batch.start);
+            }
+
+            let mut batch = self.batches.pop_front().unwrap();
+            batch.events.clear();
+            self.free.push(batch.events);
+        }
+    }
+
+    /// Evicts batches every reader has read and publishes the events sent this frame.
+    /// Without readers, a batch is readable for the frame after it was published. Nothing is
+    /// evicted with [`EventControl::Manual`].
     pub fn update(&mut self) {
-        self.read = std::mem::take(&mut self.write);
+        for batch in self.batches.iter_mut() {
+            batch.age += 1;
+        }
+
+        if self.control == EventControl::Automatic {
+            self.evict();
+        }
+
+        if !self.write.is_empty() {
+            let events = std::mem::replace(&mut self.write, self.free.pop().unwrap_or_default());
+            let start = self.next;
+            self.next += events.len() as u64;
+            self.batches.push_back(EventBatch {
+                start,
+                events,
+                age: 0,
+            });
+        }
+    }
+}
+
+impl<E: Event> Default for Events<E> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -27,11 +232,77 @@ impl<E: Event> Resource for Events<E> {}
 pub struct EventMeta {
     pub name: &'static str,
     update: fn(&mut World),
+    save: fn(&World) -> RuntimeEvents,
+    restore: fn(&mut World, &RuntimeEvents),
+}
+
+/// Publication state of an event type, captured by [`World::save_event_state`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeEvents {
+    pub name: &'static str,
+    /// Sequence number of the first retained event.
+    pub start: u64,
+    /// Sequence number of the next published event.
+    pub next: u64,
+    /// Retained events, only captured for events registered with
+    /// [`World::register_serializable_event`].
+    pub events: Option<Vec<Value>>,
+}
+
+fn save_events<E: Event>(world: &World) -> RuntimeEvents {
+    let events = world.resource::<Events<E>>();
+    RuntimeEvents {
+        name: std::any::type_name::<E>(),
+        start: events.start(),
+        next: events.next,
+        events: None,
+    }
 }
 
+fn restore_events<E: Event>(world: &mut World, state: &RuntimeEvents) {
+    world
+        .resource_mut::<Events<E>>()
+        .restore(state.next, Vec::new());
+}
+
+fn save_serializable_events<E: Event + Serialize>(world: &World) -> RuntimeEvents {
+    let events = world
+        .resource::<Events<E>>()
+        .iter()
+        .map(save_runtime_value)
+        .collect();
+
+    RuntimeEvents {
+        events,
+        ..save_events::<E>(world)
+    }
+}
+
+fn restore_serializable_events<E: Event + DeserializeOwned>(
+    world: &mut World,
+    state: &RuntimeEvents,
+) {
+    let events = state
+        .events
+        .iter()
+        .flatten()
+        .map(load_runtime_value)
+        .collect::<Option<Vec<_>>>();
+
+    match events {
+        Some(events) if !events.is_empty() => world
+            .resource_mut::<Events<E>>()
+            .restore(state.start, events),
+        _ => restore_events::<E>(world, state),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventId(pub(crate) u32);
+
 pub struct EventRegistry {
     metas: Vec<EventMeta>,
-    map: HashMap<TypeId, usize>,
+    map: HashMap<TypeId, EventId>,
 }
 
 impl EventRegistry {
@@ -42,28 +313,54 @@ impl EventRegistry {
         }
     }
 
-    pub fn register<E: Event>(&mut self) {
+    pub fn register<E: Event>(&mut self) -> EventId {
         let ty = TypeId::of::<E>();
-        if self.map.contains_key(&ty) {
-            return;
+        if let Some(id) = self.map.get(&ty) {
+            return *id;
         }
 
         let name = std::any::type_name::<E>();
-        let index = self.metas.len();
+        let id = EventId(self.metas.len() as u32);
         self.metas.push(EventMeta {
             name,
             update: |world| {
                 let events = world.resource_mut::<Events<E>>();
                 events.update();
             },
+            save: save_events::<E>,
+            restore: restore_events::<E>,
         });
 
-        self.map.insert(ty, index);
+        self.map.insert(ty, id);
+        id
+    }
+
+    /// Captures the retained events of `E` in [`World::save_event_state`].
+    pub fn register_serializable<E: Event + Serialize + DeserializeOwned>(&mut self) -> EventId {
+        let id = self.register::<E>();
+        self.metas[id.0 as usize].save = save_serializable_events::<E>;
+        self.metas[id.0 as usize].restore = restore_serializable_events::<E>;
+        id
+    }
+
+    pub fn get_id<E: Event>(&self) -> Option<EventId> {
+        self.map.get(&TypeId::of::<E>()).copied()
     }
 
     pub fn get<E: Event>(&self) -> Option<&EventMeta> {
-        let ty = TypeId::of::<E>();
-        self.map.get(&ty).and_then(|&index| self.metas.get(index))
+        self.get_id::<E>().and_then(|id| self.meta(id))
+    }
+
+    pub fn meta(&self, id: EventId) -> Option<&EventMeta> {
+        self.metas.get(id.0 as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.metas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metas.is_empty()
     }
 
     pub fn update(&self, mut world: WorldCell) {
@@ -73,57 +370,176 @@ impl EventRegistry {
     }
 }
 
-pub struct EventReader<'state, E: Event> {
-    events: &'state Events<E>,
-    index: usize,
+impl World {
+    /// Registers the event so the events still retained are carried by [`World::save_event_state`].
+    pub fn register_serializable_event<E: Event + Serialize + DeserializeOwned>(
+        &mut self,
+    ) -> EventId {
+        self.register_event::<E>();
+        self.events.register_serializable::<E>()
+    }
+
+    /// Sequence numbers of every event type, so restored readers continue where they left off.
+    pub fn save_event_state(&self) -> Vec<RuntimeEvents> {
+        self.events
+            .metas
+            .iter()
+            .map(|meta| (meta.save)(self))
+            .collect()
+    }
+
+    /// Restores state captured by [`World::save_event_state`], returning the names of event
+    /// types that aren't registered in this world.
+    pub fn restore_event_state(&mut self, state: &[RuntimeEvents]) -> Vec<&'static str> {
+        let mut missing = vec![];
+        for events in state {
+            let restore = self
+                .events
+                .metas
+                .iter()
+                .find(|meta| meta.name == events.name)
+                .map(|meta| meta.restore);
+
+            match restore {
+                Some(restore) => restore(self, events),
+                None => missing.push(events.name),
+            }
+        }
+
+        missing
+    }
+}
+
+/// Reads events with a cursor that persists across frames, events that aren't read stay
+/// available until the next run.
+pub struct EventReader<'world, 'state, E: Event> {
+    events: &'world Events<E>,
+    cursor: &'state mut u64,
 }
 
-impl<'state, E: Event> EventReader<'state, E> {
-    pub(crate) fn new(events: &'state Events<E>) -> Self {
-        Self { events, index: 0 }
+impl<'world, 'state, E: Event> EventReader<'world, 'state, E> {
+    pub(crate) fn new(events: &'world Events<E>, cursor: &'state mut u64) -> Self {
+        *cursor = (*cursor).max(events.start());
+        Self { events, cursor }
+    }
+
+    /// Returns at most `n` events, leaving the rest for later reads.
+    pub fn take_up_to(&mut self, n: usize) -> std::iter::Take<&mut Self> {
+        self.take(n)
+    }
+
+    /// Reads the events along with their sequence number, which identifies an event for every
+    /// reader of its type.
+    pub fn with_ids(&mut self) -> impl Iterator<Item = (u64, &'world E)> + '_ {
+        std::iter::from_fn(move || {
+            let id = *self.cursor;
+            self.next().map(|event| (id, event))
+        })
+    }
+
+    /// Number of events that haven't been read yet.
+    pub fn remaining(&self) -> usize {
+        (self.events.next - *self.cursor) as usize
     }
 }
 
-impl<'state, E: Event> Iterator for EventReader<'state, E> {
-    type Item = &'state E;
+impl<'world, 'state, E: Event> Iterator for EventReader<'world, 'state, E> {
+    type Item = &'world E;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.events.read.len() {
-            let event = &self.events.read[self.index];
-            self.index += 1;
-            Some(event)
-        } else {
-            None
+        let event = self.events.get(*self.cursor)?;
+        *self.cursor += 1;
+        Some(event)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+pub struct EventIter<'a, E: Event> {
+    batches: std::collections::vec_deque::Iter<'a, EventBatch<E>>,
+    events: std::slice::Iter<'a, E>,
+}
+
+impl<'a, E: Event> Iterator for EventIter<'a, E> {
+    type Item = &'a E;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.next() {
+                return Some(event);
+            }
+
+            self.events = self.batches.next()?.events.iter();
         }
     }
 }
 
-impl<'state, E: Event> IntoIterator for &'state Events<E> {
-    type Item = &'state E;
-    type IntoIter = EventReader<'state, E>;
+impl<'a, E: Event> IntoIterator for &'a Events<E> {
+    type Item = &'a E;
+    type IntoIter = EventIter<'a, E>;
 
     fn into_iter(self) -> Self::IntoIter {
-        EventReader::new(self)
+        self.iter()
     }
 }
 
-unsafe impl<E: Event> SystemArg for EventReader<'_, E> {
-    type Item<'world, 'state> = EventReader<'world, E>;
+pub struct ReaderState {
+    id: ResourceId,
+    reader: usize,
+    cursor: u64,
+}
+
+unsafe impl<E: Event> SystemArg for EventReader<'_, '_, E> {
+    type Item<'world, 'state> = EventReader<'world, 'state, E>;
 
-    type State = ();
+    type State = ReaderState;
 
     fn init(world: &mut super::World) -> Self::State {
         world.register_event::<E>();
-        ()
+        let id = world.resources().get_id::<Events<E>>().unwrap();
+        let (reader, cursor) = world.resource_mut::<Events<E>>().add_reader();
+        ReaderState { id, reader, cursor }
+    }
+
+    unsafe fn validate(
+        state: &Self::State,
+        world: super::WorldCell,
+        _: &crate::system::SystemMeta,
+    ) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<Events<E>>(state.id).is_some()
     }
 
     unsafe fn get<'world, 'state>(
-        _: &'state mut Self::State,
+        state: &'state mut Self::State,
         world: super::WorldCell<'world>,
         _: &crate::system::SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        let events = unsafe { world.get().resource::<Events<E>>() };
-        EventReader::new(events)
+        let events = unsafe { world.get().resources().get::<Events<E>>(state.id) };
+        EventReader::new(events.unwrap(), &mut state.cursor)
+    }
+
+    fn apply(state: &mut Self::State, world: &mut super::World) {
+        if let Some(events) = world.resources_mut().get_mut::<Events<E>>(state.id) {
+            events.set_cursor(state.reader, state.cursor);
+        }
+    }
+
+    fn save(state: &Self::State) -> Option<Value> {
+        save_runtime_value(&state.cursor)
+    }
+
+    fn restore(state: &mut Self::State, world: &mut super::World, saved: &Value) {
+        if let Some(cursor) = load_runtime_value(saved) {
+            state.cursor = cursor;
+            Self::apply(state, world);
+        }
+    }
+
+    fn access(state: &Self::State) -> Vec<SystemAccess> {
+        vec![SystemAccess::resource(state.id, Access::Read)]
     }
 }
 
@@ -148,11 +564,20 @@ impl<'state, E: Event> EventWriter<'state, E> {
 unsafe impl<E: Event> SystemArg for EventWriter<'_, E> {
     type Item<'world, 'state> = EventWriter<'state, E>;
 
-    type State = Vec<E>;
+    type State = (ResourceId, Vec<E>);
 
     fn init(world: &mut super::World) -> Self::State {
         world.register_event::<E>();
-        vec![]
+        (world.resources().get_id::<Events<E>>().unwrap(), vec![])
+    }
+
+    unsafe fn validate(
+        state: &Self::State,
+        world: super::WorldCell,
+        _: &crate::system::SystemMeta,
+    ) -> bool {
+        let resources = unsafe { world.get().resources() };
+        resources.get::<Events<E>>(state.0).is_some()
     }
 
     unsafe fn get<'world, 'state>(
@@ -160,11 +585,210 @@ unsafe impl<E: Event> SystemArg for EventWriter<'_, E> {
         _: super::WorldCell<'world>,
         _: &crate::system::SystemMeta,
     ) -> Self::Item<'world, 'state> {
-        EventWriter::new(state)
+        EventWriter::new(&mut state.1)
+    }
+
+    fn deferred() -> bool {
+        true
     }
 
     fn apply(state: &mut Self::State, world: &mut super::World) {
-        let events = world.resource_mut::<Events<E>>();
-        events.write.append(state);
+        let (id, written) = state;
+        if let Some(events) = world.resources_mut().get_mut::<Events<E>>(*id) {
+            events.write.append(written);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, EventControl, EventReader, EventWriter, Events};
+    use crate::{
+        app::App,
+        system::{
+            Access, IntoSystemConfigs, SystemAccess,
+            arg::{Local, SystemArg},
+            schedule::Phase,
+        },
+        world::{Resource, World},
+    };
+
+    struct Burst;
+    impl Phase for Burst {}
+
+    struct Update;
+    impl Phase for Update {}
+
+    #[derive(Debug, PartialEq)]
+    struct Request(u32);
+    impl Event for Request {}
+
+    #[derive(Default)]
+    struct Processed {
+        requests: Vec<u32>,
+        remaining: Vec<usize>,
+    }
+    impl Resource for Processed {}
+
+    fn burst(mut writer: EventWriter<Request>) {
+        for i in 0..100 {
+            writer.send(Request(i));
+        }
+    }
+
+    fn process(mut reader: EventReader<Request>, processed: &mut Processed) {
+        processed.remaining.push(reader.remaining());
+        for request in reader.take_up_to(10) {
+            processed.requests.push(request.0);
+        }
+    }
+
+    fn stalled(_: EventReader<Request>) {}
+
+    #[derive(Default)]
+    struct Seen {
+        before: Vec<(u64, u32)>,
+        after: Vec<(u64, u32)>,
+    }
+    impl Resource for Seen {}
+
+    fn read_before(mut reader: EventReader<Request>, seen: &mut Seen) {
+        seen.before
+            .extend(reader.with_ids().map(|(id, event)| (id, event.0)));
+    }
+
+    fn send_two(mut writer: EventWriter<Request>, mut sent: Local<u32>) {
+        for _ in 0..2 {
+            writer.send(Request(*sent));
+            *sent += 1;
+        }
+    }
+
+    fn read_after(mut reader: EventReader<Request>, seen: &mut Seen) {
+        seen.after
+            .extend(reader.with_ids().map(|(id, event)| (id, event.0)));
+    }
+
+    #[test]
+    fn event_reader_take_up_to() {
+        let mut app = App::new()
+            .add_resource(Processed::default())
+            .add_systems(Burst, burst)
+            .add_systems(Update, process)
+            .build();
+
+        app.run(Burst);
+        for _ in 0..12 {
+            app.run(Update);
+        }
+
+        let processed = app.world().resource::<Processed>();
+        assert_eq!(processed.requests, (0..100).collect::<Vec<_>>());
+        assert_eq!(
+            &processed.remaining[..11],
+            &[100, 90, 80, 70, 60, 50, 40, 30, 20, 10, 0]
+        );
+        assert_eq!(app.world().resource::<Events<Request>>().dropped(), 0);
+    }
+
+    #[test]
+    fn event_retention_drops_stalled_readers() {
+        let mut builder = App::new();
+        builder.world_mut().register_event::<Request>();
+        builder
+            .world_mut()
+            .resource_mut::<Events<Request>>()
+            .set_max_retention(4);
+
+        let mut app = builder
+            .add_resource(Processed::default())
+            .add_systems(Burst, burst)
+            .add_systems(Update, process)
+            .add_systems(Update, stalled)
+            .build();
+
+        app.run(Burst);
+        for _ in 0..12 {
+            app.run(Update);
+        }
+
+        let events = app.world().resource::<Events<Request>>();
+        assert!(events.is_empty());
+        assert_eq!(events.dropped(), 100);
+
+        let processed = app.world().resource::<Processed>();
+        assert_eq!(processed.requests, (0..40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn event_registration_and_access() {
+        let mut world = World::new();
+        let id = world.register_event::<Request>();
+        assert_eq!(world.register_event::<Request>(), id);
+        assert_eq!(world.events().get_id::<Request>(), Some(id));
+        assert_eq!(
+            world.events().meta(id).map(|meta| meta.name),
+            Some(std::any::type_name::<Request>())
+        );
+
+        let events = world.resources().get_id::<Events<Request>>().unwrap();
+        let state = <EventReader<Request> as SystemArg>::init(&mut world);
+        assert_eq!(
+            <EventReader<Request> as SystemArg>::access(&state),
+            vec![SystemAccess::resource(events, Access::Read)]
+        );
+    }
+
+    #[test]
+    fn event_readers_see_each_event_once() {
+        let mut app = App::new()
+            .add_resource(Seen::default())
+            .add_systems(Update, read_before.before(send_two).before(read_after))
+            .build();
+
+        for _ in 0..3 {
+            app.run(Update);
+        }
+
+        // Events are published at the end of the frame, so readers on either side of the writer
+        // read them once, in the next frame.
+        let seen = app.world().resource::<Seen>();
+        let expected = (0..4).map(|i| (i as u64, i)).collect::<Vec<_>>();
+        assert_eq!(seen.before, expected);
+        assert_eq!(seen.after, expected);
+    }
+
+    #[test]
+    fn manual_event_control() {
+        let mut events = Events::<Request>::new();
+        events.set_control(EventControl::Manual);
+        events.set_max_retention(2);
+        events.add_reader();
+
+        events.send(Request(0));
+        events.send(Request(1));
+        assert_eq!(
+            events.iter_current_update_events().collect::<Vec<_>>(),
+            vec![&Request(0), &Request(1)]
+        );
+
+        for _ in 0..8 {
+            events.update();
+        }
+        assert_eq!(events.len(), 2);
+        assert_eq!(events.dropped(), 0);
+
+        events.send(Request(2));
+        let drained = events.drain().map(|request| request.0).collect::<Vec<_>>();
+        assert_eq!(drained, vec![0, 1, 2]);
+        assert!(events.is_empty());
+        assert_eq!(events.iter_current_update_events().count(), 0);
+
+        events.send(Request(3));
+        events.update();
+        events.send(Request(4));
+        events.clear();
+        events.update();
+        assert!(events.is_empty());
     }
 }
@@ -0,0 +1,177 @@
+use super::{Entity, World};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+/// Engine level subsystem attached to a [`World`]. Extensions aren't resources, systems can't
+/// access them and they don't take part in scheduling.
+pub trait WorldExtension: Any + Send + Sync {
+    /// Called at the start of [`World::update`], in registration order, before the frame advances.
+    /// The extension is detached from the world while this runs.
+    fn on_update(&mut self, _world: &mut World) {}
+
+    fn on_entity_despawned(&mut self, _entity: Entity) {}
+}
+
+pub struct Extensions {
+    extensions: Vec<Option<Box<dyn WorldExtension>>>,
+    map: HashMap<TypeId, usize>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self {
+            extensions: Vec::new(),
+            map: HashMap::new(),
+        }
+    }
+
+    /// Inserts the extension, replacing and returning an existing one of the same type.
+    pub fn insert<T: WorldExtension>(&mut self, extension: T) -> Option<T> {
+        let ty = TypeId::of::<T>();
+        match self.map.get(&ty).copied() {
+            Some(index) => self.extensions[index]
+                .replace(Box::new(extension))
+                .and_then(|old| (old as Box<dyn Any>).downcast::<T>().ok())
+                .map(|old| *old),
+            None => {
+                self.map.insert(ty, self.extensions.len());
+                self.extensions.push(Some(Box::new(extension)));
+                None
+            }
+        }
+    }
+
+    pub fn get<T: WorldExtension>(&self) -> Option<&T> {
+        let index = *self.map.get(&TypeId::of::<T>())?;
+        let extension = self.extensions[index].as_deref()? as &dyn Any;
+        extension.downcast_ref::<T>()
+    }
+
+    pub fn get_mut<T: WorldExtension>(&mut self) -> Option<&mut T> {
+        let index = *self.map.get(&TypeId::of::<T>())?;
+        let extension = self.extensions[index].as_deref_mut()? as &mut dyn Any;
+        extension.downcast_mut::<T>()
+    }
+
+    pub fn contains<T: WorldExtension>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub(crate) fn entity_despawned(&mut self, entity: Entity) {
+        for extension in self.extensions.iter_mut().flatten() {
+            extension.on_entity_despawned(entity);
+        }
+    }
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn insert_extension<T: WorldExtension>(&mut self, extension: T) -> Option<T> {
+        self.extensions.insert(extension)
+    }
+
+    pub fn extension<T: WorldExtension>(&self) -> Option<&T> {
+        self.extensions.get::<T>()
+    }
+
+    pub fn extension_mut<T: WorldExtension>(&mut self) -> Option<&mut T> {
+        self.extensions.get_mut::<T>()
+    }
+
+    pub(crate) fn update_extensions(&mut self) {
+        let mut index = 0;
+        while index < self.extensions.extensions.len() {
+            if let Some(mut extension) = self.extensions.extensions[index].take() {
+                extension.on_update(self);
+                self.extensions.extensions[index] = Some(extension);
+            }
+
+            index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorldExtension;
+    use crate::{
+        system::query::{Query, QueryState},
+        world::{Component, Entity, World},
+    };
+
+    struct Bookkeeping(u32);
+    impl Component for Bookkeeping {}
+
+    #[derive(Default)]
+    struct DespawnCounter {
+        despawned: Vec<Entity>,
+        updates: Vec<(u32, usize)>,
+    }
+
+    impl WorldExtension for DespawnCounter {
+        fn on_update(&mut self, world: &mut World) {
+            self.updates
+                .push((world.frame().get(), self.despawned.len()));
+
+            let entity = world.spawn();
+            world.add_component(entity, Bookkeeping(world.frame().get()));
+        }
+
+        fn on_entity_despawned(&mut self, entity: Entity) {
+            self.despawned.push(entity);
+        }
+    }
+
+    #[test]
+    fn world_extension_hooks() {
+        let mut world = World::new();
+        world.register::<Bookkeeping>();
+        world.insert_extension(DespawnCounter::default());
+
+        let first = world.spawn();
+        let second = world.spawn();
+        world.despawn(first);
+        let frame = world.frame().get();
+        world.update();
+
+        world.despawn(second);
+        world.update();
+
+        let counter = world.extension::<DespawnCounter>().unwrap();
+        assert_eq!(counter.despawned, vec![first, second]);
+        assert_eq!(counter.updates, vec![(frame, 1), (frame + 1, 2)]);
+
+        let state = QueryState::<&Bookkeeping>::new(&world);
+        let query = Query::new(&world, &state);
+        let frames = query.iter().map(|b| b.0).collect::<Vec<_>>();
+        assert_eq!(frames, vec![frame, frame + 1]);
+    }
+
+    #[test]
+    fn world_extension_replace() {
+        struct Vm(u32);
+        impl WorldExtension for Vm {}
+
+        let mut world = World::new();
+        assert!(world.insert_extension(Vm(1)).is_none());
+        assert_eq!(world.insert_extension(Vm(2)).map(|vm| vm.0), Some(1));
+
+        world.extension_mut::<Vm>().unwrap().0 += 1;
+        assert_eq!(world.extension::<Vm>().map(|vm| vm.0), Some(3));
+    }
+}
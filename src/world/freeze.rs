@@ -0,0 +1,252 @@
+use super::{ArchetypeId, World};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FreezeStats {
+    /// Archetypes whose columns are all currently frozen.
+    pub frozen_archetypes: usize,
+    pub freezes: usize,
+    pub thaws: usize,
+    /// Bytes released by freezing, measured when each archetype was frozen.
+    pub bytes_reclaimed: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct FreezeState {
+    idle_frames: Option<u32>,
+    freezes: usize,
+    bytes_reclaimed: usize,
+}
+
+impl World {
+    /// Freezes the archetype's table into its compact read only form.
+    /// Read queries keep working, writes and structural changes thaw it again.
+    /// Frozen components report the freeze frame as their added and modified frame, so
+    /// change detection sees every thawed component as modified at the freeze frame.
+    /// Returns false if the archetype doesn't exist, has no columns or is already frozen.
+    pub fn freeze_archetype(&mut self, id: ArchetypeId) -> bool {
+        let frame = self.frame;
+        let Some(archetype) = self.archetypes.archetype_mut(id) else {
+            return false;
+        };
+
+        let table = archetype.table_mut();
+        if table.is_frozen() || table.columns().next().is_none() {
+            return false;
+        }
+
+        let before = table.memory_usage();
+        table.freeze(frame);
+        let after = table.memory_usage();
+
+        self.freeze.freezes += 1;
+        self.freeze.bytes_reclaimed += before.saturating_sub(after);
+        true
+    }
+
+    /// Automatically freezes non empty archetypes that haven't changed for `idle_frames` frames.
+    /// The check runs during [`World::update`] from the column level change frames, so handing
+    /// out write access counts as a change even if nothing was written. `None` disables it.
+    pub fn set_freeze_policy(&mut self, idle_frames: Option<u32>) {
+        self.freeze.idle_frames = idle_frames;
+    }
+
+    pub fn freeze_stats(&self) -> FreezeStats {
+        let archetypes = self.archetypes.archetypes();
+        FreezeStats {
+            frozen_archetypes: archetypes.iter().filter(|a| a.table().is_frozen()).count(),
+            freezes: self.freeze.freezes,
+            thaws: archetypes.iter().map(|a| a.table().thaws()).sum(),
+            bytes_reclaimed: self.freeze.bytes_reclaimed,
+        }
+    }
+
+    pub(crate) fn apply_freeze_policy(&mut self) {
+        let Some(idle_frames) = self.freeze.idle_frames else {
+            return;
+        };
+
+        let frame = self.frame.get();
+        for index in 0..self.archetypes.archetypes().len() {
+            let table = self.archetypes.archetypes()[index].table();
+            let idle = !table.is_frozen()
+                && table
+                    .last_changed()
+                    .is_some_and(|last| frame.wrapping_sub(last.get()) >= idle_frames);
+
+            if idle {
+                self.freeze_archetype(ArchetypeId(index as u32));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        system::query::{Modified, Query, QueryState},
+        world::{ArchetypeId, Component, World},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Position(u32);
+    impl Component for Position {}
+
+    #[derive(Debug, PartialEq)]
+    struct Tag(u8);
+    impl Component for Tag {}
+
+    fn setup(count: u32) -> (World, ArchetypeId) {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Tag>();
+
+        for i in 0..count {
+            let entity = world.spawn();
+            world.add_component(entity, Position(i));
+        }
+
+        let position = world.components().get_id::<Position>().unwrap();
+        let id = world.archetype_for_components(&[position]);
+        (world, id)
+    }
+
+    fn is_frozen(world: &World, id: ArchetypeId) -> bool {
+        world
+            .archetypes()
+            .archetype(id)
+            .unwrap()
+            .table()
+            .is_frozen()
+    }
+
+    fn positions(world: &World) -> Vec<u32> {
+        let state = QueryState::<&Position>::new(world);
+        let query = Query::new(world, &state);
+        let mut positions = query.iter().map(|p| p.0).collect::<Vec<_>>();
+        positions.sort();
+        positions
+    }
+
+    #[test]
+    fn freeze_reads_and_reclaims_memory() {
+        let (mut world, id) = setup(100);
+        let before = world
+            .archetypes()
+            .archetype(id)
+            .unwrap()
+            .table()
+            .memory_usage();
+
+        assert!(world.freeze_archetype(id));
+        assert!(!world.freeze_archetype(id));
+
+        let table = world.archetypes().archetype(id).unwrap().table();
+        assert!(table.is_frozen());
+        assert!(table.memory_usage() < before);
+        assert_eq!(positions(&world), (0..100).collect::<Vec<_>>());
+
+        let stats = world.freeze_stats();
+        assert_eq!(stats.frozen_archetypes, 1);
+        assert_eq!(stats.freezes, 1);
+        assert_eq!(stats.bytes_reclaimed, before - table.memory_usage());
+    }
+
+    #[test]
+    fn freeze_thaws_on_write() {
+        let (mut world, id) = setup(10);
+        world.freeze_archetype(id);
+
+        let state = QueryState::<&mut Position>::new(&world);
+        let mut query = Query::new_mut(&mut world, &state);
+        for mut position in query.iter_mut() {
+            position.0 += 1;
+        }
+
+        assert!(!is_frozen(&world, id));
+        assert_eq!(positions(&world), (1..11).collect::<Vec<_>>());
+        assert_eq!(world.freeze_stats().thaws, 1);
+    }
+
+    #[test]
+    fn freeze_thaws_on_structural_change() {
+        let (mut world, id) = setup(10);
+        world.freeze_archetype(id);
+
+        let entity = world.spawn();
+        world.add_component(entity, Position(10));
+
+        assert!(!is_frozen(&world, id));
+        assert_eq!(positions(&world), (0..11).collect::<Vec<_>>());
+
+        world.freeze_archetype(id);
+        let moved = *world
+            .archetypes()
+            .archetype(id)
+            .unwrap()
+            .table()
+            .entities()
+            .next()
+            .unwrap();
+        world.add_component(moved, Tag(0));
+
+        assert!(!is_frozen(&world, id));
+        assert_eq!(world.get_component::<Tag>(moved), Some(&Tag(0)));
+        assert_eq!(positions(&world), (0..11).collect::<Vec<_>>());
+        assert_eq!(world.freeze_stats().thaws, 2);
+    }
+
+    #[test]
+    fn freeze_change_detection() {
+        let (mut world, id) = setup(4);
+        world.update();
+        world.update();
+
+        let observed = world.frame();
+        world.freeze_archetype(id);
+        let freeze_frame = world.frame();
+        world.update();
+
+        // Frozen rows report the freeze frame for both added and modified.
+        let state = QueryState::<&Position, Modified<Position>>::new(&world);
+        let since_freeze = Query::with_frame(&world, &state, observed.previous());
+        assert_eq!(since_freeze.iter().count(), 4);
+
+        let after_freeze = Query::with_frame(&world, &state, freeze_frame);
+        assert_eq!(after_freeze.iter().count(), 0);
+
+        // Thawing keeps the freeze frame until rows are written again.
+        let write = QueryState::<&mut Position>::new(&world);
+        let mut query = Query::new_mut(&mut world, &write);
+        query.iter_mut().take(1).for_each(|mut p| p.0 += 10);
+
+        let after_freeze = Query::with_frame(&world, &state, freeze_frame);
+        assert_eq!(after_freeze.iter().count(), 1);
+    }
+
+    #[test]
+    fn freeze_policy() {
+        let (mut world, id) = setup(4);
+        world.set_freeze_policy(Some(3));
+
+        for _ in 0..3 {
+            world.update();
+        }
+        assert!(!is_frozen(&world, id));
+
+        world.update();
+        assert!(is_frozen(&world, id));
+        assert_eq!(world.freeze_stats().freezes, 1);
+
+        // Handing out write access restarts the idle count, even without writing.
+        let write = QueryState::<&mut Position>::new(&world);
+        Query::new_mut(&mut world, &write).iter_mut().count();
+        for _ in 0..3 {
+            world.update();
+        }
+        assert!(!is_frozen(&world, id));
+
+        world.update();
+        assert!(is_frozen(&world, id));
+        assert_eq!(world.freeze_stats().freezes, 2);
+    }
+}
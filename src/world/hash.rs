@@ -0,0 +1,396 @@
+use super::{Component, ComponentId, Entity, Resource, ResourceId, World};
+use crate::core::{Frame, StableHasher};
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone, Default)]
+pub struct StateHashConfig {
+    /// Components to hash, `None` hashes every hashable component.
+    pub components: Option<Vec<ComponentId>>,
+    /// Hashable resources to include.
+    pub resources: Vec<ResourceId>,
+    /// Only rehash components added or modified since the previous incremental call.
+    /// Writes that bypass change detection, like [`World::get_component_mut`], are missed.
+    pub incremental: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateHash {
+    pub total: u64,
+    /// Order independent digest of each hashed component type, sorted by id.
+    pub components: Vec<(ComponentId, u64)>,
+    pub resources: Vec<(ResourceId, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateHashMismatch {
+    Component {
+        id: ComponentId,
+        name: &'static str,
+        expected: Option<u64>,
+        found: Option<u64>,
+    },
+    Resource {
+        id: ResourceId,
+        name: &'static str,
+        expected: Option<u64>,
+        found: Option<u64>,
+    },
+}
+
+/// Per entity component hashes kept between incremental [`World::state_hash`] calls.
+#[derive(Default)]
+pub(crate) struct StateHashCache {
+    sums: Vec<(ComponentId, u64)>,
+    entries: HashMap<(Entity, ComponentId), (u64, u32)>,
+    frame: Option<Frame>,
+    epoch: u32,
+}
+
+fn contribution(entity: Entity, component: ComponentId, value: u64) -> u64 {
+    StableHasher::hash_one(&(entity.id(), entity.generation(), component, value))
+}
+
+impl World {
+    pub fn register_hashable<C: Component + Hash>(&mut self) -> ComponentId {
+        self.archetypes.register_hashable::<C>()
+    }
+
+    pub fn register_hashable_resource<R: Resource + Send + Hash>(&mut self) -> ResourceId {
+        self.resources.register_hashable::<true, R>()
+    }
+
+    /// Order independent digest of the selected components and resources, identical across
+    /// platforms for identical state.
+    pub fn state_hash(&mut self, config: &StateHashConfig) -> u64 {
+        self.state_hash_report(config).total
+    }
+
+    /// Like [`World::state_hash`], with the digest of every component type and resource.
+    pub fn state_hash_report(&mut self, config: &StateHashConfig) -> StateHash {
+        let selected = self.hashed_components(config);
+        let components = match config.incremental {
+            true => self.component_digests_incremental(&selected),
+            false => self.component_digests(&selected),
+        };
+
+        let resources = config
+            .resources
+            .iter()
+            .map(|id| {
+                let meta = self.resources.get_meta(*id).unwrap();
+                let hash = meta
+                    .hash_fn()
+                    .unwrap_or_else(|| panic!("Resource is not hashable: {}", meta.name()));
+                let value = self
+                    .resources
+                    .get_ptr_by_id(*id)
+                    .map(|ptr| unsafe { hash(ptr.as_ptr()) });
+
+                (*id, StableHasher::hash_one(&value))
+            })
+            .collect::<Vec<_>>();
+
+        StateHash {
+            total: StableHasher::hash_one(&(&components, &resources)),
+            components,
+            resources,
+        }
+    }
+
+    /// Compares the current state against a hash computed elsewhere, listing the component
+    /// types and resources whose digests differ.
+    pub fn state_hash_diff(
+        &mut self,
+        config: &StateHashConfig,
+        expected: &StateHash,
+    ) -> Vec<StateHashMismatch> {
+        fn diff<I: Copy + Ord>(
+            expected: &[(I, u64)],
+            found: &[(I, u64)],
+            mut mismatch: impl FnMut(I, Option<u64>, Option<u64>),
+        ) {
+            let mut ids = expected
+                .iter()
+                .chain(found)
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids.dedup();
+
+            let get = |list: &[(I, u64)], id| list.iter().find(|(i, _)| *i == id).map(|e| e.1);
+            for id in ids {
+                let (expected, found) = (get(expected, id), get(found, id));
+                if expected != found {
+                    mismatch(id, expected, found);
+                }
+            }
+        }
+
+        let found = self.state_hash_report(config);
+        let mut mismatches = Vec::new();
+        diff(
+            &expected.components,
+            &found.components,
+            |id, expected, found| {
+                mismatches.push(StateHashMismatch::Component {
+                    id,
+                    name: self.components().meta(id).map_or("", |meta| meta.name()),
+                    expected,
+                    found,
+                });
+            },
+        );
+        diff(
+            &expected.resources,
+            &found.resources,
+            |id, expected, found| {
+                mismatches.push(StateHashMismatch::Resource {
+                    id,
+                    name: self.resources.get_meta(id).map_or("", |meta| meta.name()),
+                    expected,
+                    found,
+                });
+            },
+        );
+
+        mismatches
+    }
+
+    fn hashed_components(&self, config: &StateHashConfig) -> Vec<ComponentId> {
+        let metas = self.components().metas();
+        let mut selected = match &config.components {
+            Some(components) => components.clone(),
+            None => metas
+                .iter()
+                .filter(|meta| meta.hash_fn().is_some())
+                .map(|meta| meta.id())
+                .collect(),
+        };
+
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+
+    /// Visits every selected component with the frame it last changed, values are only hashed
+    /// when the visitor calls `hash`.
+    fn visit_components(
+        &self,
+        selected: &[ComponentId],
+        mut visit: impl FnMut(usize, Entity, ComponentId, Option<Frame>, &dyn Fn() -> u64),
+    ) {
+        let current = self.frame;
+        for archetype in self.archetypes.archetypes() {
+            let table = archetype.table();
+            for (index, id) in selected.iter().enumerate() {
+                let Some(column) = table.get_column(*id) else {
+                    continue;
+                };
+
+                let meta = self.components().meta(*id).unwrap();
+                let hash = meta
+                    .hash_fn()
+                    .unwrap_or_else(|| panic!("Component is not hashable: {}", meta.name()));
+
+                let (bytes, _, layout) = column.raw_parts();
+                for (row, entity) in table.entities().enumerate() {
+                    let frame = column.status(row).map(|status| {
+                        match status.added.is_newer(current, status.modified) {
+                            true => status.added,
+                            false => status.modified,
+                        }
+                    });
+
+                    let value = || unsafe { hash(bytes.as_ptr().add(row * layout.size())) };
+                    visit(index, *entity, *id, frame, &value);
+                }
+            }
+        }
+    }
+
+    fn component_digests(&self, selected: &[ComponentId]) -> Vec<(ComponentId, u64)> {
+        let mut sums = selected.iter().map(|id| (*id, 0u64)).collect::<Vec<_>>();
+        self.visit_components(selected, |index, entity, id, _, hash| {
+            let sum = &mut sums[index].1;
+            *sum = sum.wrapping_add(contribution(entity, id, hash()));
+        });
+
+        sums
+    }
+
+    fn component_digests_incremental(
+        &mut self,
+        selected: &[ComponentId],
+    ) -> Vec<(ComponentId, u64)> {
+        let mut cache = std::mem::take(&mut self.hash_cache);
+        if !cache.sums.iter().map(|(id, _)| id).eq(selected) {
+            cache = StateHashCache {
+                sums: selected.iter().map(|id| (*id, 0)).collect(),
+                ..Default::default()
+            };
+        }
+
+        cache.epoch = cache.epoch.wrapping_add(1);
+        let epoch = cache.epoch;
+        let current = self.frame;
+        // Changes made during the frame of the previous call may have happened after it.
+        let since = cache.frame.map(Frame::previous);
+
+        let StateHashCache { sums, entries, .. } = &mut cache;
+        self.visit_components(selected, |index, entity, id, frame, hash| {
+            let key = (entity, id);
+            let changed = match (since, frame) {
+                (Some(since), Some(frame)) => frame.is_newer(current, since),
+                _ => true,
+            };
+
+            if !changed && let Some(entry) = entries.get_mut(&key) {
+                entry.1 = epoch;
+                return;
+            }
+
+            let hash = contribution(entity, id, hash());
+            let old = entries.insert(key, (hash, epoch)).map_or(0, |(old, _)| old);
+            let sum = &mut sums[index].1;
+            *sum = sum.wrapping_sub(old).wrapping_add(hash);
+        });
+
+        entries.retain(|(_, id), (hash, seen)| {
+            if *seen == epoch {
+                return true;
+            }
+
+            let index = selected.binary_search(id).unwrap();
+            sums[index].1 = sums[index].1.wrapping_sub(*hash);
+            false
+        });
+
+        cache.frame = Some(current);
+        let digests = cache.sums.clone();
+        self.hash_cache = cache;
+        digests
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StateHashConfig, StateHashMismatch};
+    use crate::{
+        system::query::{Query, QueryState},
+        world::{Component, Entity, Resource, World},
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Position(i32, i32);
+    impl Component for Position {}
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Velocity(i32, i32);
+    impl Component for Velocity {}
+
+    #[derive(Debug, Hash)]
+    struct Seed(u64);
+    impl Resource for Seed {}
+
+    fn simulation() -> (World, Vec<Entity>) {
+        let mut world = World::new();
+        world.register_hashable::<Position>();
+        world.register_hashable::<Velocity>();
+        world.register_hashable_resource::<Seed>();
+        world.add_resource(Seed(7));
+
+        let entities = (0..16)
+            .map(|i| {
+                let entity = world.spawn();
+                world.add_component(entity, Position(i, -i));
+                world.add_component(entity, Velocity(1, i % 3));
+                entity
+            })
+            .collect();
+
+        (world, entities)
+    }
+
+    fn step(world: &mut World) {
+        let state = QueryState::<(&mut Position, &Velocity)>::new(world);
+        let mut query = Query::new_mut(world, &state);
+        for (mut position, velocity) in query.iter_mut() {
+            position.0 += velocity.0;
+            position.1 += velocity.1;
+        }
+
+        world.update();
+    }
+
+    fn config(world: &World, incremental: bool) -> StateHashConfig {
+        StateHashConfig {
+            resources: vec![world.resources().get_id::<Seed>().unwrap()],
+            incremental,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn state_hash_identical_simulations() {
+        let (mut left, _) = simulation();
+        let (mut right, _) = simulation();
+        let (full, incremental) = (config(&left, false), config(&right, true));
+
+        for _ in 0..5 {
+            assert_eq!(left.state_hash(&full), right.state_hash(&incremental));
+            step(&mut left);
+            step(&mut right);
+        }
+
+        let before = left.state_hash(&full);
+        left.resource_mut::<Seed>().0 += 1;
+        assert_ne!(left.state_hash(&full), before);
+    }
+
+    #[test]
+    fn state_hash_localizes_divergence() {
+        let (mut left, _) = simulation();
+        let (mut right, entities) = simulation();
+        let config = config(&left, false);
+
+        step(&mut left);
+        step(&mut right);
+        right.add_component(entities[3], Velocity(2, 0));
+
+        let expected = left.state_hash_report(&config);
+        assert_ne!(right.state_hash(&config), expected.total);
+
+        let velocity = right.components().get_id::<Velocity>().unwrap();
+        let mismatches = right.state_hash_diff(&config, &expected);
+        assert_eq!(mismatches.len(), 1);
+        assert!(matches!(
+            mismatches[0],
+            StateHashMismatch::Component { id, name, .. } if id == velocity && name.ends_with("Velocity")
+        ));
+    }
+
+    #[test]
+    fn state_hash_incremental_matches_full() {
+        let (mut world, entities) = simulation();
+        let full = config(&world, false);
+        let incremental = config(&world, true);
+
+        for (frame, entity) in entities.into_iter().enumerate().take(8) {
+            match frame % 4 {
+                0 => {
+                    let entity = world.spawn();
+                    world.add_component(entity, Position(frame as i32, 0));
+                }
+                1 => {
+                    world.despawn(entity);
+                }
+                2 => world.remove_component::<Velocity>(entity),
+                _ => world.add_component(entity, Velocity(-1, -1)),
+            }
+
+            assert_eq!(world.state_hash(&incremental), world.state_hash(&full));
+            step(&mut world);
+            assert_eq!(world.state_hash(&incremental), world.state_hash(&full));
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use super::{
+    Command, CommandKind, Component, Entity, EntityCommands, EntityMapper, MapEntities, World,
+};
+use serde::{Deserialize, Serialize};
+
+/// The parent of an entity, kept in sync with the parent's [`Children`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Parent(Entity);
+
+impl Parent {
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+impl Component for Parent {}
+
+impl MapEntities for Parent {
+    fn map_entities(&mut self, mapper: &EntityMapper) {
+        self.0 = mapper.map(self.0);
+    }
+}
+
+/// The children of an entity in the order they were added, kept in sync with their [`Parent`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Children(Vec<Entity>);
+
+impl MapEntities for Children {
+    fn map_entities(&mut self, mapper: &EntityMapper) {
+        for entity in &mut self.0 {
+            *entity = mapper.map(*entity);
+        }
+    }
+}
+
+impl Children {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+}
+
+impl Component for Children {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyError {
+    Dead(Entity),
+    /// The entity can't be its own parent.
+    SelfParent(Entity),
+    /// The new parent is a descendant of the child.
+    Cycle {
+        child: Entity,
+        parent: Entity,
+    },
+}
+
+impl std::fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HierarchyError::Dead(entity) => write!(f, "{} is not alive", entity),
+            HierarchyError::SelfParent(entity) => write!(f, "{} can't be its own parent", entity),
+            HierarchyError::Cycle { child, parent } => {
+                write!(f, "{} is a descendant of {}", parent, child)
+            }
+        }
+    }
+}
+
+impl World {
+    /// Makes `parent` the parent of `child`, removing it from the children of its old parent.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<(), HierarchyError> {
+        for entity in [child, parent] {
+            if !self.entities.is_alive(entity) {
+                return Err(HierarchyError::Dead(entity));
+            }
+        }
+
+        if child == parent {
+            return Err(HierarchyError::SelfParent(child));
+        }
+
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            if entity == child {
+                return Err(HierarchyError::Cycle { child, parent });
+            }
+            ancestor = self.get_component::<Parent>(entity).map(Parent::get);
+        }
+
+        if self.get_component::<Parent>(child) == Some(&Parent(parent)) {
+            return Ok(());
+        }
+
+        self.remove_parent(child);
+        self.add_component(child, Parent(parent));
+        match self.get_component_mut::<Children>(parent) {
+            Some(children) => children.0.push(child),
+            None => self.add_component(parent, Children(vec![child])),
+        }
+
+        Ok(())
+    }
+
+    /// Detaches the entity from its parent, returning the old parent.
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let parent = self.get_component::<Parent>(child)?.get();
+        self.remove_component::<Parent>(child);
+        self.remove_child(parent, child);
+        Some(parent)
+    }
+
+    /// Despawns the entity and all of its descendants.
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        self.remove_parent(entity);
+
+        let mut stack = vec![entity];
+        while let Some(entity) = stack.pop() {
+            if let Some(children) = self.get_component::<Children>(entity) {
+                stack.extend(children.iter());
+            }
+
+            self.despawn(entity);
+        }
+    }
+
+    /// Removes the despawned entity from its parent and orphans its children.
+    pub(crate) fn detach_hierarchy(&mut self, entity: Entity) {
+        if let Some(parent) = self.get_component::<Parent>(entity).map(Parent::get) {
+            self.remove_child(parent, entity);
+        }
+
+        let children = match self.get_component_mut::<Children>(entity) {
+            Some(children) => std::mem::take(&mut children.0),
+            None => return,
+        };
+
+        for child in children {
+            self.remove_component::<Parent>(child);
+        }
+    }
+
+    fn remove_child(&mut self, parent: Entity, child: Entity) {
+        let Some(children) = self.get_component_mut::<Children>(parent) else {
+            return;
+        };
+
+        children.0.retain(|entity| *entity != child);
+        if children.is_empty() {
+            self.remove_component::<Children>(parent);
+        }
+    }
+}
+
+/// Invalid parents are ignored, see [`World::set_parent`].
+pub struct SetParent {
+    pub child: Entity,
+    pub parent: Entity,
+}
+
+impl Command for SetParent {
+    fn execute(self, world: &mut World) {
+        let _ = world.set_parent(self.child, self.parent);
+    }
+
+    fn kind(&self, world: &World) -> CommandKind {
+        CommandKind::Insert {
+            entity: self.child,
+            component: unsafe { world.components().get_id_unchecked::<Parent>() },
+        }
+    }
+}
+
+pub struct RemoveParent(pub Entity);
+
+impl Command for RemoveParent {
+    fn execute(self, world: &mut World) {
+        world.remove_parent(self.0);
+    }
+
+    fn kind(&self, world: &World) -> CommandKind {
+        CommandKind::Remove {
+            entity: self.0,
+            component: unsafe { world.components().get_id_unchecked::<Parent>() },
+        }
+    }
+}
+
+pub struct DespawnRecursive(pub Entity);
+
+impl Command for DespawnRecursive {
+    fn execute(self, world: &mut World) {
+        world.despawn_recursive(self.0);
+    }
+
+    fn kind(&self, _: &World) -> CommandKind {
+        CommandKind::Despawn(self.0)
+    }
+}
+
+impl EntityCommands<'_> {
+    pub fn set_parent(self, parent: Entity) -> Self {
+        let child = self.id();
+        self.add_command(SetParent { child, parent })
+    }
+
+    pub fn add_child(self, child: Entity) -> Self {
+        let parent = self.id();
+        self.add_command(SetParent { child, parent })
+    }
+
+    pub fn remove_parent(self) -> Self {
+        let child = self.id();
+        self.add_command(RemoveParent(child))
+    }
+
+    pub fn despawn_recursive(self) {
+        let entity = self.id();
+        self.add_command(DespawnRecursive(entity));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Children, HierarchyError, Parent};
+    use crate::{
+        app::App,
+        system::{query::Query, schedule::Phase},
+        world::{Commands, Entity, Resource, World},
+    };
+    use std::sync::Mutex;
+
+    fn children(world: &World, entity: Entity) -> Vec<Entity> {
+        world
+            .get_component::<Children>(entity)
+            .map_or(vec![], |children| children.iter().collect())
+    }
+
+    #[test]
+    fn set_and_remove_parent() {
+        let mut world = World::new();
+        let (a, b, child) = (world.spawn(), world.spawn(), world.spawn());
+
+        world.set_parent(child, a).unwrap();
+        assert_eq!(world.get_component::<Parent>(child), Some(&Parent(a)));
+        assert_eq!(children(&world, a), [child]);
+
+        world.set_parent(child, b).unwrap();
+        assert_eq!(world.get_component::<Parent>(child), Some(&Parent(b)));
+        assert!(world.get_component::<Children>(a).is_none());
+        assert_eq!(children(&world, b), [child]);
+
+        assert_eq!(
+            world.set_parent(b, child),
+            Err(HierarchyError::Cycle {
+                child: b,
+                parent: child
+            })
+        );
+        assert_eq!(world.set_parent(b, b), Err(HierarchyError::SelfParent(b)));
+
+        assert_eq!(world.remove_parent(child), Some(b));
+        assert!(world.get_component::<Parent>(child).is_none());
+        assert!(world.get_component::<Children>(b).is_none());
+    }
+
+    #[test]
+    fn despawn_keeps_hierarchy_consistent() {
+        let mut world = World::new();
+        let (root, middle, leaf, other) =
+            (world.spawn(), world.spawn(), world.spawn(), world.spawn());
+        world.set_parent(middle, root).unwrap();
+        world.set_parent(other, root).unwrap();
+        world.set_parent(leaf, middle).unwrap();
+
+        world.despawn(middle);
+        assert_eq!(children(&world, root), [other]);
+        assert!(world.get_component::<Parent>(leaf).is_none());
+
+        world.set_parent(leaf, other).unwrap();
+        world.despawn_recursive(other);
+        assert!(!world.entities().is_alive(other));
+        assert!(!world.entities().is_alive(leaf));
+        assert!(world.get_component::<Children>(root).is_none());
+        assert!(world.entities().is_alive(root));
+    }
+
+    struct Update;
+    impl Phase for Update {}
+
+    struct Cleanup;
+    impl Phase for Cleanup {}
+
+    #[derive(Default)]
+    struct Spawned(Mutex<Vec<Entity>>);
+    impl Resource for Spawned {}
+
+    fn build(mut commands: Commands, spawned: &Spawned) {
+        let root = commands.spawn().id();
+        let first = commands.spawn().set_parent(root).id();
+        let second = commands.spawn().id();
+        let leaf = commands.spawn().id();
+        commands.entity(root).add_child(second);
+        commands.entity(first).add_child(leaf);
+        spawned
+            .0
+            .lock()
+            .unwrap()
+            .extend([root, first, second, leaf]);
+    }
+
+    fn cleanup(mut commands: Commands, spawned: &Spawned, query: Query<&Children>) {
+        let [root, first, second, leaf] = spawned.0.lock().unwrap()[..] else {
+            unreachable!()
+        };
+        let descendants = query.iter_descendants(root).collect::<Vec<_>>();
+        assert_eq!(descendants, [first, leaf, second]);
+        assert_eq!(query.children(root), [first, second]);
+
+        commands.entity(root).despawn_recursive();
+    }
+
+    #[test]
+    fn hierarchy_commands() {
+        let mut app = App::new()
+            .add_resource(Spawned::default())
+            .add_systems(Update, build)
+            .add_systems(Cleanup, cleanup)
+            .build();
+
+        app.run(Update);
+        let spawned = app.world().resource::<Spawned>().0.lock().unwrap().clone();
+        let [root, first, second, leaf] = spawned[..] else {
+            unreachable!()
+        };
+        assert_eq!(children(app.world(), root), [first, second]);
+        assert_eq!(children(app.world(), first), [leaf]);
+        assert_eq!(
+            app.world().get_component::<Parent>(leaf).map(Parent::get),
+            Some(first)
+        );
+
+        app.run(Cleanup);
+        for entity in spawned {
+            assert!(!app.world().entities().is_alive(entity));
+        }
+    }
+}
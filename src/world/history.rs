@@ -0,0 +1,299 @@
+use super::{Archetypes, Component, ComponentId, Components, Entity, Resource};
+use crate::core::Frame;
+use std::{
+    any::TypeId,
+    collections::{HashMap, VecDeque},
+};
+
+#[cfg(test)]
+thread_local! {
+    /// Columns whose rows [`ChangeHistory::collect`] scanned on this thread.
+    static SCANNED_COLUMNS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChangeRecord {
+    pub frame: Frame,
+    pub entity: Entity,
+    pub component: ComponentId,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeHistoryConfig {
+    /// Number of frames of records to keep.
+    pub frames: u32,
+    /// Components to track. `None` tracks every component.
+    pub components: Option<Vec<ComponentId>>,
+}
+
+impl ChangeHistoryConfig {
+    pub fn new(frames: u32) -> Self {
+        Self {
+            frames,
+            components: None,
+        }
+    }
+
+    pub fn with_component(mut self, id: ComponentId) -> Self {
+        self.components.get_or_insert_with(Vec::new).push(id);
+        self
+    }
+
+    pub fn tracks(&self, id: ComponentId) -> bool {
+        match &self.components {
+            Some(components) => components.contains(&id),
+            None => true,
+        }
+    }
+}
+
+/// Ring of recent component changes, recorded by the end of frame maintenance.
+/// Only ids are stored, component values are never captured.
+pub struct ChangeHistory {
+    config: ChangeHistoryConfig,
+    records: VecDeque<ChangeRecord>,
+    /// Ids of the components registered when the history last recorded.
+    types: HashMap<TypeId, ComponentId>,
+}
+
+impl ChangeHistory {
+    pub fn new(config: ChangeHistoryConfig) -> Self {
+        Self {
+            config,
+            records: VecDeque::new(),
+            types: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ChangeHistoryConfig {
+        &self.config
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &ChangeRecord> {
+        self.records.iter()
+    }
+
+    pub fn for_entity(&self, entity: Entity) -> impl Iterator<Item = &ChangeRecord> {
+        self.records.iter().filter(move |r| r.entity == entity)
+    }
+
+    /// Records of `C`, empty if `C` wasn't registered when the history last recorded.
+    pub fn for_component<C: Component>(&self) -> impl Iterator<Item = &ChangeRecord> {
+        let id = self.types.get(&TypeId::of::<C>()).copied();
+        self.records.iter().filter(move |r| Some(r.component) == id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    pub(crate) fn record_removed(
+        &mut self,
+        components: &Components,
+        entity: Entity,
+        component: ComponentId,
+        frame: Frame,
+    ) {
+        self.sync_types(components);
+        if self.config.tracks(component) {
+            self.records.push_back(ChangeRecord {
+                frame,
+                entity,
+                component,
+                kind: ChangeKind::Removed,
+            });
+        }
+    }
+
+    /// Records every tracked component added or modified in the given frame
+    /// and evicts records older than the configured window. Columns whose latest
+    /// added and modified frames are older than `frame` are skipped without
+    /// looking at their rows.
+    pub(crate) fn collect(&mut self, archetypes: &Archetypes, frame: Frame) {
+        self.sync_types(archetypes.components());
+        for archetype in archetypes.archetypes() {
+            let table = archetype.table();
+            for (id, column) in table.columns() {
+                let changed = column.changed();
+                if !self.config.tracks(*id) || (changed.added != frame && changed.modified != frame)
+                {
+                    continue;
+                }
+
+                #[cfg(test)]
+                SCANNED_COLUMNS.with(|scanned| scanned.set(scanned.get() + 1));
+
+                for (status, entity) in column.frames().iter().zip(table.entities()) {
+                    let kind = if status.added == frame {
+                        ChangeKind::Added
+                    } else if status.modified == frame {
+                        ChangeKind::Modified
+                    } else {
+                        continue;
+                    };
+
+                    self.records.push_back(ChangeRecord {
+                        frame,
+                        entity: *entity,
+                        component: *id,
+                        kind,
+                    });
+                }
+            }
+        }
+
+        while let Some(record) = self.records.front() {
+            if frame.relative_to(record.frame).get() <= self.config.frames.saturating_sub(1) {
+                break;
+            }
+
+            self.records.pop_front();
+        }
+    }
+
+    fn sync_types(&mut self, components: &Components) {
+        if self.types.len() < components.len() {
+            for meta in &components.metas()[self.types.len()..] {
+                self.types.insert(meta.type_id(), meta.id());
+            }
+        }
+    }
+}
+
+impl Resource for ChangeHistory {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeHistory, ChangeHistoryConfig, ChangeKind, SCANNED_COLUMNS};
+    use crate::world::{Component, World};
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Speed(u32);
+    impl Component for Speed {}
+
+    #[test]
+    fn change_history_records() {
+        let mut world = World::new();
+        let health = world.register::<Health>();
+        world.register::<Speed>();
+        world.add_resource(ChangeHistory::new(
+            ChangeHistoryConfig::new(10).with_component(health),
+        ));
+
+        let entity = world.spawn();
+        world.add_component(entity, Health(10));
+        world.add_component(entity, Speed(1));
+        let added = world.frame();
+        world.update();
+
+        world.add_component(entity, Health(5));
+        let modified = world.frame();
+        world.update();
+
+        world.remove_component::<Health>(entity);
+        let removed = world.frame();
+        world.update();
+
+        let history = world.resource::<ChangeHistory>();
+        let records = history
+            .for_entity(entity)
+            .map(|r| (r.frame, r.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            records,
+            vec![
+                (added, ChangeKind::Added),
+                (modified, ChangeKind::Modified),
+                (removed, ChangeKind::Removed),
+            ]
+        );
+        assert_eq!(history.for_component::<Health>().count(), 3);
+        assert_eq!(history.for_component::<Speed>().count(), 0);
+    }
+
+    #[test]
+    fn change_history_evicts_oldest_frames() {
+        let mut world = World::new();
+        world.register::<Health>();
+        world.add_resource(ChangeHistory::new(ChangeHistoryConfig::new(2)));
+
+        let entity = world.spawn();
+        world.add_component(entity, Health(0));
+        let first = world.frame();
+        world.update();
+
+        for value in 1..4 {
+            world.add_component(entity, Health(value));
+            world.update();
+        }
+
+        let history = world.resource::<ChangeHistory>();
+        assert_eq!(history.len(), 2);
+        assert!(history.records().all(|r| r.frame != first));
+    }
+
+    #[test]
+    fn change_history_skips_unchanged_columns() {
+        let mut world = World::new();
+        world.register::<Health>();
+        world.register::<Speed>();
+        world.add_resource(ChangeHistory::new(ChangeHistoryConfig::new(10)));
+
+        for value in 0..100 {
+            world.spawn_bundle((Health(value), Speed(value)));
+        }
+        let entity = world.spawn_bundle((Health(0),));
+
+        let scanned = || SCANNED_COLUMNS.with(|scanned| scanned.replace(0));
+        scanned();
+        world.update();
+        assert_eq!(scanned(), 3);
+
+        world.update();
+        assert_eq!(scanned(), 0);
+
+        world.add_component(entity, Health(1));
+        world.update();
+        assert_eq!(scanned(), 1);
+        assert_eq!(world.resource::<ChangeHistory>().len(), 202);
+    }
+
+    #[test]
+    fn change_history_disabled() {
+        let mut world = World::new();
+        world.register::<Health>();
+
+        let entity = world.spawn();
+        world.add_component(entity, Health(0));
+        world.remove_component::<Health>(entity);
+        SCANNED_COLUMNS.with(|scanned| scanned.set(0));
+        for _ in 0..3 {
+            world.add_component(entity, Health(1));
+            world.update();
+        }
+
+        assert!(world.try_resource::<ChangeHistory>().is_none());
+        assert_eq!(SCANNED_COLUMNS.with(|scanned| scanned.get()), 0);
+    }
+}
@@ -0,0 +1,177 @@
+use super::{Component, ComponentId, Entity, Lifecycle, Row, World};
+use std::collections::HashMap;
+
+/// Maps entities of a scene or merged world to the entities created for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityMapper {
+    map: HashMap<Entity, Entity>,
+}
+
+impl EntityMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, from: Entity, to: Entity) -> Option<Entity> {
+        self.map.insert(from, to)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<Entity> {
+        self.map.get(&entity).copied()
+    }
+
+    /// The entity created for `entity`, or `entity` itself if it wasn't part of the source.
+    pub fn map(&self, entity: Entity) -> Entity {
+        self.get(entity).unwrap_or(entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.map.iter().map(|(from, to)| (*from, *to))
+    }
+}
+
+/// Components holding entity references that must stay valid when their entities are copied
+/// into another world.
+pub trait MapEntities {
+    fn map_entities(&mut self, mapper: &EntityMapper);
+}
+
+/// Remaps the entity references of the entity's component, see [`World::register_map_entities`].
+pub type MapEntitiesFn = fn(&mut World, Entity, &EntityMapper);
+
+pub(crate) fn map_component_entities<C: Component + MapEntities>(
+    world: &mut World,
+    entity: Entity,
+    mapper: &EntityMapper,
+) {
+    if let Some(component) = world.get_component_mut::<C>(entity) {
+        component.map_entities(mapper);
+    }
+}
+
+impl World {
+    /// Registers the component so its entity references are remapped by [`World::merge`].
+    pub fn register_map_entities<C: Component + MapEntities>(&mut self) -> ComponentId {
+        self.archetypes.register_map_entities::<C>()
+    }
+
+    /// Moves every entity of `other` into this world as a new entity. Components missing here are
+    /// registered by type, and the ones registered with [`World::register_map_entities`] have
+    /// their references remapped. Resources of `other` are dropped.
+    pub fn merge(&mut self, mut other: World) -> EntityMapper {
+        let entities = other
+            .archetypes()
+            .archetypes()
+            .iter()
+            .flat_map(|archetype| archetype.table().entities().copied())
+            .collect::<Vec<_>>();
+
+        // Every entity is allocated first so references to entities merged later can be mapped.
+        let mut mapper = EntityMapper::new();
+        for entity in &entities {
+            mapper.insert(*entity, self.entities.spawn());
+        }
+
+        let mut merged = Vec::with_capacity(entities.len());
+        for entity in entities {
+            let (_, cells) = other.archetypes.remove_entity(entity).unwrap();
+            let mut row = Row::new();
+            for (id, cell) in cells.into_cells() {
+                let meta = other.components().meta(id).unwrap();
+                row.insert_cell(self.archetypes.register_meta(meta), cell);
+            }
+
+            let components = row.ids().to_vec();
+            let target = mapper.map(entity);
+            let archetype = self.archetypes.get_or_create(&components);
+            self.archetypes.spawn_in(archetype, target, row, self.frame);
+            merged.push((target, components));
+        }
+
+        for (entity, components) in &merged {
+            for component in components {
+                let map = self
+                    .components()
+                    .meta(*component)
+                    .and_then(|meta| meta.map_entities_fn());
+                if let Some(map) = map {
+                    map(self, *entity, &mapper);
+                }
+            }
+        }
+
+        for (entity, components) in merged {
+            let observed = components
+                .into_iter()
+                .filter(|component| self.is_observed(*component))
+                .collect::<Vec<_>>();
+            self.trigger_all(Lifecycle::Add, entity, &observed);
+            self.trigger_all(Lifecycle::Insert, entity, &observed);
+        }
+
+        mapper
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntityMapper, MapEntities};
+    use crate::world::{Children, Component, Entity, Parent, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Debug, PartialEq)]
+    struct Target(Entity);
+    impl Component for Target {}
+
+    impl MapEntities for Target {
+        fn map_entities(&mut self, mapper: &EntityMapper) {
+            self.0 = mapper.map(self.0);
+        }
+    }
+
+    #[test]
+    fn merge_remaps_entities() {
+        let mut other = World::new();
+        other.register::<Health>();
+        other.register_map_entities::<Target>();
+        let outside = Entity::new(99, 1);
+        let root = other.spawn_bundle((Health(10),));
+        let child = other.spawn_bundle((Target(root),));
+        let stray = other.spawn_bundle((Target(outside),));
+        other.set_parent(child, root).unwrap();
+
+        let mut world = World::new();
+        world.register_map_entities::<Target>();
+        let existing = world.spawn_bundle((Target(root),));
+
+        let mapper = world.merge(other);
+        assert_eq!(mapper.len(), 3);
+        let (root, child, stray) = (mapper.map(root), mapper.map(child), mapper.map(stray));
+        assert_ne!(root, existing);
+
+        assert_eq!(world.get_component::<Health>(root), Some(&Health(10)));
+        assert_eq!(world.get_component::<Target>(child), Some(&Target(root)));
+        assert_eq!(world.get_component::<Target>(stray), Some(&Target(outside)));
+        assert_eq!(
+            world.get_component::<Parent>(child).map(Parent::get),
+            Some(root)
+        );
+        let children = world.get_component::<Children>(root).unwrap();
+        assert_eq!(children.as_slice(), [child]);
+
+        // Components of the destination world are left alone.
+        let target = world.get_component::<Target>(existing).unwrap();
+        assert_eq!(target.0, Entity::new(0, 1));
+    }
+}
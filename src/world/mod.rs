@@ -1,31 +1,75 @@
-use crate::core::Frame;
+use crate::{
+    core::{CHECK_FRAME_THRESHOLD, Frame, TaskPool},
+    system::{
+        SystemId,
+        error::SystemErrors,
+        executor::{CancelHandle, ExecutorConfig},
+        query::{BaseFilter, BaseQuery, Query, QueryState},
+    },
+};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicU32, Ordering},
+    },
+};
 
 pub mod archetype;
+pub mod bundle;
 pub mod cell;
 pub mod command;
 pub mod component;
+pub mod disabled;
 pub mod entity;
+pub mod entity_ref;
 pub mod event;
+pub mod extension;
+pub mod freeze;
+pub mod hash;
+pub mod hierarchy;
+pub mod history;
+pub mod merge;
+pub mod name;
+pub mod observer;
+pub mod relationship;
 pub mod resource;
+pub mod route;
+pub mod scene;
+pub mod snapshot;
+pub mod stats;
 
 pub use archetype::*;
+pub use bundle::*;
 pub use cell::*;
 pub use command::*;
 pub use component::*;
+pub use disabled::*;
 pub use entity::*;
+pub use entity_ref::*;
 pub use event::*;
+pub use extension::*;
+pub use freeze::*;
+pub use hash::*;
+pub use hierarchy::*;
+pub use history::*;
+pub use merge::*;
+pub use name::*;
+pub use observer::*;
+pub use relationship::*;
 pub use resource::*;
+pub use route::*;
+pub use scene::*;
+pub use snapshot::*;
+pub use stats::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct WorldId(u32);
 impl WorldId {
     fn new() -> Self {
-        static mut ID: u32 = 0;
-        unsafe {
-            let id = ID;
-            ID += 1;
-            WorldId(id)
-        }
+        static ID: AtomicU32 = AtomicU32::new(0);
+        WorldId(ID.fetch_add(1, Ordering::Relaxed))
     }
 }
 
@@ -36,24 +80,75 @@ pub struct World {
     entities: Entities,
     events: EventRegistry,
     frame: Frame,
+    last_change_check: Frame,
+    cancel: CancelHandle,
+    errors: SystemErrors,
+    task_pool: OnceLock<TaskPool>,
+    extensions: Extensions,
+    observers: Observers,
+    freeze: FreezeState,
+    command_middleware: Vec<CommandMiddleware>,
+    /// Number of phases running, phases can run nested schedules, see [`World::run_schedule`].
+    phase_depth: u32,
+    resource_removals: Vec<ResourceId>,
+    hash_cache: StateHashCache,
+    query_states: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    systems: u32,
+    borrows: Arc<WorldBorrows>,
+    /// Archetypes already reported with [`ArchetypeCreated`].
+    reported_archetypes: ArchetypeGeneration,
 }
 
 impl World {
     pub fn new() -> Self {
-        World {
+        let mut world = World {
             id: WorldId::new(),
             archetypes: Archetypes::new(),
             resources: Resources::new(),
             entities: Entities::new(),
             events: EventRegistry::new(),
             frame: Frame(1),
-        }
+            last_change_check: Frame(1),
+            cancel: CancelHandle::new(),
+            errors: SystemErrors::default(),
+            task_pool: OnceLock::new(),
+            extensions: Extensions::new(),
+            observers: Observers::default(),
+            freeze: FreezeState::default(),
+            command_middleware: Vec::new(),
+            phase_depth: 0,
+            resource_removals: Vec::new(),
+            hash_cache: StateHashCache::default(),
+            query_states: HashMap::new(),
+            systems: 0,
+            borrows: Arc::default(),
+            reported_archetypes: ArchetypeGeneration::ZERO,
+        };
+
+        world.register_clone::<Parent>();
+        world.register_clone::<Children>();
+        world.register_serde_mapped::<Parent>();
+        world.register_serde_mapped::<Children>();
+        world.register_map_entities::<Parent>();
+        world.register_map_entities::<Children>();
+        world.register_clone::<Name>();
+        world.register_serde::<Name>();
+        world.register_clone::<Disabled>();
+        world.register_serde::<Disabled>();
+        world
     }
 
     pub fn id(&self) -> WorldId {
         self.id
     }
 
+    /// Ids of the systems built for this world, counting up from zero in the order they're built.
+    pub fn next_system_id(&mut self) -> SystemId {
+        let id = SystemId(self.systems);
+        self.systems += 1;
+        id
+    }
+
     pub fn components(&self) -> &Components {
         self.archetypes.components()
     }
@@ -82,6 +177,16 @@ impl World {
         &self.entities
     }
 
+    /// Returns false for despawned entities, even when their index was recycled.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    /// See [`Entities::set_local_range`].
+    pub fn set_local_entity_range(&mut self, range: std::ops::RangeFrom<u32>) {
+        self.entities.set_local_range(range);
+    }
+
     pub fn events(&self) -> &EventRegistry {
         &self.events
     }
@@ -90,10 +195,122 @@ impl World {
         self.frame
     }
 
+    /// Frame of the last [`World::check_change_frames`] pass.
+    pub fn last_change_check(&self) -> Frame {
+        self.last_change_check
+    }
+
+    /// Clamps the frames of every component and resource, see [`Frame::check`], so change
+    /// detection keeps working after the frame counter wraps around. Ran by [`World::update`]
+    /// every [`CHECK_FRAME_THRESHOLD`] frames. Systems are clamped separately, see
+    /// [`Systems::check_change_frames`](crate::system::schedule::Systems::check_change_frames).
+    pub fn check_change_frames(&mut self) {
+        self.archetypes.check_change_frames(self.frame);
+        self.resources.check_change_frames(self.frame);
+        self.last_change_check = self.frame;
+    }
+
+    /// Moves the world to a saved frame, see [`App::restore_runtime_state`](crate::app::App::restore_runtime_state).
+    pub fn restore_frame(&mut self, frame: Frame) {
+        self.frame = frame;
+    }
+
+    /// Borrow flags of the running systems, checked by debug builds.
+    pub fn borrows(&self) -> &Arc<WorldBorrows> {
+        &self.borrows
+    }
+
+    /// Runs `f` with fresh borrow flags, for systems run from an exclusive system that holds the
+    /// whole world. The flags of the running systems are restored afterwards.
+    pub(crate) fn with_nested_borrows<T>(&mut self, f: impl FnOnce(&mut World) -> T) -> T {
+        let borrows = std::mem::take(&mut self.borrows);
+        let result = f(self);
+        self.borrows = borrows;
+        result
+    }
+
+    pub fn cancel_handle(&self) -> &CancelHandle {
+        &self.cancel
+    }
+
+    /// Worker threads shared by the parallel executors of the world, created on first use with
+    /// the [`ExecutorConfig`] resource.
+    pub fn task_pool(&self) -> &TaskPool {
+        self.task_pool.get_or_init(|| {
+            let config = self.try_resource::<ExecutorConfig>().copied();
+            TaskPool::new(config.unwrap_or_default().threads)
+        })
+    }
+
+    /// Failures of fallible systems that haven't been handled yet.
+    pub fn system_errors(&self) -> &SystemErrors {
+        &self.errors
+    }
+
+    /// Starts counting the allocations made on the current thread.
+    #[cfg(feature = "alloc-audit")]
+    pub fn allocation_guard(&self) -> crate::core::AllocationGuard {
+        crate::core::AllocationGuard::new()
+    }
+
+    /// Adds middleware that can inspect, skip or replace commands as they are applied.
+    pub fn add_command_middleware(&mut self, middleware: CommandMiddleware) {
+        self.command_middleware.push(middleware);
+    }
+
     pub fn register<C: Component>(&mut self) -> ComponentId {
         self.archetypes.register::<C>()
     }
 
+    pub fn register_pod<C: PodComponent>(&mut self) -> ComponentId {
+        self.archetypes.register_pod::<C>()
+    }
+
+    /// Makes inserting `C` also insert `R::default()` if the entity doesn't have `R`.
+    pub fn register_required<C: Component, R: Component + Default>(&mut self) -> ComponentId {
+        self.archetypes.register_required::<C, R>()
+    }
+
+    /// Returns the archetype an entity with exactly these components would be stored in,
+    /// creating it eagerly if it doesn't exist yet.
+    pub fn archetype_for_components(&mut self, components: &[ComponentId]) -> ArchetypeId {
+        self.archetypes.get_or_create(components)
+    }
+
+    /// Queries the world without a system. The state is created on first use and cached per
+    /// query type.
+    pub fn query<Q: BaseQuery + 'static>(&mut self) -> Query<'_, '_, Q> {
+        self.query_filtered::<Q, ()>()
+    }
+
+    pub fn query_filtered<Q: BaseQuery + 'static, F: BaseFilter + 'static>(
+        &mut self,
+    ) -> Query<'_, '_, Q, F> {
+        let ty = TypeId::of::<QueryState<Q, F>>();
+        let mut state = match self.query_states.remove(&ty) {
+            Some(state) => state,
+            None => Box::new(QueryState::<Q, F>::init(self)),
+        };
+        if let Some(state) = state.downcast_mut::<QueryState<Q, F>>() {
+            state.update_archetypes(self);
+        }
+        self.query_states.insert(ty, state);
+
+        let state = self.query_states[&ty]
+            .downcast_ref::<QueryState<Q, F>>()
+            .unwrap();
+        let frame = self.frame().previous();
+        // The world is borrowed mutably for as long as the query lives.
+        unsafe { Query::new_unchecked(self, state, frame) }
+    }
+
+    pub fn on_archetype_created(
+        &mut self,
+        callback: impl Fn(&mut Archetype) + Send + Sync + 'static,
+    ) {
+        self.archetypes.on_created(callback);
+    }
+
     pub fn register_resource<R: Resource + Send>(&mut self) -> ResourceId {
         self.resources.register::<true, R>()
     }
@@ -102,40 +319,54 @@ impl World {
         self.resources.register::<false, R>()
     }
 
-    pub fn register_event<E: Event>(&mut self) {
+    pub fn register_event<E: Event>(&mut self) -> EventId {
         if !self.resources.contains::<Events<E>>() {
             self.add_resource(Events::<E>::new());
+
+            // Only archetypes created after registration are reported.
+            if TypeId::of::<E>() == TypeId::of::<ArchetypeCreated>() {
+                self.reported_archetypes = self.archetypes.generation();
+            }
         }
 
-        self.events.register::<E>();
+        self.events.register::<E>()
     }
 
-    pub fn add_resource<R: Resource + Send>(&mut self, resource: R) {
-        self.resources.add::<true, R>(resource);
+    /// Adds the resource, returning the value it replaced.
+    pub fn add_resource<R: Resource + Send>(&mut self, resource: R) -> Option<R> {
+        self.resources
+            .add_with_frame::<true, R>(resource, self.frame)
     }
 
-    pub fn add_non_send_resource<R: Resource>(&mut self, resource: R) {
-        self.resources.add::<false, R>(resource);
+    pub fn add_non_send_resource<R: Resource>(&mut self, resource: R) -> Option<R> {
+        self.resources
+            .add_with_frame::<false, R>(resource, self.frame)
+    }
+
+    /// The resource, adding the value returned by `f` first if it doesn't exist.
+    pub fn resource_or_insert_with<R: Resource + Send>(&mut self, f: impl FnOnce() -> R) -> &mut R {
+        if !self.resources.contains::<R>() {
+            self.add_resource(f());
+        }
+
+        self.resource_mut::<R>()
     }
 
     pub fn resource<R: Resource + Send>(&self) -> &R {
         self.resources
             .get_id::<R>()
             .and_then(|id| self.resources.get::<R>(id))
-            .expect(&format!(
-                "Resource not found: {}",
-                std::any::type_name::<R>()
-            ))
+            .unwrap_or_else(|| panic!("Resource not found: {}", std::any::type_name::<R>()))
     }
 
     pub fn resource_mut<R: Resource + Send>(&mut self) -> &mut R {
         self.resources
             .get_id::<R>()
-            .and_then(|id| self.resources.get_mut::<R>(id))
-            .expect(&format!(
-                "Resource not found: {}",
-                std::any::type_name::<R>()
-            ))
+            .and_then(|id| {
+                self.resources.modify(id, self.frame);
+                self.resources.get_mut::<R>(id)
+            })
+            .unwrap_or_else(|| panic!("Resource not found: {}", std::any::type_name::<R>()))
     }
 
     pub fn try_resource<R: Resource + Send>(&self) -> Option<&R> {
@@ -145,29 +376,37 @@ impl World {
     }
 
     pub fn try_resource_mut<R: Resource + Send>(&mut self) -> Option<&mut R> {
-        self.resources
-            .get_id::<R>()
-            .and_then(|id| self.resources.get_mut::<R>(id))
+        self.resources.get_id::<R>().and_then(|id| {
+            self.resources.modify(id, self.frame);
+            self.resources.get_mut::<R>(id)
+        })
     }
 
     pub fn non_send_resource<R: Resource>(&self) -> &R {
         self.resources
             .get_id::<R>()
             .and_then(|id| self.resources.get::<R>(id))
-            .expect(&format!(
-                "Non Send Resource not found: {}",
-                std::any::type_name::<R>()
-            ))
+            .unwrap_or_else(|| {
+                panic!(
+                    "Non Send Resource not found: {}",
+                    std::any::type_name::<R>()
+                )
+            })
     }
 
     pub fn non_send_resource_mut<R: Resource>(&mut self) -> &mut R {
         self.resources
             .get_id::<R>()
-            .and_then(|id| self.resources.get_mut::<R>(id))
-            .expect(&format!(
-                "Non Send Resource not found: {}",
-                std::any::type_name::<R>()
-            ))
+            .and_then(|id| {
+                self.resources.modify(id, self.frame);
+                self.resources.get_mut::<R>(id)
+            })
+            .unwrap_or_else(|| {
+                panic!(
+                    "Non Send Resource not found: {}",
+                    std::any::type_name::<R>()
+                )
+            })
     }
 
     pub fn try_non_send_resource<R: Resource>(&self) -> Option<&R> {
@@ -177,13 +416,94 @@ impl World {
     }
 
     pub fn try_non_send_resource_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.resources.get_id::<R>().and_then(|id| {
+            self.resources.modify(id, self.frame);
+            self.resources.get_mut::<R>(id)
+        })
+    }
+
+    /// Takes the resource out of the world and hands it to `f` along with the world, so its
+    /// update logic can query entities. The resource is put back afterwards and marked modified,
+    /// `f` can't add another `R` in the meantime.
+    pub fn resource_scope<R: Resource + Send, T>(
+        &mut self,
+        f: impl FnOnce(&mut World, &mut R) -> T,
+    ) -> T {
+        match self.try_resource_scope(f) {
+            Ok(Some(result)) => result,
+            Ok(None) => panic!("Resource not found: {}", std::any::type_name::<R>()),
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Like [`World::resource_scope`], `None` if the resource doesn't exist. Taking the resource
+    /// out goes through the same guard as [`World::remove_resource`], so it fails while a phase
+    /// is running.
+    pub fn try_resource_scope<R: Resource + Send, T>(
+        &mut self,
+        f: impl FnOnce(&mut World, &mut R) -> T,
+    ) -> Result<Option<T>, ResourceLockedError> {
+        let Some((id, mut resource)) = self.take_resource::<R>()? else {
+            return Ok(None);
+        };
+
+        let result = f(self, &mut resource);
+        self.resources.reinsert(id, resource, self.frame);
+        Ok(Some(result))
+    }
+
+    /// Generation of the resource, bumped when it is inserted or replaced.
+    /// Returns `None` while the resource doesn't exist.
+    pub fn resource_generation<R: Resource>(&self) -> Option<u32> {
         self.resources
             .get_id::<R>()
-            .and_then(|id| self.resources.get_mut::<R>(id))
+            .and_then(|id| self.resources.generation(id))
     }
 
-    pub fn remove_resource<R: Resource>(&mut self) -> Option<R> {
-        self.resources.remove::<R>()
+    /// Removes the resource. While a phase is running, running systems may still hold the
+    /// resource, so nothing is removed and an error is returned, see
+    /// [`World::remove_resource_deferred`].
+    pub fn remove_resource<R: Resource>(&mut self) -> Result<Option<R>, ResourceLockedError> {
+        Ok(self.take_resource::<R>()?.map(|(_, resource)| resource))
+    }
+
+    /// Drops the resource, or at the end of the outermost phase if one is running.
+    pub fn remove_resource_deferred<R: Resource>(&mut self) {
+        match self.phase_depth > 0 {
+            true => self.resource_removals.extend(self.resources.get_id::<R>()),
+            false => drop(self.resources.remove::<R>()),
+        }
+    }
+
+    /// Takes the resource out of storage, guarded against running phases.
+    fn take_resource<R: Resource>(
+        &mut self,
+    ) -> Result<Option<(ResourceId, R)>, ResourceLockedError> {
+        if self.phase_depth > 0 {
+            return Err(ResourceLockedError(std::any::type_name::<R>()));
+        }
+
+        let Some(id) = self.resources.get_id::<R>() else {
+            return Ok(None);
+        };
+
+        Ok(self.resources.remove::<R>().map(|resource| (id, resource)))
+    }
+
+    pub(crate) fn begin_phase(&mut self) {
+        self.phase_depth += 1;
+    }
+
+    /// Applies resource removals that were requested while the outermost phase was running.
+    pub(crate) fn end_phase(&mut self) {
+        self.phase_depth -= 1;
+        if self.phase_depth > 0 {
+            return;
+        }
+
+        for id in self.resource_removals.drain(..) {
+            self.resources.remove_by_id(id);
+        }
     }
 
     pub unsafe fn cell(&self) -> WorldCell {
@@ -198,9 +518,34 @@ impl World {
         entity
     }
 
+    /// Spawns an entity with an id chosen by the caller, see [`Entities::spawn_at`].
+    pub fn spawn_at(&mut self, entity: Entity) -> Result<Entity, SpawnAtError> {
+        if self.entities.is_alive(entity) {
+            return Ok(entity);
+        }
+
+        let entity = self.entities.spawn_at(entity.id(), entity.generation())?;
+        self.archetypes.add_entity(entity);
+        Ok(entity)
+    }
+
     pub fn despawn(&mut self, entity: Entity) -> Option<(ArchetypeId, Row)> {
+        if self.entities.is_alive(entity) {
+            let observed = self.observed_components(entity);
+            self.trigger_all(Lifecycle::Despawn, entity, &observed);
+            self.trigger_all(Lifecycle::Remove, entity, &observed);
+        }
+
+        self.detach_hierarchy(entity);
+        self.observers.entity_despawned(entity);
         self.entities.despawn(entity);
-        self.archetypes.remove_entity(entity)
+        let removed = self.archetypes.remove_entity(entity);
+        if let Some((_, row)) = &removed {
+            self.record_removed(entity, row.ids());
+            self.extensions.entity_despawned(entity);
+        }
+
+        removed
     }
 
     pub fn get_component<C: Component>(&self, entity: Entity) -> Option<&C> {
@@ -211,25 +556,158 @@ impl World {
         self.archetypes.get_component_mut::<C>(entity)
     }
 
+    /// Does nothing if the entity is not alive. Registers the component first if it isn't
+    /// registered yet.
     pub fn add_component<C: Component>(&mut self, entity: Entity, component: C) {
+        if !self.entities.is_alive(entity) {
+            return;
+        }
+
+        let id = self.register::<C>();
+        if self
+            .components()
+            .meta(id)
+            .is_some_and(|meta| !meta.required().is_empty())
+        {
+            let mut row = Row::new();
+            row.insert(id, component);
+            return self.add_components(entity, row);
+        }
+
+        let observed = self.is_observed(id);
+        let added = observed && !self.try_entity(entity).is_some_and(|e| e.contains_id(id));
         self.archetypes.add_component(entity, component, self.frame);
+
+        if added {
+            self.trigger(Lifecycle::Add, entity, id);
+        }
+        if observed {
+            self.trigger(Lifecycle::Insert, entity, id);
+        }
     }
 
     pub fn remove_component<C: Component>(&mut self, entity: Entity) {
-        self.archetypes.remove_component::<C>(entity);
+        let Some(id) = self.components().get_id::<C>() else {
+            return;
+        };
+
+        if self.is_observed(id) && self.try_entity(entity).is_some_and(|e| e.contains_id(id)) {
+            self.trigger(Lifecycle::Remove, entity, id);
+        }
+
+        if self.archetypes.remove_component::<C>(entity).is_some() {
+            self.record_removed(entity, &[id]);
+        }
     }
 
+    /// Does nothing if the entity is not alive.
     pub fn add_components(&mut self, entity: Entity, components: Row) {
+        if !self.entities.is_alive(entity) {
+            return;
+        }
+
+        let (observed, added) = self.observed_insert(entity, components.ids());
         self.archetypes
             .add_components(entity, components, self.frame);
+
+        self.trigger_all(Lifecycle::Add, entity, &added);
+        self.trigger_all(Lifecycle::Insert, entity, &observed);
     }
 
     pub fn remove_components(&mut self, entity: Entity, components: Vec<ComponentId>) {
-        self.archetypes.remove_components(entity, components);
+        let observed = self.observed_components(entity);
+        for component in observed.iter().filter(|id| components.contains(id)) {
+            self.trigger(Lifecycle::Remove, entity, *component);
+        }
+
+        if let Some(removed) = self.archetypes.remove_components(entity, components) {
+            self.record_removed(entity, removed.ids());
+        }
+    }
+
+    fn record_removed(&mut self, entity: Entity, components: &[ComponentId]) {
+        self.archetypes
+            .record_removed(entity, components, self.frame);
+
+        let Some(id) = self.resources.get_id::<ChangeHistory>() else {
+            return;
+        };
+
+        if let Some(history) = self.resources.get_mut::<ChangeHistory>(id) {
+            for component in components {
+                history.record_removed(
+                    self.archetypes.components(),
+                    entity,
+                    *component,
+                    self.frame,
+                );
+            }
+        }
     }
 
+    /// Spawns the entities reserved with [`Entities::reserve`] that no command spawned yet,
+    /// without components.
+    pub fn flush(&mut self) {
+        for id in self.entities.flush() {
+            self.archetypes.add_entity(Entity::new(id, 1));
+        }
+    }
+
+    /// Ends the current frame. Called once per frame after every phase ran, with no system
+    /// running and every deferred command applied. In order, it:
+    /// 1. Runs the [`WorldExtension`]s.
+    /// 2. Spawns reserved entities whose commands never ran, see [`World::flush`].
+    /// 3. Records the frame into [`ChangeHistory`] and applies the freeze policy.
+    /// 4. Drops component removals older than the frame, readers keep seeing removals of the
+    ///    frame that just ended until the next update.
+    /// 5. Clamps stale frames every [`CHECK_FRAME_THRESHOLD`] frames, see
+    ///    [`World::check_change_frames`].
+    /// 6. Sends [`ArchetypeCreated`] for the archetypes created since the last update.
+    /// 7. Advances the frame and publishes the events sent during it.
+    ///
+    /// Changes made after the update are reported as made in the next frame.
     pub fn update(&mut self) {
+        self.update_extensions();
+        self.flush();
+
+        if let Some(history) = self
+            .resources
+            .get_id::<ChangeHistory>()
+            .and_then(|id| self.resources.get_mut::<ChangeHistory>(id))
+        {
+            history.collect(&self.archetypes, self.frame);
+        }
+
+        self.apply_freeze_policy();
+        self.archetypes.update_removed(self.frame);
+
+        if self.frame.relative_to(self.last_change_check).get() >= CHECK_FRAME_THRESHOLD {
+            self.check_change_frames();
+        }
+
+        self.send_archetype_events();
         self.frame += 1;
         self.events.update(unsafe { self.cell() });
     }
+
+    fn send_archetype_events(&mut self) {
+        let Some(events) = self
+            .resources
+            .get_id::<Events<ArchetypeCreated>>()
+            .and_then(|id| self.resources.get_mut::<Events<ArchetypeCreated>>(id))
+        else {
+            return;
+        };
+
+        for archetype in self.archetypes.created_since(self.reported_archetypes) {
+            let set = self.archetypes.archetype_set(archetype.id());
+            let components = set.map(|set| self.archetypes.component_set(set).to_vec());
+            events.send(ArchetypeCreated {
+                id: archetype.id(),
+                components: components.unwrap_or_default(),
+            });
+        }
+
+        self.reported_archetypes = self.archetypes.generation();
+    }
 }
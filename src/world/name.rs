@@ -0,0 +1,41 @@
+use super::Component;
+use serde::{Deserialize, Serialize};
+
+/// Human readable label of an entity, shown by [`World::entity_debug`](super::World::entity_debug)
+/// and the [`Debug`] output of [`EntityRef`](super::EntityRef).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Name(String);
+
+impl Name {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn set(&mut self, name: impl Into<String>) {
+        self.0 = name.into();
+    }
+}
+
+impl Component for Name {}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self(name.to_string())
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
@@ -0,0 +1,529 @@
+use super::{Component, ComponentHooks, ComponentId, Entity, Event, World};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    marker::PhantomData,
+};
+
+/// Structural change of a single component on an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lifecycle {
+    Add,
+    Insert,
+    Remove,
+    Despawn,
+}
+
+/// Typed [`Lifecycle`] event observed with [`World::observe`].
+pub trait LifecycleEvent: Send + Sync + 'static {
+    type Component: Component;
+
+    const LIFECYCLE: Lifecycle;
+}
+
+/// The component was added to an entity that didn't have it.
+pub struct OnAdd<C: Component>(PhantomData<C>);
+
+/// The component was added or replaced.
+pub struct OnInsert<C: Component>(PhantomData<C>);
+
+/// The component is about to be removed, including when its entity is despawned.
+pub struct OnRemove<C: Component>(PhantomData<C>);
+
+/// The entity holding the component is about to be despawned.
+pub struct OnDespawn<C: Component>(PhantomData<C>);
+
+macro_rules! impl_lifecycle_event {
+    ($($event:ident => $lifecycle:ident),*) => {
+        $(impl<C: Component> LifecycleEvent for $event<C> {
+            type Component = C;
+
+            const LIFECYCLE: Lifecycle = Lifecycle::$lifecycle;
+        })*
+    };
+}
+
+impl_lifecycle_event!(OnAdd => Add, OnInsert => Insert, OnRemove => Remove, OnDespawn => Despawn);
+
+/// The entity and component an observer was triggered for.
+pub struct Trigger<E: LifecycleEvent> {
+    entity: Entity,
+    component: ComponentId,
+    _marker: PhantomData<E>,
+}
+
+impl<E: LifecycleEvent> Trigger<E> {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn component(&self) -> ComponentId {
+        self.component
+    }
+}
+
+/// An event triggered with [`World::trigger_event`] or [`World::trigger_targets`].
+pub struct EventTrigger<'a, E: Event> {
+    event: &'a E,
+    target: Option<Entity>,
+}
+
+impl<'a, E: Event> EventTrigger<'a, E> {
+    pub fn event(&self) -> &'a E {
+        self.event
+    }
+
+    /// The entity the event was triggered for, `None` for broadcast events.
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+}
+
+/// Entities an event is triggered for.
+pub trait TriggerTargets: Send + Sync + 'static {
+    fn targets(&self) -> &[Entity];
+}
+
+impl TriggerTargets for Entity {
+    fn targets(&self) -> &[Entity] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl TriggerTargets for Vec<Entity> {
+    fn targets(&self) -> &[Entity] {
+        self
+    }
+}
+
+impl<const N: usize> TriggerTargets for [Entity; N] {
+    fn targets(&self) -> &[Entity] {
+        self
+    }
+}
+
+type Observer = Box<dyn FnMut(Entity, ComponentId, &mut World) + Send + Sync>;
+
+type EventObserver = Box<dyn FnMut(&dyn Any, Option<Entity>, &mut World) + Send + Sync>;
+
+#[derive(Default)]
+pub struct Observers {
+    observers: HashMap<(Lifecycle, ComponentId), Vec<Observer>>,
+    watched: HashMap<ComponentId, usize>,
+    events: HashMap<TypeId, Vec<EventObserver>>,
+    targeted: HashMap<Entity, HashMap<TypeId, Vec<EventObserver>>>,
+}
+
+impl Observers {
+    pub fn len(&self) -> usize {
+        self.watched.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watched.is_empty()
+    }
+
+    fn insert(&mut self, event: Lifecycle, component: ComponentId, observer: Observer) {
+        self.observers
+            .entry((event, component))
+            .or_default()
+            .push(observer);
+        *self.watched.entry(component).or_default() += 1;
+    }
+
+    fn watches(&self, component: ComponentId) -> bool {
+        self.watched.contains_key(&component)
+    }
+
+    /// Drops the observers registered for the entity.
+    pub(crate) fn entity_despawned(&mut self, entity: Entity) {
+        self.targeted.remove(&entity);
+    }
+}
+
+fn event_observer<E: Event>(
+    mut observer: impl FnMut(EventTrigger<E>, &mut World) + Send + Sync + 'static,
+) -> EventObserver {
+    Box::new(move |event, target, world| {
+        let event = event.downcast_ref::<E>().unwrap();
+        observer(EventTrigger { event, target }, world)
+    })
+}
+
+impl World {
+    /// Registers the component, replacing its hooks.
+    pub fn register_with_hooks<C: Component>(&mut self, hooks: ComponentHooks) -> ComponentId {
+        self.archetypes.register_with_hooks::<C>(hooks)
+    }
+
+    /// Runs the observer every time `E` happens to any entity, after the component's hooks.
+    pub fn observe<E: LifecycleEvent>(
+        &mut self,
+        mut observer: impl FnMut(Trigger<E>, &mut World) + Send + Sync + 'static,
+    ) {
+        let component = self.register::<E::Component>();
+        let observer = move |entity, component, world: &mut World| {
+            let trigger = Trigger {
+                entity,
+                component,
+                _marker: PhantomData,
+            };
+            observer(trigger, world)
+        };
+
+        self.observers
+            .insert(E::LIFECYCLE, component, Box::new(observer));
+    }
+
+    /// Runs the observer every time `E` is triggered, broadcast or for any entity.
+    pub fn observe_event<E: Event>(
+        &mut self,
+        observer: impl FnMut(EventTrigger<E>, &mut World) + Send + Sync + 'static,
+    ) {
+        self.observers
+            .events
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(event_observer(observer));
+    }
+
+    /// Runs the observer every time `E` is triggered for the entity. The observer is dropped
+    /// when the entity is despawned.
+    pub fn observe_entity<E: Event>(
+        &mut self,
+        entity: Entity,
+        observer: impl FnMut(EventTrigger<E>, &mut World) + Send + Sync + 'static,
+    ) {
+        if !self.entities.is_alive(entity) {
+            return;
+        }
+
+        self.observers
+            .targeted
+            .entry(entity)
+            .or_default()
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(event_observer(observer));
+    }
+
+    /// Runs the observers of `E` that aren't registered for an entity.
+    pub fn trigger_event<E: Event>(&mut self, event: E) {
+        self.run_event_observers(&event, None);
+    }
+
+    /// Runs the observers of `E` registered for each target, followed by the global ones.
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: impl TriggerTargets) {
+        for target in targets.targets() {
+            self.run_event_observers(&event, Some(*target));
+        }
+    }
+
+    fn run_event_observers<E: Event>(&mut self, event: &E, target: Option<Entity>) {
+        let ty = TypeId::of::<E>();
+        let event = event as &dyn Any;
+
+        if let Some(target) = target {
+            let observers = self
+                .observers
+                .targeted
+                .get_mut(&target)
+                .and_then(|events| events.get_mut(&ty));
+            if let Some(observers) = observers {
+                let mut running = std::mem::take(observers);
+                for observer in running.iter_mut() {
+                    observer(event, Some(target), self);
+                }
+
+                // Observing the entity again after it was despawned would leak the observers.
+                if self.entities.is_alive(target) {
+                    let observers = self
+                        .observers
+                        .targeted
+                        .entry(target)
+                        .or_default()
+                        .entry(ty)
+                        .or_default();
+                    running.append(observers);
+                    *observers = running;
+                }
+            }
+        }
+
+        let Some(observers) = self.observers.events.get_mut(&ty) else {
+            return;
+        };
+
+        let mut running = std::mem::take(observers);
+        for observer in running.iter_mut() {
+            observer(event, target, self);
+        }
+
+        let observers = self.observers.events.entry(ty).or_default();
+        running.append(observers);
+        *observers = running;
+    }
+
+    /// Whether a hook or an observer reacts to changes of the component.
+    pub(crate) fn is_observed(&self, component: ComponentId) -> bool {
+        self.observers.watches(component)
+            || self
+                .components()
+                .meta(component)
+                .is_some_and(|meta| !meta.hooks().is_empty())
+    }
+
+    /// Runs the component's hook and observers for the event.
+    pub(crate) fn trigger(&mut self, event: Lifecycle, entity: Entity, component: ComponentId) {
+        let hook = self
+            .components()
+            .meta(component)
+            .and_then(|meta| meta.hooks().get(event));
+        if let Some(hook) = hook {
+            hook(self, entity);
+        }
+
+        let key = (event, component);
+        let Some(observers) = self.observers.observers.get_mut(&key) else {
+            return;
+        };
+
+        // Observers are detached while they run so they can observe new events themselves.
+        let mut running = std::mem::take(observers);
+        for observer in running.iter_mut() {
+            observer(entity, component, self);
+        }
+
+        let observers = self.observers.observers.entry(key).or_default();
+        running.append(observers);
+        *observers = running;
+    }
+
+    pub(crate) fn trigger_all(
+        &mut self,
+        event: Lifecycle,
+        entity: Entity,
+        components: &[ComponentId],
+    ) {
+        for component in components {
+            self.trigger(event, entity, *component);
+        }
+    }
+
+    /// Splits the observed components about to be inserted, including the required components
+    /// the entity doesn't have, into all of them and the ones the entity doesn't have yet.
+    pub(crate) fn observed_insert(
+        &self,
+        entity: Entity,
+        components: &[ComponentId],
+    ) -> (Vec<ComponentId>, Vec<ComponentId>) {
+        let required = self.components().required_by(components);
+        let observed = components
+            .iter()
+            .copied()
+            .chain(required.iter().map(|(id, _)| *id).filter(|component| {
+                !self
+                    .try_entity(entity)
+                    .is_some_and(|entity| entity.contains_id(*component))
+            }))
+            .filter(|component| self.is_observed(*component))
+            .collect::<Vec<_>>();
+        let added = observed
+            .iter()
+            .copied()
+            .filter(|component| {
+                !self
+                    .try_entity(entity)
+                    .is_some_and(|entity| entity.contains_id(*component))
+            })
+            .collect();
+
+        (observed, added)
+    }
+
+    /// Components of the entity with a hook or an observer.
+    pub(crate) fn observed_components(&self, entity: Entity) -> Vec<ComponentId> {
+        let Some(set) = self
+            .archetypes
+            .entity_archetype(entity)
+            .and_then(|archetype| self.archetypes.archetype_set(archetype))
+        else {
+            return vec![];
+        };
+
+        let components = self.archetypes.component_set(set);
+        components
+            .iter()
+            .copied()
+            .filter(|component| self.is_observed(*component))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventTrigger, OnAdd, OnDespawn, OnInsert, OnRemove, Trigger};
+    use crate::{
+        app::App,
+        system::schedule::Phase,
+        world::{Commands, Component, ComponentHooks, Entity, Event, Resource, World},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    impl Component for Health {}
+
+    #[derive(Default)]
+    struct Log(Vec<(&'static str, Entity)>);
+    impl Resource for Log {}
+
+    fn log(world: &mut World, event: &'static str, entity: Entity) {
+        world.resource_mut::<Log>().0.push((event, entity));
+    }
+
+    #[test]
+    fn component_hooks() {
+        let mut world = World::new();
+        world.add_resource(Log::default());
+        world.register_with_hooks::<Health>(
+            ComponentHooks::new()
+                .on_add(|world, entity| log(world, "add", entity))
+                .on_insert(|world, entity| log(world, "insert", entity))
+                .on_remove(|world, entity| {
+                    // The component can still be read while it's removed.
+                    assert!(world.get_component::<Health>(entity).is_some());
+                    log(world, "remove", entity)
+                })
+                .on_despawn(|world, entity| log(world, "despawn", entity)),
+        );
+
+        let entity = world.spawn();
+        world.add_component(entity, Health(1));
+        world.add_component(entity, Health(2));
+        world.remove_component::<Health>(entity);
+        world.remove_component::<Health>(entity);
+        let other = world.spawn_bundle((Health(3),));
+        world.despawn(other);
+
+        assert_eq!(
+            world.resource::<Log>().0,
+            [
+                ("add", entity),
+                ("insert", entity),
+                ("insert", entity),
+                ("remove", entity),
+                ("add", other),
+                ("insert", other),
+                ("despawn", other),
+                ("remove", other),
+            ]
+        );
+    }
+
+    #[test]
+    fn observers() {
+        let mut world = World::new();
+        world.add_resource(Log::default());
+
+        world.observe(|trigger: Trigger<OnAdd<Health>>, world: &mut World| {
+            log(world, "add", trigger.entity());
+            let health = world.get_component::<Health>(trigger.entity()).unwrap();
+            assert_eq!(health, &Health(10));
+        });
+        world.observe(|trigger: Trigger<OnInsert<Health>>, world: &mut World| {
+            log(world, "insert", trigger.entity());
+        });
+        world.observe(|trigger: Trigger<OnRemove<Health>>, world: &mut World| {
+            log(world, "remove", trigger.entity());
+        });
+        world.observe(|trigger: Trigger<OnDespawn<Health>>, world: &mut World| {
+            log(world, "despawn", trigger.entity());
+        });
+
+        let entities = world.spawn_batch([(Health(10),), (Health(10),)]);
+        world.entity_mut(entities[0]).remove::<Health>();
+        world.despawn(entities[1]);
+        world.despawn(entities[0]);
+
+        assert_eq!(
+            world.resource::<Log>().0,
+            [
+                ("add", entities[0]),
+                ("insert", entities[0]),
+                ("add", entities[1]),
+                ("insert", entities[1]),
+                ("remove", entities[0]),
+                ("despawn", entities[1]),
+                ("remove", entities[1]),
+            ]
+        );
+    }
+
+    struct Damage(u32);
+    impl Event for Damage {}
+
+    #[derive(Default)]
+    struct Hits(Vec<(&'static str, Option<Entity>, u32)>);
+    impl Resource for Hits {}
+
+    struct Targets([Entity; 2]);
+    impl Resource for Targets {}
+
+    struct Update;
+    impl Phase for Update {}
+
+    fn attack(targets: &Targets, mut commands: Commands) {
+        commands.trigger_targets(Damage(5), targets.0[0]);
+        commands.trigger_targets(Damage(2), targets.0);
+        commands.trigger(Damage(1));
+    }
+
+    #[test]
+    fn targeted_events() {
+        let mut builder = App::new();
+        let world = builder.world_mut();
+        let player = world.spawn();
+        let enemy = world.spawn();
+
+        world.observe_event(|trigger: EventTrigger<Damage>, world: &mut World| {
+            let hit = ("global", trigger.target(), trigger.event().0);
+            world.resource_mut::<Hits>().0.push(hit);
+        });
+        world.observe_entity(
+            player,
+            |trigger: EventTrigger<Damage>, world: &mut World| {
+                let hit = ("player", trigger.target(), trigger.event().0);
+                world.resource_mut::<Hits>().0.push(hit);
+            },
+        );
+
+        let mut app = builder
+            .add_resource(Hits::default())
+            .add_resource(Targets([player, enemy]))
+            .add_systems(Update, attack)
+            .build();
+        app.run(Update);
+
+        let hits = std::mem::take(&mut app.world_mut().resource_mut::<Hits>().0);
+        assert_eq!(
+            hits,
+            [
+                ("player", Some(player), 5),
+                ("global", Some(player), 5),
+                ("player", Some(player), 2),
+                ("global", Some(player), 2),
+                ("global", Some(enemy), 2),
+                ("global", None, 1),
+            ]
+        );
+
+        // Observers of an entity are dropped with it.
+        app.world_mut().despawn(player);
+        app.world_mut().trigger_targets(Damage(3), player);
+        assert_eq!(
+            app.world().resource::<Hits>().0,
+            [("global", Some(player), 3)]
+        );
+        assert!(app.world().observers.targeted.is_empty());
+    }
+}
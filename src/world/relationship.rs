@@ -0,0 +1,248 @@
+use super::{Component, ComponentHooks, ComponentId, Entity, World, WorldExtension};
+use std::{collections::HashMap, marker::PhantomData};
+
+/// Component linking its entity to a target, like `OwnedBy(Entity)`. Once registered with
+/// [`World::register_relationship`] every target keeps a [`RelationshipTarget`] listing the
+/// entities related to it. Retarget by inserting a new value, mutating the component in place
+/// bypasses the index.
+pub trait Relationship: Component {
+    fn target(&self) -> Entity;
+}
+
+/// The entities whose `R` points at this entity, in the order they were related. Kept in sync
+/// by the hooks of `R`, removing it removes `R` from every source.
+pub struct RelationshipTarget<R: Relationship> {
+    sources: Vec<Entity>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Relationship> RelationshipTarget<R> {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.sources.iter().copied()
+    }
+
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.sources
+    }
+
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.sources.contains(&entity)
+    }
+}
+
+impl<R: Relationship> std::fmt::Debug for RelationshipTarget<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RelationshipTarget")
+            .field(&self.sources)
+            .finish()
+    }
+}
+
+impl<R: Relationship> Component for RelationshipTarget<R> {}
+
+/// The target each source is indexed under. Replacing `R` only runs its insert hook, by then
+/// the old value is gone.
+struct RelationshipLinks<R: Relationship> {
+    targets: HashMap<Entity, Entity>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Relationship> WorldExtension for RelationshipLinks<R> {}
+
+impl World {
+    /// Registers `R` and its [`RelationshipTarget`], replacing the hooks of both.
+    pub fn register_relationship<R: Relationship>(&mut self) -> ComponentId {
+        self.register_with_hooks::<RelationshipTarget<R>>(
+            ComponentHooks::new().on_remove(unlink_sources::<R>),
+        );
+
+        if self.extension::<RelationshipLinks<R>>().is_none() {
+            self.insert_extension(RelationshipLinks::<R> {
+                targets: HashMap::new(),
+                _marker: PhantomData,
+            });
+        }
+
+        self.register_with_hooks::<R>(
+            ComponentHooks::new()
+                .on_insert(link::<R>)
+                .on_remove(unlink::<R>),
+        )
+    }
+
+    /// The entities whose `R` points at the target, empty if there are none.
+    pub fn related<R: Relationship>(&self, target: Entity) -> &[Entity] {
+        self.get_component::<RelationshipTarget<R>>(target)
+            .map_or(&[], RelationshipTarget::as_slice)
+    }
+}
+
+fn links<R: Relationship>(world: &mut World) -> &mut HashMap<Entity, Entity> {
+    let links = world.extension_mut::<RelationshipLinks<R>>();
+    &mut links.expect("Relationship is not registered").targets
+}
+
+/// Indexes the source under its new target. Relationships to dead entities are removed.
+fn link<R: Relationship>(world: &mut World, source: Entity) {
+    let Some(target) = world.get_component::<R>(source).map(R::target) else {
+        return;
+    };
+
+    if !world.entities.is_alive(target) {
+        world.remove_component::<R>(source);
+        return;
+    }
+
+    let previous = links::<R>(world).insert(source, target);
+    if previous == Some(target) {
+        return;
+    }
+
+    if let Some(previous) = previous {
+        remove_source::<R>(world, previous, source);
+    }
+
+    match world.get_component_mut::<RelationshipTarget<R>>(target) {
+        Some(related) => related.sources.push(source),
+        None => world.add_component(
+            target,
+            RelationshipTarget::<R> {
+                sources: vec![source],
+                _marker: PhantomData,
+            },
+        ),
+    }
+}
+
+fn unlink<R: Relationship>(world: &mut World, source: Entity) {
+    if let Some(target) = links::<R>(world).remove(&source) {
+        remove_source::<R>(world, target, source);
+    }
+}
+
+/// Removes `R` from every source of the target, the target is losing its index.
+fn unlink_sources<R: Relationship>(world: &mut World, target: Entity) {
+    let sources = match world.get_component_mut::<RelationshipTarget<R>>(target) {
+        Some(related) => std::mem::take(&mut related.sources),
+        None => return,
+    };
+
+    for source in sources {
+        if links::<R>(world).remove(&source).is_some() {
+            world.remove_component::<R>(source);
+        }
+    }
+}
+
+fn remove_source<R: Relationship>(world: &mut World, target: Entity, source: Entity) {
+    let Some(related) = world.get_component_mut::<RelationshipTarget<R>>(target) else {
+        return;
+    };
+
+    related.sources.retain(|entity| *entity != source);
+    if related.is_empty() {
+        world.remove_component::<RelationshipTarget<R>>(target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Relationship, RelationshipTarget};
+    use crate::{
+        app::App,
+        system::{query::Query, schedule::Phase},
+        world::{Component, Entity, World},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct OwnedBy(Entity);
+    impl Component for OwnedBy {}
+
+    impl Relationship for OwnedBy {
+        fn target(&self) -> Entity {
+            self.0
+        }
+    }
+
+    #[test]
+    fn relationship_index() {
+        let mut world = World::new();
+        world.register_relationship::<OwnedBy>();
+        let (a, b) = (world.spawn(), world.spawn());
+        let (sword, shield) = (world.spawn(), world.spawn());
+
+        world.add_component(sword, OwnedBy(a));
+        world.add_component(shield, OwnedBy(a));
+        assert_eq!(world.related::<OwnedBy>(a), [sword, shield]);
+
+        // Retargeting moves the source to the new target's index.
+        world.add_component(sword, OwnedBy(b));
+        assert_eq!(world.related::<OwnedBy>(a), [shield]);
+        assert_eq!(world.related::<OwnedBy>(b), [sword]);
+
+        world.remove_component::<OwnedBy>(shield);
+        assert!(
+            world
+                .get_component::<RelationshipTarget<OwnedBy>>(a)
+                .is_none()
+        );
+
+        world.despawn(sword);
+        assert!(world.related::<OwnedBy>(b).is_empty());
+    }
+
+    #[test]
+    fn relationship_despawned_target() {
+        let mut world = World::new();
+        world.register_relationship::<OwnedBy>();
+        let (owner, item) = (world.spawn(), world.spawn());
+        world.add_component(item, OwnedBy(owner));
+
+        world.despawn(owner);
+        assert_eq!(world.get_component::<OwnedBy>(item), None);
+
+        // Relating to a dead entity doesn't stick.
+        world.add_component(item, OwnedBy(owner));
+        assert_eq!(world.get_component::<OwnedBy>(item), None);
+
+        let other = world.spawn_bundle((OwnedBy(item),));
+        world.remove_component::<RelationshipTarget<OwnedBy>>(item);
+        assert_eq!(world.get_component::<OwnedBy>(other), None);
+    }
+
+    struct Update;
+    impl Phase for Update {}
+
+    fn traverse(owners: Query<&RelationshipTarget<OwnedBy>>, items: Query<&OwnedBy>) {
+        for owner in owners.iter() {
+            for item in owner.iter() {
+                let target = items.target(item).unwrap();
+                assert!(owners.related(target).contains(&item));
+            }
+        }
+    }
+
+    #[test]
+    fn relationship_queries() {
+        let mut app = App::new()
+            .register_relationship::<OwnedBy>()
+            .add_systems(Update, traverse)
+            .build();
+        let world = app.world_mut();
+        let owner = world.spawn();
+        let items = (0..3)
+            .map(|_| world.spawn_bundle((OwnedBy(owner),)))
+            .collect::<Vec<_>>();
+
+        app.run(Update);
+        assert_eq!(app.world().related::<OwnedBy>(owner), items);
+    }
+}
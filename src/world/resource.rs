@@ -1,8 +1,18 @@
+use super::ResourceSerde;
+use super::component::{CloneFn, HashFn, clone_value, hash_value};
 use crate::{
-    core::{Frame, sparse::SparseIndex},
+    core::{Frame, TypeMeta, blob::BlobCell, sparse::SparseIndex},
     ext,
 };
-use std::{any::TypeId, collections::HashMap, thread::ThreadId};
+use std::{
+    alloc::Layout,
+    any::TypeId,
+    collections::HashMap,
+    hash::Hash,
+    ptr::NonNull,
+    sync::atomic::{AtomicU32, Ordering},
+    thread::ThreadId,
+};
 
 pub trait Resource: Sized + 'static {}
 
@@ -18,29 +28,60 @@ impl SparseIndex for ResourceId {
     }
 }
 
+/// Returned when a resource is taken out of the world while a phase is running, running systems
+/// may still hold it. Use [`World::remove_resource_deferred`](super::World::remove_resource_deferred)
+/// to remove it once the phase ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLockedError(pub &'static str);
+
+impl std::fmt::Display for ResourceLockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Resource {} can't be taken while a phase is running",
+            self.0
+        )
+    }
+}
+
 pub struct ResourceMeta {
     name: &'static str,
     added: Frame,
-    modified: Frame,
+    modified: AtomicU32,
+    generation: u32,
     exists: bool,
     send: bool,
-    offset: usize,
-    size: usize,
+    layout: Layout,
+    data: NonNull<u8>,
     drop: fn(*mut u8),
+    hash: Option<HashFn>,
+    clone: Option<CloneFn>,
+    serde: Option<ResourceSerde>,
     owner: Option<ThreadId>,
 }
 
 impl ResourceMeta {
-    pub fn new<const SEND: bool, R: Resource>(offset: usize) -> Self {
+    pub fn new<const SEND: bool, R: Resource>() -> Self {
+        let layout = Layout::new::<R>();
+        let data = match layout.size() {
+            0 => NonNull::new(std::ptr::without_provenance_mut(layout.align())).unwrap(),
+            _ => NonNull::new(unsafe { std::alloc::alloc(layout) })
+                .unwrap_or_else(|| std::alloc::handle_alloc_error(layout)),
+        };
+
         Self {
             name: ext::short_type_name::<R>(),
             added: Frame::ZERO,
-            modified: Frame::ZERO,
+            modified: AtomicU32::new(0),
+            generation: 0,
             exists: false,
             send: SEND,
-            offset,
-            size: std::mem::size_of::<R>(),
+            layout,
+            data,
             drop: |ptr| unsafe { std::ptr::drop_in_place(ptr as *mut R) },
+            hash: None,
+            clone: None,
+            serde: None,
             owner: None,
         }
     }
@@ -58,42 +99,76 @@ impl ResourceMeta {
     }
 
     pub fn modified(&self) -> Frame {
-        self.modified
+        Frame(self.modified.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn mark_modified(&self, frame: Frame) {
+        self.modified.store(frame.get(), Ordering::Relaxed);
+    }
+
+    /// Bumped every time the resource is inserted or replaced, but not when it is mutated.
+    /// Zero until the resource is first inserted.
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 
     pub fn send(&self) -> bool {
         self.send
     }
 
-    pub fn offset(&self) -> usize {
-        self.offset
+    pub fn size(&self) -> usize {
+        self.layout.size()
     }
 
-    pub fn size(&self) -> usize {
-        self.size
+    pub fn layout(&self) -> Layout {
+        self.layout
     }
 
     pub fn owner(&self) -> Option<ThreadId> {
         self.owner
     }
 
+    /// Hash function of resources registered as hashable.
+    pub fn hash_fn(&self) -> Option<HashFn> {
+        self.hash
+    }
+
+    /// Clone function of resources registered as cloneable.
+    pub fn clone_fn(&self) -> Option<CloneFn> {
+        self.clone
+    }
+
+    /// Scene (de)serializers of resources registered with serde support.
+    pub fn serde(&self) -> Option<ResourceSerde> {
+        self.serde
+    }
+
     /// Check if the resource is accessible from the current thread.
     pub fn has_access(&self) -> bool {
         self.send || self.owner == Some(std::thread::current().id())
     }
 }
 
+/// Resource storage. Every resource lives in its own allocation that is made when the
+/// resource is registered and kept until the storage is dropped, so pointers obtained through
+/// [`Resources::get_ptr_by_id`] stay valid while the resource exists.
 pub struct Resources {
-    data: Vec<u8>,
     meta: Vec<ResourceMeta>,
     index: HashMap<TypeId, ResourceId>,
     is_send: bool,
 }
 
+unsafe impl Send for Resources {}
+unsafe impl Sync for Resources {}
+
+#[cfg(test)]
+thread_local! {
+    static LOOKUPS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
 impl Resources {
     pub fn new() -> Self {
         Self {
-            data: Vec::new(),
             meta: Vec::new(),
             index: HashMap::new(),
             is_send: true,
@@ -104,24 +179,55 @@ impl Resources {
         self.is_send
     }
 
+    /// Number of type lookups made on the current thread.
+    #[cfg(test)]
+    pub(crate) fn lookups() -> usize {
+        LOOKUPS.with(|lookups| lookups.get())
+    }
+
+    fn lookup<R: Resource>(&self) -> Option<ResourceId> {
+        #[cfg(test)]
+        LOOKUPS.with(|lookups| lookups.set(lookups.get() + 1));
+
+        self.index.get(&TypeId::of::<R>()).copied()
+    }
+
     pub fn register<const SEND: bool, R: Resource>(&mut self) -> ResourceId {
-        let id = TypeId::of::<R>();
-        if let Some(id) = self.index.get(&id).copied() {
+        if let Some(id) = self.lookup::<R>() {
             return id;
         }
 
-        let index = self.meta.len();
-        let meta = ResourceMeta::new::<SEND, R>(self.data.len());
-
+        let id = ResourceId(self.meta.len() as u32);
         self.is_send = self.is_send && SEND;
-        self.data.resize(meta.offset + meta.size, 0);
-        self.index.insert(id, ResourceId(index as u32));
-        self.meta.push(meta);
+        self.index.insert(TypeId::of::<R>(), id);
+        self.meta.push(ResourceMeta::new::<SEND, R>());
+
+        id
+    }
+
+    pub fn register_hashable<const SEND: bool, R: Resource + Hash>(&mut self) -> ResourceId {
+        let id = self.register::<SEND, R>();
+        self.meta[id.to_usize()].hash = Some(hash_value::<R>);
+        id
+    }
+
+    pub fn register_clone<const SEND: bool, R: Resource + Clone>(&mut self) -> ResourceId {
+        let id = self.register::<SEND, R>();
+        self.meta[id.to_usize()].clone = Some(clone_value::<R>);
+        id
+    }
 
-        ResourceId(index as u32)
+    pub fn register_serde<const SEND: bool, R: Resource>(
+        &mut self,
+        serde: ResourceSerde,
+    ) -> ResourceId {
+        let id = self.register::<SEND, R>();
+        self.meta[id.to_usize()].serde = Some(serde);
+        id
     }
 
-    pub fn add<const SEND: bool, R: Resource>(&mut self, resource: R) -> ResourceId {
+    /// Adds the resource, returning the value it replaced.
+    pub fn add<const SEND: bool, R: Resource>(&mut self, resource: R) -> Option<R> {
         self.add_with_frame::<SEND, R>(resource, Frame::ZERO)
     }
 
@@ -129,55 +235,57 @@ impl Resources {
         &mut self,
         resource: R,
         frame: Frame,
-    ) -> ResourceId {
-        let ty = TypeId::of::<R>();
-        let id = match self.index.get(&ty).copied() {
-            Some(id) => id,
-            None => self.register::<SEND, R>(),
-        };
+    ) -> Option<R> {
+        let id = self.register::<SEND, R>();
+        let meta = &mut self.meta[id.to_usize()];
+        let dst = meta.data.as_ptr() as *mut R;
+        let previous = meta.exists.then(|| unsafe { std::ptr::read(dst) });
+
+        meta.added = frame;
+        *meta.modified.get_mut() = frame.get();
+        meta.generation = meta.generation.wrapping_add(1);
+        meta.exists = true;
+        if !SEND {
+            meta.owner = Some(std::thread::current().id());
+        }
 
-        let (offset, size) = {
-            let meta = &mut self.meta[id.to_usize()];
-            meta.added = frame;
-            meta.exists = true;
-            (meta.offset, meta.size)
-        };
+        unsafe { std::ptr::write(dst, resource) };
 
-        unsafe {
-            let dst = self.data[offset..offset + size].as_mut_ptr();
-            std::ptr::copy_nonoverlapping(&resource as *const R as *const u8, dst, size);
+        previous
+    }
 
-            std::mem::forget(resource);
+    /// The resource, adding the value returned by `f` first if it doesn't exist.
+    pub fn get_or_insert_with<const SEND: bool, R: Resource>(
+        &mut self,
+        f: impl FnOnce() -> R,
+    ) -> &mut R {
+        let id = self.register::<SEND, R>();
+        if !self.meta[id.to_usize()].exists {
+            self.add::<SEND, R>(f());
         }
 
-        id
+        self.get_mut::<R>(id)
+            .unwrap_or_else(|| panic!("Resource not accessible: {}", std::any::type_name::<R>()))
     }
 
     pub fn get_id<R: Resource>(&self) -> Option<ResourceId> {
-        let id = TypeId::of::<R>();
-        self.index.get(&id).copied()
+        self.lookup::<R>()
     }
 
-    pub fn get<R: Resource>(&self, id: ResourceId) -> Option<&R> {
-        let id = id.to_usize();
-        let meta = self.meta.get(id)?;
-        if !meta.exists || !meta.has_access() {
-            return None;
-        }
+    /// Pointer to the resource, or `None` if it doesn't exist or isn't accessible from this thread.
+    pub fn get_ptr_by_id(&self, id: ResourceId) -> Option<NonNull<u8>> {
+        let meta = self.meta.get(id.to_usize())?;
+        (meta.exists && meta.has_access()).then_some(meta.data)
+    }
 
-        let data = &self.data[meta.offset..meta.offset + meta.size];
-        Some(unsafe { &*(data.as_ptr() as *const R) })
+    pub fn get<R: Resource>(&self, id: ResourceId) -> Option<&R> {
+        let ptr = self.get_ptr_by_id(id)?;
+        Some(unsafe { ptr.cast::<R>().as_ref() })
     }
 
     pub fn get_mut<R: Resource>(&mut self, id: ResourceId) -> Option<&mut R> {
-        let id = id.to_usize();
-        let meta = self.meta.get(id)?;
-        if !meta.exists || !meta.has_access() {
-            return None;
-        }
-
-        let data = &mut self.data[meta.offset..meta.offset + meta.size];
-        Some(unsafe { &mut *(data.as_mut_ptr() as *mut R) })
+        let ptr = self.get_ptr_by_id(id)?;
+        Some(unsafe { ptr.cast::<R>().as_mut() })
     }
 
     pub fn get_meta(&self, id: ResourceId) -> Option<&ResourceMeta> {
@@ -185,57 +293,255 @@ impl Resources {
     }
 
     pub fn remove<R: Resource>(&mut self) -> Option<R> {
-        let id = TypeId::of::<R>();
-        let id = self.index.get(&id).copied()?;
+        let id = self.lookup::<R>()?;
         let meta = self.meta.get_mut(id.to_usize())?;
         if !meta.exists || !meta.has_access() {
             return None;
         }
         meta.exists = false;
 
-        let data = &mut self.data[meta.offset..meta.offset + meta.size];
-        let resource = unsafe { std::ptr::read(data.as_mut_ptr() as *const R) };
+        Some(unsafe { std::ptr::read(meta.data.as_ptr() as *const R) })
+    }
+
+    /// Puts back a resource taken with [`Resources::remove`], keeping the frame it was added in
+    /// and its generation, and marks it modified.
+    pub(crate) fn reinsert<R: Resource>(&mut self, id: ResourceId, resource: R, frame: Frame) {
+        let meta = &mut self.meta[id.to_usize()];
+        assert!(
+            !meta.exists,
+            "Resource {} was added while it was taken out",
+            meta.name
+        );
+
+        unsafe { std::ptr::write(meta.data.as_ptr() as *mut R, resource) };
+        meta.exists = true;
+        *meta.modified.get_mut() = frame.get();
+    }
+
+    /// Drops the resource in place.
+    pub fn remove_by_id(&mut self, id: ResourceId) -> bool {
+        let Some(meta) = self.meta.get_mut(id.to_usize()) else {
+            return false;
+        };
+
+        if !meta.exists || !meta.has_access() {
+            return false;
+        }
+
+        meta.exists = false;
+        (meta.drop)(meta.data.as_ptr());
+        true
+    }
+
+    /// Clones the resource into a cell. `None` if it doesn't exist, isn't accessible from this
+    /// thread or wasn't registered as cloneable.
+    pub(crate) fn clone_cell(&self, id: ResourceId) -> Option<BlobCell> {
+        let meta = self.meta.get(id.to_usize())?;
+        let clone = meta.clone?;
+        let src = self.get_ptr_by_id(id)?;
+        let meta = TypeMeta {
+            name: meta.name,
+            layout: meta.layout,
+            drop: Some(meta.drop),
+        };
+
+        unsafe { Some(BlobCell::init(meta, |dst| clone(src.as_ptr(), dst))) }
+    }
+
+    /// Replaces the resource with a clone of the cell, or removes it if there's none.
+    pub(crate) fn restore_cell(&mut self, id: ResourceId, cell: Option<&BlobCell>, frame: Frame) {
+        let Some(cell) = cell else {
+            self.remove_by_id(id);
+            return;
+        };
+
+        let meta = &mut self.meta[id.to_usize()];
+        let clone = meta.clone.expect("Resource is not cloneable");
+        if meta.exists {
+            (meta.drop)(meta.data.as_ptr());
+        }
 
-        return Some(resource);
+        unsafe { clone(cell.data().as_ptr(), meta.data.as_ptr()) };
+        meta.exists = true;
+        meta.generation = meta.generation.wrapping_add(1);
+        *meta.modified.get_mut() = frame.get();
     }
 
-    pub fn modify(&mut self, id: ResourceId, frame: Frame) {
-        let id = id.to_usize();
-        if let Some(meta) = self.meta.get_mut(id) {
+    pub fn modify(&self, id: ResourceId, frame: Frame) {
+        if let Some(meta) = self.meta.get(id.to_usize()) {
             if meta.exists && meta.has_access() {
-                meta.modified = frame;
+                meta.mark_modified(frame);
             }
         }
     }
 
-    pub fn contains<R: Resource>(&self) -> bool {
-        let ty = TypeId::of::<R>();
-        let id = match self.index.get(&ty).copied() {
-            Some(id) => id,
-            None => return false,
-        };
+    /// Clamps the added and modified frames of every resource, see [`Frame::check`].
+    pub(crate) fn check_change_frames(&mut self, current: Frame) {
+        for meta in &mut self.meta {
+            meta.added.check(current);
+            let mut modified = Frame(*meta.modified.get_mut());
+            modified.check(current);
+            *meta.modified.get_mut() = modified.get();
+        }
+    }
+
+    pub fn generation(&self, id: ResourceId) -> Option<u32> {
+        self.meta
+            .get(id.to_usize())
+            .filter(|meta| meta.exists)
+            .map(|meta| meta.generation)
+    }
 
-        let id = id.to_usize();
-        self.meta.get(id).map_or(false, |meta| meta.exists)
+    pub fn contains<R: Resource>(&self) -> bool {
+        self.lookup::<R>()
+            .and_then(|id| self.meta.get(id.to_usize()))
+            .is_some_and(|meta| meta.exists)
     }
 
     pub fn len(&self) -> usize {
         self.meta.len()
     }
+
+    /// Number of resources currently stored, [`Resources::len`] counts registered ones.
+    pub fn count(&self) -> usize {
+        self.meta.iter().filter(|meta| meta.exists).count()
+    }
 }
 
 impl Drop for Resources {
     fn drop(&mut self) {
         for meta in std::mem::take(&mut self.meta) {
             if meta.exists {
-                let data = &mut self.data[meta.offset..meta.offset + meta.size];
-                let drop = meta.drop;
-                drop(data.as_mut_ptr())
+                (meta.drop)(meta.data.as_ptr());
+            }
+
+            if meta.layout.size() > 0 {
+                unsafe { std::alloc::dealloc(meta.data.as_ptr(), meta.layout) };
             }
         }
     }
 }
 
+/// Shared access to a resource that knows whether it changed since the system last ran.
+pub struct Res<'a, R: Resource> {
+    value: &'a R,
+    meta: &'a ResourceMeta,
+    last_run: Frame,
+    current: Frame,
+}
+
+impl<'a, R: Resource> Res<'a, R> {
+    pub fn new(value: &'a R, meta: &'a ResourceMeta, last_run: Frame, current: Frame) -> Self {
+        Self {
+            value,
+            meta,
+            last_run,
+            current,
+        }
+    }
+
+    /// Whether the resource was inserted since the system last ran.
+    pub fn is_added(&self) -> bool {
+        self.meta.added().is_newer(self.current, self.last_run)
+    }
+
+    /// Whether the resource was inserted or mutated since the system last ran.
+    pub fn is_changed(&self) -> bool {
+        self.meta.modified().is_newer(self.current, self.last_run)
+    }
+
+    pub fn into_inner(self) -> &'a R {
+        self.value
+    }
+}
+
+impl<'a, R: Resource> std::ops::Deref for Res<'a, R> {
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<'a, R: Resource> AsRef<R> for Res<'a, R> {
+    fn as_ref(&self) -> &R {
+        self.value
+    }
+}
+
+/// Mutable access to a resource that only marks it modified when it's dereferenced mutably.
+pub struct ResMut<'a, R: Resource> {
+    value: &'a mut R,
+    meta: &'a ResourceMeta,
+    last_run: Frame,
+    current: Frame,
+}
+
+impl<'a, R: Resource> ResMut<'a, R> {
+    pub fn new(value: &'a mut R, meta: &'a ResourceMeta, last_run: Frame, current: Frame) -> Self {
+        Self {
+            value,
+            meta,
+            last_run,
+            current,
+        }
+    }
+
+    pub fn is_added(&self) -> bool {
+        self.meta.added().is_newer(self.current, self.last_run)
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.meta.modified().is_newer(self.current, self.last_run)
+    }
+
+    /// Replaces the value and marks it modified only if the new value is different.
+    pub fn set_if_neq(&mut self, value: R) -> bool
+    where
+        R: PartialEq,
+    {
+        if *self.value == value {
+            return false;
+        }
+
+        *self.value = value;
+        self.meta.mark_modified(self.current);
+        true
+    }
+
+    /// Mutable access that doesn't mark the resource modified.
+    pub fn bypass_change_detection(&mut self) -> &mut R {
+        self.value
+    }
+
+    /// Converts into a plain reference, marking the resource modified.
+    pub fn into_inner(self) -> &'a mut R {
+        self.meta.mark_modified(self.current);
+        self.value
+    }
+}
+
+impl<'a, R: Resource> std::ops::Deref for ResMut<'a, R> {
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<'a, R: Resource> std::ops::DerefMut for ResMut<'a, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.meta.mark_modified(self.current);
+        self.value
+    }
+}
+
+impl<'a, R: Resource> AsRef<R> for ResMut<'a, R> {
+    fn as_ref(&self) -> &R {
+        self.value
+    }
+}
+
 pub struct NonSend<'a, R: Resource>(&'a R);
 impl<'a, R: Resource> NonSend<'a, R> {
     pub fn new(resource: &'a R) -> Self {
@@ -290,6 +596,94 @@ impl<'a, R: Resource> AsMut<R> for NonSendMut<'a, R> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceVersion {
+    pub generation: u32,
+    pub modified: Frame,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceChange {
+    Unchanged,
+    Modified,
+    /// The resource was removed and inserted again, or overwritten.
+    Replaced,
+}
+
+/// Read access to a resource along with its generation and change frames.
+pub struct ResVersioned<'a, R: Resource> {
+    resource: &'a R,
+    meta: &'a ResourceMeta,
+}
+
+impl<'a, R: Resource> ResVersioned<'a, R> {
+    pub fn new(resource: &'a R, meta: &'a ResourceMeta) -> Self {
+        Self { resource, meta }
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.meta.generation
+    }
+
+    pub fn added(&self) -> Frame {
+        self.meta.added
+    }
+
+    pub fn modified(&self) -> Frame {
+        self.meta.modified()
+    }
+
+    pub fn version(&self) -> ResourceVersion {
+        ResourceVersion {
+            generation: self.meta.generation,
+            modified: self.meta.modified(),
+        }
+    }
+
+    pub fn change_since(&self, previous: ResourceVersion) -> ResourceChange {
+        if self.meta.generation != previous.generation {
+            ResourceChange::Replaced
+        } else if self.meta.modified() != previous.modified {
+            ResourceChange::Modified
+        } else {
+            ResourceChange::Unchanged
+        }
+    }
+}
+
+impl<'a, R: Resource> std::ops::Deref for ResVersioned<'a, R> {
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.resource
+    }
+}
+
+impl<'a, R: Resource> AsRef<R> for ResVersioned<'a, R> {
+    fn as_ref(&self) -> &R {
+        self.resource
+    }
+}
+
+/// [`ResVersioned`] that also reports whether a missing resource was removed or never inserted.
+pub enum TryResVersioned<'a, R: Resource> {
+    Present(ResVersioned<'a, R>),
+    /// The resource existed before, `generation` is the last one it had.
+    Removed {
+        generation: u32,
+    },
+    Absent,
+}
+
+impl<'a, R: Resource> TryResVersioned<'a, R> {
+    pub fn present(self) -> Option<ResVersioned<'a, R>> {
+        match self {
+            TryResVersioned::Present(resource) => Some(resource),
+            _ => None,
+        }
+    }
+}
+
 pub struct Cloned<R: Resource>(R);
 impl<R: Resource> Cloned<R> {
     pub fn new(resource: R) -> Self {
@@ -339,7 +733,8 @@ mod tests {
     #[test]
     fn resources_add() {
         let mut resources = Resources::new();
-        let id = resources.add::<true, u32>(10);
+        assert_eq!(resources.add::<true, u32>(10), None);
+        let id = resources.get_id::<u32>().unwrap();
 
         assert_eq!(resources.get(id), Some(&10));
     }
@@ -357,7 +752,8 @@ mod tests {
     #[test]
     fn validate_resource_access() {
         let mut resources = Resources::new();
-        let id = resources.add::<false, u32>(10);
+        resources.add::<false, u32>(10);
+        let id = resources.get_id::<u32>().unwrap();
 
         std::thread::scope(|scope| {
             scope.spawn(move || {
@@ -366,4 +762,294 @@ mod tests {
             });
         });
     }
+
+    #[test]
+    fn resource_generation() {
+        use super::{ResourceChange, ResourceVersion, TryResVersioned};
+        use crate::{system::readonly::ReadOnlySystems, world::World};
+        use std::sync::{Arc, Mutex};
+
+        struct Config(u32);
+        impl Resource for Config {}
+
+        #[derive(Debug, PartialEq)]
+        enum Observed {
+            Absent,
+            Added(u32),
+            Change(ResourceChange, u32),
+            Removed(u32),
+        }
+
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let last = Mutex::new(None::<ResourceVersion>);
+        let log = observed.clone();
+        let consumer = move |config: TryResVersioned<Config>| {
+            let mut last = last.lock().unwrap();
+            let observation = match config {
+                TryResVersioned::Present(config) => {
+                    let observation = match *last {
+                        Some(previous) => Observed::Change(config.change_since(previous), config.0),
+                        None => Observed::Added(config.0),
+                    };
+                    *last = Some(config.version());
+                    observation
+                }
+                TryResVersioned::Removed { generation } => Observed::Removed(generation),
+                TryResVersioned::Absent => Observed::Absent,
+            };
+            log.lock().unwrap().push(observation);
+        };
+
+        let mut world = World::new();
+        let mut systems = ReadOnlySystems::new();
+        systems.add_systems(&mut world, consumer).unwrap();
+        let mut frame = |world: &mut World| {
+            systems.run(world);
+            world.update();
+        };
+
+        frame(&mut world);
+        assert_eq!(world.resource_generation::<Config>(), None);
+
+        world.add_resource(Config(1));
+        frame(&mut world);
+        frame(&mut world);
+
+        world.resource_mut::<Config>().0 = 2;
+        frame(&mut world);
+
+        world.add_resource(Config(3));
+        frame(&mut world);
+        assert_eq!(world.resource_generation::<Config>(), Some(2));
+
+        world.remove_resource::<Config>().unwrap();
+        frame(&mut world);
+        assert_eq!(world.resource_generation::<Config>(), None);
+
+        world.add_resource(Config(4));
+        frame(&mut world);
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            vec![
+                Observed::Absent,
+                Observed::Added(1),
+                Observed::Change(ResourceChange::Unchanged, 1),
+                Observed::Change(ResourceChange::Modified, 2),
+                Observed::Change(ResourceChange::Replaced, 3),
+                Observed::Removed(2),
+                Observed::Change(ResourceChange::Replaced, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn resources_steady_state_skips_lookups() {
+        use crate::{
+            system::{
+                executor::RunMode,
+                schedule::{Phase, Schedule},
+            },
+            world::{Event, EventReader, EventWriter, World},
+        };
+
+        struct Update;
+        impl Phase for Update {}
+
+        struct Hit;
+        impl Event for Hit {}
+
+        #[derive(Default)]
+        struct Score(u32);
+        impl Resource for Score {}
+
+        let mut schedule = Schedule::new(RunMode::Sequential);
+        schedule.add_systems(Update, |score: &Score| assert!(score.0 < 100));
+        schedule.add_systems(Update, |score: &mut Score| score.0 += 1);
+        schedule.add_systems(Update, |mut writer: EventWriter<Hit>| writer.send(Hit));
+        schedule.add_systems(Update, |reader: EventReader<Hit>| {
+            reader.for_each(drop);
+        });
+
+        let mut world = World::new();
+        world.add_resource(Score::default());
+        let systems = schedule.build(&mut world).unwrap();
+
+        for _ in 0..3 {
+            let lookups = Resources::lookups();
+            systems.run(&mut world, Update);
+            assert_eq!(Resources::lookups(), lookups);
+            world.update();
+        }
+
+        assert_eq!(world.resource::<Score>().0, 3);
+    }
+
+    #[test]
+    fn resources_remove_mid_phase() {
+        use crate::{
+            system::{
+                IntoSystemConfigs,
+                executor::RunMode,
+                schedule::{Phase, Schedule},
+            },
+            world::{Command, Commands, World},
+        };
+
+        struct Update;
+        impl Phase for Update {}
+
+        #[derive(Debug, PartialEq)]
+        struct Config(u32);
+        impl Resource for Config {}
+
+        struct RemoveConfig;
+        impl Command for RemoveConfig {
+            fn execute(self, world: &mut World) {
+                let error = world.remove_resource::<Config>().unwrap_err();
+                assert_eq!(error.0, std::any::type_name::<Config>());
+                assert!(world.try_resource_scope(|_, _: &mut Config| ()).is_err());
+                world.remove_resource_deferred::<Config>();
+            }
+        }
+
+        let remove = |mut commands: Commands| commands.add(RemoveConfig);
+        let read = |config: &Config| assert_eq!(config.0, 7);
+
+        let mut schedule = Schedule::new(RunMode::Sequential);
+        schedule.add_systems(Update, remove.before(read));
+
+        let mut world = World::new();
+        world.add_resource(Config(7));
+        let systems = schedule.build(&mut world).unwrap();
+
+        systems.run(&mut world, Update);
+        assert!(!world.resources().contains::<Config>());
+        assert_eq!(world.remove_resource::<Config>(), Ok(None));
+
+        world.add_resource(Config(8));
+        let config = world.remove_resource::<Config>().unwrap();
+        assert_eq!(config.map(|c| c.0), Some(8));
+
+        world.add_resource(Config(9));
+        world.remove_resource_deferred::<Config>();
+        assert!(!world.resources().contains::<Config>());
+    }
+
+    #[test]
+    fn resources_replace_drops_previous() {
+        struct Counted(#[allow(dead_code)] Rc<()>);
+        impl Resource for Counted {}
+
+        let counter = Rc::new(());
+        let mut resources = Resources::new();
+        resources.add::<false, Counted>(Counted(counter.clone()));
+        resources.add::<false, Counted>(Counted(counter.clone()));
+
+        assert_eq!(Rc::strong_count(&counter), 2);
+    }
+
+    #[test]
+    fn resources_replace() {
+        let mut resources = Resources::new();
+        assert_eq!(resources.add::<true, u32>(1), None);
+        assert_eq!(resources.add::<true, u32>(2), Some(1));
+
+        *resources.get_or_insert_with::<true, u32>(|| unreachable!()) += 1;
+        assert_eq!(resources.remove::<u32>(), Some(3));
+        assert_eq!(*resources.get_or_insert_with::<true, u32>(|| 4), 4);
+        assert_eq!(
+            resources
+                .get_meta(resources.get_id::<u32>().unwrap())
+                .unwrap()
+                .generation(),
+            3
+        );
+    }
+
+    #[test]
+    fn resource_change_detection() {
+        use super::{Res, ResMut};
+        use crate::{
+            app::App,
+            system::{IntoSystemConfigs, condition::resource_changed, schedule::Phase},
+        };
+
+        struct Update;
+        impl Phase for Update {}
+
+        #[derive(PartialEq)]
+        struct Score(u32);
+        impl Resource for Score {}
+
+        struct Target(u32);
+        impl Resource for Target {}
+
+        #[derive(Default)]
+        struct Observed(Vec<(bool, bool)>, u32);
+        impl Resource for Observed {}
+
+        let write = |mut score: ResMut<Score>, target: &Target| {
+            score.set_if_neq(Score(target.0));
+        };
+        let observe = |score: Res<Score>, observed: &mut Observed| {
+            observed.0.push((score.is_added(), score.is_changed()));
+        };
+        let react = |observed: &mut Observed| observed.1 += 1;
+
+        let mut app = App::new()
+            .add_resource(Score(0))
+            .add_resource(Target(0))
+            .add_resource(Observed::default())
+            .add_systems(Update, write.before(observe))
+            .add_systems(Update, react.run_if(resource_changed::<Score>()))
+            .build();
+
+        app.run(Update);
+        app.run(Update);
+        app.world_mut().resource_mut::<Target>().0 = 5;
+        app.run(Update);
+
+        let observed = app.world().resource::<Observed>();
+        assert_eq!(observed.0, [(true, true), (false, false), (false, true)]);
+        assert_eq!(observed.1, 2);
+        assert_eq!(app.world().resource::<Score>().0, 5);
+    }
+
+    #[test]
+    fn resource_scope() {
+        use crate::world::{Component, World};
+
+        struct Health(u32);
+        impl Component for Health {}
+
+        #[derive(Default)]
+        struct Total(u32);
+        impl Resource for Total {}
+
+        let mut world = World::new();
+        world.register::<Health>();
+        world.spawn_bundle((Health(3),));
+        world.spawn_bundle((Health(4),));
+        world.add_resource(Total::default());
+        let generation = world.resource_generation::<Total>();
+
+        let count = world.resource_scope(|world, total: &mut Total| {
+            assert!(world.try_resource::<Total>().is_none());
+            total.0 = world.query::<&Health>().iter().map(|health| health.0).sum();
+            world.query::<&Health>().iter().count()
+        });
+
+        assert_eq!((count, world.resource::<Total>().0), (2, 7));
+        assert_eq!(world.resource_generation::<Total>(), generation);
+        assert_eq!(world.try_resource_scope(|_, _: &mut u32| ()), Ok(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "was added while it was taken out")]
+    fn resource_scope_rejects_reinsertion() {
+        let mut world = crate::world::World::new();
+        world.add_resource(1u32);
+        world.resource_scope(|world, _: &mut u32| world.add_resource(2u32));
+    }
 }
@@ -0,0 +1,250 @@
+use super::{
+    Bundle, Command, CommandKind, CommandQueue, Entity, EntityMapper, Event, Events, World,
+    WorldExtension,
+};
+use crate::app::AppLabel;
+
+/// Entities spawned in another world with [`CrossWorldQueue::spawn`], sent back to the world
+/// that queued them once the commands were routed, see [`App::route_commands`](crate::app::App::route_commands).
+pub struct CrossWorldSpawned {
+    /// Label of the world the entities were spawned in.
+    pub world: &'static str,
+    /// Maps the source entities passed to [`CrossWorldQueue::spawn`] to the spawned entities.
+    pub entities: EntityMapper,
+}
+
+impl Event for CrossWorldSpawned {}
+
+/// Spawns the bundle and records the entity for its source, see [`CrossWorldQueue::spawn`].
+struct RoutedSpawn<B: Bundle> {
+    source: Entity,
+    bundle: B,
+}
+
+impl<B: Bundle> Command for RoutedSpawn<B> {
+    fn execute(self, world: &mut World) {
+        let entity = world.spawn_bundle(self.bundle);
+        if world.extension::<RoutedSpawns>().is_none() {
+            world.insert_extension(RoutedSpawns::default());
+        }
+
+        let spawns = world.extension_mut::<RoutedSpawns>().unwrap();
+        spawns.0.insert(self.source, entity);
+    }
+
+    fn kind(&self, _: &World) -> CommandKind {
+        CommandKind::Spawn
+    }
+}
+
+/// Entities spawned by routed commands while a [`CrossWorldQueue`] is applied.
+#[derive(Default)]
+struct RoutedSpawns(EntityMapper);
+
+impl WorldExtension for RoutedSpawns {}
+
+/// Commands queued for another world, see [`Commands::for_world`](super::Commands::for_world).
+/// Entities of the queuing world mean nothing in the target world, spawn through
+/// [`CrossWorldQueue::spawn`] and read the [`CrossWorldSpawned`] mapping to refer to them.
+#[derive(Default)]
+pub struct CrossWorldQueue {
+    commands: CommandQueue,
+}
+
+impl CrossWorldQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn add<C: Command>(&mut self, command: C) {
+        self.commands.add(command);
+    }
+
+    /// Spawns the bundle in the target world for `source`, an entity of the queuing world.
+    pub fn spawn<B: Bundle>(&mut self, source: Entity, bundle: B) {
+        self.commands.add(RoutedSpawn { source, bundle });
+    }
+
+    /// Moves the commands of `other` to the end of this queue, leaving `other` empty.
+    pub fn append(&mut self, other: &mut CrossWorldQueue) {
+        self.commands.append(&mut other.commands);
+    }
+
+    /// Executes the commands on the target world in order, through its
+    /// [`CommandMiddleware`](super::CommandMiddleware) like any other queue. Returns the entity
+    /// spawned for each source entity, spawns skipped by the middleware are left out.
+    pub fn execute(&mut self, world: &mut World) -> EntityMapper {
+        self.commands.execute(world);
+        world
+            .extension_mut::<RoutedSpawns>()
+            .map(|spawns| std::mem::take(&mut spawns.0))
+            .unwrap_or_default()
+    }
+
+    fn clear(&mut self) {
+        // Dropping the queue drops the commands without running them.
+        self.commands = CommandQueue::new();
+    }
+}
+
+/// Queues of commands waiting to be routed to other worlds, by target label.
+#[derive(Default)]
+pub(crate) struct RoutedCommands {
+    queues: Vec<(&'static str, CrossWorldQueue)>,
+}
+
+impl RoutedCommands {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queues.iter().all(|(_, queue)| queue.is_empty())
+    }
+
+    pub(crate) fn queue(&mut self, label: &'static str) -> &mut CrossWorldQueue {
+        let index = match self.queues.iter().position(|(name, _)| *name == label) {
+            Some(index) => index,
+            None => {
+                self.queues.push((label, CrossWorldQueue::new()));
+                self.queues.len() - 1
+            }
+        };
+
+        &mut self.queues[index].1
+    }
+
+    /// Moves the commands of `other` to the end of the queues with the same labels.
+    pub(crate) fn append(&mut self, other: &mut RoutedCommands) {
+        for (label, queue) in &mut other.queues {
+            if !queue.is_empty() {
+                self.queue(label).append(queue);
+            }
+        }
+    }
+}
+
+impl WorldExtension for RoutedCommands {}
+
+impl World {
+    /// Queue of commands for the world with the label, applied by
+    /// [`App::route_commands`](crate::app::App::route_commands).
+    pub fn for_world(&mut self, label: impl AppLabel) -> &mut CrossWorldQueue {
+        self.routed_commands().queue(label.name())
+    }
+
+    /// Applies the commands queued for `label` to `target`, then sends the entities they spawned
+    /// back to this world as a [`CrossWorldSpawned`] event.
+    pub fn apply_routed_commands(&mut self, label: &'static str, target: &mut World) {
+        let queue = self.routed_commands().queue(label);
+        if queue.is_empty() {
+            return;
+        }
+
+        let mut commands = std::mem::take(queue);
+        let entities = commands.execute(target);
+
+        // Keep the allocation of the queue for the next frame.
+        let queue = self.routed_commands().queue(label);
+        commands.append(queue);
+        *queue = commands;
+
+        if !entities.is_empty() {
+            self.register_event::<CrossWorldSpawned>();
+            self.resource_mut::<Events<CrossWorldSpawned>>()
+                .send(CrossWorldSpawned {
+                    world: label,
+                    entities,
+                });
+        }
+    }
+
+    /// Drops the commands queued for the worlds `keep` returns false for.
+    pub(crate) fn retain_routed_commands(&mut self, keep: impl Fn(&'static str) -> bool) {
+        if let Some(routed) = self.extension_mut::<RoutedCommands>() {
+            for (label, queue) in &mut routed.queues {
+                if !keep(label) {
+                    queue.clear();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn routed_commands(&mut self) -> &mut RoutedCommands {
+        if self.extension::<RoutedCommands>().is_none() {
+            self.insert_extension(RoutedCommands::default());
+        }
+
+        self.extension_mut::<RoutedCommands>().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrossWorldSpawned;
+    use crate::{
+        app::AppLabel,
+        world::{
+            Command, CommandContext, CommandDecision, CommandKind, Component, Entity, Events,
+            Resource, World,
+        },
+    };
+
+    struct RenderApp;
+    impl AppLabel for RenderApp {}
+
+    struct Mesh(#[allow(dead_code)] u32);
+    impl Component for Mesh {}
+
+    #[derive(Default)]
+    struct Buffers(u32);
+    impl Resource for Buffers {}
+
+    struct CreateBuffer;
+    impl Command for CreateBuffer {
+        fn execute(self, world: &mut World) {
+            world.resource_mut::<Buffers>().0 += 1;
+        }
+    }
+
+    fn skip_spawns(context: &mut CommandContext) -> CommandDecision {
+        match context.kind() {
+            CommandKind::Spawn => CommandDecision::Skip,
+            _ => CommandDecision::Allow,
+        }
+    }
+
+    #[test]
+    fn routed_commands_run_through_middleware() {
+        let mut main = World::new();
+        let source = main.spawn();
+
+        let mut render = World::new();
+        render.register::<Mesh>();
+        render.add_resource(Buffers::default());
+
+        let route = |main: &mut World, render: &mut World| {
+            main.for_world(RenderApp).spawn(source, (Mesh(0),));
+            main.for_world(RenderApp).add(CreateBuffer);
+            main.apply_routed_commands(RenderApp.name(), render);
+        };
+
+        route(&mut main, &mut render);
+        assert_eq!(render.query::<Entity>().iter().count(), 1);
+        assert_eq!(render.resource::<Buffers>().0, 1);
+        let events = main.resource::<Events<CrossWorldSpawned>>();
+        assert_eq!(events.iter_current_update_events().count(), 1);
+
+        // Skipped spawns run nothing and map nothing.
+        render.add_command_middleware(skip_spawns);
+        route(&mut main, &mut render);
+        assert_eq!(render.query::<Entity>().iter().count(), 1);
+        assert_eq!(render.resource::<Buffers>().0, 2);
+        let events = main.resource::<Events<CrossWorldSpawned>>();
+        assert_eq!(events.iter_current_update_events().count(), 1);
+    }
+}
@@ -0,0 +1,340 @@
+use super::{
+    Component, ComponentId, Entity, EntityMapper, MapEntities, Resource, ResourceId, World,
+};
+use crate::core::SparseIndex;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_value::{DeserializerError, SerializerError, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Serializes the value behind the pointer into a format independent [`Value`].
+pub type SerializeFn = unsafe fn(*const u8) -> Result<Value, SerializerError>;
+
+/// Deserializes the component and inserts it into the entity.
+pub type InsertComponentFn =
+    fn(&mut World, Entity, Value, &EntityMapper) -> Result<(), DeserializerError>;
+
+/// Deserializes the resource and adds it to the world.
+pub type InsertResourceFn = fn(&mut World, Value) -> Result<(), DeserializerError>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ComponentSerde {
+    pub serialize: SerializeFn,
+    pub insert: InsertComponentFn,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceSerde {
+    pub serialize: SerializeFn,
+    pub insert: InsertResourceFn,
+}
+
+unsafe fn serialize_value<T: Serialize>(ptr: *const u8) -> Result<Value, SerializerError> {
+    serde_value::to_value(unsafe { &*(ptr as *const T) })
+}
+
+fn insert_component<C: Component + DeserializeOwned>(
+    world: &mut World,
+    entity: Entity,
+    value: Value,
+    _: &EntityMapper,
+) -> Result<(), DeserializerError> {
+    world.add_component(entity, value.deserialize_into::<C>()?);
+    Ok(())
+}
+
+fn insert_mapped_component<C: Component + DeserializeOwned + MapEntities>(
+    world: &mut World,
+    entity: Entity,
+    value: Value,
+    mapper: &EntityMapper,
+) -> Result<(), DeserializerError> {
+    let mut component = value.deserialize_into::<C>()?;
+    component.map_entities(mapper);
+    world.add_component(entity, component);
+    Ok(())
+}
+
+fn insert_resource<R: Resource + Send + DeserializeOwned>(
+    world: &mut World,
+    value: Value,
+) -> Result<(), DeserializerError> {
+    world.add_resource(value.deserialize_into::<R>()?);
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    /// No component or resource with this name was registered with serde support.
+    Unregistered(String),
+    Serialize {
+        name: String,
+        error: String,
+    },
+    Deserialize {
+        name: String,
+        error: String,
+    },
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Unregistered(name) => write!(f, "No serde support registered: {}", name),
+            SceneError::Serialize { name, error } => {
+                write!(f, "Failed to serialize {}: {}", name, error)
+            }
+            SceneError::Deserialize { name, error } => {
+                write!(f, "Failed to deserialize {}: {}", name, error)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    pub entity: Entity,
+    /// Serialized components keyed by their registered name.
+    pub components: BTreeMap<String, Value>,
+}
+
+/// Serializable copy of the entities, components and resources of a world, see
+/// [`World::to_scene`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub entities: Vec<SceneEntity>,
+    /// Serialized resources keyed by their registered name.
+    pub resources: BTreeMap<String, Value>,
+}
+
+impl Scene {
+    /// Spawns a new entity for every entity of the scene and inserts its components. Entity
+    /// references in components registered with [`World::register_serde_mapped`] are remapped
+    /// to the new entities. Resources replace the world's current ones.
+    pub fn spawn_into(&self, world: &mut World) -> Result<EntityMapper, SceneError> {
+        let components = world
+            .components()
+            .metas()
+            .iter()
+            .filter_map(|meta| Some((meta.name(), meta.serde()?)))
+            .collect::<HashMap<_, _>>();
+        let resources = (0..world.resources().len())
+            .map(ResourceId::from_usize)
+            .filter_map(|id| {
+                let meta = world.resources().get_meta(id)?;
+                Some((meta.name(), meta.serde()?))
+            })
+            .collect::<HashMap<_, _>>();
+
+        // Names are checked up front so an unknown component doesn't leave a partial scene.
+        let names = self
+            .entities
+            .iter()
+            .flat_map(|entity| entity.components.keys());
+        for name in names {
+            if !components.contains_key(name.as_str()) {
+                return Err(SceneError::Unregistered(name.clone()));
+            }
+        }
+
+        for name in self.resources.keys() {
+            if !resources.contains_key(name.as_str()) {
+                return Err(SceneError::Unregistered(name.clone()));
+            }
+        }
+
+        let mut mapper = EntityMapper::new();
+        for entity in &self.entities {
+            mapper.insert(entity.entity, world.spawn());
+        }
+
+        let error = |name: &String, error: DeserializerError| SceneError::Deserialize {
+            name: name.clone(),
+            error: error.to_string(),
+        };
+
+        for entity in &self.entities {
+            for (name, value) in &entity.components {
+                let serde = components[name.as_str()];
+                let target = mapper.map(entity.entity);
+                (serde.insert)(world, target, value.clone(), &mapper)
+                    .map_err(|e| error(name, e))?;
+            }
+        }
+
+        for (name, value) in &self.resources {
+            let serde = resources[name.as_str()];
+            (serde.insert)(world, value.clone()).map_err(|e| error(name, e))?;
+        }
+
+        Ok(mapper)
+    }
+}
+
+impl World {
+    /// Registers the component so it's included in scenes, see [`World::to_scene`].
+    pub fn register_serde<C: Component + Serialize + DeserializeOwned>(&mut self) -> ComponentId {
+        self.archetypes.register_serde::<C>(ComponentSerde {
+            serialize: serialize_value::<C>,
+            insert: insert_component::<C>,
+        })
+    }
+
+    /// Like [`World::register_serde`], remapping the entities the component references when a
+    /// scene is spawned.
+    pub fn register_serde_mapped<C>(&mut self) -> ComponentId
+    where
+        C: Component + Serialize + DeserializeOwned + MapEntities,
+    {
+        self.archetypes.register_serde::<C>(ComponentSerde {
+            serialize: serialize_value::<C>,
+            insert: insert_mapped_component::<C>,
+        })
+    }
+
+    pub fn register_serde_resource<R>(&mut self) -> ResourceId
+    where
+        R: Resource + Send + Serialize + DeserializeOwned,
+    {
+        self.resources.register_serde::<true, R>(ResourceSerde {
+            serialize: serialize_value::<R>,
+            insert: insert_resource::<R>,
+        })
+    }
+
+    /// Serializes every entity with the components and resources registered with serde support.
+    /// Other components are left out, entities are ordered by id.
+    pub fn to_scene(&self) -> Result<Scene, SceneError> {
+        let error = |name: &str, error: SerializerError| SceneError::Serialize {
+            name: name.to_string(),
+            error: error.to_string(),
+        };
+
+        let mut entities = Vec::new();
+        for archetype in self.archetypes().archetypes() {
+            let table = archetype.table();
+            let columns = table
+                .columns()
+                .filter_map(|(id, column)| {
+                    let meta = self.components().meta(*id)?;
+                    Some((meta.name(), meta.serde()?.serialize, column))
+                })
+                .collect::<Vec<_>>();
+
+            for (row, entity) in table.entities().enumerate() {
+                let mut components = BTreeMap::new();
+                for (name, serialize, column) in &columns {
+                    let (ptr, _) = column.get_raw(row).unwrap();
+                    let value = unsafe { serialize(ptr.as_ptr()) }.map_err(|e| error(name, e))?;
+                    components.insert(name.to_string(), value);
+                }
+
+                entities.push(SceneEntity {
+                    entity: *entity,
+                    components,
+                });
+            }
+        }
+
+        entities.sort_by_key(|entity| entity.entity.id());
+
+        let mut resources = BTreeMap::new();
+        for id in (0..self.resources.len()).map(ResourceId::from_usize) {
+            let meta = self.resources.get_meta(id).unwrap();
+            let (Some(serde), Some(ptr)) = (meta.serde(), self.resources.get_ptr_by_id(id)) else {
+                continue;
+            };
+
+            let value = unsafe { (serde.serialize)(ptr.as_ptr()) };
+            resources.insert(
+                meta.name().to_string(),
+                value.map_err(|e| error(meta.name(), e))?,
+            );
+        }
+
+        Ok(Scene {
+            entities,
+            resources,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scene, SceneError};
+    use crate::world::{Children, Component, Entity, Parent, Resource, World};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+    impl Component for Position {}
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Label(String);
+    impl Component for Label {}
+
+    #[derive(Debug, PartialEq)]
+    struct Handle(u32);
+    impl Component for Handle {}
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Level(u32);
+    impl Resource for Level {}
+
+    fn scene_world() -> World {
+        let mut world = World::new();
+        world.register_serde::<Position>();
+        world.register_serde::<Label>();
+        world.register_serde_resource::<Level>();
+        world.register::<Handle>();
+        world
+    }
+
+    #[test]
+    fn scene_round_trip() {
+        let mut world = scene_world();
+        world.add_resource(Level(3));
+        let parent = world.spawn_bundle((Label("root".into()), Handle(1)));
+        let child = world.spawn_bundle((Position { x: 1.0, y: 2.0 },));
+        world.set_parent(child, parent).unwrap();
+
+        let json = serde_json::to_string(&world.to_scene().unwrap()).unwrap();
+        let scene = serde_json::from_str::<Scene>(&json).unwrap();
+        assert_eq!(scene.entities.len(), 2);
+
+        let mut other = scene_world();
+        other.spawn();
+        let mapper = scene.spawn_into(&mut other).unwrap();
+        let (parent, child) = (mapper.map(parent), mapper.map(child));
+        assert_ne!(parent, child);
+
+        assert_eq!(
+            other.get_component::<Label>(parent),
+            Some(&Label("root".into()))
+        );
+        assert_eq!(other.get_component::<Handle>(parent), None);
+        let position = other.get_component::<Position>(child);
+        assert_eq!(position, Some(&Position { x: 1.0, y: 2.0 }));
+        assert_eq!(
+            other.get_component::<Parent>(child).map(Parent::get),
+            Some(parent)
+        );
+        let children = other.get_component::<Children>(parent).unwrap();
+        assert_eq!(children.as_slice(), [child]);
+        assert_eq!(other.resource::<Level>(), &Level(3));
+    }
+
+    #[test]
+    fn scene_unregistered_component() {
+        let mut world = scene_world();
+        world.spawn_bundle((Label("a".into()),));
+        let scene = world.to_scene().unwrap();
+
+        let mut other = World::new();
+        let result = scene.spawn_into(&mut other);
+        assert_eq!(result, Err(SceneError::Unregistered("Label".into())));
+        assert!(!other.is_alive(Entity::new(0, 1)));
+    }
+}
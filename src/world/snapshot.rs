@@ -0,0 +1,898 @@
+use super::{
+    ArchetypeQuery, Component, ComponentId, ComponentMeta, Entities, Entity, EntityMapper,
+    Resource, ResourceId, World, component::CloneFn, hash::StateHashCache,
+};
+use crate::core::{
+    ObjectStatus, SparseIndex, TypeMeta,
+    blob::{Blob, BlobCell},
+};
+use serde_value::Value;
+use std::alloc::Layout;
+
+#[cfg(test)]
+thread_local! {
+    /// Column byte buffers bulk inserted by [`World::restore_pod`] on this thread.
+    static COPIED_COLUMNS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    /// Rows [`World::restore_pod`] moved or inserted one entity at a time on this thread.
+    static ENTITY_ROWS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PodSnapshotError {
+    /// The component was not registered as a [`PodComponent`](super::PodComponent), nor with
+    /// serde support when falling back with [`NonPodPolicy::Serde`].
+    NotPod(&'static str),
+    /// The component is not registered in the world.
+    Unregistered(ComponentId),
+    /// The component registered under the captured id has another name, components were
+    /// registered in a different order.
+    NameMismatch {
+        component: ComponentId,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The component layout differs from the one captured in the snapshot.
+    LayoutMismatch {
+        name: &'static str,
+        expected: Layout,
+        found: Layout,
+    },
+    Serialize {
+        name: &'static str,
+        error: String,
+    },
+    Deserialize {
+        name: &'static str,
+        error: String,
+    },
+}
+
+impl std::fmt::Display for PodSnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PodSnapshotError::NotPod(name) => write!(f, "Component is not POD: {}", name),
+            PodSnapshotError::Unregistered(id) => write!(f, "Component not registered: {:?}", id),
+            PodSnapshotError::NameMismatch {
+                component,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Component {:?} mismatch: expected {}, found {}",
+                component, expected, found
+            ),
+            PodSnapshotError::LayoutMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Layout mismatch for {}: expected {:?}, found {:?}",
+                name, expected, found
+            ),
+            PodSnapshotError::Serialize { name, error } => {
+                write!(f, "Failed to serialize {}: {}", name, error)
+            }
+            PodSnapshotError::Deserialize { name, error } => {
+                write!(f, "Failed to deserialize {}: {}", name, error)
+            }
+        }
+    }
+}
+
+/// What [`World::snapshot_pod_with`] does with components that weren't registered as POD.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NonPodPolicy {
+    /// Fails with [`PodSnapshotError::NotPod`].
+    #[default]
+    Error,
+    /// Serializes the components registered with [`World::register_serde`] one value at a time,
+    /// and inserts them one entity at a time on restore. Other components still fail.
+    Serde,
+}
+
+pub struct PodColumn {
+    pub component: ComponentId,
+    pub name: &'static str,
+    pub layout: Layout,
+    pub count: usize,
+    pub bytes: Vec<u8>,
+    pub frames: Vec<ObjectStatus>,
+}
+
+/// Values of a non POD column, captured with [`NonPodPolicy::Serde`].
+pub struct PodSerdeColumn {
+    pub component: ComponentId,
+    pub name: &'static str,
+    pub values: Vec<Value>,
+}
+
+pub struct PodArchetype {
+    pub components: Vec<ComponentId>,
+    pub entities: Vec<Entity>,
+    pub columns: Vec<PodColumn>,
+    pub serde: Vec<PodSerdeColumn>,
+}
+
+/// Byte level copy of archetype tables containing only POD components.
+pub struct PodSnapshot {
+    archetypes: Vec<PodArchetype>,
+}
+
+impl PodSnapshot {
+    pub fn archetypes(&self) -> &[PodArchetype] {
+        &self.archetypes
+    }
+
+    pub fn entity_count(&self) -> usize {
+        self.archetypes.iter().map(|a| a.entities.len()).sum()
+    }
+}
+
+impl World {
+    /// Captures every archetype matching `filter` by copying its column bytes.
+    /// Fails if a matched archetype contains a component that wasn't registered as POD.
+    pub fn snapshot_pod(&self, filter: &ArchetypeQuery) -> Result<PodSnapshot, PodSnapshotError> {
+        self.snapshot_pod_with(filter, NonPodPolicy::Error)
+    }
+
+    /// Like [`World::snapshot_pod`], handling components that aren't POD with the policy.
+    pub fn snapshot_pod_with(
+        &self,
+        filter: &ArchetypeQuery,
+        policy: NonPodPolicy,
+    ) -> Result<PodSnapshot, PodSnapshotError> {
+        let mut archetypes = Vec::new();
+        for archetype in self.archetypes().query_iter(filter) {
+            let table = archetype.table();
+            let mut components = Vec::new();
+            let mut columns = Vec::new();
+            let mut serde = Vec::new();
+
+            for (id, column) in table.columns() {
+                let meta = self.components().meta(*id).unwrap();
+                components.push(*id);
+                if !meta.is_pod() {
+                    let serialize = match (policy, meta.serde()) {
+                        (NonPodPolicy::Serde, Some(serde)) => serde.serialize,
+                        _ => return Err(PodSnapshotError::NotPod(meta.name())),
+                    };
+
+                    let values = (0..column.len())
+                        .map(|row| {
+                            let (ptr, _) = column.get_raw(row).unwrap();
+                            unsafe { serialize(ptr.as_ptr()) }
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|error| PodSnapshotError::Serialize {
+                            name: meta.name(),
+                            error: error.to_string(),
+                        })?;
+
+                    serde.push(PodSerdeColumn {
+                        component: *id,
+                        name: meta.name(),
+                        values,
+                    });
+                    continue;
+                }
+
+                let (bytes, count, layout) = column.raw_parts();
+                columns.push(PodColumn {
+                    component: *id,
+                    name: meta.name(),
+                    layout,
+                    count,
+                    bytes: bytes.to_vec(),
+                    frames: (0..count).filter_map(|row| column.status(row)).collect(),
+                });
+            }
+
+            archetypes.push(PodArchetype {
+                components,
+                entities: table.entities().copied().collect(),
+                columns,
+                serde,
+            });
+        }
+
+        Ok(PodSnapshot { archetypes })
+    }
+
+    /// Restores the entities captured by [`World::snapshot_pod`] by bulk inserting their bytes,
+    /// one copy per column. Captured ids are claimed in the entity allocator with
+    /// [`Entities::spawn_at`], so restoring into a fresh world recreates every entity. Captured
+    /// entities that are still alive are moved back, dropping their current components, and
+    /// captured entities whose slot was recycled since are skipped. Entities that aren't part of
+    /// the snapshot are left untouched.
+    pub fn restore_pod(&mut self, snapshot: &PodSnapshot) -> Result<(), PodSnapshotError> {
+        for archetype in &snapshot.archetypes {
+            for column in &archetype.columns {
+                let meta = self.pod_meta(column.component, column.name)?;
+                if !meta.is_pod() {
+                    return Err(PodSnapshotError::NotPod(meta.name()));
+                }
+
+                if meta.layout() != column.layout {
+                    return Err(PodSnapshotError::LayoutMismatch {
+                        name: meta.name(),
+                        expected: column.layout,
+                        found: meta.layout(),
+                    });
+                }
+            }
+
+            for column in &archetype.serde {
+                let meta = self.pod_meta(column.component, column.name)?;
+                if meta.serde().is_none() {
+                    return Err(PodSnapshotError::NotPod(meta.name()));
+                }
+            }
+        }
+
+        let claimed = self.claim_pod_entities(snapshot);
+        let mapper = EntityMapper::new();
+        for (archetype, claimed) in snapshot.archetypes.iter().zip(claimed) {
+            let compacted = claimed.map(|claimed| compact_pod_archetype(archetype, &claimed));
+            let archetype = compacted.as_ref().unwrap_or(archetype);
+            let components = archetype
+                .columns
+                .iter()
+                .map(|column| column.component)
+                .collect::<Vec<_>>();
+            let id = self.archetype_for_components(&components);
+
+            unsafe {
+                self.archetypes_mut()
+                    .extend_raw(id, &archetype.entities, |component| {
+                        let column = archetype
+                            .columns
+                            .iter()
+                            .find(|column| column.component == component)
+                            .unwrap();
+
+                        (column.bytes.as_slice(), column.frames.as_slice())
+                    });
+            }
+
+            #[cfg(test)]
+            COPIED_COLUMNS.with(|copied| copied.set(copied.get() + archetype.columns.len()));
+
+            for column in &archetype.serde {
+                let meta = self.components().meta(column.component).unwrap();
+                let insert = meta.serde().unwrap().insert;
+                let name = meta.name();
+                for (entity, value) in archetype.entities.iter().zip(&column.values) {
+                    insert(self, *entity, value.clone(), &mapper).map_err(|error| {
+                        PodSnapshotError::Deserialize {
+                            name,
+                            error: error.to_string(),
+                        }
+                    })?;
+                }
+
+                #[cfg(test)]
+                ENTITY_ROWS.with(|rows| rows.set(rows.get() + column.values.len()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The component registered under the captured id, if it has the captured name.
+    fn pod_meta(
+        &self,
+        component: ComponentId,
+        name: &'static str,
+    ) -> Result<&ComponentMeta, PodSnapshotError> {
+        let meta = self
+            .components()
+            .meta(component)
+            .ok_or(PodSnapshotError::Unregistered(component))?;
+
+        match meta.name() == name {
+            true => Ok(meta),
+            false => Err(PodSnapshotError::NameMismatch {
+                component,
+                expected: name,
+                found: meta.name(),
+            }),
+        }
+    }
+
+    /// Claims the captured ids in the allocator in ascending order, which keeps the indices
+    /// skipped by [`Entities::spawn_at`] down to the gaps between them. Entities that are still
+    /// alive are moved out of their current archetype. Returns per archetype which rows were
+    /// claimed, `None` if all of them were.
+    fn claim_pod_entities(&mut self, snapshot: &PodSnapshot) -> Vec<Option<Vec<bool>>> {
+        let mut order = snapshot
+            .archetypes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, archetype)| {
+                let rows = archetype.entities.iter().enumerate();
+                rows.map(move |(row, entity)| (*entity, index, row))
+            })
+            .collect::<Vec<_>>();
+        order.sort_unstable_by_key(|(entity, _, _)| entity.id());
+
+        let mut claimed = vec![None::<Vec<bool>>; snapshot.archetypes.len()];
+        for (entity, index, row) in order {
+            if self.entities.is_alive(entity) {
+                self.archetypes.remove_entity(entity);
+
+                #[cfg(test)]
+                ENTITY_ROWS.with(|rows| rows.set(rows.get() + 1));
+            } else if self
+                .entities
+                .spawn_at(entity.id(), entity.generation())
+                .is_err()
+            {
+                let len = snapshot.archetypes[index].entities.len();
+                claimed[index].get_or_insert_with(|| vec![true; len])[row] = false;
+            }
+        }
+
+        claimed
+    }
+}
+
+/// Copy of the captured archetype with only the claimed rows.
+fn compact_pod_archetype(archetype: &PodArchetype, claimed: &[bool]) -> PodArchetype {
+    let rows = || claimed.iter().enumerate().filter(|(_, claimed)| **claimed);
+    let columns = archetype
+        .columns
+        .iter()
+        .map(|column| {
+            let size = column.bytes.len() / column.count.max(1);
+            let bytes = rows()
+                .flat_map(|(row, _)| &column.bytes[row * size..(row + 1) * size])
+                .copied()
+                .collect::<Vec<_>>();
+            let frames = rows()
+                .map(|(row, _)| column.frames[row])
+                .collect::<Vec<_>>();
+
+            PodColumn {
+                component: column.component,
+                name: column.name,
+                layout: column.layout,
+                count: frames.len(),
+                bytes,
+                frames,
+            }
+        })
+        .collect();
+    let serde = archetype
+        .serde
+        .iter()
+        .map(|column| PodSerdeColumn {
+            component: column.component,
+            name: column.name,
+            values: rows().map(|(row, _)| column.values[row].clone()).collect(),
+        })
+        .collect();
+
+    PodArchetype {
+        components: archetype.components.clone(),
+        entities: rows().map(|(row, _)| archetype.entities[row]).collect(),
+        columns,
+        serde,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The component was registered without a clone function, see [`World::register_clone`].
+    NotCloneable(&'static str),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::NotCloneable(name) => write!(f, "Component is not cloneable: {}", name),
+        }
+    }
+}
+
+struct SnapshotColumn {
+    component: ComponentId,
+    clone: CloneFn,
+    data: Blob,
+    frames: Vec<ObjectStatus>,
+}
+
+struct SnapshotArchetype {
+    components: Vec<ComponentId>,
+    entities: Vec<Entity>,
+    columns: Vec<SnapshotColumn>,
+}
+
+/// Copy of every entity, its components and the cloneable resources of a world, see
+/// [`World::snapshot`].
+pub struct WorldSnapshot {
+    entities: Entities,
+    archetypes: Vec<SnapshotArchetype>,
+    resources: Vec<(ResourceId, Option<BlobCell>)>,
+}
+
+impl WorldSnapshot {
+    pub fn entity_count(&self) -> usize {
+        self.archetypes.iter().map(|a| a.entities.len()).sum()
+    }
+}
+
+/// Clones `count` values of `meta`'s type stored back to back in `bytes` into a new blob.
+unsafe fn clone_blob(clone: CloneFn, bytes: &[u8], count: usize, meta: TypeMeta) -> Blob {
+    let mut data = Blob::with_meta(meta);
+    data.reserve(count);
+    for index in 0..count {
+        let src = unsafe { bytes.as_ptr().add(index * meta.layout.size()) };
+        unsafe { data.push_with(|dst| clone(src, dst)) };
+    }
+
+    data
+}
+
+impl World {
+    /// Registers the component so it can be captured by [`World::snapshot`].
+    pub fn register_clone<C: Component + Clone>(&mut self) -> ComponentId {
+        self.archetypes.register_clone::<C>()
+    }
+
+    /// Opts the resource into [`World::snapshot`].
+    pub fn register_clone_resource<R: Resource + Send + Clone>(&mut self) -> ResourceId {
+        self.resources.register_clone::<true, R>()
+    }
+
+    /// Clones every entity with its components, the entity allocator and the resources registered
+    /// with [`World::register_clone_resource`]. Fails if an entity has a component that was
+    /// registered without [`World::register_clone`] or [`World::register_pod`].
+    pub fn snapshot(&self) -> Result<WorldSnapshot, SnapshotError> {
+        let mut archetypes = Vec::new();
+        for archetype in self.archetypes().archetypes() {
+            let table = archetype.table();
+            if table.is_empty() {
+                continue;
+            }
+
+            let mut components = Vec::new();
+            let mut columns = Vec::new();
+            for (id, column) in table.columns() {
+                let meta = self.components().meta(*id).unwrap();
+                let clone = meta
+                    .clone_fn()
+                    .ok_or(SnapshotError::NotCloneable(meta.name()))?;
+
+                let (bytes, count, _) = column.raw_parts();
+                let data = unsafe { clone_blob(clone, bytes, count, *meta.type_meta()) };
+                components.push(*id);
+                columns.push(SnapshotColumn {
+                    component: *id,
+                    clone,
+                    data,
+                    frames: (0..count).filter_map(|row| column.status(row)).collect(),
+                });
+            }
+
+            archetypes.push(SnapshotArchetype {
+                components,
+                entities: table.entities().copied().collect(),
+                columns,
+            });
+        }
+
+        let resources = (0..self.resources.len())
+            .map(ResourceId::from_usize)
+            .filter(|id| {
+                let meta = self.resources.get_meta(*id).unwrap();
+                meta.clone_fn().is_some()
+            })
+            .map(|id| (id, self.resources.clone_cell(id)))
+            .collect();
+
+        Ok(WorldSnapshot {
+            entities: self.entities.clone(),
+            archetypes,
+            resources,
+        })
+    }
+
+    /// Rolls the world back to the snapshot, which can be restored any number of times.
+    /// Every current entity is dropped without running hooks or observers, and restored
+    /// components keep the frames they had when captured. Cloneable resources that didn't
+    /// exist in the snapshot are removed.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        self.archetypes.clear_entities();
+        self.entities = snapshot.entities.clone();
+
+        for archetype in &snapshot.archetypes {
+            let id = self.archetype_for_components(&archetype.components);
+            let columns = archetype
+                .columns
+                .iter()
+                .map(|column| {
+                    let (bytes, count, meta) = (
+                        column.data.as_bytes(),
+                        column.data.len(),
+                        *column.data.meta(),
+                    );
+                    let data = unsafe { clone_blob(column.clone, bytes, count, meta) };
+                    (column.component, data, column.frames.as_slice())
+                })
+                .collect::<Vec<_>>();
+
+            unsafe {
+                self.archetypes
+                    .extend_raw(id, &archetype.entities, |component| {
+                        let (_, data, frames) =
+                            columns.iter().find(|(id, _, _)| *id == component).unwrap();
+
+                        (data.as_bytes(), *frames)
+                    });
+            }
+
+            // The clones were moved into the archetype.
+            for (_, mut data, _) in columns {
+                unsafe { data.set_len(0) };
+            }
+        }
+
+        let frame = self.frame;
+        for (id, cell) in &snapshot.resources {
+            self.resources.restore_cell(*id, cell.as_ref(), frame);
+        }
+
+        // Cached component hashes describe the state that was just replaced.
+        self.hash_cache = StateHashCache::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{COPIED_COLUMNS, ENTITY_ROWS, NonPodPolicy, PodSnapshotError, SnapshotError};
+    use crate::world::{ArchetypeQuery, Component, PodComponent, Resource, World};
+    use serde::{Deserialize, Serialize};
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+    impl Component for Position {}
+    unsafe impl PodComponent for Position {}
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Velocity {
+        x: f32,
+        y: f32,
+    }
+    impl Component for Velocity {}
+    unsafe impl PodComponent for Velocity {}
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Name(String);
+    impl Component for Name {}
+
+    fn position_filter(world: &mut World) -> ArchetypeQuery {
+        let mut filter = ArchetypeQuery::default();
+        filter.include(world.register_pod::<Position>());
+        filter
+    }
+
+    #[test]
+    fn pod_snapshot_million_entities() {
+        const COUNT: usize = 1_000_000;
+
+        let mut world = World::new();
+        let filter = position_filter(&mut world);
+        let entities = world.spawn_batch((0..COUNT).map(|i| {
+            let position = Position {
+                x: i as f32,
+                y: -(i as f32),
+            };
+            (position,)
+        }));
+
+        let snapshot = world.snapshot_pod(&filter).unwrap();
+        assert_eq!(snapshot.entity_count(), COUNT);
+
+        let mut restored = World::new();
+        restored.register_pod::<Position>();
+        COPIED_COLUMNS.with(|copied| copied.set(0));
+        ENTITY_ROWS.with(|rows| rows.set(0));
+        restored.restore_pod(&snapshot).unwrap();
+
+        // A single bulk copy of the only column, no entity is moved or inserted on its own.
+        assert_eq!(COPIED_COLUMNS.with(|copied| copied.get()), 1);
+        assert_eq!(ENTITY_ROWS.with(|rows| rows.get()), 0);
+
+        let source = world
+            .query::<&Position>()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        let target = restored
+            .query::<&Position>()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(source, target);
+        assert!(entities.iter().all(|entity| restored.is_alive(*entity)));
+        assert_eq!(
+            restored.get_component::<Position>(entities[COUNT - 1]),
+            world.get_component::<Position>(entities[COUNT - 1])
+        );
+    }
+
+    #[test]
+    fn pod_snapshot_restores_into_empty_world() {
+        let mut world = World::new();
+        let filter = position_filter(&mut world);
+        world.register_pod::<Velocity>();
+
+        let velocity = Velocity { x: 1.0, y: 2.0 };
+        let moving = world.spawn_bundle((Position { x: 1.0, y: 0.0 }, velocity));
+        let gone = world.spawn_bundle((Position { x: 2.0, y: 0.0 },));
+        world.despawn(gone);
+        let recycled = world.spawn_bundle((Position { x: 3.0, y: 0.0 },));
+        let snapshot = world.snapshot_pod(&filter).unwrap();
+
+        let mut restored = World::new();
+        restored.register_pod::<Position>();
+        restored.register_pod::<Velocity>();
+        restored.restore_pod(&snapshot).unwrap();
+
+        assert!(restored.is_alive(moving) && restored.is_alive(recycled));
+        assert!(!restored.is_alive(gone));
+        assert_eq!(restored.get_component::<Velocity>(moving), Some(&velocity));
+        assert_eq!(
+            restored.get_component::<Position>(recycled),
+            Some(&Position { x: 3.0, y: 0.0 })
+        );
+
+        // Local spawns don't hand out the restored ids.
+        let spawned = restored.spawn();
+        assert!(spawned != moving && spawned != recycled);
+    }
+
+    #[test]
+    fn pod_snapshot_rejects_renamed_components() {
+        let mut world = World::new();
+        let filter = position_filter(&mut world);
+        world.spawn_bundle((Position { x: 1.0, y: 0.0 },));
+        let snapshot = world.snapshot_pod(&filter).unwrap();
+
+        // Same layout, registered under the id Position had.
+        let mut restored = World::new();
+        restored.register_pod::<Velocity>();
+        restored.register_pod::<Position>();
+
+        let result = restored.restore_pod(&snapshot);
+        assert!(matches!(result, Err(PodSnapshotError::NameMismatch { .. })));
+        assert_eq!(restored.query::<&Velocity>().iter().count(), 0);
+    }
+
+    #[test]
+    fn pod_snapshot_serde_fallback() {
+        let mut world = World::new();
+        let filter = position_filter(&mut world);
+        world.register_serde::<Name>();
+        let position = Position { x: 4.0, y: 5.0 };
+        let entity = world.spawn_bundle((position, Name("a".into())));
+
+        let result = world.snapshot_pod(&filter);
+        assert!(matches!(result, Err(PodSnapshotError::NotPod(_))));
+
+        let snapshot = world
+            .snapshot_pod_with(&filter, NonPodPolicy::Serde)
+            .unwrap();
+        let mut restored = World::new();
+        restored.register_pod::<Position>();
+        restored.register_serde::<Name>();
+        ENTITY_ROWS.with(|rows| rows.set(0));
+        restored.restore_pod(&snapshot).unwrap();
+
+        // Only the serde column is inserted one entity at a time.
+        assert_eq!(ENTITY_ROWS.with(|rows| rows.get()), 1);
+        assert_eq!(restored.get_component::<Position>(entity), Some(&position));
+        assert_eq!(
+            restored.get_component::<Name>(entity),
+            Some(&Name("a".into()))
+        );
+    }
+
+    #[test]
+    fn pod_snapshot_round_trip() {
+        let mut world = World::new();
+        let position = world.register_pod::<Position>();
+
+        let entities = (0..10_000)
+            .map(|i| {
+                let entity = world.spawn();
+                world.add_component(
+                    entity,
+                    Position {
+                        x: i as f32,
+                        y: 0.0,
+                    },
+                );
+                entity
+            })
+            .collect::<Vec<_>>();
+
+        let mut filter = ArchetypeQuery::default();
+        filter.include(position);
+        let snapshot = world.snapshot_pod(&filter).unwrap();
+        assert_eq!(snapshot.entity_count(), entities.len());
+
+        for entity in &entities {
+            world.get_component_mut::<Position>(*entity).unwrap().y = 1.0;
+        }
+        world.remove_component::<Position>(entities[0]);
+
+        world.restore_pod(&snapshot).unwrap();
+
+        for (i, entity) in entities.iter().enumerate() {
+            let expected = Position {
+                x: i as f32,
+                y: 0.0,
+            };
+            assert_eq!(world.get_component::<Position>(*entity), Some(&expected));
+        }
+    }
+
+    #[test]
+    fn pod_snapshot_skips_dead_entities() {
+        let mut world = World::new();
+        let position = world.register_pod::<Position>();
+
+        let entities = (0..4)
+            .map(|i| {
+                let entity = world.spawn();
+                let position = Position {
+                    x: i as f32,
+                    y: 0.0,
+                };
+                world.add_component(entity, position);
+                entity
+            })
+            .collect::<Vec<_>>();
+
+        let mut filter = ArchetypeQuery::default();
+        filter.include(position);
+        let snapshot = world.snapshot_pod(&filter).unwrap();
+
+        world.despawn(entities[1]);
+        world.despawn(entities[2]);
+        let recycled = world.spawn();
+        world.get_component_mut::<Position>(entities[3]).unwrap().y = 1.0;
+
+        world.restore_pod(&snapshot).unwrap();
+
+        assert_eq!(world.query::<&Position>().iter().count(), 2);
+        for (i, entity) in [(0, entities[0]), (3, entities[3])] {
+            let expected = Position {
+                x: i as f32,
+                y: 0.0,
+            };
+            assert_eq!(world.get_component::<Position>(entity), Some(&expected));
+        }
+
+        for entity in &entities[1..3] {
+            assert!(world.archetypes().entity_archetype(*entity).is_none());
+        }
+        assert_eq!(world.get_component::<Position>(recycled), None);
+    }
+
+    #[test]
+    fn pod_snapshot_rejects_non_pod() {
+        let mut world = World::new();
+        world.register_pod::<Position>();
+        let name = world.register::<Name>();
+
+        let entity = world.spawn();
+        world.add_component(entity, Name("Bob".into()));
+
+        let mut filter = ArchetypeQuery::default();
+        filter.include(name);
+
+        let result = world.snapshot_pod(&filter);
+        assert!(matches!(result, Err(PodSnapshotError::NotPod(_))));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Seed(u64);
+    impl Resource for Seed {}
+
+    #[test]
+    fn world_snapshot_rollback() {
+        let mut world = World::new();
+        world.register_pod::<Position>();
+        world.register_clone::<Name>();
+        world.register_clone_resource::<Seed>();
+        world.add_resource(Seed(1));
+
+        let position = Position { x: 1.0, y: 2.0 };
+        let a = world.spawn_bundle((position, Name("a".into())));
+        let b = world.spawn_bundle((Name("b".into()),));
+        let snapshot = world.snapshot().unwrap();
+        assert_eq!(snapshot.entity_count(), 2);
+
+        let next = world.spawn();
+        world.despawn(next);
+        world.despawn(b);
+        world.get_component_mut::<Name>(a).unwrap().0.push('!');
+        world.remove_component::<Position>(a);
+        world.resource_mut::<Seed>().0 = 7;
+
+        for _ in 0..2 {
+            world.restore(&snapshot);
+            assert!(world.is_alive(b));
+            assert!(!world.is_alive(next));
+            assert_eq!(world.get_component::<Name>(a), Some(&Name("a".into())));
+            assert_eq!(world.get_component::<Position>(a), Some(&position));
+            assert_eq!(world.get_component::<Name>(b), Some(&Name("b".into())));
+            assert_eq!(world.resource::<Seed>(), &Seed(1));
+
+            // The allocator is rolled back too, so the same entity is handed out again.
+            assert_eq!(world.spawn(), next);
+            world.resources_mut().remove::<Seed>();
+        }
+    }
+
+    #[test]
+    fn world_snapshot_drops_clones_once() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        #[repr(align(32))]
+        #[derive(Clone)]
+        struct Counted(Arc<AtomicUsize>);
+        impl Component for Counted {}
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut world = World::new();
+        world.register_clone::<Counted>();
+        let entity = world.spawn_bundle((Counted(drops.clone()),));
+
+        let snapshot = world.snapshot().unwrap();
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // Restoring drops the live component and moves in a clone of the snapshot's.
+        world.restore(&snapshot);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        let counted = world.get_component::<Counted>(entity).unwrap();
+        assert_eq!(std::ptr::from_ref(counted) as usize % 32, 0);
+
+        drop(snapshot);
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+        drop(world);
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn world_snapshot_rejects_non_cloneable() {
+        #[derive(Debug, PartialEq)]
+        struct Handle(u32);
+        impl Component for Handle {}
+
+        let mut world = World::new();
+        world.register::<Handle>();
+        let entity = world.spawn_bundle((Handle(3),));
+
+        let result = world.snapshot();
+        assert!(matches!(result, Err(SnapshotError::NotCloneable(_))));
+        assert_eq!(world.get_component::<Handle>(entity), Some(&Handle(3)));
+    }
+}
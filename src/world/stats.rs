@@ -0,0 +1,75 @@
+use super::{ArchetypeId, World};
+
+/// Size of the world at a point in time, see [`World::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldStats {
+    /// Live entities, reserved ones included.
+    pub entities: usize,
+    pub archetypes: usize,
+    /// Archetypes holding at least one entity.
+    pub archetype_entities: Vec<(ArchetypeId, usize)>,
+    /// Heap bytes held by every table, allocated capacity included.
+    pub table_bytes: usize,
+    /// Resources currently in the world, not counting registered but absent ones.
+    pub resources: usize,
+}
+
+impl World {
+    /// Walks every archetype, meant for debug overlays rather than every frame of a hot loop.
+    pub fn stats(&self) -> WorldStats {
+        let archetypes = self.archetypes.archetypes();
+        WorldStats {
+            entities: self.entities.len(),
+            archetypes: archetypes.len(),
+            archetype_entities: archetypes
+                .iter()
+                .filter(|archetype| !archetype.table().is_empty())
+                .map(|archetype| (archetype.id(), archetype.table().len()))
+                .collect(),
+            table_bytes: archetypes
+                .iter()
+                .map(|archetype| archetype.table().memory_usage())
+                .sum(),
+            resources: self.resources.count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::{ArchetypeId, Component, Resource, World};
+
+    struct Health(u32);
+    impl Component for Health {}
+
+    struct Score;
+    impl Resource for Score {}
+
+    #[test]
+    fn world_stats() {
+        let mut world = World::new();
+        world.register::<Health>();
+        let empty = world.stats();
+        assert_eq!(empty.entities, 0);
+        assert!(empty.archetype_entities.is_empty());
+
+        world.spawn();
+        let entities = (0..3)
+            .map(|health| world.spawn_bundle((Health(health),)))
+            .collect::<Vec<_>>();
+        let health = world.get_component::<Health>(entities[2]).unwrap();
+        assert_eq!(health.0, 2);
+        world.add_resource(Score);
+
+        let stats = world.stats();
+        assert_eq!(stats.entities, 4);
+        assert_eq!(stats.archetypes, world.archetypes().archetypes().len());
+        assert_eq!(stats.archetype_entities.len(), 2);
+        assert!(stats.archetype_entities.contains(&(ArchetypeId::EMPTY, 1)));
+        assert!(stats.table_bytes >= 3 * std::mem::size_of::<Health>());
+        assert_eq!(stats.resources, 1);
+
+        world.remove_resource::<Score>().unwrap();
+        assert_eq!(world.stats().resources, 0);
+    }
+}
@@ -0,0 +1,99 @@
+//! Installs [`CountingAllocator`] as the global allocator, which is why these tests live in
+//! their own binary instead of next to the allocator.
+
+use hive_ecs::{
+    app::App,
+    core::{AllocationGuard, CountingAllocator},
+    system::{query::Query, schedule::Phase},
+    world::{Command, Commands, Component, Event, EventReader, EventWriter, Resource, World},
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+#[test]
+fn allocation_guard_counts() {
+    let guard = AllocationGuard::new();
+    let values = std::hint::black_box(vec![1u32, 2, 3]);
+    assert_eq!(guard.allocations(), 1);
+
+    let guard = AllocationGuard::new();
+    let sum: u32 = values.iter().sum();
+    assert_eq!(sum, 6);
+    assert_eq!(guard.allocations(), 0);
+}
+
+struct Update;
+impl Phase for Update {}
+
+struct Position(f32);
+impl Component for Position {}
+
+struct Velocity(f32);
+impl Component for Velocity {}
+
+struct Hit(u32);
+impl Event for Hit {}
+
+#[derive(Default)]
+struct Stats {
+    hits: u32,
+    commands: u32,
+}
+impl Resource for Stats {}
+
+struct Record;
+impl Command for Record {
+    fn execute(self, world: &mut World) {
+        world.resource_mut::<Stats>().commands += 1;
+    }
+}
+
+fn movement(mut query: Query<(&mut Position, &Velocity)>) {
+    for (mut position, velocity) in query.iter_mut() {
+        position.0 += velocity.0;
+    }
+}
+
+fn send(mut writer: EventWriter<Hit>) {
+    writer.send(Hit(1));
+}
+
+fn receive(reader: EventReader<Hit>, stats: &mut Stats) {
+    stats.hits += reader.map(|hit| hit.0).sum::<u32>();
+}
+
+fn record(mut commands: Commands) {
+    commands.add(Record);
+}
+
+#[test]
+fn steady_state_frames_do_not_allocate() {
+    let mut builder = App::new();
+    builder
+        .register::<Position>()
+        .register::<Velocity>()
+        .add_resource(Stats::default())
+        .add_systems(Update, movement)
+        .add_systems(Update, send)
+        .add_systems(Update, receive)
+        .add_systems(Update, record)
+        .forbid_steady_state_allocations(2);
+
+    for i in 0..100 {
+        let world = builder.world_mut();
+        let entity = world.spawn();
+        world.add_component(entity, Position(0.0));
+        world.add_component(entity, Velocity(i as f32));
+    }
+
+    let mut app = builder.build();
+    for _ in 0..100 {
+        app.run(Update);
+    }
+
+    assert!(app.allocation_violations().is_empty());
+    let stats = app.world().resource::<Stats>();
+    assert_eq!(stats.commands, 100);
+    assert_eq!(stats.hits, 99);
+}